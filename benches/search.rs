@@ -0,0 +1,174 @@
+//! Benchmarks of the construction of prefix trees and of searches
+//!
+//! Run with `cargo bench` (or `task bench`). The dictionaries and texts are generated from a
+//! fixed seed, so that results are comparable between runs. The `baseline` group runs the
+//! same searches with the `aho-corasick` crate, as a reference for the cost of the character
+//! bookkeeping (NFC normalization, character ranges) of this library.
+use aho_corasick_ref::AhoCorasick;
+use criterion::{BatchSize, BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::hint::black_box;
+
+use ac_search_rs::trie::{self, SearchOptions, TrieRoot};
+
+/// Number of patterns of the dictionary used in the search benchmarks
+const SEARCH_PATTERNS: usize = 10_000;
+
+/// Approximate length in bytes of the searched texts
+const TEXT_BYTES: usize = 100_000;
+
+/// Generate a random lowercase ASCII word with a length in the given range
+fn random_word(rng: &mut StdRng, min_len: usize, max_len: usize) -> String {
+    let length = rng.random_range(min_len..=max_len);
+    (0..length)
+        .map(|_| rng.random_range(b'a'..=b'z') as char)
+        .collect()
+}
+
+/// Generate the given number of distinct random patterns
+fn random_patterns(count: usize, seed: u64) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seen = HashSet::with_capacity(count);
+    let mut patterns = Vec::with_capacity(count);
+    while patterns.len() < count {
+        let word = random_word(&mut rng, 6, 12);
+        if seen.insert(word.clone()) {
+            patterns.push(word);
+        }
+    }
+    patterns
+}
+
+/// Text made of the patterns of the dictionary separated by spaces, so that most of its
+/// words are matches
+fn dense_text(patterns: &[String]) -> String {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut text = String::with_capacity(TEXT_BYTES + 16);
+    while text.len() < TEXT_BYTES {
+        text.push_str(&patterns[rng.random_range(0..patterns.len())]);
+        text.push(' ');
+    }
+    text
+}
+
+/// Text made of random words, so that matches are rare
+fn sparse_text() -> String {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut text = String::with_capacity(TEXT_BYTES + 16);
+    while text.len() < TEXT_BYTES {
+        text.push_str(&random_word(&mut rng, 1, 10));
+        text.push(' ');
+    }
+    text
+}
+
+fn build_tree(patterns: &[String], opts: SearchOptions) -> TrieRoot {
+    trie::create_prefix_tree(trie::add_keyword_slot(patterns.to_vec()), Some(opts)).unwrap()
+}
+
+fn bench_construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("construction");
+    group.sample_size(10);
+    for count in [10_000, 100_000, 1_000_000] {
+        let dictionary = trie::add_keyword_slot(random_patterns(count, 1));
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &dictionary,
+            |b, dictionary| {
+                b.iter_batched(
+                    || dictionary.clone(),
+                    |dictionary| trie::create_prefix_tree(dictionary, None).unwrap(),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_search(c: &mut Criterion) {
+    let patterns = random_patterns(SEARCH_PATTERNS, 3);
+    let texts = [("dense", dense_text(&patterns)), ("sparse", sparse_text())];
+    let modes = [
+        ("case_sensitive", SearchOptions::default()),
+        (
+            "case_insensitive",
+            SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            },
+        ),
+        (
+            "check_bounds",
+            SearchOptions {
+                check_bounds: true,
+                ..Default::default()
+            },
+        ),
+    ];
+
+    let mut group = c.benchmark_group("search");
+    for (mode, opts) in modes {
+        let tree = build_tree(&patterns, opts);
+        for (density, text) in &texts {
+            // The case-insensitive searches are run on the uppercase texts, so that no match
+            // is found by comparing the characters directly
+            let text = if tree.options().case_sensitive {
+                text.clone()
+            } else {
+                text.to_uppercase()
+            };
+            group.throughput(Throughput::Bytes(text.len() as u64));
+            group.bench_with_input(BenchmarkId::new(mode, density), &text, |b, text| {
+                b.iter(|| tree.find_text_matches(black_box(text.as_str())).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_baseline(c: &mut Criterion) {
+    let patterns = random_patterns(SEARCH_PATTERNS, 3);
+    let tree = build_tree(&patterns, SearchOptions::default());
+    let reference = AhoCorasick::new(&patterns).unwrap();
+
+    let mut group = c.benchmark_group("baseline");
+    for (density, text) in [("dense", dense_text(&patterns)), ("sparse", sparse_text())] {
+        // Both libraries report the same (overlapping) matches
+        assert_eq!(
+            tree.find_text_matches(&text).unwrap().len(),
+            reference.find_overlapping_iter(&text).count()
+        );
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("ac_search_rs", density),
+            &text,
+            |b, text| b.iter(|| tree.find_text_matches(black_box(text.as_str())).unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("aho-corasick", density),
+            &text,
+            |b, text| {
+                b.iter(|| {
+                    reference
+                        .find_overlapping_iter(black_box(text.as_str()))
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+    }
+    group.throughput(Throughput::Elements(patterns.len() as u64));
+    group.bench_function("ac_search_rs/construction", |b| {
+        b.iter(|| build_tree(black_box(&patterns), SearchOptions::default()))
+    });
+    group.bench_function("aho-corasick/construction", |b| {
+        b.iter(|| AhoCorasick::new(black_box(&patterns)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_construction, bench_search, bench_baseline);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+//! Run the HTTP Search Service
+//!
+//! Usage: `search_server [ADDRESS] [WORKERS]`, listening on 127.0.0.1:8080 with 4 worker
+//! threads by default. See the `server` module for the endpoints.
+use ac_search_rs::server::SearchServer;
+use std::{env, process, sync::Arc};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let addr = args
+        .next()
+        .unwrap_or_else(|| String::from("127.0.0.1:8080"));
+    let workers = match args.next().map(|w| w.parse::<usize>()) {
+        None => 4,
+        Some(Ok(w)) => w,
+        Some(Err(e)) => {
+            eprintln!("Invalid number of workers: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let server = Arc::new(SearchServer::new(None));
+    println!("Listening on {}", addr);
+    if let Err(e) = server.serve(addr.as_str(), workers) {
+        eprintln!("Server error: {}", e);
+        process::exit(1);
+    }
+}
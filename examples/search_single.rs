@@ -1,11 +1,7 @@
-//! CLI Application to Run Simple Searches and Save Results as TSV
+//! CLI Application to Run Simple Searches and Save Results as TSV, CSV or JSON
+use ac_search_rs::output::{MatchWriter, OutputFormat};
 use ac_search_rs::trie;
-use std::{
-    fmt::Display,
-    fs,
-    io::{self, BufRead, Write},
-    process,
-};
+use std::{fmt::Display, fs, io, process};
 
 use clap::Parser;
 
@@ -22,14 +18,23 @@ use clap::Parser;
 ///               -o save-here.tsv \
 ///               --case-insensitive \
 ///               --word-bounds
+///
+/// # Split a single large file into chunks searched on several threads
+/// search_single -d my-dictionary-file.txt -t huge-text-file.txt --parallel-chunks
+///
+/// # Save the matches as JSON lines
+/// search_single -d my-dictionary-file.txt -t my-text-file.txt \
+///               -o matches.jsonl --output-format jsonl
 /// ```
 #[derive(Parser, Debug)]
 #[command(about, version)]
 struct Args {
     /// File containing the dictionary of keywords to find
     ///
-    /// This must be a file where each line contains a value and keyword
-    /// to match separated by a tab.
+    /// This must be a TSV, CSV or JSON file, according to its extension
+    /// (TSV by default). In TSV files, each line contains a value and
+    /// keyword to match separated by a tab, optionally followed by a
+    /// category (entity label) in a third column. See `read_dictionary`.
     #[arg(short, long)]
     dictionary_file: String,
 
@@ -48,68 +53,75 @@ struct Args {
     /// The filepath to output the results to
     #[arg(short, long, default_value = "output.tsv")]
     output_file: String,
+
+    /// Format of the output file: tsv, csv, json or jsonl.
+    #[arg(long, default_value = "tsv")]
+    output_format: OutputFormat,
+
+    /// If true, split the text into overlapping chunks and search them in parallel.
+    #[arg(long, default_value_t = false)]
+    parallel_chunks: bool,
+
+    /// Number of threads to use with parallel chunks. Defaults to the available parallelism.
+    #[arg(long)]
+    num_threads: Option<usize>,
+
+    /// If true, print the text with the matches marked below it.
+    #[arg(long, default_value_t = false)]
+    debug: bool,
 }
 
 fn err_to_string<T: Display>(err: T) -> String {
     format!("Execution failed. Error: {}", err)
 }
 
-/// Read the dictionary of search terms from a file.
-///
-/// Reads the value / keyword pairs from a given filepath. The file must contain
-/// a value and keyword in each line, separated by a tab character. If only the value
-/// is provided, the same string will also be used as a keyword.
-fn read_dictionary(filepath: &str) -> io::Result<Vec<(String, Option<String>)>> {
-    let file = fs::File::open(filepath)?;
-    let buf = io::BufReader::new(file);
-
-    let mut elems = Vec::new();
-    for line in buf.lines() {
-        match line {
-            Err(err) => return Err(err),
-            Ok(s) => {
-                let mut parts = s.split('\t');
-                let value = parts.next().unwrap().trim().replace('\t', " ");
-                let keyword = parts.next().map(|s| s.trim().replace('\t', " "));
-                elems.push((value, keyword));
-            }
-        }
-    }
-    Ok(elems)
-}
-
-/// Save the encountered matches to the output file.
-///
-/// Saves the matches in a TSV format at the output path.
-fn save_matches(matches: Vec<trie::Match>, filepath: &str) -> io::Result<()> {
-    let mut out_file = fs::File::create(filepath)?;
-    out_file.write_all(b"start\tend\tvalue\tkeyword\n")?;
+/// Save the encountered matches to the output file in the requested format.
+fn save_matches(
+    matches: Vec<trie::Match>,
+    source: &str,
+    filepath: &str,
+    format: OutputFormat,
+) -> io::Result<()> {
+    let out_file = io::BufWriter::new(fs::File::create(filepath)?);
+    let mut writer = MatchWriter::new(out_file, format);
     for m in matches {
-        let (start, end) = m.char_range();
-        let line = format!("{}\t{}\t{}\t{}\n", start, end, m.value(), m.keyword());
-        out_file.write_all(line.as_bytes())?;
+        writer.write_match(&m, source, None)?;
     }
-
+    writer.finish()?;
     Ok(())
 }
 
 fn run(args: Args) -> Result<(), String> {
-    let dictionary = read_dictionary(&args.dictionary_file).map_err(err_to_string)?;
+    let dictionary = trie::read_dictionary(&args.dictionary_file, None).map_err(err_to_string)?;
     let content = fs::read_to_string(&args.text_file).map_err(err_to_string)?;
-    let prefix_tree = trie::create_prefix_tree(
+    let prefix_tree = trie::create_from_dictionary(
         dictionary,
         Some(trie::SearchOptions {
             case_sensitive: !args.case_insensitive,
             check_bounds: args.word_bounds,
+            ..Default::default()
         }),
     )
     .map_err(err_to_string)?;
 
-    let matches = prefix_tree
-        .find_text_matches(content)
-        .map_err(err_to_string)?;
+    let matches = if args.parallel_chunks {
+        prefix_tree.find_text_matches_parallel(&content, args.num_threads)
+    } else {
+        prefix_tree.find_text_matches(&content)
+    }
+    .map_err(err_to_string)?;
 
-    save_matches(matches, &args.output_file).map_err(err_to_string)?;
+    if args.debug {
+        print!("{}", trie::render_matches(&content, &matches));
+    }
+
+    save_matches(
+        matches,
+        &args.text_file,
+        &args.output_file,
+        args.output_format,
+    )
+    .map_err(err_to_string)?;
     Ok(())
 }
 
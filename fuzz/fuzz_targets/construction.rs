@@ -0,0 +1,51 @@
+//! Build prefix trees from arbitrary dictionaries and options, and check that each pattern
+//! is found in its own text, also after saving and loading the tree.
+#![no_main]
+
+use ac_search_rs::trie::{self, SearchOptions, TrieRoot};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    patterns: Vec<String>,
+    flags: u8,
+}
+
+fuzz_target!(|input: Input| {
+    let opts = SearchOptions {
+        case_sensitive: input.flags & 1 == 0,
+        check_bounds: input.flags & 2 != 0,
+        ignore_separators: input.flags & 4 != 0,
+        ignore_digit_separators: input.flags & 8 != 0,
+        ..Default::default()
+    };
+    let plain = input.flags & 0b1100 == 0;
+
+    // Empty and duplicate patterns (also after normalization) are rejected with an error
+    let dictionary = trie::add_keyword_slot(input.patterns.clone());
+    let Ok(tree) = trie::create_prefix_tree(dictionary, Some(opts)) else {
+        return;
+    };
+    let _ = tree.stats();
+    let restored = TrieRoot::from_bytes(&tree.to_bytes()).expect("saved tree is loaded");
+
+    for (pattern_id, pattern) in input.patterns.iter().enumerate() {
+        let matches = tree.find_text_matches(pattern).expect("search succeeds");
+        assert_eq!(
+            matches,
+            restored
+                .find_text_matches(pattern)
+                .expect("search succeeds")
+        );
+
+        // Separators ignored at the ends of the pattern are not part of its match
+        let full = (0, pattern.chars().count());
+        assert!(
+            matches
+                .iter()
+                .any(|m| m.pattern_id() == pattern_id && (!plain || m.char_range() == full)),
+            "pattern {pattern:?} not found in itself: {matches:?}"
+        );
+    }
+});
@@ -0,0 +1,57 @@
+//! Search arbitrary texts with prefix trees of arbitrary dictionaries and options, and check
+//! that the ranges of the matches are consistent with the text.
+#![no_main]
+
+use ac_search_rs::trie::{self, MatchKind, SearchOptions};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use unicode_normalization::{UnicodeNormalization, is_nfc};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    patterns: Vec<String>,
+    haystack: String,
+    flags: u8,
+}
+
+fuzz_target!(|input: Input| {
+    let opts = SearchOptions {
+        case_sensitive: input.flags & 1 == 0,
+        check_bounds: input.flags & 2 != 0,
+        ignore_separators: input.flags & 4 != 0,
+        ignore_digit_separators: input.flags & 8 != 0,
+        match_kind: match (input.flags >> 4) & 3 {
+            1 => MatchKind::LeftmostLongest,
+            2 => MatchKind::LeftmostFirst,
+            _ => MatchKind::Overlapping,
+        },
+        ..Default::default()
+    };
+    let plain = input.flags & 0b1101 == 0;
+
+    let dictionary = trie::add_keyword_slot(input.patterns);
+    let Ok(tree) = trie::create_prefix_tree(dictionary, Some(opts)) else {
+        return;
+    };
+    let text = input.haystack.as_str();
+    let matches = tree.find_text_matches(text).expect("search succeeds");
+
+    for m in &matches {
+        let (start, end) = m.char_range();
+        let bytes = m.byte_range();
+        assert!(start < end, "empty match {m:?}");
+        assert!(bytes.end <= text.len(), "match {m:?} out of bounds");
+        assert!(
+            text.is_char_boundary(bytes.start) && text.is_char_boundary(bytes.end),
+            "match {m:?} splits a character"
+        );
+        assert_eq!(text[..bytes.start].chars().count(), start);
+        assert_eq!(text[bytes.clone()].chars().count(), end - start);
+
+        // Without folding or ignored characters, the match is the pattern as normalized in
+        // the (normalized) text
+        if plain && is_nfc(text) {
+            assert_eq!(m.matched_text(text), m.value().nfc().collect::<String>());
+        }
+    }
+});
@@ -0,0 +1,74 @@
+/*
+ * C API of the ac_search_rs library, built with the "c_api" feature:
+ *
+ *     cargo build --release --features c_api
+ *
+ * and linked against the resulting libac_search_rs shared library. See the documentation of
+ * the "ffi" module for details.
+ */
+#ifndef AC_SEARCH_H
+#define AC_SEARCH_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Flags of ac_trie_new */
+#define AC_CASE_INSENSITIVE 1u
+#define AC_CHECK_BOUNDS (1u << 1)
+#define AC_IGNORE_SEPARATORS (1u << 2)
+
+/* Prefix tree built with ac_trie_new */
+typedef struct AcTrie AcTrie;
+
+/* List of matches returned by ac_trie_search */
+typedef struct AcMatches AcMatches;
+
+/*
+ * Build a prefix tree from "count" NUL-terminated UTF-8 patterns, each reported with the
+ * keyword at the same index of "keywords". Either "keywords" or any of its items can be NULL
+ * to report patterns with themselves as keyword. Returns NULL on error.
+ */
+AcTrie *ac_trie_new(const char *const *patterns, const char *const *keywords, size_t count,
+                    uint32_t flags);
+
+/* Release a tree built with ac_trie_new. Does nothing if it is NULL. */
+void ac_trie_free(AcTrie *trie);
+
+/*
+ * Search the "len" bytes of UTF-8 text at "text", which need not be NUL-terminated. The
+ * ranges of the matches are byte offsets into the text. Returns NULL on error.
+ */
+AcMatches *ac_trie_search(const AcTrie *trie, const char *text, size_t len);
+
+/* Release a list of matches returned by ac_trie_search. Does nothing if it is NULL. */
+void ac_matches_free(AcMatches *matches);
+
+/* Number of matches in a list */
+size_t ac_matches_len(const AcMatches *matches);
+
+/* Byte offsets of the start and end of a match in the searched text */
+size_t ac_match_start(const AcMatches *matches, size_t index);
+size_t ac_match_end(const AcMatches *matches, size_t index);
+
+/* Index of the matched pattern in the array given to ac_trie_new */
+size_t ac_match_pattern_id(const AcMatches *matches, size_t index);
+
+/*
+ * Matched pattern (normalized) and keyword of a match, owned by the list. NULL if the index is
+ * out of bounds.
+ */
+const char *ac_match_value(const AcMatches *matches, size_t index);
+const char *ac_match_keyword(const AcMatches *matches, size_t index);
+
+/* Description of the last error in the calling thread, or NULL if there was none */
+const char *ac_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AC_SEARCH_H */
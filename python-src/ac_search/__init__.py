@@ -4,6 +4,7 @@ Aho-Corasick algorithm for efficient text searches - implemented in Rust!
 
 from .ac_search_rs import (
     PyMatch,
+    PyMatchIter,
     PyTrie,
     normalize_string,
     search_in_text,
@@ -17,5 +18,6 @@ __all__ = [
     "search_in_texts",
     "to_dictionary",
     "PyMatch",
+    "PyMatchIter",
     "PyTrie",
 ]
@@ -0,0 +1,240 @@
+"""
+Type stubs of the Rust extension module. Keep in sync with src/py_bind.rs.
+"""
+
+from collections.abc import Callable, Iterable, Iterator
+from typing import Any, final
+
+def normalize_string(input: str) -> str:
+    """
+    Normalize the given string to unicode NFC standard. Searches apply this
+    normalization internally, reporting character ranges of the input text.
+    """
+
+def search_in_text(
+    dictionary: dict[str, str],
+    haystack: str,
+    case_sensitive: bool = True,
+    check_bounds: bool = False,
+) -> list[PyMatch]:
+    """
+    Search for all occurences of strings in the "dictionary" in the given
+    "haystack". The dictionary must be a mapping of pattern -> keyword.
+    """
+
+def search_in_texts(
+    dictionary: dict[str, str],
+    haystacks: list[str],
+    case_sensitive: bool = True,
+    check_bounds: bool = False,
+    num_threads: int | None = None,
+) -> list[list[PyMatch]]:
+    """
+    Search for all occurences of strings in the "dictionary" in the given
+    "haystack" strings, in parallel.
+    """
+
+@final
+class PyMatch:
+    """
+    A Match found in a text. This contains the start character, end
+    character, and the string value of the match.
+    """
+
+    @property
+    def value(self) -> str:
+        """The matching value found in the string"""
+
+    @property
+    def kw(self) -> str:
+        """The standard keyword associated with the match"""
+
+    @property
+    def from_char(self) -> int:
+        """Start of the match character range in the input text"""
+
+    @property
+    def to_char(self) -> int:
+        """End of the match character range in the input text"""
+
+    @property
+    def from_byte(self) -> int:
+        """Start of the match byte range in the input text encoded as UTF-8"""
+
+    @property
+    def to_byte(self) -> int:
+        """End of the match byte range in the input text encoded as UTF-8"""
+
+    @property
+    def score(self) -> float | None:
+        """Confidence score of the match, if computed by the search"""
+
+    @property
+    def category(self) -> str | None:
+        """Category (entity label) of the matched pattern, if any"""
+
+    @property
+    def pattern_id(self) -> int:
+        """ID of the matched pattern: its position in the dictionary"""
+
+    def __new__(
+        cls, from_char: int, to_char: int, value: str, keyword: str
+    ) -> PyMatch: ...
+    def byte_range(self) -> slice:
+        """
+        Get the range of bytes of the match as a slice, to index the UTF-8
+        encoded text.
+        """
+
+    def to_dict(self) -> dict[str, str | int | float | None]:
+        """
+        Get the attributes of the match as a dictionary.
+        """
+
+    def __iter__(self) -> Iterator[int | str]: ...
+    def __len__(self) -> int: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+
+@final
+class PyMatchIter:
+    """
+    Lazy iterator over the matches of a text, returned by "PyTrie.finditer".
+    """
+
+    def __iter__(self) -> PyMatchIter: ...
+    def __next__(self) -> PyMatch: ...
+
+@final
+class PyTrie:
+    """
+    Prefix tree for performing string searches.
+    """
+
+    @property
+    def keywords(self) -> list[str]:
+        """The list of keywords stored in the trie"""
+
+    def __new__(
+        cls,
+        dictionary: dict[str, str] | Iterable[tuple[str, str]],
+        case_sensitive: bool = True,
+        check_bounds: bool = False,
+        compute_scores: bool = False,
+        categories: dict[str, str] | None = None,
+        track_coverage: bool = False,
+        max_chain_depth: int | None = None,
+        match_kind: str = "overlapping",
+        word_segmentation: bool = False,
+        whitespace: str = "exact",
+        wildcard: str | None = None,
+        max_matches: int | None = None,
+        max_text_chars: int | None = None,
+        time_limit: float | None = None,
+    ) -> PyTrie: ...
+    @staticmethod
+    def from_file(
+        path: str,
+        format: str | None = None,
+        case_sensitive: bool = True,
+        check_bounds: bool = False,
+        compute_scores: bool = False,
+        track_coverage: bool = False,
+        max_chain_depth: int | None = None,
+        match_kind: str = "overlapping",
+        word_segmentation: bool = False,
+        whitespace: str = "exact",
+        wildcard: str | None = None,
+        max_matches: int | None = None,
+        max_text_chars: int | None = None,
+        time_limit: float | None = None,
+    ) -> PyTrie:
+        """Instantiate a prefix tree from a TSV, CSV or JSON dictionary file."""
+
+    @staticmethod
+    def load(path: str) -> PyTrie:
+        """Load a prefix tree from a file written with "save"."""
+
+    def save(self, path: str) -> None:
+        """Save the prefix tree to a file."""
+
+    def to_bytes(self) -> bytes:
+        """Serialize the prefix tree into bytes."""
+
+    @staticmethod
+    def from_bytes(data: bytes) -> PyTrie:
+        """Rebuild a prefix tree serialized with "to_bytes"."""
+
+    def __reduce__(
+        self,
+    ) -> tuple[Callable[[bytes], PyTrie], tuple[bytes]]: ...
+    def add_patterns(
+        self, dictionary: dict[str, str] | Iterable[tuple[str, str]]
+    ) -> None:
+        """Add new entries from a mapping or pairs of pattern, keyword."""
+
+    def pattern(self, pattern_id: int) -> tuple[str, str] | None:
+        """Return the (pattern, keyword) entry with the given ID."""
+
+    def total_nodes(self) -> int:
+        """Return the total number of nodes in the prefix tree"""
+
+    def stats(self) -> dict[str, int | float]:
+        """Return statistics of the size and shape of the prefix tree."""
+
+    def never_matched(self) -> list[tuple[str, str]] | None:
+        """Return the entries that have not matched in any search so far."""
+
+    def search(self, text: str, min_length: int = 0) -> list[PyMatch]:
+        """Search for occurrences of the defined patterns in the text."""
+
+    def finditer(self, text: str) -> PyMatchIter:
+        """Iterate lazily over the matches of the text."""
+
+    def search_arrays(
+        self, text: str, min_length: int = 0
+    ) -> dict[str, list[Any]]:
+        """Search the text, returning the matches as a dictionary of lists."""
+
+    def search_with_context(
+        self, text: str, window_chars: int = 30
+    ) -> list[tuple[PyMatch, str, str]]:
+        """Search the text, returning each match with the text around it."""
+
+    def longest_match(self, text: str, start: int = 0) -> PyMatch | None:
+        """Return the longest entry matching the text at "start"."""
+
+    def count_matches(self, text: str) -> int:
+        """Return the number of matches in the text."""
+
+    def contains_any(self, text: str) -> bool:
+        """Return whether there is any match in the text."""
+
+    def first_match(self, text: str) -> PyMatch | None:
+        """Return the first match in the text, if any."""
+
+    def first_matches(self, text: str, n: int) -> list[PyMatch]:
+        """Return the first "n" matches in the text."""
+
+    def replace_all(self, text: str) -> str:
+        """Return the text with each match replaced by its keyword."""
+
+    def scan(
+        self, text: str, callback: Callable[[PyMatch], bool | None]
+    ) -> bool:
+        """Call "callback" with each match as soon as it is found."""
+
+    def search_bytes(
+        self, data: bytes, skip_invalid: bool = False
+    ) -> list[PyMatch]:
+        """Search raw bytes that may not be valid UTF-8."""
+
+    def search_many(
+        self,
+        texts: list[str],
+        num_threads: int | None = None,
+        min_length: int = 0,
+        timeout: float | None = None,
+        on_progress: Callable[[int, int], None] | None = None,
+    ) -> list[list[PyMatch] | None]:
+        """Search a list of texts in parallel."""
@@ -0,0 +1,267 @@
+//! Grep-like CLI to Find the Patterns of a Dictionary in Files
+use ac_search_rs::output::{MatchWriter, OutputFormat};
+use ac_search_rs::trie::{self, DictionaryFormat, InvalidUtf8, Match, SearchResult, TrieRoot};
+use std::{
+    fmt::Display,
+    fs,
+    io::{self, BufRead, BufWriter, Write},
+    process,
+};
+
+use clap::Parser;
+
+/// Name shown for the text read from the standard input
+const STDIN_NAME: &str = "(standard input)";
+
+/// Find the patterns of a dictionary in files, line by line, printing each match as
+/// `file:line:column:keyword`. Lines and columns start at 1, and columns are byte offsets
+/// as in grep. Exits with status 0 if there are matches, 1 if there are none and 2 on errors.
+///
+/// Usage Examples
+/// ```shell
+/// # Search files
+/// ac-grep -d my-dictionary-file.tsv notes.txt logs/*.log
+///
+/// # Search the standard input, ignoring case
+/// cat notes.txt | ac-grep -d my-dictionary-file.tsv -i
+///
+/// # Count matches per keyword
+/// ac-grep -d my-dictionary-file.tsv -w notes.txt | cut -d: -f4 | sort | uniq -c
+///
+/// # Print the matches as JSON lines
+/// ac-grep -d my-dictionary-file.tsv --output-format jsonl notes.txt | jq .keyword
+///
+/// # Draw the automaton of a small dictionary
+/// ac-grep -d my-dictionary-file.tsv --dump-dot | dot -Tsvg > automaton.svg
+/// ```
+#[derive(Parser, Debug)]
+#[command(about, version)]
+struct Args {
+    /// File containing the dictionary of keywords to find
+    ///
+    /// This must be a TSV, CSV or JSON file, where each entry has a pattern,
+    /// optionally followed by its keyword, category (entity label), case
+    /// sensitivity and metadata. The format is given by the file extension,
+    /// and files with other extensions are read as TSV.
+    #[arg(short, long)]
+    dictionary_file: String,
+
+    /// Format of the dictionary file (tsv, csv or json), if not given by its extension.
+    #[arg(long)]
+    dictionary_format: Option<DictionaryFormat>,
+
+    /// Files to search. The standard input is read if there are none or for "-".
+    files: Vec<String>,
+
+    /// If true, return only matches with words bounds at the start and end.
+    #[arg(short, long, default_value_t = false)]
+    word_bounds: bool,
+
+    /// If true, make matches case-insensitive
+    #[arg(short = 'i', long, default_value_t = false)]
+    case_insensitive: bool,
+
+    /// If true, print the matched text instead of the keyword.
+    #[arg(short = 'o', long, default_value_t = false)]
+    only_matching: bool,
+
+    /// If true, print only the number of matches of each file.
+    #[arg(short, long, default_value_t = false)]
+    count: bool,
+
+    /// Print the matches as records in a format (tsv, csv, json or jsonl) instead of grep-like
+    /// lines, including their line number and character and byte ranges.
+    #[arg(long)]
+    output_format: Option<OutputFormat>,
+
+    /// Print the automaton built from the dictionary in the Graphviz DOT format instead of
+    /// searching, e.g. to draw it with `dot -Tsvg`.
+    #[arg(long, default_value_t = false)]
+    dump_dot: bool,
+
+    /// Encoding of the files (e.g. windows-1252 or shift_jis), if not UTF-8. Must be
+    /// compatible with ASCII, so that lines can be split. Columns are byte offsets into the
+    /// encoded lines.
+    #[cfg(feature = "encoding")]
+    #[arg(long, value_parser = parse_encoding)]
+    encoding: Option<&'static trie::Encoding>,
+}
+
+/// Find an encoding by its label, as defined by the WHATWG Encoding Standard.
+#[cfg(feature = "encoding")]
+fn parse_encoding(label: &str) -> Result<&'static trie::Encoding, String> {
+    match trie::Encoding::for_label(label.as_bytes()) {
+        Some(encoding) if encoding.is_ascii_compatible() => Ok(encoding),
+        Some(_) => Err(format!("encoding not compatible with ASCII: {}", label)),
+        None => Err(format!("unknown encoding: {}", label)),
+    }
+}
+
+fn err_to_string<T: Display>(err: T) -> String {
+    format!("Execution failed. Error: {}", err)
+}
+
+/// Destination of the matches: grep-like lines or one of the formats of `MatchWriter`.
+enum Output<W: Write> {
+    Grep(W),
+    Records(MatchWriter<W>),
+}
+
+impl<W: Write> Output<W> {
+    /// Complete the output after the last match.
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Grep(mut out) => out.flush(),
+            Self::Records(writer) => writer.finish().map(|_| ()),
+        }
+    }
+}
+
+/// Search a line, decoding it first if an encoding is given. Otherwise, invalid UTF-8
+/// sequences are replaced, so binary files can be searched too, but then the character
+/// ranges of the matches in the line are byte offsets.
+#[cfg_attr(not(feature = "encoding"), allow(unused_variables))]
+fn search_line<'a>(
+    prefix_tree: &'a TrieRoot,
+    content: &[u8],
+    args: &Args,
+) -> SearchResult<Vec<Match<'a>>> {
+    #[cfg(feature = "encoding")]
+    if let Some(encoding) = args.encoding {
+        return prefix_tree.find_matches_encoded(content, encoding);
+    }
+    match std::str::from_utf8(content) {
+        Ok(text) => prefix_tree.find_text_matches(text),
+        Err(_) => prefix_tree.find_matches_lossy(content, InvalidUtf8::Replace),
+    }
+}
+
+/// Search the lines of a reader, printing the matches. Returns the number of matches found.
+fn grep_reader<R: BufRead, W: Write>(
+    prefix_tree: &TrieRoot,
+    name: &str,
+    mut reader: R,
+    output: &mut Output<W>,
+    args: &Args,
+) -> io::Result<usize> {
+    let mut total = 0;
+    let mut line = Vec::new();
+    let mut line_num = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        line_num += 1;
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+
+        let matches = search_line(prefix_tree, content, args).map_err(io::Error::other)?;
+        total += matches.len();
+        let out = match output {
+            _ if args.count => continue,
+            Output::Grep(out) => out,
+            Output::Records(writer) => {
+                for m in &matches {
+                    writer.write_match(m, name, Some(line_num))?;
+                }
+                continue;
+            }
+        };
+        for m in matches {
+            let range = m.byte_range();
+            write!(out, "{}:{}:{}:", name, line_num, range.start + 1)?;
+            if args.only_matching {
+                out.write_all(&content[range])?;
+                out.write_all(b"\n")?;
+            } else {
+                writeln!(out, "{}", m.keyword())?;
+            }
+        }
+    }
+    if args.count {
+        match output {
+            Output::Grep(out) => writeln!(out, "{}:{}", name, total)?,
+            Output::Records(_) => {}
+        }
+    }
+    Ok(total)
+}
+
+/// Search all the files, returning whether any match was found. Files that can't be read are
+/// reported and skipped.
+fn run(args: &Args) -> Result<bool, String> {
+    let dictionary = trie::read_dictionary(&args.dictionary_file, args.dictionary_format)
+        .map_err(err_to_string)?;
+    let prefix_tree = trie::create_from_dictionary(
+        dictionary,
+        Some(trie::SearchOptions {
+            case_sensitive: !args.case_insensitive,
+            check_bounds: args.word_bounds,
+            ..Default::default()
+        }),
+    )
+    .map_err(err_to_string)?;
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if args.dump_dot {
+        out.write_all(prefix_tree.to_dot().as_bytes())
+            .and_then(|_| out.flush())
+            .map_err(err_to_string)?;
+        return Ok(true);
+    }
+    let mut output = match args.output_format {
+        None => Output::Grep(out),
+        Some(format) => Output::Records(MatchWriter::new(out, format).with_line_numbers()),
+    };
+    let files = match args.files.is_empty() {
+        true => vec![String::from("-")],
+        false => args.files.clone(),
+    };
+
+    let mut found = false;
+    let mut failed = false;
+    for file in &files {
+        let searched = if file == "-" {
+            grep_reader(
+                &prefix_tree,
+                STDIN_NAME,
+                io::stdin().lock(),
+                &mut output,
+                args,
+            )
+        } else {
+            fs::File::open(file).and_then(|f| {
+                grep_reader(&prefix_tree, file, io::BufReader::new(f), &mut output, args)
+            })
+        };
+        match searched {
+            Ok(total) => found |= total > 0,
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(found),
+            Err(e) => {
+                eprintln!("ac-grep: {}: {}", file, e);
+                failed = true;
+            }
+        }
+    }
+    match output.finish() {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(err_to_string(e)),
+        _ => {}
+    }
+    match failed {
+        true => Err(String::from("Some files could not be searched")),
+        false => Ok(found),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(true) => process::exit(0),
+        Ok(false) => process::exit(1),
+        Err(s) => {
+            eprintln!("{s}");
+            process::exit(2);
+        }
+    }
+}
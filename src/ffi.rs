@@ -0,0 +1,344 @@
+//! C API.
+//!
+//! Exposes the matcher to C, C++, Go and other languages with a C foreign function interface,
+//! without going through Python. The declarations are in `include/ac_search.h`.
+//!
+//! A tree is built with `ac_trie_new` from arrays of NUL-terminated UTF-8 patterns and
+//! keywords, and released with `ac_trie_free`. `ac_trie_search` returns the matches of a text
+//! as an `AcMatches` list, read with the `ac_match_*` accessors and released with
+//! `ac_matches_free`. The list owns copies of the values and keywords of the matches, so it
+//! can outlive the tree.
+//!
+//! Functions that fail return NULL, and the reason can be read with `ac_last_error`.
+use super::trie::{InvalidUtf8, SearchOptions, TrieRoot, create_prefix_tree};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+/// Flag of `ac_trie_new` to ignore the case of the patterns and texts
+pub const AC_CASE_INSENSITIVE: u32 = 1;
+
+/// Flag of `ac_trie_new` to report only matches at word boundaries
+pub const AC_CHECK_BOUNDS: u32 = 1 << 1;
+
+/// Flag of `ac_trie_new` to treat hyphens and whitespace in the patterns as optional
+pub const AC_IGNORE_SEPARATORS: u32 = 1 << 2;
+
+thread_local! {
+    /// Description of the last error of the calling thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Record the error of a failed call, to be read with `ac_last_error`.
+fn set_last_error(message: impl Into<Vec<u8>>) {
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Prefix tree built through the C API.
+pub struct AcTrie {
+    trie: TrieRoot,
+}
+
+/// A match found through the C API, owning its strings.
+struct AcMatch {
+    start: usize,
+    end: usize,
+    pattern_id: usize,
+    value: CString,
+    keyword: CString,
+}
+
+/// List of matches returned by `ac_trie_search`.
+pub struct AcMatches {
+    matches: Vec<AcMatch>,
+}
+
+/// Read a NUL-terminated UTF-8 string given by the caller.
+///
+/// # Safety
+/// The pointer must be NULL or point to a NUL-terminated string.
+unsafe fn read_str(s: *const c_char, what: &str) -> Option<String> {
+    if s.is_null() {
+        set_last_error(format!("{} is NULL", what));
+        return None;
+    }
+    match unsafe { CStr::from_ptr(s) }.to_str() {
+        Ok(s) => Some(s.to_string()),
+        Err(_) => {
+            set_last_error(format!("{} is not valid UTF-8", what));
+            None
+        }
+    }
+}
+
+/// Build a prefix tree from `count` patterns, each reported with the keyword at the same
+/// index of `keywords`. Either `keywords` or any of its items can be NULL to report patterns
+/// with themselves as keyword. `flags` is a combination of the `AC_*` flags.
+///
+/// Returns NULL if the dictionary is invalid (e.g. empty or with duplicates).
+///
+/// # Safety
+/// `patterns` must point to `count` NUL-terminated strings, and `keywords` must be NULL or
+/// point to `count` NUL-terminated strings or NULLs.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_trie_new(
+    patterns: *const *const c_char,
+    keywords: *const *const c_char,
+    count: usize,
+    flags: u32,
+) -> *mut AcTrie {
+    if patterns.is_null() {
+        set_last_error("patterns is NULL");
+        return ptr::null_mut();
+    }
+    let mut dictionary = Vec::with_capacity(count);
+    for i in 0..count {
+        let Some(pattern) = (unsafe { read_str(*patterns.add(i), "pattern") }) else {
+            return ptr::null_mut();
+        };
+        let keyword = match keywords.is_null() {
+            true => ptr::null(),
+            false => unsafe { *keywords.add(i) },
+        };
+        let keyword = match keyword.is_null() {
+            true => None,
+            false => match unsafe { read_str(keyword, "keyword") } {
+                None => return ptr::null_mut(),
+                some => some,
+            },
+        };
+        dictionary.push((pattern, keyword));
+    }
+
+    let options = SearchOptions {
+        case_sensitive: flags & AC_CASE_INSENSITIVE == 0,
+        check_bounds: flags & AC_CHECK_BOUNDS != 0,
+        ignore_separators: flags & AC_IGNORE_SEPARATORS != 0,
+        ..Default::default()
+    };
+    match create_prefix_tree(dictionary, Some(options)) {
+        Ok(trie) => Box::into_raw(Box::new(AcTrie { trie })),
+        Err(e) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a tree built with `ac_trie_new`. Does nothing if it is NULL.
+///
+/// # Safety
+/// The tree must have been returned by `ac_trie_new` and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_trie_free(trie: *mut AcTrie) {
+    if !trie.is_null() {
+        drop(unsafe { Box::from_raw(trie) });
+    }
+}
+
+/// Search the `len` bytes of UTF-8 text at `text`, which need not be NUL-terminated. Invalid
+/// UTF-8 sequences are replaced with U+FFFD, so no match can span them. The ranges of the
+/// matches are byte offsets into the text.
+///
+/// Returns NULL if the search fails.
+///
+/// # Safety
+/// `trie` must be a tree returned by `ac_trie_new`, and `text` must point to `len` readable
+/// bytes (or be NULL if `len` is 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_trie_search(
+    trie: *const AcTrie,
+    text: *const c_char,
+    len: usize,
+) -> *mut AcMatches {
+    let Some(trie) = (unsafe { trie.as_ref() }) else {
+        set_last_error("trie is NULL");
+        return ptr::null_mut();
+    };
+    let data = match (text.is_null(), len) {
+        (_, 0) => &[][..],
+        (true, _) => {
+            set_last_error("text is NULL");
+            return ptr::null_mut();
+        }
+        (false, _) => unsafe { std::slice::from_raw_parts(text as *const u8, len) },
+    };
+
+    match trie.trie.find_matches_lossy(data, InvalidUtf8::Replace) {
+        Ok(found) => {
+            let matches = found
+                .iter()
+                .map(|m| AcMatch {
+                    start: m.byte_range().start,
+                    end: m.byte_range().end,
+                    pattern_id: m.pattern_id(),
+                    value: CString::new(m.value()).unwrap_or_default(),
+                    keyword: CString::new(m.keyword()).unwrap_or_default(),
+                })
+                .collect();
+            Box::into_raw(Box::new(AcMatches { matches }))
+        }
+        Err(e) => {
+            set_last_error(e.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Release a list of matches returned by `ac_trie_search`. Does nothing if it is NULL.
+///
+/// # Safety
+/// The list must have been returned by `ac_trie_search` and not released yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_matches_free(matches: *mut AcMatches) {
+    if !matches.is_null() {
+        drop(unsafe { Box::from_raw(matches) });
+    }
+}
+
+/// Get a match of a list, or None if the list is NULL or the index out of bounds.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+unsafe fn get_match<'m>(matches: *const AcMatches, index: usize) -> Option<&'m AcMatch> {
+    unsafe { matches.as_ref() }.and_then(|m| m.matches.get(index))
+}
+
+/// Number of matches in a list. Returns 0 if it is NULL.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_matches_len(matches: *const AcMatches) -> usize {
+    unsafe { matches.as_ref() }.map_or(0, |m| m.matches.len())
+}
+
+/// Byte offset of the start of a match in the searched text. Returns 0 if the index is out of
+/// bounds.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_match_start(matches: *const AcMatches, index: usize) -> usize {
+    unsafe { get_match(matches, index) }.map_or(0, |m| m.start)
+}
+
+/// Byte offset of the end of a match in the searched text. Returns 0 if the index is out of
+/// bounds.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_match_end(matches: *const AcMatches, index: usize) -> usize {
+    unsafe { get_match(matches, index) }.map_or(0, |m| m.end)
+}
+
+/// Index of the matched pattern in the array given to `ac_trie_new`. Returns 0 if the index
+/// is out of bounds.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_match_pattern_id(matches: *const AcMatches, index: usize) -> usize {
+    unsafe { get_match(matches, index) }.map_or(0, |m| m.pattern_id)
+}
+
+/// Matched pattern, in its normalized form, as a NUL-terminated string owned by the list.
+/// Returns NULL if the index is out of bounds.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_match_value(matches: *const AcMatches, index: usize) -> *const c_char {
+    unsafe { get_match(matches, index) }.map_or(ptr::null(), |m| m.value.as_ptr())
+}
+
+/// Keyword of a match, as a NUL-terminated string owned by the list. Returns NULL if the
+/// index is out of bounds.
+///
+/// # Safety
+/// The list must be NULL or returned by `ac_trie_search`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ac_match_keyword(
+    matches: *const AcMatches,
+    index: usize,
+) -> *const c_char {
+    unsafe { get_match(matches, index) }.map_or(ptr::null(), |m| m.keyword.as_ptr())
+}
+
+/// Description of the last error that occurred in the calling thread, or NULL if there was
+/// none. The string is valid until the next failed call in the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn ac_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read a string returned by the C API
+    fn to_str<'s>(s: *const c_char) -> &'s str {
+        unsafe { CStr::from_ptr(s) }.to_str().unwrap()
+    }
+
+    #[test]
+    fn test_ffi_search() {
+        let patterns = [c"New York", c"NYC", c"café"].map(|s| s.as_ptr());
+        let keywords = [ptr::null(), c"New York".as_ptr(), ptr::null()];
+        unsafe {
+            let trie = ac_trie_new(patterns.as_ptr(), keywords.as_ptr(), 3, AC_CASE_INSENSITIVE);
+            assert!(!trie.is_null());
+
+            let text = "nyc café, NEW YORK\u{0}";
+            let matches = ac_trie_search(trie, text.as_ptr() as *const c_char, text.len() - 1);
+            ac_trie_free(trie);
+
+            assert_eq!(ac_matches_len(matches), 3);
+            let found: Vec<_> = (0..3)
+                .map(|i| {
+                    (
+                        ac_match_start(matches, i),
+                        ac_match_end(matches, i),
+                        ac_match_pattern_id(matches, i),
+                        to_str(ac_match_value(matches, i)),
+                        to_str(ac_match_keyword(matches, i)),
+                    )
+                })
+                .collect();
+            assert_eq!(
+                found,
+                [
+                    (0, 3, 1, "nyc", "New York"),
+                    (4, 9, 2, "café", "café"),
+                    (11, 19, 0, "new york", "new york"),
+                ]
+            );
+            assert!(ac_match_value(matches, 3).is_null());
+            assert_eq!(ac_match_end(matches, 3), 0);
+            ac_matches_free(matches);
+        }
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let patterns = [c"ab", c"ab"].map(|s| s.as_ptr());
+        unsafe {
+            let trie = ac_trie_new(patterns.as_ptr(), ptr::null(), 2, 0);
+            assert!(trie.is_null());
+            assert_eq!(to_str(ac_last_error()), "Duplicate node");
+
+            let trie = ac_trie_new(patterns.as_ptr(), ptr::null(), 1, 0);
+            let matches = ac_trie_search(trie, ptr::null(), 3);
+            assert!(matches.is_null());
+            assert_eq!(to_str(ac_last_error()), "text is NULL");
+
+            let matches = ac_trie_search(trie, ptr::null(), 0);
+            assert_eq!(ac_matches_len(matches), 0);
+            ac_matches_free(matches);
+            ac_trie_free(trie);
+            assert_eq!(ac_matches_len(ptr::null()), 0);
+        }
+    }
+}
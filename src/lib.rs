@@ -6,5 +6,12 @@
 #[cfg(feature = "python_bind")]
 pub mod py_bind;
 
+#[cfg(feature = "c_api")]
+pub mod ffi;
 pub mod multi_proc;
+pub mod output;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod trie;
+#[cfg(feature = "wasm_bind")]
+pub mod wasm_bind;
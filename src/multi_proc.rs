@@ -1,7 +1,56 @@
+use crate::trie::{CancelToken, SearchError, SearchResult};
 use log;
+#[cfg(feature = "rayon")]
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(feature = "rayon")]
+use std::sync::{Arc, OnceLock};
 use std::thread;
+use std::time::{Duration, Instant};
 
-const MAX_THREADS: usize = 16;
+/// Default maximum number of threads to use when the caller does not request a number
+const DEFAULT_MAX_THREADS: usize = 16;
+
+/// Environment variable used to override the default maximum number of threads
+pub const MAX_THREADS_VAR: &str = "AC_SEARCH_MAX_THREADS";
+
+/// Maximum number of threads set with `set_max_threads`. Zero means not set.
+static MAX_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the maximum number of threads to use for parallel processing when the caller does not
+/// request a specific number of threads. Passing `None` restores the default.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::multi_proc;
+///
+/// multi_proc::set_max_threads(Some(64));
+/// assert_eq!(multi_proc::max_threads(), 64);
+/// ```
+pub fn set_max_threads(limit: Option<usize>) {
+    MAX_THREADS.store(limit.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Get the maximum number of threads to use for parallel processing when the caller does not
+/// request a specific number of threads.
+///
+/// This is the value given to `set_max_threads` if any, otherwise the value of the
+/// `AC_SEARCH_MAX_THREADS` environment variable if it is set to a positive integer. Defaults
+/// to 16.
+pub fn max_threads() -> usize {
+    match MAX_THREADS.load(Ordering::Relaxed) {
+        0 => match std::env::var(MAX_THREADS_VAR).map(|v| v.trim().parse::<usize>()) {
+            Ok(Ok(i)) if i > 0 => i,
+            Ok(_) => {
+                log::warn!("Invalid value for {}. Using default.", MAX_THREADS_VAR);
+                DEFAULT_MAX_THREADS
+            }
+            Err(_) => DEFAULT_MAX_THREADS,
+        },
+        i => i,
+    }
+}
 
 /// Get the max number of threads to use for parallel processing.
 ///
@@ -19,6 +68,22 @@ pub fn get_total_threads() -> usize {
     }
 }
 
+/// Get the number of threads to use to process the given number of items, given the number
+/// requested by the caller (if any). The default is capped by `max_threads`.
+fn threads_for(num_threads: Option<usize>, total_items: usize) -> usize {
+    match num_threads {
+        None => get_total_threads().min(total_items).min(max_threads()),
+        Some(i) => {
+            if i == 0 {
+                log::warn!("Invalid thread count: {}. Using default.", i);
+                get_total_threads().min(total_items).min(max_threads())
+            } else {
+                i.min(total_items)
+            }
+        }
+    }
+}
+
 /// Apply a function in parallel to the given items. Results will be returned in the
 /// same order as the inputs.
 ///
@@ -29,67 +94,525 @@ pub fn get_total_threads() -> usize {
 /// let items: Vec<i32> = (0..1000).collect();
 /// let mapped: Vec<i32> = multi_proc::parallel_apply(items, |num| num * 2 + 1, None);
 /// ```
-pub fn parallel_apply<T, U, F>(mut items: Vec<T>, mapping: F, num_threads: Option<usize>) -> Vec<U>
+///
+/// With the `rayon` feature, this and the other helpers of this module process the items in a
+/// rayon thread pool, which balances the load by work-stealing. The number of threads is
+/// chosen the same way, so without a number of threads it is capped by `max_threads`.
+pub fn parallel_apply<T, U, F>(items: Vec<T>, mapping: F, num_threads: Option<usize>) -> Vec<U>
 where
     F: Fn(T) -> U + Send + Sync,
-    T: Clone + Send,
+    T: Send,
+    U: Send,
+{
+    parallel_apply_indexed(items, |_, elem| mapping(elem), num_threads, None)
+}
+
+/// Get a rayon thread pool with the given number of threads. The pools are created on first
+/// use and kept for the later calls, so that threads are not spawned for each batch.
+#[cfg(feature = "rayon")]
+fn rayon_pool(num_threads: usize) -> Option<Arc<rayon::ThreadPool>> {
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> = OnceLock::new();
+    let mut pools = POOLS.get_or_init(Default::default).lock().unwrap();
+    if let Some(pool) = pools.get(&num_threads) {
+        return Some(pool.clone());
+    }
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+    {
+        Ok(pool) => Some(pools.entry(num_threads).or_insert(Arc::new(pool)).clone()),
+        Err(e) => {
+            log::warn!(
+                "Unable to build a thread pool: {}. Using the global pool.",
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Run an operation in a rayon thread pool with the given number of threads. The operation
+/// runs in the current pool (by default the global one) if it already has that size.
+#[cfg(feature = "rayon")]
+fn in_rayon_pool<R, F>(num_threads: usize, op: F) -> R
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    if num_threads == rayon::current_num_threads() {
+        return op();
+    }
+    match rayon_pool(num_threads) {
+        Some(pool) => pool.install(op),
+        None => op(),
+    }
+}
+
+/// Apply a function in parallel to the items of a slice, without taking ownership of them.
+/// Results will be returned in the same order as the inputs.
+///
+/// This avoids copying the items (e.g. large texts) when the caller needs to keep them
+/// after processing.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::multi_proc;
+///
+/// let texts = vec![String::from("abc"), String::from("de")];
+/// let lengths = multi_proc::parallel_apply_slice(&texts, |s| s.len(), None);
+/// assert_eq!(lengths, [3, 2]);
+/// assert_eq!(texts[0], "abc");
+/// ```
+pub fn parallel_apply_slice<T, U, F>(items: &[T], mapping: F, num_threads: Option<usize>) -> Vec<U>
+where
+    F: Fn(&T) -> U + Send + Sync,
+    T: Sync,
+    U: Send,
+{
+    parallel_apply_indexed(
+        items.iter().collect(),
+        |_, elem| mapping(elem),
+        num_threads,
+        None,
+    )
+}
+
+/// Apply a fallible function in parallel to the given items. Results will be returned in the
+/// same order as the inputs.
+///
+/// As soon as the function fails for any of the items, the remaining items are skipped and the
+/// error is returned. If several items fail concurrently, the first error to occur is returned.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::multi_proc;
+///
+/// let items = vec!["1", "2", "x", "4"];
+/// let parsed = multi_proc::try_parallel_apply(items.clone(), |s| s.parse::<i32>(), None);
+/// assert!(parsed.is_err());
+///
+/// let parsed = multi_proc::try_parallel_apply(vec!["1", "2"], |s| s.parse::<i32>(), None);
+/// assert_eq!(parsed, Ok(vec![1, 2]));
+/// ```
+pub fn try_parallel_apply<T, U, E, F>(
+    items: Vec<T>,
+    mapping: F,
+    num_threads: Option<usize>,
+) -> Result<Vec<U>, E>
+where
+    F: Fn(T) -> Result<U, E> + Send + Sync,
+    T: Send,
+    U: Send,
+    E: Send,
+{
+    let failed = AtomicBool::new(false);
+    let first_error = Mutex::new(None);
+    let results = parallel_apply_indexed(
+        items,
+        |_, elem| {
+            if failed.load(Ordering::Relaxed) {
+                return None;
+            }
+            match mapping(elem) {
+                Ok(mapped) => Some(mapped),
+                Err(e) => {
+                    if !failed.swap(true, Ordering::Relaxed) {
+                        *first_error.lock().unwrap() = Some(e);
+                    }
+                    None
+                }
+            }
+        },
+        num_threads,
+        None,
+    );
+
+    if let Some(e) = first_error.into_inner().unwrap() {
+        return Err(e);
+    }
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// Apply a fallible function in parallel to the given items, skipping the remaining items once
+/// the token is cancelled. Results will be returned in the same order as the inputs.
+///
+/// Returns a `SearchError::Cancelled` error if the token is cancelled before all the items are
+/// processed, or the first error returned by the function. The items being processed when
+/// the token is cancelled are not interrupted, unless the function checks the token itself
+/// (e.g. searching with `TrieRoot::find_text_matches_cancellable`).
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::{multi_proc, trie};
+/// use ac_search_rs::trie::{CancelToken, SearchError};
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let texts = vec!["ab", "abab"];
+///
+/// let token = CancelToken::new();
+/// let search = |text| prefix_tree.find_text_matches_cancellable(text, &token);
+/// let found = multi_proc::parallel_apply_cancellable(texts.clone(), search, None, &token);
+/// assert_eq!(found.unwrap()[1].len(), 2);
+///
+/// token.cancel();
+/// let found = multi_proc::parallel_apply_cancellable(texts, search, None, &token);
+/// assert_eq!(found.err(), Some(SearchError::Cancelled));
+/// ```
+pub fn parallel_apply_cancellable<T, U, F>(
+    items: Vec<T>,
+    mapping: F,
+    num_threads: Option<usize>,
+    token: &CancelToken,
+) -> SearchResult<Vec<U>>
+where
+    F: Fn(T) -> SearchResult<U> + Send + Sync,
+    T: Send,
+    U: Send,
+{
+    try_parallel_apply(
+        items,
+        |elem| {
+            if token.is_cancelled() {
+                return Err(SearchError::Cancelled);
+            }
+            mapping(elem)
+        },
+        num_threads,
+    )
+}
+
+/// Apply a function in parallel to the given items, passing the index of each item to the
+/// function along with the item. Results will be returned in the same order as the inputs.
+///
+/// The items are split into chunks of `chunk_size` items, which are handed to the threads as
+/// they become idle. Smaller chunks balance the load better when the processing time of the
+/// items is skewed, at the cost of more synchronization. By default, the items are split
+/// evenly between the threads, or each item is its own chunk with the `rayon` feature.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::multi_proc;
+///
+/// let items: Vec<&str> = vec!["a", "bb", "ccc"];
+/// let mapped = multi_proc::parallel_apply_indexed(
+///     items,
+///     |idx, s| format!("{idx}:{s}"),
+///     Some(2),
+///     Some(1),
+/// );
+/// assert_eq!(mapped, ["0:a", "1:bb", "2:ccc"]);
+/// ```
+pub fn parallel_apply_indexed<T, U, F>(
+    items: Vec<T>,
+    mapping: F,
+    num_threads: Option<usize>,
+    chunk_size: Option<usize>,
+) -> Vec<U>
+where
+    F: Fn(usize, T) -> U + Send + Sync,
+    T: Send,
+    U: Send,
+{
+    process_chunks(
+        items,
+        |start, elems, out| {
+            for (offset, elem) in elems.into_iter().enumerate() {
+                out.push(mapping(start + offset, elem));
+            }
+        },
+        num_threads,
+        chunk_size,
+    )
+}
+
+/// Apply a function in parallel to the given items, flattening the values returned for each
+/// item into a single vector. Results will be returned in the same order as the inputs.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::{multi_proc, trie};
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// let texts = vec![String::from("abab"), String::from("cd"), String::from("ab")];
+/// let matches = multi_proc::parallel_flat_map(
+///     texts,
+///     |text| prefix_tree.find_text_matches(text).unwrap(),
+///     None,
+/// );
+/// assert_eq!(matches.len(), 3);
+/// ```
+pub fn parallel_flat_map<T, U, I, F>(
+    items: Vec<T>,
+    mapping: F,
+    num_threads: Option<usize>,
+) -> Vec<U>
+where
+    F: Fn(T) -> I + Send + Sync,
+    I: IntoIterator<Item = U>,
+    T: Send,
+    U: Send,
+{
+    process_chunks(
+        items,
+        |_, elems, out| {
+            for elem in elems {
+                out.extend(mapping(elem));
+            }
+        },
+        num_threads,
+        None,
+    )
+}
+
+/// Split the items into chunks and process them in parallel. The processing function receives
+/// the index of the first item of the chunk, the items and the vector to push the outputs to.
+/// Outputs will be returned in the same order as the chunks.
+///
+/// All the helpers of this module go through this function, so with the `rayon` feature they
+/// all run in a rayon thread pool.
+fn process_chunks<T, U, F>(
+    items: Vec<T>,
+    process: F,
+    num_threads: Option<usize>,
+    chunk_size: Option<usize>,
+) -> Vec<U>
+where
+    F: Fn(usize, Vec<T>, &mut Vec<U>) + Send + Sync,
+    T: Send,
     U: Send,
 {
     if items.is_empty() {
         return Vec::new();
     }
-    let n_threads = match num_threads {
-        None => get_total_threads().min(items.len()).min(MAX_THREADS),
-        Some(i) => {
-            if i == 0 {
-                log::warn!("Invalid thread count: {}. Using default.", i);
-                get_total_threads().min(items.len()).min(MAX_THREADS)
-            } else {
-                i.min(items.len())
-            }
-        }
-    };
+    let total_items = items.len();
+    let pool_threads = threads_for(num_threads, usize::MAX);
+    let n_threads = pool_threads.min(total_items);
 
     log::debug!("Mapping with {} threads", n_threads);
 
     if n_threads == 1 {
-        // Single thread - run simple mapping
-        let mut out = Vec::with_capacity(items.len());
-        for elem in items {
-            out.push(mapping(elem));
-        }
+        // Single thread - process all items as one chunk
+        let mut out = Vec::with_capacity(total_items);
+        process(0, items, &mut out);
         return out;
     }
 
-    let chunk_size = items.len() / n_threads + 1;
-    let mut input_groups: Vec<Vec<T>> = Vec::with_capacity(n_threads);
-    for chunk in items.chunks_mut(chunk_size) {
-        input_groups.push(chunk.into());
+    // Rayon splits the chunks between its threads, so by default each item is its own chunk
+    #[cfg(feature = "rayon")]
+    let default_size = 1;
+    #[cfg(not(feature = "rayon"))]
+    let default_size = total_items / n_threads + 1;
+    let chunk_size = match chunk_size {
+        Some(0) => {
+            log::warn!("Invalid chunk size: 0. Using default.");
+            default_size
+        }
+        Some(size) => size,
+        None => default_size,
+    };
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        let results: Vec<Vec<U>> = in_rayon_pool(pool_threads, || {
+            items
+                .into_par_iter()
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(idx, elems)| {
+                    let mut mapped = Vec::with_capacity(elems.len());
+                    process(idx * chunk_size, elems, &mut mapped);
+                    mapped
+                })
+                .collect()
+        });
+        results.into_iter().flatten().collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        process_chunks_threaded(items, process, n_threads, chunk_size)
     }
+}
 
-    let mut output = Vec::with_capacity(items.len());
+/// Process the chunks of items on the given number of scoped threads, which take the chunks
+/// from a shared queue as they become idle.
+#[cfg(not(feature = "rayon"))]
+fn process_chunks_threaded<T, U, F>(
+    items: Vec<T>,
+    process: F,
+    n_threads: usize,
+    chunk_size: usize,
+) -> Vec<U>
+where
+    F: Fn(usize, Vec<T>, &mut Vec<U>) + Send + Sync,
+    T: Send,
+    U: Send,
+{
+    let total_items = items.len();
+    // Stack of (index of first item, items) chunks to process, with the first chunk on top.
+    // The chunks are split off the end of the input, so the items are moved into the chunks
+    // without being copied, and the first chunk reuses the input's own buffer.
+    let mut chunks = Vec::with_capacity(total_items / chunk_size + 1);
+    let mut items = items;
+    let mut start = (total_items - 1) / chunk_size * chunk_size;
+    while start > 0 {
+        chunks.push((start, items.split_off(start)));
+        start -= chunk_size;
+    }
+    chunks.push((0, items));
+    let queue = Mutex::new(chunks);
 
+    let mut results: Vec<(usize, Vec<U>)> = Vec::with_capacity(total_items / chunk_size + 1);
     thread::scope(|s| {
         let mut handles = Vec::with_capacity(n_threads);
-        for elems in input_groups {
+        for _ in 0..n_threads {
             handles.push(s.spawn(|| {
-                let mut outputs = Vec::with_capacity(elems.len());
-                for elem in elems {
-                    outputs.push(mapping(elem));
+                let mut outputs = Vec::new();
+                loop {
+                    let next = queue.lock().unwrap().pop();
+                    let Some((start, elems)) = next else {
+                        break;
+                    };
+                    let mut mapped = Vec::with_capacity(elems.len());
+                    process(start, elems, &mut mapped);
+                    outputs.push((start, mapped));
                 }
                 outputs
             }));
         }
 
         for h in handles {
-            let mut res = h.join().unwrap();
-            output.append(&mut res);
+            results.append(&mut h.join().unwrap());
         }
     });
 
+    results.sort_unstable_by_key(|(start, _)| *start);
+    let mut output = Vec::with_capacity(results.iter().map(|(_, m)| m.len()).sum());
+    for (_, mut mapped) in results {
+        output.append(&mut mapped);
+    }
     output
 }
 
+/// Apply a function in parallel to the given items, giving each item a time budget. Results
+/// will be returned in the same order as the inputs.
+///
+/// The mapping function receives the deadline for the item along with the item, computed when
+/// processing of the item starts. Threads cannot be interrupted, so the function must check
+/// the deadline itself and give up when it is exceeded (e.g. returning a timeout error), so
+/// that one slow item does not stall the whole batch.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::{multi_proc, trie};
+/// use std::time::Duration;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// let texts = vec![String::from("ab"), String::from("abab")];
+/// let results = multi_proc::parallel_apply_with_budget(
+///     texts,
+///     |text, deadline| prefix_tree.find_text_matches_until(text, deadline),
+///     None,
+///     Duration::from_secs(5),
+/// );
+/// assert_eq!(results[1].as_ref().unwrap().len(), 2);
+/// ```
+pub fn parallel_apply_with_budget<T, U, F>(
+    items: Vec<T>,
+    mapping: F,
+    num_threads: Option<usize>,
+    budget: Duration,
+) -> Vec<U>
+where
+    F: Fn(T, Instant) -> U + Send + Sync,
+    T: Send,
+    U: Send,
+{
+    parallel_apply_indexed(
+        items,
+        |_, elem| mapping(elem, Instant::now() + budget),
+        num_threads,
+        None,
+    )
+}
+
+/// Apply a function in parallel to the given items, reporting the progress of the batch.
+/// Results will be returned in the same order as the inputs.
+///
+/// After each item is processed, `on_progress` is called with the number of items processed
+/// so far and the total number of items, e.g. to update a progress bar. The calls are made
+/// from the worker threads one at a time, with increasing counts.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::multi_proc;
+/// use std::sync::Mutex;
+///
+/// let reported = Mutex::new(Vec::new());
+/// let mapped = multi_proc::parallel_apply_with_progress(
+///     vec![1, 2, 3],
+///     |num| num * 10,
+///     Some(2),
+///     |done, total| reported.lock().unwrap().push((done, total)),
+/// );
+/// assert_eq!(mapped, [10, 20, 30]);
+/// assert_eq!(reported.into_inner().unwrap(), [(1, 3), (2, 3), (3, 3)]);
+/// ```
+pub fn parallel_apply_with_progress<T, U, F, P>(
+    items: Vec<T>,
+    mapping: F,
+    num_threads: Option<usize>,
+    on_progress: P,
+) -> Vec<U>
+where
+    F: Fn(T) -> U + Send + Sync,
+    P: Fn(usize, usize) + Sync,
+    T: Send,
+    U: Send,
+{
+    let progress = Progress::new(items.len(), on_progress);
+    parallel_apply_indexed(
+        items,
+        |_, elem| {
+            let mapped = mapping(elem);
+            progress.item_done();
+            mapped
+        },
+        num_threads,
+        None,
+    )
+}
+
+/// Count of the processed items of a batch, reported to a progress callback.
+pub(crate) struct Progress<P> {
+    done: Mutex<usize>,
+    total: usize,
+    on_progress: P,
+}
+
+impl<P: Fn(usize, usize)> Progress<P> {
+    pub(crate) fn new(total: usize, on_progress: P) -> Self {
+        Self {
+            done: Mutex::new(0),
+            total,
+            on_progress,
+        }
+    }
+
+    /// Count one more item as processed and report it. The lock is held during the call, so
+    /// that the counts are reported in order.
+    pub(crate) fn item_done(&self) {
+        let mut done = self.done.lock().unwrap();
+        *done += 1;
+        (self.on_progress)(*done, self.total);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,4 +630,184 @@ mod tests {
             assert_eq!(idx, item - 1)
         }
     }
+
+    #[test]
+    fn test_parallel_apply_moves_items() {
+        // Items need not be Clone, and are handed to the threads without copying their data
+        struct Text(String);
+
+        let texts: Vec<Text> = (0..40).map(|i| Text(i.to_string())).collect();
+        let buffers: Vec<usize> = texts.iter().map(|t| t.0.as_ptr() as usize).collect();
+        for chunk_size in [None, Some(1), Some(7), Some(40), Some(100)] {
+            let texts: Vec<Text> = (0..40).map(|i| Text(i.to_string())).collect();
+            let values = parallel_apply_indexed(texts, |_, t| t.0, Some(3), chunk_size);
+            assert_eq!(values, (0..40).map(|i| i.to_string()).collect::<Vec<_>>());
+        }
+
+        let moved = parallel_apply(texts, |t| t.0.as_ptr() as usize, Some(3));
+        assert_eq!(moved, buffers);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_rayon_pools() {
+        // Pools are kept for the later calls with the same number of threads
+        let pool = rayon_pool(3).unwrap();
+        assert!(Arc::ptr_eq(&pool, &rayon_pool(3).unwrap()));
+        assert_eq!(pool.current_num_threads(), 3);
+
+        // All the helpers run in a pool of the requested size
+        let sizes = parallel_apply(
+            (0..20).collect(),
+            |_: usize| rayon::current_num_threads(),
+            Some(3),
+        );
+        assert!(sizes.iter().all(|&n| n == 3));
+        let sizes = parallel_apply_slice(&[1, 2, 3], |_| rayon::current_num_threads(), Some(2));
+        assert_eq!(sizes, [2, 2, 2]);
+        let sizes: Result<Vec<usize>, ()> = try_parallel_apply(
+            (0..20).collect(),
+            |_: usize| Ok(rayon::current_num_threads()),
+            Some(3),
+        );
+        assert!(sizes.unwrap().iter().all(|&n| n == 3));
+
+        // Without a number of threads, the pool is capped by the thread ceiling
+        let sizes = parallel_apply(
+            (0..20).collect(),
+            |_: usize| rayon::current_num_threads(),
+            None,
+        );
+        let expected = threads_for(None, usize::MAX);
+        assert!(sizes.iter().all(|&n| n == expected || expected == 1));
+    }
+
+    #[test]
+    fn test_parallel_apply_with_progress() {
+        let reported = Mutex::new(Vec::new());
+        let mapped = parallel_apply_with_progress(
+            (0..100).collect(),
+            |num: usize| num * 2,
+            Some(4),
+            |done, total| reported.lock().unwrap().push((done, total)),
+        );
+        assert_eq!(mapped, (0..100).map(|n| n * 2).collect::<Vec<_>>());
+        let expected: Vec<(usize, usize)> = (1..=100).map(|d| (d, 100)).collect();
+        assert_eq!(reported.into_inner().unwrap(), expected);
+
+        let calls = AtomicUsize::new(0);
+        let mapped: Vec<usize> = parallel_apply_with_progress(
+            Vec::new(),
+            |n| n,
+            None,
+            |_, _| {
+                calls.fetch_add(1, Ordering::Relaxed);
+            },
+        );
+        assert!(mapped.is_empty());
+        assert_eq!(calls.into_inner(), 0);
+    }
+
+    #[test]
+    fn test_parallel_apply_cancellable() {
+        let token = CancelToken::new();
+        let mapped =
+            parallel_apply_cancellable((0..50).collect(), |n: usize| Ok(n + 1), Some(3), &token);
+        assert_eq!(mapped, Ok((1..=50).collect()));
+
+        // Cancelling from one item skips the items not yet started
+        let processed = AtomicUsize::new(0);
+        let mapped = parallel_apply_cancellable(
+            (0..1000).collect(),
+            |n: usize| {
+                processed.fetch_add(1, Ordering::Relaxed);
+                if n == 10 {
+                    token.cancel();
+                }
+                Ok(n)
+            },
+            Some(2),
+            &token,
+        );
+        assert_eq!(mapped, Err(SearchError::Cancelled));
+        assert!(processed.into_inner() < 1000);
+    }
+
+    #[test]
+    fn test_parallel_apply_slice() {
+        let my_inputs: Vec<String> = (0..50).map(|i| i.to_string()).collect();
+        let mapped = parallel_apply_slice(&my_inputs, |s| s.parse::<usize>().unwrap(), Some(3));
+        assert_eq!(mapped, (0..50).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn test_parallel_flat_map() {
+        let mapped = parallel_flat_map((0..20).collect(), |num: usize| 0..num, Some(3));
+        let expected: Vec<usize> = (0..20).flat_map(|num| 0..num).collect();
+        assert_eq!(mapped, expected);
+    }
+
+    #[test]
+    fn test_try_parallel_apply() {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let result = try_parallel_apply(
+            (0..1000).collect(),
+            |num: usize| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                if num == 10 { Err(num) } else { Ok(num * 2) }
+            },
+            Some(1),
+        );
+        assert_eq!(result, Err(10));
+        assert_eq!(calls.load(Ordering::Relaxed), 11);
+
+        let result: Result<Vec<usize>, ()> =
+            try_parallel_apply((0..100).collect(), |num: usize| Ok(num * 2), Some(4));
+        assert_eq!(result.unwrap(), (0..200).step_by(2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_threads_for() {
+        assert_eq!(threads_for(Some(40), 100), 40);
+        assert_eq!(threads_for(Some(40), 10), 10);
+        assert!(threads_for(None, 100) <= max_threads());
+    }
+
+    #[test]
+    fn test_parallel_apply_with_budget() {
+        let budget = Duration::from_millis(20);
+        let mapped = parallel_apply_with_budget(
+            vec![1u64, 500, 2],
+            |millis, deadline| {
+                let start = Instant::now();
+                while start.elapsed() < Duration::from_millis(millis) {
+                    if Instant::now() >= deadline {
+                        return Err(millis);
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok(millis)
+            },
+            Some(2),
+            budget,
+        );
+        assert_eq!(mapped, [Ok(1), Err(500), Ok(2)]);
+    }
+
+    #[test]
+    fn test_parallel_apply_indexed() {
+        let my_inputs: Vec<usize> = (0..103).map(|i| i * 2).collect();
+        for chunk_size in [None, Some(1), Some(7), Some(500)] {
+            let mapped = parallel_apply_indexed(
+                my_inputs.clone(),
+                |idx, num| (idx, num),
+                Some(4),
+                chunk_size,
+            );
+            assert_eq!(mapped.len(), my_inputs.len());
+            for (idx, &(i, num)) in mapped.iter().enumerate() {
+                assert_eq!((i, num), (idx, 2 * idx));
+            }
+        }
+    }
 }
@@ -0,0 +1,319 @@
+//! Output formats for search results.
+//!
+//! Writes the matches found in files as TSV, CSV, JSON or JSON lines, so that the results of
+//! the command line tools can be fed to other programs (e.g. jq or pandas). Every format has
+//! the same fields for each match: the `source` file, optionally the `line` number (from 1),
+//! the character range (`start`, `end`), the byte range (`start_byte`, `end_byte`), the
+//! `value`, the `keyword` and the `category`.
+use super::trie::Match;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Format of the matches written by `MatchWriter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Tab separated values, with a header line
+    #[default]
+    Tsv,
+
+    /// Comma separated values, with a header line. Fields are quoted when needed.
+    Csv,
+
+    /// A single JSON array of objects
+    Json,
+
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// Get a format from its name ("tsv", "csv", "json" or "jsonl"). Returns None if the name
+    /// is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tsv" => Some(Self::Tsv),
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "jsonl" => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| format!("Unknown output format: {}", s))
+    }
+}
+
+/// Write a string as a JSON string literal.
+fn write_json_str<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    let mut copied = 0;
+    for (i, c) in s.char_indices() {
+        if c >= ' ' && c != '"' && c != '\\' {
+            continue;
+        }
+        out.write_all(&s.as_bytes()[copied..i])?;
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            _ => write!(out, "\\u{:04x}", c as u32)?,
+        }
+        copied = i + c.len_utf8();
+    }
+    out.write_all(&s.as_bytes()[copied..])?;
+    out.write_all(b"\"")
+}
+
+/// Write a field of a TSV file, escaping the tabs, line breaks and backslashes in it.
+fn write_tsv_field<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    if !s.contains(['\t', '\n', '\r', '\\']) {
+        return out.write_all(s.as_bytes());
+    }
+    for c in s.chars() {
+        match c {
+            '\t' => out.write_all(b"\\t")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\\' => out.write_all(b"\\\\")?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Write a field of a CSV file, quoting it if it contains separators, quotes or line breaks.
+fn write_csv_field<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    if s.contains([',', '"', '\n', '\r']) {
+        write!(out, "\"{}\"", s.replace('"', "\"\""))
+    } else {
+        out.write_all(s.as_bytes())
+    }
+}
+
+/// Writes matches to an output in one of the supported formats.
+///
+/// The header (or opening bracket of JSON) is written with the first match, and `finish` must
+/// be called after the last one to complete the output.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::output::{MatchWriter, OutputFormat};
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("café")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// let mut writer = MatchWriter::new(Vec::new(), OutputFormat::Jsonl);
+/// for m in prefix_tree.find_text_matches("un café").unwrap() {
+///     writer.write_match(&m, "menu.txt", None).unwrap();
+/// }
+/// let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+/// assert_eq!(
+///     out,
+///     "{\"source\":\"menu.txt\",\"start\":3,\"end\":7,\"start_byte\":3,\"end_byte\":8,\
+///      \"value\":\"café\",\"keyword\":\"café\",\"category\":null}\n"
+/// );
+/// ```
+pub struct MatchWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    line_numbers: bool,
+    written: usize,
+}
+
+impl<W: Write> MatchWriter<W> {
+    /// Instantiate a writer of matches in the given format.
+    pub fn new(out: W, format: OutputFormat) -> Self {
+        Self {
+            out,
+            format,
+            line_numbers: false,
+            written: 0,
+        }
+    }
+
+    /// Include the number of the line of each match, for texts searched line by line.
+    pub fn with_line_numbers(mut self) -> Self {
+        self.line_numbers = true;
+        self
+    }
+
+    /// Write the header of the output, before the first match.
+    fn write_header(&mut self) -> io::Result<()> {
+        let sep = match self.format {
+            OutputFormat::Tsv => "\t",
+            OutputFormat::Csv => ",",
+            OutputFormat::Json => return self.out.write_all(b"["),
+            OutputFormat::Jsonl => return Ok(()),
+        };
+        let mut columns = vec!["source"];
+        if self.line_numbers {
+            columns.push("line");
+        }
+        columns.extend([
+            "start",
+            "end",
+            "start_byte",
+            "end_byte",
+            "value",
+            "keyword",
+            "category",
+        ]);
+        writeln!(self.out, "{}", columns.join(sep))
+    }
+
+    /// Write a match found in the given source (e.g. file name), at the given line if line
+    /// numbers are included.
+    pub fn write_match(&mut self, m: &Match, source: &str, line: Option<usize>) -> io::Result<()> {
+        if self.written == 0 {
+            self.write_header()?;
+        }
+        self.written += 1;
+        let (start, end) = m.char_range();
+        let bytes = m.byte_range();
+        let line = line.filter(|_| self.line_numbers);
+
+        match self.format {
+            OutputFormat::Tsv | OutputFormat::Csv => {
+                let mut fields = vec![source.to_string()];
+                if self.line_numbers {
+                    fields.push(line.map(|l| l.to_string()).unwrap_or_default());
+                }
+                fields.extend([start, end, bytes.start, bytes.end].map(|n| n.to_string()));
+                fields.extend(
+                    [m.value(), m.keyword(), m.category().unwrap_or_default()].map(String::from),
+                );
+                for (i, field) in fields.iter().enumerate() {
+                    match (self.format, i) {
+                        (_, 0) => {}
+                        (OutputFormat::Tsv, _) => self.out.write_all(b"\t")?,
+                        _ => self.out.write_all(b",")?,
+                    }
+                    match self.format {
+                        OutputFormat::Csv => write_csv_field(&mut self.out, field)?,
+                        _ => write_tsv_field(&mut self.out, field)?,
+                    }
+                }
+                self.out.write_all(b"\n")
+            }
+            OutputFormat::Json | OutputFormat::Jsonl => {
+                if self.format == OutputFormat::Json && self.written > 1 {
+                    self.out.write_all(b",")?;
+                }
+                self.out.write_all(b"{\"source\":")?;
+                write_json_str(&mut self.out, source)?;
+                if self.line_numbers {
+                    match line {
+                        Some(l) => write!(self.out, ",\"line\":{}", l)?,
+                        None => self.out.write_all(b",\"line\":null")?,
+                    }
+                }
+                write!(
+                    self.out,
+                    ",\"start\":{},\"end\":{},\"start_byte\":{},\"end_byte\":{},\"value\":",
+                    start, end, bytes.start, bytes.end
+                )?;
+                write_json_str(&mut self.out, m.value())?;
+                self.out.write_all(b",\"keyword\":")?;
+                write_json_str(&mut self.out, m.keyword())?;
+                self.out.write_all(b",\"category\":")?;
+                match m.category() {
+                    Some(c) => write_json_str(&mut self.out, c)?,
+                    None => self.out.write_all(b"null")?,
+                }
+                self.out.write_all(b"}")?;
+                if self.format == OutputFormat::Jsonl {
+                    self.out.write_all(b"\n")?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Complete the output after the last match, writing the header if there were no
+    /// matches, and flush it. Returns the underlying output.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.written == 0 {
+            self.write_header()?;
+        }
+        if self.format == OutputFormat::Json {
+            self.out.write_all(b"]\n")?;
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::trie::create_gazetteer;
+    use super::*;
+
+    /// Write the matches of the text in the given format
+    fn write_all(format: OutputFormat, text: &str, line_numbers: bool) -> String {
+        let pt = create_gazetteer(
+            vec![
+                (String::from("a,b"), None, Some(String::from("X"))),
+                (String::from("é\""), Some(String::from("q\t\u{1}")), None),
+            ],
+            None,
+        )
+        .unwrap();
+        let mut writer = MatchWriter::new(Vec::new(), format);
+        if line_numbers {
+            writer = writer.with_line_numbers();
+        }
+        for m in pt.find_text_matches(text).unwrap() {
+            writer.write_match(&m, "in.txt", Some(2)).unwrap();
+        }
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_output_formats() {
+        let text = "a,b é\"";
+        assert_eq!(
+            write_all(OutputFormat::Tsv, text, true),
+            "source\tline\tstart\tend\tstart_byte\tend_byte\tvalue\tkeyword\tcategory\n\
+             in.txt\t2\t0\t3\t0\t3\ta,b\ta,b\tX\n\
+             in.txt\t2\t4\t6\t4\t7\té\"\tq\\t\u{1}\t\n"
+        );
+        assert_eq!(
+            write_all(OutputFormat::Csv, text, false),
+            "source,start,end,start_byte,end_byte,value,keyword,category\n\
+             in.txt,0,3,0,3,\"a,b\",\"a,b\",X\n\
+             in.txt,4,6,4,7,\"é\"\"\",q\t\u{1},\n"
+        );
+        assert_eq!(
+            write_all(OutputFormat::Json, text, false),
+            "[{\"source\":\"in.txt\",\"start\":0,\"end\":3,\"start_byte\":0,\"end_byte\":3,\
+             \"value\":\"a,b\",\"keyword\":\"a,b\",\"category\":\"X\"},\
+             {\"source\":\"in.txt\",\"start\":4,\"end\":6,\"start_byte\":4,\"end_byte\":7,\
+             \"value\":\"é\\\"\",\"keyword\":\"q\\t\\u0001\",\"category\":null}]\n"
+        );
+        assert_eq!(
+            write_all(OutputFormat::Jsonl, "a,b", true),
+            "{\"source\":\"in.txt\",\"line\":2,\"start\":0,\"end\":3,\"start_byte\":0,\
+             \"end_byte\":3,\"value\":\"a,b\",\"keyword\":\"a,b\",\"category\":\"X\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_output_no_matches() {
+        assert_eq!(write_all(OutputFormat::Json, "xyz", false), "[]\n");
+        assert_eq!(write_all(OutputFormat::Jsonl, "xyz", false), "");
+        assert_eq!(
+            write_all(OutputFormat::Csv, "", false),
+            "source,start,end,start_byte,end_byte,value,keyword,category\n"
+        );
+        assert_eq!(OutputFormat::from_str("jsonl"), Ok(OutputFormat::Jsonl));
+        assert!(OutputFormat::from_str("xml").is_err());
+    }
+}
@@ -3,15 +3,19 @@
 //! This module provides wrappers and python bindings to access the package
 //! functionality from Python.
 use super::multi_proc;
+use super::trie::cancel::Interrupt;
 use super::trie::*;
 use pyo3::exceptions as py_errs;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
-use std::collections::HashSet;
+use pyo3::types::{PyBytes, PyDict, PySlice, PyString};
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use unicode_normalization::UnicodeNormalization;
 
-/// Normalize the given string to unicode NFC standard. This is needed to
-/// properly check word bounds.
+/// Normalize the given string to unicode NFC standard. Searches apply this
+/// normalization internally, reporting character ranges of the input text.
 #[pyfunction]
 #[pyo3(signature = (input: "str") -> "str")]
 fn normalize_string(input: String) -> String {
@@ -34,6 +38,33 @@ fn map_error_py(err: SearchError) -> PyErr {
             "Node {} does not have a fallback link!",
             i
         )),
+        SearchError::InvalidCheckpoint => {
+            PyErr::new::<py_errs::PyValueError, _>("Invalid or incompatible search checkpoint!")
+        }
+        SearchError::PatternNotFound(p) => {
+            PyErr::new::<py_errs::PyKeyError, _>(format!("Pattern not found in dictionary: {}", p))
+        }
+        SearchError::DictionaryFile(e) => {
+            PyErr::new::<py_errs::PyIOError, _>(format!("Failed to read dictionary file: {}", e))
+        }
+        SearchError::Timeout => PyErr::new::<py_errs::PyTimeoutError, _>("Search timed out!"),
+        SearchError::Cancelled => PyErr::new::<py_errs::PyRuntimeError, _>("Search cancelled!"),
+        SearchError::Io(_, e) => PyErr::new::<py_errs::PyIOError, _>(format!("IO error: {}", e)),
+        SearchError::InvalidOptions(e) => {
+            PyErr::new::<py_errs::PyValueError, _>(format!("Invalid search options: {}", e))
+        }
+        SearchError::LimitExceeded(e) => {
+            PyErr::new::<py_errs::PyValueError, _>(format!("Search limit exceeded: {}", e))
+        }
+        SearchError::Serialization(e) => {
+            PyErr::new::<py_errs::PyValueError, _>(format!("Serialization error: {}", e))
+        }
+        SearchError::BuildBudget(e) => {
+            PyErr::new::<py_errs::PyValueError, _>(format!("Build budget exceeded: {}", e))
+        }
+        SearchError::InvalidText(e) => {
+            PyErr::new::<py_errs::PyValueError, _>(format!("Invalid text: {}", e))
+        }
     }
 }
 
@@ -56,6 +87,26 @@ pub struct PyMatch {
     ///End of the match character range in the input text
     #[pyo3(get)]
     pub to_char: usize,
+
+    /// Start of the match byte range in the input text encoded as UTF-8
+    #[pyo3(get)]
+    pub from_byte: usize,
+
+    /// End of the match byte range in the input text encoded as UTF-8
+    #[pyo3(get)]
+    pub to_byte: usize,
+
+    /// Confidence score of the match, if computed by the search
+    #[pyo3(get)]
+    pub score: Option<f32>,
+
+    /// Category (entity label) of the matched pattern, if any
+    #[pyo3(get)]
+    pub category: Option<String>,
+
+    /// ID of the matched pattern: the position of its entry in the dictionary
+    #[pyo3(get)]
+    pub pattern_id: usize,
 }
 
 impl<'a> From<&'a Match<'a>> for PyMatch {
@@ -63,11 +114,17 @@ impl<'a> From<&'a Match<'a>> for PyMatch {
     /// in the Python API
     fn from(m: &'a Match) -> Self {
         let (start, end) = m.char_range();
+        let bytes = m.byte_range();
         Self {
             value: m.value().to_string(),
             kw: m.keyword().to_string(),
             from_char: start,
             to_char: end,
+            from_byte: bytes.start,
+            to_byte: bytes.end,
+            score: m.score(),
+            category: m.category().map(String::from),
+            pattern_id: m.pattern_id(),
         }
     }
 }
@@ -75,7 +132,7 @@ impl<'a> From<&'a Match<'a>> for PyMatch {
 #[pymethods]
 impl PyMatch {
     /// Initialize a new match given the start and end of its character range, and the
-    /// string value.
+    /// string value. The byte range is set equal to the character range.
     #[new]
     #[pyo3(signature = (from_char: "int", to_char: "int", value: "str", keyword: "str"))]
     pub fn new(from_char: usize, to_char: usize, value: String, keyword: String) -> PyResult<Self> {
@@ -92,11 +149,82 @@ impl PyMatch {
         Ok(Self {
             from_char,
             to_char,
+            from_byte: from_char,
+            to_byte: to_char,
             value,
             kw: keyword,
+            score: None,
+            category: None,
+            pattern_id: 0,
         })
     }
 
+    /// Get the range of bytes of the match as a slice, to index the UTF-8 encoded text.
+    #[pyo3(signature = () -> "slice")]
+    pub fn byte_range<'py>(&self, py: Python<'py>) -> Bound<'py, PySlice> {
+        PySlice::new(py, self.from_byte as isize, self.to_byte as isize, 1)
+    }
+
+    /// Get the attributes of the match as a dictionary, e.g. to build a pandas DataFrame with
+    /// `DataFrame([m.to_dict() for m in matches])`.
+    #[pyo3(signature = () -> "dict[str, str | int | float | None]")]
+    pub fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("value", &self.value)?;
+        dict.set_item("kw", &self.kw)?;
+        dict.set_item("from_char", self.from_char)?;
+        dict.set_item("to_char", self.to_char)?;
+        dict.set_item("from_byte", self.from_byte)?;
+        dict.set_item("to_byte", self.to_byte)?;
+        dict.set_item("score", self.score)?;
+        dict.set_item("category", &self.category)?;
+        dict.set_item("pattern_id", self.pattern_id)?;
+        Ok(dict)
+    }
+
+    /// Iterate over the character range, value and keyword of the match, so that it can be
+    /// unpacked as `from_char, to_char, value, kw = m`.
+    pub fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        (self.from_char, self.to_char, &self.value, &self.kw)
+            .into_pyobject(py)?
+            .try_iter()
+            .map(Bound::into_any)
+    }
+
+    pub fn __len__(&self) -> usize {
+        4
+    }
+
+    /// Matches are equal if all their attributes are equal.
+    pub fn __eq__(&self, other: PyRef<'_, Self>) -> bool {
+        self.value == other.value
+            && self.kw == other.kw
+            && self.from_char == other.from_char
+            && self.to_char == other.to_char
+            && self.from_byte == other.from_byte
+            && self.to_byte == other.to_byte
+            && self.score == other.score
+            && self.category == other.category
+            && self.pattern_id == other.pattern_id
+    }
+
+    pub fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            &self.value,
+            &self.kw,
+            self.from_char,
+            self.to_char,
+            self.from_byte,
+            self.to_byte,
+            self.score.map(f32::to_bits),
+            &self.category,
+            self.pattern_id,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn __repr__(&self) -> String {
         format!(
             "PyMatch(from_char={}, to_char={}, value=\"{}\", kw=\"{}\")",
@@ -112,11 +240,13 @@ impl PyMatch {
 ///
 /// This is a wrapper around the Rust prefix tree implementation to avoid
 /// recomputing the trie unnecessarily when calling from Python. This wrapper
-/// is essentially immutable once created.
-#[pyclass]
+/// is essentially immutable once created, except for adding patterns with
+/// "add_patterns".
+#[pyclass(module = "ac_search.ac_search_rs")]
 pub struct PyTrie {
-    /// The Rust implemented Trie that is wrapped
-    trie_inner: TrieRoot,
+    /// The Rust implemented Trie that is wrapped. Shared with the iterators of "finditer",
+    /// which borrow it.
+    trie_inner: Arc<TrieRoot>,
 
     /// The list of keywords stored in the trie
     #[pyo3(get)]
@@ -125,32 +255,203 @@ pub struct PyTrie {
 
 #[pymethods]
 impl PyTrie {
-    /// Instantiate a prefix tree from a mapping of pattern -> keyword
+    /// Instantiate a prefix tree from a mapping of pattern -> keyword, or an iterable of
+    /// (pattern, keyword) tuples, which avoids building a large dict first. Optionally, a
+    /// mapping of pattern -> category (entity label) can be given to report the category of
+    /// the matches.
     #[new]
-    #[pyo3(signature = (dictionary: "dict[str, str]", case_sensitive=true, check_bounds=false))]
+    #[pyo3(signature = (
+        dictionary: "dict[str, str] | Iterable[tuple[str, str]]",
+        case_sensitive=true,
+        check_bounds=false,
+        compute_scores=false,
+        categories: "dict[str, str] | None" = None,
+        track_coverage=false,
+        max_chain_depth: "int | None" = None,
+        match_kind: "str" = "overlapping",
+        word_segmentation=false,
+        whitespace: "str" = "exact",
+        wildcard: "str | None" = None,
+        max_matches: "int | None" = None,
+        max_text_chars: "int | None" = None,
+        time_limit: "float | None" = None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        dictionary: &Bound<'_, PyDict>,
+        dictionary: &Bound<'_, PyAny>,
         case_sensitive: bool,
         check_bounds: bool,
+        compute_scores: bool,
+        categories: Option<&Bound<'_, PyDict>>,
+        track_coverage: bool,
+        max_chain_depth: Option<usize>,
+        match_kind: &str,
+        word_segmentation: bool,
+        whitespace: &str,
+        wildcard: Option<char>,
+        max_matches: Option<usize>,
+        max_text_chars: Option<usize>,
+        time_limit: Option<f64>,
     ) -> PyResult<Self> {
-        let entries = py_dict_to_vector(dictionary)?;
-        let opts = Some(SearchOptions {
+        let opts = search_options(
             case_sensitive,
             check_bounds,
-        });
-        let trie_inner = create_prefix_tree(entries, opts).map_err(map_error_py)?;
+            compute_scores,
+            max_chain_depth,
+            match_kind,
+            word_segmentation,
+            whitespace,
+            wildcard,
+            max_matches,
+            max_text_chars,
+            time_limit,
+        )?;
+        let pairs = py_pairs_to_vector(dictionary)?;
+        let mut entries = Vec::with_capacity(pairs.len());
+        for (pattern, keyword) in pairs {
+            let category = match categories {
+                None => None,
+                Some(dct) => dct.get_item(&pattern)?.map(|c| c.extract()).transpose()?,
+            };
+            entries.push((pattern, keyword, category));
+        }
+        let mut trie_inner = create_gazetteer(entries, Some(opts)).map_err(map_error_py)?;
+        if track_coverage {
+            trie_inner.track_coverage();
+        }
+        Ok(Self::from_trie(trie_inner))
+    }
 
-        // Avoid storing duplicates
-        let mut keywords = HashSet::with_capacity(dictionary.len());
-        for node in trie_inner.nodes_vec() {
-            if let Some((_, keyword)) = node.value_keyword() {
-                keywords.insert(keyword.to_string());
-            }
+    /// Instantiate a prefix tree from a dictionary file, read in Rust without building the
+    /// dictionary in Python. The format ("tsv", "csv" or "json") is given by the file
+    /// extension if not set. Besides the pattern, each entry can set its keyword, category,
+    /// case sensitivity, word bounds and metadata, as described in the README. The search
+    /// options are the same as those of the constructor.
+    #[staticmethod]
+    #[pyo3(signature = (
+        path: "str",
+        format: "str | None" = None,
+        case_sensitive=true,
+        check_bounds=false,
+        compute_scores=false,
+        track_coverage=false,
+        max_chain_depth: "int | None" = None,
+        match_kind: "str" = "overlapping",
+        word_segmentation=false,
+        whitespace: "str" = "exact",
+        wildcard: "str | None" = None,
+        max_matches: "int | None" = None,
+        max_text_chars: "int | None" = None,
+        time_limit: "float | None" = None) -> "PyTrie")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_file(
+        path: &str,
+        format: Option<&str>,
+        case_sensitive: bool,
+        check_bounds: bool,
+        compute_scores: bool,
+        track_coverage: bool,
+        max_chain_depth: Option<usize>,
+        match_kind: &str,
+        word_segmentation: bool,
+        whitespace: &str,
+        wildcard: Option<char>,
+        max_matches: Option<usize>,
+        max_text_chars: Option<usize>,
+        time_limit: Option<f64>,
+    ) -> PyResult<Self> {
+        let format = format
+            .map(|name| {
+                DictionaryFormat::from_name(name).ok_or_else(|| {
+                    PyErr::new::<py_errs::PyValueError, _>(format!(
+                        "Unknown dictionary format: {}",
+                        name
+                    ))
+                })
+            })
+            .transpose()?;
+        let opts = search_options(
+            case_sensitive,
+            check_bounds,
+            compute_scores,
+            max_chain_depth,
+            match_kind,
+            word_segmentation,
+            whitespace,
+            wildcard,
+            max_matches,
+            max_text_chars,
+            time_limit,
+        )?;
+        let entries = read_dictionary(path, format).map_err(map_error_py)?;
+        let mut trie_inner = create_from_dictionary(entries, Some(opts)).map_err(map_error_py)?;
+        if track_coverage {
+            trie_inner.track_coverage();
         }
-        Ok(Self {
-            trie_inner,
-            keywords: keywords.drain().collect(),
-        })
+        Ok(Self::from_trie(trie_inner))
+    }
+
+    /// Load a prefix tree from a file written with "save".
+    #[staticmethod]
+    #[pyo3(signature = (path: "str") -> "PyTrie")]
+    pub fn load(path: &str) -> PyResult<Self> {
+        let trie_inner = TrieRoot::load(path).map_err(map_error_py)?;
+        Ok(Self::from_trie(trie_inner))
+    }
+
+    /// Save the prefix tree to a file, to load it later without building it again.
+    #[pyo3(signature = (path: "str") -> "None")]
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        self.trie_inner.save(path).map_err(map_error_py)
+    }
+
+    /// Serialize the prefix tree into bytes, to rebuild it later with "from_bytes".
+    #[pyo3(signature = () -> "bytes")]
+    pub fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.trie_inner.to_bytes())
+    }
+
+    /// Rebuild a prefix tree serialized with "to_bytes".
+    #[staticmethod]
+    #[pyo3(signature = (data: "bytes") -> "PyTrie")]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let trie_inner = TrieRoot::from_bytes(data).map_err(map_error_py)?;
+        Ok(Self::from_trie(trie_inner))
+    }
+
+    /// Support pickling the prefix tree, e.g. to send it to multiprocessing workers. It is
+    /// pickled in its serialized form, so it is not built again when unpickled.
+    pub fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyBytes>,))> {
+        let from_bytes = slf.get_type().getattr("from_bytes")?;
+        Ok((from_bytes, (slf.borrow().to_bytes(slf.py()),)))
+    }
+
+    /// Add new entries from a mapping of pattern -> keyword, or an iterable of (pattern,
+    /// keyword) tuples, to the prefix tree, without building it again. Raises an error if a
+    /// pattern is already in the tree.
+    #[pyo3(signature = (dictionary: "dict[str, str] | Iterable[tuple[str, str]]") -> "None")]
+    pub fn add_patterns(&mut self, dictionary: &Bound<'_, PyAny>) -> PyResult<()> {
+        let patterns = py_pairs_to_vector(dictionary)?;
+        Arc::get_mut(&mut self.trie_inner)
+            .ok_or_else(|| {
+                PyErr::new::<py_errs::PyRuntimeError, _>(
+                    "Can't add patterns while iterating over matches!",
+                )
+            })?
+            .add_patterns(patterns)
+            .map_err(map_error_py)?;
+        self.keywords = trie_keywords(&self.trie_inner);
+        Ok(())
+    }
+
+    /// Return the (pattern, keyword) entry with the given ID, as reported by the
+    /// "pattern_id" of the matches, or None if there is no pattern with that ID.
+    #[pyo3(signature = (pattern_id: "int") -> "tuple[str, str] | None")]
+    pub fn pattern(&self, pattern_id: usize) -> Option<(String, String)> {
+        self.trie_inner
+            .pattern(pattern_id)
+            .map(|(p, k)| (p.to_string(), k.to_string()))
     }
 
     /// Return the total number of nodes in the prefix tree
@@ -158,33 +459,298 @@ impl PyTrie {
         self.trie_inner.total_nodes()
     }
 
-    /// Search for occurrences of the defined patterns in the given text
-    #[pyo3(signature = (text: "str") -> "list[PyMatch]")]
-    pub fn search(&self, text: String) -> PyResult<Vec<PyMatch>> {
+    /// Return statistics of the size and shape of the prefix tree as a dictionary, with the
+    /// numbers of nodes, edges and patterns, the maximum and average depth of the nodes, the
+    /// largest number of edges leaving a node and an estimate of the memory used in bytes.
+    #[pyo3(signature = () -> "dict[str, int | float]")]
+    pub fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = self.trie_inner.stats();
+        let dict = PyDict::new(py);
+        dict.set_item("total_nodes", stats.total_nodes)?;
+        dict.set_item("total_edges", stats.total_edges)?;
+        dict.set_item("total_patterns", stats.total_patterns)?;
+        dict.set_item("max_depth", stats.max_depth)?;
+        dict.set_item("avg_depth", stats.avg_depth)?;
+        dict.set_item("max_fan_out", stats.max_fan_out)?;
+        dict.set_item("heap_bytes", stats.heap_bytes)?;
+        Ok(dict)
+    }
+
+    /// Return the (pattern, keyword) entries that have not matched in any search so far.
+    /// Returns None if the trie was not created with "track_coverage".
+    #[pyo3(signature = () -> "list[tuple[str, str]] | None")]
+    pub fn never_matched(&self) -> Option<Vec<(String, String)>> {
+        let entries = self.trie_inner.never_matched()?;
+        Some(
+            entries
+                .into_iter()
+                .map(|(p, k)| (p.to_string(), k.to_string()))
+                .collect(),
+        )
+    }
+
+    /// Search for occurrences of the defined patterns in the given text. Patterns shorter than
+    /// "min_length" characters are ignored. The GIL is released during the search, so other
+    /// Python threads can run meanwhile.
+    #[pyo3(signature = (text: "str", min_length: "int" = 0) -> "list[PyMatch]")]
+    pub fn search(&self, py: Python<'_>, text: &str, min_length: usize) -> PyResult<Vec<PyMatch>> {
+        py.detach(|| {
+            let results = self
+                .trie_inner
+                .find_text_matches_min_len(text, min_length)
+                .map_err(map_error_py)?;
+
+            Ok(results.iter().map(PyMatch::from).collect())
+        })
+    }
+
+    /// Iterate lazily over the matches of the text, in the same order as "search", scanning
+    /// it only as far as needed to produce each match. Useful for texts with very many
+    /// matches, or to stop at the first matches of interest.
+    #[pyo3(signature = (text: "str") -> "PyMatchIter")]
+    pub fn finditer(&self, text: &str) -> PyMatchIter {
+        PyMatchIter::new(Arc::clone(&self.trie_inner), text.into())
+    }
+
+    /// Search the text as "search", returning the matches as columns instead of PyMatch
+    /// objects: a dictionary with the keys of "PyMatch.to_dict", mapped to lists of the
+    /// attribute of each match. This avoids creating an object per match in texts with many
+    /// matches, and can be given directly to e.g. `pandas.DataFrame`. The strings of a pattern
+    /// are shared by all its matches.
+    #[pyo3(signature = (text: "str", min_length: "int" = 0) -> "dict[str, list[Any]]")]
+    pub fn search_arrays<'py>(
+        &self,
+        py: Python<'py>,
+        text: &str,
+        min_length: usize,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let matches = py
+            .detach(|| self.trie_inner.find_text_matches_min_len(text, min_length))
+            .map_err(map_error_py)?;
+
+        // Python strings of the value, keyword and category of each pattern found
+        let mut strings: HashMap<usize, [Bound<'py, PyAny>; 3]> = HashMap::new();
+        let mut columns: [Vec<Bound<'py, PyAny>>; 3] = Default::default();
+        for m in &matches {
+            let pattern_strings = strings.entry(m.pattern_id()).or_insert_with(|| {
+                [
+                    PyString::new(py, m.value()).into_any(),
+                    PyString::new(py, m.keyword()).into_any(),
+                    match m.category() {
+                        Some(category) => PyString::new(py, category).into_any(),
+                        None => py.None().into_bound(py),
+                    },
+                ]
+            });
+            for (column, s) in columns.iter_mut().zip(pattern_strings.iter()) {
+                column.push(s.clone());
+            }
+        }
+        let [values, keywords, categories] = columns;
+        let numbers = |f: fn(&Match) -> usize| matches.iter().map(f).collect::<Vec<_>>();
+
+        let dict = PyDict::new(py);
+        dict.set_item("value", values)?;
+        dict.set_item("kw", keywords)?;
+        dict.set_item("from_char", numbers(|m| m.char_range().0))?;
+        dict.set_item("to_char", numbers(|m| m.char_range().1))?;
+        dict.set_item("from_byte", numbers(|m| m.byte_range().start))?;
+        dict.set_item("to_byte", numbers(|m| m.byte_range().end))?;
+        let scores: Vec<Option<f32>> = matches.iter().map(Match::score).collect();
+        dict.set_item("score", scores)?;
+        dict.set_item("category", categories)?;
+        dict.set_item("pattern_id", numbers(|m| m.pattern_id()))?;
+        Ok(dict)
+    }
+
+    /// Search the text as "search", returning each match along with the text surrounding it:
+    /// a tuple (match, before, after) where "before" and "after" hold up to "window_chars"
+    /// characters of the text before and after the match.
+    #[pyo3(signature = (text: "str", window_chars: "int" = 30) -> "list[tuple[PyMatch, str, str]]")]
+    pub fn search_with_context(
+        &self,
+        py: Python<'_>,
+        text: &str,
+        window_chars: usize,
+    ) -> PyResult<Vec<(PyMatch, String, String)>> {
+        py.detach(|| {
+            let results = self
+                .trie_inner
+                .find_matches_with_context(text, window_chars)
+                .map_err(map_error_py)?;
+
+            Ok(results
+                .iter()
+                .map(|m| {
+                    let found = PyMatch::from(m.get_match());
+                    (found, m.before().to_string(), m.after().to_string())
+                })
+                .collect())
+        })
+    }
+
+    /// Return the longest dictionary entry matching the text exactly at the character
+    /// position "start", or None if no entry matches there. Useful for maximal munch
+    /// tokenization.
+    #[pyo3(signature = (text: "str", start: "int" = 0) -> "PyMatch | None")]
+    pub fn longest_match(&self, text: &str, start: usize) -> Option<PyMatch> {
+        self.trie_inner
+            .longest_match_at(text, start)
+            .as_ref()
+            .map(PyMatch::from)
+    }
+
+    /// Return the number of matches in the text, without creating them.
+    #[pyo3(signature = (text: "str") -> "int")]
+    pub fn count_matches(&self, text: &str) -> PyResult<usize> {
+        self.trie_inner.count_matches(text).map_err(map_error_py)
+    }
+
+    /// Return whether there is any match in the text, stopping at the first one found.
+    #[pyo3(signature = (text: "str") -> "bool")]
+    pub fn contains_any(&self, text: &str) -> PyResult<bool> {
+        self.trie_inner.contains_any(text).map_err(map_error_py)
+    }
+
+    /// Return the first match in the text, stopping the scan as soon as it is found, or None
+    /// if there are no matches.
+    #[pyo3(signature = (text: "str") -> "PyMatch | None")]
+    pub fn first_match(&self, text: &str) -> PyResult<Option<PyMatch>> {
+        let found = self
+            .trie_inner
+            .find_first_match(text)
+            .map_err(map_error_py)?;
+        Ok(found.as_ref().map(PyMatch::from))
+    }
+
+    /// Return the first "n" matches in the text, stopping the scan once they are found.
+    #[pyo3(signature = (text: "str", n: "int") -> "list[PyMatch]")]
+    pub fn first_matches(&self, text: &str, n: usize) -> PyResult<Vec<PyMatch>> {
+        let found = self
+            .trie_inner
+            .find_first_n_matches(text, n)
+            .map_err(map_error_py)?;
+        Ok(found.iter().map(PyMatch::from).collect())
+    }
+
+    /// Return the text with each match replaced by its keyword. Overlapping matches are
+    /// resolved taking the longest leftmost one, unless another match kind was set.
+    #[pyo3(signature = (text: "str") -> "str")]
+    pub fn replace_all(&self, text: &str) -> PyResult<String> {
+        self.trie_inner.replace_all(text).map_err(map_error_py)
+    }
+
+    /// Search the text calling "callback" with each match as soon as it is found, without
+    /// building the full list of matches. The search stops early if the callback returns
+    /// False. Returns whether the whole text was searched.
+    #[pyo3(signature = (text: "str", callback: "Callable[[PyMatch], bool | None]") -> "bool")]
+    pub fn scan(&self, text: &str, callback: &Bound<'_, PyAny>) -> PyResult<bool> {
+        let mut callback_err = None;
+        let finished = self
+            .trie_inner
+            .for_each_match(text, |m| match callback.call1((PyMatch::from(&m),)) {
+                Ok(res) => !matches!(res.extract::<bool>(), Ok(false)),
+                Err(e) => {
+                    callback_err = Some(e);
+                    false
+                }
+            })
+            .map_err(map_error_py)?;
+
+        match callback_err {
+            Some(e) => Err(e),
+            None => Ok(finished),
+        }
+    }
+
+    /// Search for occurrences of the defined patterns in raw bytes that may not be valid UTF-8.
+    /// Invalid sequences are replaced with U+FFFD, or dropped if "skip_invalid" is set. The
+    /// ranges of the matches are byte offsets into the data.
+    #[pyo3(signature = (data: "bytes", skip_invalid=false) -> "list[PyMatch]")]
+    pub fn search_bytes(&self, data: &[u8], skip_invalid: bool) -> PyResult<Vec<PyMatch>> {
+        let invalid = if skip_invalid {
+            InvalidUtf8::Skip
+        } else {
+            InvalidUtf8::Replace
+        };
         let results = self
             .trie_inner
-            .find_text_matches(text)
+            .find_matches_lossy(data, invalid)
             .map_err(map_error_py)?;
 
         Ok(results.iter().map(PyMatch::from).collect())
     }
 
     /// Search for occurrences in a list of texts. Search will be done in parallel across texts.
-    #[pyo3(signature = (texts: "list[str]", num_threads: "int | None" = None) -> "list[list[PyMatch]]")]
+    /// Patterns shorter than "min_length" characters are ignored.
+    ///
+    /// If a "timeout" (in seconds) is given, the search of each text is abandoned when it
+    /// exceeds it, and None is returned for that text while the rest complete. The GIL is
+    /// released during the search.
+    ///
+    /// If "on_progress" is given, it is called after each text is searched with the number of
+    /// texts searched so far and the total number of texts, e.g. to update a progress bar.
+    #[pyo3(signature = (
+        texts: "list[str]",
+        num_threads: "int | None" = None,
+        min_length: "int" = 0,
+        timeout: "float | None" = None,
+        on_progress: "Callable[[int, int], None] | None" = None,
+    ) -> "list[list[PyMatch] | None]")]
     pub fn search_many(
         &self,
+        py: Python<'_>,
         texts: Vec<String>,
         num_threads: Option<usize>,
-    ) -> PyResult<Vec<Vec<PyMatch>>> {
-        let results = multi_proc::parallel_apply(texts, |txt| self.search(txt), num_threads);
-        let mut results_out = Vec::with_capacity(results.len());
-        for r in results {
-            match r {
-                Ok(v) => results_out.push(v),
-                Err(e) => return Err(e),
+        min_length: usize,
+        timeout: Option<f64>,
+        on_progress: Option<Py<PyAny>>,
+    ) -> PyResult<Vec<Option<Vec<PyMatch>>>> {
+        // The first error raised by the progress callback, which is not called again after it
+        let callback_err = std::sync::Mutex::new(None);
+        let progress = multi_proc::Progress::new(texts.len(), |done, total| {
+            let Some(callback) = &on_progress else {
+                return;
+            };
+            let mut err = callback_err.lock().unwrap();
+            if err.is_none() {
+                *err = Python::attach(|py| callback.call1(py, (done, total)).err());
             }
-        }
-        Ok(results_out)
+        });
+        let search = |txt: String, deadline: Option<Instant>| {
+            let found = match self.trie_inner.find_matches_filtered(
+                &txt,
+                min_length,
+                Interrupt::until(deadline),
+            ) {
+                Ok(found) => Ok(Some(found.iter().map(PyMatch::from).collect())),
+                Err(SearchError::Timeout) => Ok(None),
+                Err(e) => Err(map_error_py(e)),
+            };
+            progress.item_done();
+            found
+        };
+        let budget = timeout
+            .map(|secs| {
+                Duration::try_from_secs_f64(secs)
+                    .map_err(|_| PyErr::new::<py_errs::PyValueError, _>("Invalid timeout value!"))
+            })
+            .transpose()?;
+        py.detach(|| match budget {
+            None => multi_proc::try_parallel_apply(texts, |txt| search(txt, None), num_threads),
+            Some(budget) => multi_proc::parallel_apply_with_budget(
+                texts,
+                |txt, deadline| search(txt, Some(deadline)),
+                num_threads,
+                budget,
+            )
+            .into_iter()
+            .collect(),
+        })
+        .and_then(|results| match callback_err.into_inner().unwrap() {
+            Some(e) => Err(e),
+            None => Ok(results),
+        })
     }
 
     pub fn __str__(&self) -> String {
@@ -196,6 +762,134 @@ impl PyTrie {
     }
 }
 
+impl PyTrie {
+    /// Wrap a prefix tree, collecting its keywords.
+    fn from_trie(trie_inner: TrieRoot) -> Self {
+        Self {
+            keywords: trie_keywords(&trie_inner),
+            trie_inner: Arc::new(trie_inner),
+        }
+    }
+}
+
+/// Lazy iterator over the matches of a text, returned by "PyTrie.finditer". The text is
+/// scanned only as far as needed to produce the next match.
+///
+/// The patterns of the prefix tree can't be added while an iterator over its matches is alive.
+#[pyclass(module = "ac_search.ac_search_rs")]
+pub struct PyMatchIter {
+    /// The Rust iterator, borrowing the tree and text below. Declared first, so that it is
+    /// dropped before them.
+    matches: Mutex<MatchIter<'static, 'static>>,
+
+    /// Text and prefix tree searched, kept alive and unchanged while the iterator borrows them
+    _text: Box<str>,
+    _trie: Arc<TrieRoot>,
+}
+
+impl PyMatchIter {
+    /// Iterate over the matches of the text in the given prefix tree.
+    fn new(trie: Arc<TrieRoot>, text: Box<str>) -> Self {
+        let matches = trie.match_iter(&text);
+        // SAFETY: the iterator borrows the tree and the text, which are heap allocations
+        // owned by this object and never moved or changed: the text is not exposed, and the
+        // tree can't be mutated while this handle to it exists (see "add_patterns"). The
+        // iterator is dropped before both, since it is declared first.
+        let matches = unsafe {
+            std::mem::transmute::<MatchIter<'_, '_>, MatchIter<'static, 'static>>(matches)
+        };
+        Self {
+            matches: Mutex::new(matches),
+            _text: text,
+            _trie: trie,
+        }
+    }
+}
+
+#[pymethods]
+impl PyMatchIter {
+    pub fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    pub fn __next__(&mut self) -> PyResult<Option<PyMatch>> {
+        let matches = self.matches.get_mut().unwrap();
+        let next = matches.next_match().map_err(map_error_py)?;
+        Ok(next.as_ref().map(PyMatch::from))
+    }
+}
+
+/// Get the distinct keywords of the patterns stored in a prefix tree.
+fn trie_keywords(trie: &TrieRoot) -> Vec<String> {
+    // Avoid storing duplicates
+    let mut keywords = HashSet::new();
+    for id in 0..trie.total_patterns() {
+        if let Some((_, keyword)) = trie.pattern(id) {
+            keywords.insert(keyword.to_string());
+        }
+    }
+    keywords.drain().collect()
+}
+
+/// Build the search options of a prefix tree from the arguments of its Python constructors.
+#[allow(clippy::too_many_arguments)]
+fn search_options(
+    case_sensitive: bool,
+    check_bounds: bool,
+    compute_scores: bool,
+    max_chain_depth: Option<usize>,
+    match_kind: &str,
+    word_segmentation: bool,
+    whitespace: &str,
+    wildcard: Option<char>,
+    max_matches: Option<usize>,
+    max_text_chars: Option<usize>,
+    time_limit: Option<f64>,
+) -> PyResult<SearchOptions> {
+    let match_kind = MatchKind::from_name(match_kind).ok_or_else(|| {
+        PyErr::new::<py_errs::PyValueError, _>(format!("Unknown match kind: {}", match_kind))
+    })?;
+    let whitespace = WhitespaceMatch::from_name(whitespace).ok_or_else(|| {
+        PyErr::new::<py_errs::PyValueError, _>(format!("Unknown whitespace mode: {}", whitespace))
+    })?;
+    let time_limit = time_limit
+        .map(|secs| {
+            Duration::try_from_secs_f64(secs)
+                .map_err(|_| PyErr::new::<py_errs::PyValueError, _>("Invalid time limit value!"))
+        })
+        .transpose()?;
+    Ok(SearchOptions {
+        case_sensitive,
+        check_bounds,
+        word_segmentation,
+        compute_scores,
+        max_chain_depth,
+        match_kind,
+        whitespace,
+        wildcard,
+        limits: SearchLimits {
+            max_matches,
+            max_text_chars,
+            time_limit,
+        },
+        ..Default::default()
+    })
+}
+
+/// Convert a dictionary of python str -> str, or an iterable of (str, str) tuples, into the
+/// vector expected by the Rust API.
+fn py_pairs_to_vector(obj: &Bound<'_, PyAny>) -> PyResult<Vec<(String, Option<String>)>> {
+    if let Ok(dct) = obj.cast::<PyDict>() {
+        return py_dict_to_vector(dct);
+    }
+    let mut items = Vec::with_capacity(obj.len().unwrap_or(0));
+    for item in obj.try_iter()? {
+        let (key, val): (String, String) = item?.extract()?;
+        items.push((key, Some(val)));
+    }
+    Ok(items)
+}
+
 /// Convert a dictionary of python str -> str into the vector expected by the Rust API.
 fn py_dict_to_vector(dct: &Bound<'_, PyDict>) -> PyResult<Vec<(String, Option<String>)>> {
     let mut items = Vec::with_capacity(dct.len());
@@ -223,12 +917,11 @@ fn search_in_text(
     let opts = SearchOptions {
         case_sensitive,
         check_bounds,
+        ..Default::default()
     };
     let prefix_tree =
         create_prefix_tree(py_dict_to_vector(dictionary)?, Some(opts)).map_err(map_error_py)?;
-    let matches = prefix_tree
-        .find_text_matches(haystack)
-        .map_err(map_error_py)?;
+    let matches = prefix_tree.find_matches(&haystack).map_err(map_error_py)?;
 
     Ok(matches.iter().map(PyMatch::from).collect())
 }
@@ -255,30 +948,29 @@ fn search_in_texts(
     let opts = SearchOptions {
         case_sensitive,
         check_bounds,
+        ..Default::default()
     };
     let dct = py_dict_to_vector(dictionary)?;
     let prefix_tree = create_prefix_tree(dct, Some(opts)).map_err(map_error_py)?;
+    search_texts_with(&prefix_tree, haystacks, num_threads)
+}
 
-    let matches = multi_proc::parallel_apply(
+/// Search several texts in parallel with any search backend.
+fn search_texts_with<S: Searcher + Sync + ?Sized>(
+    searcher: &S,
+    haystacks: Vec<String>,
+    num_threads: Option<usize>,
+) -> PyResult<Vec<Vec<PyMatch>>> {
+    multi_proc::try_parallel_apply(
         haystacks,
         |txt| {
-            prefix_tree
-                .find_text_matches(txt)
+            searcher
+                .find_matches(&txt)
                 .map_err(map_error_py)
                 .map(|result| result.iter().map(PyMatch::from).collect())
         },
         num_threads,
-    );
-
-    let mut matches_list = Vec::with_capacity(matches.len());
-    for m in matches {
-        match m {
-            Err(e) => return Err(e),
-            Ok(item) => matches_list.push(item),
-        }
-    }
-
-    Ok(matches_list)
+    )
 }
 
 /// The module to expose as importable from Python.
@@ -295,5 +987,5 @@ pub mod aho_corasick_search {
     }
 
     #[pymodule_export]
-    use super::{PyMatch, PyTrie, normalize_string, search_in_text, search_in_texts};
+    use super::{PyMatch, PyMatchIter, PyTrie, normalize_string, search_in_text, search_in_texts};
 }
@@ -0,0 +1,478 @@
+//! Embeddable HTTP search service.
+//!
+//! Exposes the matcher over a small JSON API, so that it can be run as a sidecar by
+//! applications that can't use the Rust or Python libraries directly. Dictionaries are built
+//! once and kept in memory, shared between the worker threads handling the requests, and
+//! batches of texts are searched in parallel.
+//!
+//! Endpoints:
+//! - `GET /health`: check that the service is up.
+//! - `PUT /dictionaries/{name}`: build (or replace) a dictionary. The body is an object with
+//!   the `entries` of the dictionary, either pattern strings or objects with a `pattern` and
+//!   optional `keyword` and `category`, and optional search `options` (`case_sensitive`,
+//!   `check_bounds`, `ignore_separators`, `ignore_digit_separators`, `compute_scores`,
+//!   `max_chain_depth`, `match_kind`, `whitespace`, `wildcard` and the search limits
+//!   `max_matches`, `max_text_chars` and `time_limit_ms`). Dictionaries whose tree would be
+//!   too large, e.g. with too many wildcards, are rejected with a 400 error before they take
+//!   up the memory.
+//! - `DELETE /dictionaries/{name}`: remove a dictionary.
+//! - `POST /dictionaries/{name}/search`: search the `texts` (list of strings) of the body.
+//!   Returns a list of matches for each text, with their character ranges, or a 422 error if
+//!   a text exceeds the search limits of the dictionary.
+//!
+//! Requests with a body larger than the limit of the service (16 MiB by default, see
+//! `SearchServer::with_max_body_bytes`) are rejected with a 413 error without reading it.
+use super::multi_proc;
+use super::trie::{
+    GazetteerEntry, Match, MatchKind, SearchError, SearchOptions, TrieRoot, WhitespaceMatch,
+    create_gazetteer,
+};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::io::Read;
+use std::net::ToSocketAddrs;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Response, Server};
+
+/// Response of the service: HTTP status code and JSON body.
+type ApiResponse = (u16, Value);
+
+/// Default maximum size in bytes of the body of a request
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Search service holding the dictionaries built through its API.
+pub struct SearchServer {
+    dictionaries: RwLock<HashMap<String, Arc<TrieRoot>>>,
+    num_threads: Option<usize>,
+    max_body_bytes: usize,
+}
+
+impl SearchServer {
+    /// Instantiate a service without dictionaries. Batches of texts are searched with the given
+    /// number of threads, or the default of `multi_proc` if None.
+    pub fn new(num_threads: Option<usize>) -> Self {
+        Self {
+            dictionaries: RwLock::new(HashMap::new()),
+            num_threads,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    /// Set the maximum size in bytes of the body of the requests. Larger requests are rejected
+    /// with a 413 error, so that a client can't exhaust the memory of the service.
+    pub fn with_max_body_bytes(mut self, limit: usize) -> Self {
+        self.max_body_bytes = limit;
+        self
+    }
+
+    /// Add a dictionary built beforehand to the service, replacing any with the same name.
+    pub fn insert_dictionary(&self, name: &str, trie: TrieRoot) {
+        self.dictionaries
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Arc::new(trie));
+    }
+
+    /// Listen for requests on the given address, handling them with `workers` threads. This
+    /// blocks the current thread until the listener fails.
+    ///
+    /// Example
+    /// ```no_run
+    /// use ac_search_rs::server::SearchServer;
+    /// use std::sync::Arc;
+    ///
+    /// let server = Arc::new(SearchServer::new(None));
+    /// server.serve("127.0.0.1:8080", 4).unwrap();
+    /// ```
+    pub fn serve<A: ToSocketAddrs>(
+        self: &Arc<Self>,
+        addr: A,
+        workers: usize,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let listener = Arc::new(Server::http(addr)?);
+        log::info!("Search server listening on {}", listener.server_addr());
+
+        let handles: Vec<_> = (0..workers.max(1))
+            .map(|_| {
+                let listener = Arc::clone(&listener);
+                let service = Arc::clone(self);
+                thread::spawn(move || {
+                    for request in listener.incoming_requests() {
+                        service.respond(request);
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().map_err(|_| "Server worker panicked")?;
+        }
+        Ok(())
+    }
+
+    /// Handle an HTTP request and send the response.
+    fn respond(&self, mut request: tiny_http::Request) {
+        let length = request.body_length();
+        let (status, value) = match read_body(request.as_reader(), length, self.max_body_bytes) {
+            Ok(body) => self.handle(request.method(), request.url(), &body),
+            Err(response) => response,
+        };
+        log::debug!("{} {} -> {}", request.method(), request.url(), status);
+
+        let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(header);
+        if let Err(e) = request.respond(response) {
+            log::warn!("Failed to send response: {}", e);
+        }
+    }
+
+    /// Handle a request given its method, URL and body, returning the response status and
+    /// body.
+    pub(crate) fn handle(&self, method: &Method, url: &str, body: &str) -> ApiResponse {
+        let path: Vec<&str> = url
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .collect();
+        match (method, path.as_slice()) {
+            (Method::Get, ["health"]) => (200, json!({"status": "ok"})),
+            (Method::Put, ["dictionaries", name]) => self.build_dictionary(name, body),
+            (Method::Delete, ["dictionaries", name]) => {
+                match self.dictionaries.write().unwrap().remove(*name) {
+                    Some(_) => (200, json!({"name": name})),
+                    None => not_found(name),
+                }
+            }
+            (Method::Post, ["dictionaries", name, "search"]) => self.search(name, body),
+            _ => error_response(404, format!("Unknown endpoint: {} {}", method, url)),
+        }
+    }
+
+    /// Build a dictionary from the entries and options in the request body.
+    fn build_dictionary(&self, name: &str, body: &str) -> ApiResponse {
+        let request = match parse_body(body) {
+            Ok(r) => r,
+            Err(e) => return e,
+        };
+        let entries = match request.get("entries").map(parse_entries) {
+            Some(Ok(entries)) => entries,
+            Some(Err(e)) => return e,
+            None => return error_response(400, "Missing dictionary entries"),
+        };
+        let options = match request.get("options").map(parse_options).transpose() {
+            Ok(opts) => opts,
+            Err(e) => return e,
+        };
+
+        match create_gazetteer(entries, options) {
+            Ok(trie) => {
+                let total_patterns = trie.total_patterns();
+                self.insert_dictionary(name, trie);
+                (201, json!({"name": name, "patterns": total_patterns}))
+            }
+            Err(e) => error_response(400, e.to_string()),
+        }
+    }
+
+    /// Search the texts in the request body with a dictionary.
+    fn search(&self, name: &str, body: &str) -> ApiResponse {
+        let Some(trie) = self.dictionaries.read().unwrap().get(name).cloned() else {
+            return not_found(name);
+        };
+        let request = match parse_body(body) {
+            Ok(r) => r,
+            Err(e) => return e,
+        };
+        let texts: Option<Vec<&str>> = request
+            .get("texts")
+            .and_then(Value::as_array)
+            .and_then(|t| t.iter().map(Value::as_str).collect());
+        let Some(texts) = texts else {
+            return error_response(400, "Expected a list of strings as 'texts'");
+        };
+
+        let results = multi_proc::try_parallel_apply(
+            texts,
+            |text| {
+                trie.find_text_matches(text)
+                    .map(|found| found.iter().map(match_to_json).collect::<Vec<Value>>())
+            },
+            self.num_threads,
+        );
+        match results {
+            Ok(results) => (200, json!({"results": results})),
+            Err(e @ SearchError::LimitExceeded(_)) => error_response(422, e.to_string()),
+            Err(e) => error_response(500, e.to_string()),
+        }
+    }
+}
+
+/// Build an error response with the given status and message.
+fn error_response<S: Into<String>>(status: u16, message: S) -> ApiResponse {
+    (status, json!({"error": message.into()}))
+}
+
+/// Read the body of a request, given its length from the `Content-Length` header if any. At
+/// most `limit` bytes are read, and larger bodies are rejected with a 413 error.
+fn read_body<R: Read>(
+    reader: R,
+    length: Option<usize>,
+    limit: usize,
+) -> Result<String, ApiResponse> {
+    let too_large = || error_response(413, format!("Request body larger than {} bytes", limit));
+    if length.is_some_and(|len| len > limit) {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    reader
+        .take(limit as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| error_response(400, format!("Invalid request body: {}", e)))?;
+    if body.len() > limit {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+/// Response for a dictionary that does not exist.
+fn not_found(name: &str) -> ApiResponse {
+    error_response(404, format!("Dictionary not found: {}", name))
+}
+
+/// Parse a request body, which must be a JSON object.
+fn parse_body(body: &str) -> Result<Map<String, Value>, ApiResponse> {
+    match serde_json::from_str(body) {
+        Ok(Value::Object(obj)) => Ok(obj),
+        Ok(_) => Err(error_response(400, "Expected a JSON object")),
+        Err(e) => Err(error_response(400, format!("Invalid JSON: {}", e))),
+    }
+}
+
+/// Parse the entries of a dictionary: either pattern strings or objects with a pattern and
+/// optional keyword and category.
+fn parse_entries(value: &Value) -> Result<Vec<GazetteerEntry>, ApiResponse> {
+    let invalid = || error_response(400, "Invalid dictionary entry");
+    let field = |obj: &Map<String, Value>, key: &str| -> Result<Option<String>, ApiResponse> {
+        match obj.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(invalid()),
+        }
+    };
+
+    let Some(items) = value.as_array() else {
+        return Err(error_response(400, "Expected a list of dictionary entries"));
+    };
+    let mut entries = Vec::with_capacity(items.len());
+    for item in items {
+        match item {
+            Value::String(pattern) => entries.push((pattern.clone(), None, None)),
+            Value::Object(obj) => {
+                let pattern = field(obj, "pattern")?.ok_or_else(invalid)?;
+                entries.push((pattern, field(obj, "keyword")?, field(obj, "category")?));
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(entries)
+}
+
+/// Parse the search options of a dictionary. Missing options take their default values.
+fn parse_options(value: &Value) -> Result<SearchOptions, ApiResponse> {
+    let Some(obj) = value.as_object() else {
+        return Err(error_response(400, "Expected an object of search options"));
+    };
+    let mut opts = SearchOptions::default();
+    for (key, val) in obj {
+        let flag = val.as_bool();
+        match (key.as_str(), flag) {
+            ("case_sensitive", Some(b)) => opts.case_sensitive = b,
+            ("check_bounds", Some(b)) => opts.check_bounds = b,
+            ("ignore_separators", Some(b)) => opts.ignore_separators = b,
+            ("ignore_digit_separators", Some(b)) => opts.ignore_digit_separators = b,
+            ("compute_scores", Some(b)) => opts.compute_scores = b,
+            ("dense_dfa", Some(b)) => opts.dense_dfa = b,
+            ("max_chain_depth", _) if val.is_null() || val.is_u64() => {
+                opts.max_chain_depth = val.as_u64().map(|d| d as usize);
+            }
+            ("max_matches", _) if val.is_null() || val.is_u64() => {
+                opts.limits.max_matches = val.as_u64().map(|m| m as usize);
+            }
+            ("max_text_chars", _) if val.is_null() || val.is_u64() => {
+                opts.limits.max_text_chars = val.as_u64().map(|c| c as usize);
+            }
+            ("time_limit_ms", _) if val.is_null() || val.is_u64() => {
+                opts.limits.time_limit = val.as_u64().map(Duration::from_millis);
+            }
+            ("match_kind", _) => match val.as_str().and_then(MatchKind::from_name) {
+                Some(kind) => opts.match_kind = kind,
+                None => return Err(error_response(400, format!("Invalid option: {}", key))),
+            },
+            ("wildcard", _) if val.is_null() => opts.wildcard = None,
+            ("wildcard", _) => {
+                let mut chars = val.as_str().unwrap_or_default().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => opts.wildcard = Some(c),
+                    _ => return Err(error_response(400, format!("Invalid option: {}", key))),
+                }
+            }
+            ("whitespace", _) => match val.as_str().and_then(WhitespaceMatch::from_name) {
+                Some(mode) => opts.whitespace = mode,
+                None => return Err(error_response(400, format!("Invalid option: {}", key))),
+            },
+            _ => return Err(error_response(400, format!("Invalid option: {}", key))),
+        }
+    }
+    Ok(opts)
+}
+
+/// Represent a match as a JSON object.
+fn match_to_json(m: &Match) -> Value {
+    let (start, end) = m.char_range();
+    json!({
+        "start": start,
+        "end": end,
+        "value": m.value(),
+        "keyword": m.keyword(),
+        "category": m.category(),
+        "score": m.score(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_dictionary_lifecycle() {
+        let server = SearchServer::new(Some(2));
+        let body = json!({
+            "entries": ["ab", {"pattern": "Cd", "keyword": "CD", "category": "X"}],
+            "options": {"case_sensitive": false, "check_bounds": true},
+        });
+        let (status, res) = server.handle(&Method::Put, "/dictionaries/test", &body.to_string());
+        assert_eq!((status, &res["patterns"]), (201, &json!(2)));
+
+        let body = json!({"texts": ["ab cd", "xab", "CD"]});
+        let (status, res) = server.handle(
+            &Method::Post,
+            "/dictionaries/test/search",
+            &body.to_string(),
+        );
+        assert_eq!(status, 200);
+        let counts: Vec<usize> = res["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r.as_array().unwrap().len())
+            .collect();
+        assert_eq!(counts, [2, 0, 1]);
+        assert_eq!(
+            res["results"][2][0],
+            json!({"start": 0, "end": 2, "value": "cd", "keyword": "CD", "category": "X", "score": null})
+        );
+
+        let (status, _) = server.handle(&Method::Delete, "/dictionaries/test", "");
+        assert_eq!(status, 200);
+        let (status, _) = server.handle(
+            &Method::Post,
+            "/dictionaries/test/search",
+            &body.to_string(),
+        );
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn test_server_search_limits() {
+        let server = SearchServer::new(Some(2));
+        let body = json!({
+            "entries": ["a"],
+            "options": {"max_matches": 3, "max_text_chars": null, "time_limit_ms": 60000},
+        });
+        let (status, _) = server.handle(&Method::Put, "/dictionaries/lim", &body.to_string());
+        assert_eq!(status, 201);
+
+        let search = |texts: Value| {
+            let body = json!({ "texts": texts });
+            server.handle(&Method::Post, "/dictionaries/lim/search", &body.to_string())
+        };
+        assert_eq!(search(json!(["aaa", "b"])).0, 200);
+        let (status, res) = search(json!(["aaa", "aaaa"]));
+        assert_eq!(status, 422);
+        assert!(res["error"].as_str().unwrap().contains("limit"));
+    }
+
+    #[test]
+    fn test_server_body_limit() {
+        let body = "x".repeat(20);
+        assert_eq!(read_body(body.as_bytes(), Some(20), 20), Ok(body.clone()));
+        assert_eq!(read_body(body.as_bytes(), None, 20), Ok(body.clone()));
+
+        // Rejected from the Content-Length header, or once the limit is exceeded when reading
+        for length in [Some(20), None] {
+            let (status, res) = read_body(body.as_bytes(), length, 19).unwrap_err();
+            assert_eq!(status, 413);
+            assert_eq!(res["error"], "Request body larger than 19 bytes");
+        }
+        assert_eq!(
+            read_body(body.as_bytes(), Some(1000), 19).unwrap_err().0,
+            413
+        );
+
+        let server = SearchServer::new(None).with_max_body_bytes(1024);
+        assert_eq!(server.max_body_bytes, 1024);
+        assert_eq!(
+            SearchServer::new(None).max_body_bytes,
+            DEFAULT_MAX_BODY_BYTES
+        );
+    }
+
+    #[test]
+    fn test_server_wildcard_budget() {
+        let server = SearchServer::new(None);
+        let body = json!({
+            "entries": ["abcdefghij", "????????"],
+            "options": {"wildcard": "?"},
+        });
+        let (status, res) = server.handle(&Method::Put, "/dictionaries/wild", &body.to_string());
+        assert_eq!(status, 400);
+        assert!(res["error"].as_str().unwrap().contains("budget"));
+        assert_eq!(
+            server
+                .handle(
+                    &Method::Post,
+                    "/dictionaries/wild/search",
+                    r#"{"texts": []}"#
+                )
+                .0,
+            404
+        );
+    }
+
+    #[test]
+    fn test_server_invalid_requests() {
+        let server = SearchServer::new(None);
+        assert_eq!(server.handle(&Method::Get, "/health", "").0, 200);
+        assert_eq!(server.handle(&Method::Get, "/unknown", "").0, 404);
+
+        for body in [
+            "not json",
+            "[]",
+            r#"{"entries": "ab"}"#,
+            r#"{"entries": [1]}"#,
+            r#"{"entries": []}"#,
+            r#"{"entries": ["ab"], "options": {"check_bounds": 1}}"#,
+            r#"{"entries": ["ab"], "options": {"match_kind": "longest"}}"#,
+            r#"{"entries": ["ab"], "options": {"max_matches": -1}}"#,
+        ] {
+            let (status, res) = server.handle(&Method::Put, "/dictionaries/x", body);
+            assert_eq!(status, 400, "Body: {body}");
+            assert!(res["error"].is_string());
+        }
+    }
+}
@@ -1,20 +1,157 @@
-use std::collections::VecDeque;
-use unicode_normalization::UnicodeNormalization;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+pub mod analysis;
+pub use analysis::*;
+pub mod anchored;
+pub mod bytes;
+pub use bytes::*;
+pub mod builder;
+pub use builder::*;
+pub mod cancel;
+pub use cancel::CancelToken;
+pub mod context;
+pub use context::*;
+pub mod count;
+pub(crate) mod coverage;
+pub mod cursor;
+pub use cursor::*;
+pub(crate) mod dfa;
+pub mod dictionary;
+pub use dictionary::*;
+pub mod diff;
+pub use diff::*;
+pub(crate) mod dump;
+#[cfg(feature = "encoding")]
+pub mod encoded;
+#[cfg(feature = "encoding")]
+pub use encoded::*;
+pub mod haystack;
+pub use haystack::*;
+pub mod insert;
+pub mod kwic;
+pub use kwic::*;
+pub mod limits;
+pub(crate) mod lines;
+pub use limits::SearchLimits;
+pub mod match_iter;
+pub use match_iter::*;
+pub mod match_kind;
+pub use match_kind::MatchKind;
+pub(crate) mod normalize;
+pub mod normalizer;
+pub use normalizer::*;
+pub mod outcome;
+pub use outcome::*;
+pub mod overlaps;
+pub use overlaps::*;
+pub mod owned;
+pub use owned::OwnedMatch;
+pub mod parallel;
+pub mod payload;
+pub use payload::*;
+pub mod persist;
+#[cfg(test)]
+mod proptests;
+pub mod regions;
+pub use regions::*;
+pub mod reload;
+pub use reload::*;
+pub mod render;
+pub mod replace;
+pub use render::*;
+pub mod report;
+pub use report::*;
 pub mod ring_buffer;
 pub use ring_buffer::RingBuffer;
+pub(crate) mod score;
+pub(crate) use score::Score;
 pub mod search;
 pub use search::*;
+pub mod searcher;
+pub use searcher::*;
+pub mod shared;
+pub use shared::*;
+pub(crate) mod single;
+pub mod stats;
+pub use stats::TrieStats;
+pub mod stream;
+pub use stream::*;
+pub mod suffix;
+pub mod trace;
+pub use trace::*;
+pub mod trie_set;
+pub use trie_set::*;
+pub mod walk;
+pub use walk::*;
+pub mod whitespace;
+pub use whitespace::WhitespaceMatch;
+pub(crate) mod wildcard;
 
 /// Type alias to reference the ID of a node in the prefix tree.
 pub type NodeId = usize;
 
+/// Identifier of a pattern of the dictionary: the position of its entry in the dictionary the
+/// tree was built from. Patterns added later with `TrieRoot::add_patterns` take the following
+/// IDs, so the IDs are stable for the lifetime of the tree.
+pub type PatternId = usize;
+
 /// Errors that can be raised by the library functions
+///
+/// The errors implement `std::error::Error`, and are `Send + Sync + 'static`, so they can be
+/// wrapped by error handling libraries such as `anyhow`. Errors caused by another error (e.g.
+/// input / output errors) give access to it with `Error::source`.
+///
+/// New variants may be added in future versions, so matches on this type must include a
+/// wildcard arm.
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum SearchError {
+    /// A node ID does not reference a node of the tree
     InvalidNodeId(NodeId),
+
+    /// The dictionary has the same pattern twice, after normalization
     DuplicateNode,
+
+    /// The dictionary is empty or has an empty pattern
     InvalidDictionary,
+
+    /// A node of the tree has no failure link
     MissingLink(NodeId),
+
+    /// A stream checkpoint was not taken from a search with the same tree
+    InvalidCheckpoint,
+
+    /// A pattern given by the caller is not in the dictionary
+    PatternNotFound(String),
+
+    /// A dictionary file could not be read or parsed. Holds the reason.
+    DictionaryFile(String),
+
+    /// The search was not done by its deadline
+    Timeout,
+
+    /// The search was abandoned because its `CancelToken` was cancelled
+    Cancelled,
+
+    /// An input / output operation failed. Holds the kind of the error and the error itself,
+    /// which is also the source of this one.
+    Io(std::io::ErrorKind, ErrorSource),
+
+    /// The search options are not valid or not supported by this build (e.g. they require a
+    /// feature that is not enabled). Holds the reason.
+    InvalidOptions(String),
+
+    /// A search exceeded one of the `SearchLimits` of the tree. Holds the limit exceeded.
+    LimitExceeded(String),
+
+    /// Data could not be serialized or deserialized
+    Serialization(String),
+
+    /// Building the tree would exceed the configured limits (e.g. on its size)
+    BuildBudget(String),
+
+    /// A text to search is not valid (e.g. bytes that are not UTF-8)
+    InvalidText(String),
 }
 
 impl std::fmt::Display for SearchError {
@@ -24,17 +161,78 @@ impl std::fmt::Display for SearchError {
             Self::DuplicateNode => "Duplicate node".to_string(),
             Self::InvalidDictionary => "Invalid dictionary".to_string(),
             Self::MissingLink(id) => format!("Missing link for node ID: {}", id),
+            Self::InvalidCheckpoint => "Invalid search checkpoint".to_string(),
+            Self::PatternNotFound(p) => format!("Pattern not found in dictionary: {}", p),
+            Self::DictionaryFile(e) => format!("Failed to read dictionary file: {}", e),
+            Self::Timeout => "Search deadline exceeded".to_string(),
+            Self::Cancelled => "Search cancelled".to_string(),
+            Self::Io(_, e) => format!("IO error: {}", e),
+            Self::InvalidOptions(e) => format!("Invalid search options: {}", e),
+            Self::LimitExceeded(e) => format!("Search limit exceeded: {}", e),
+            Self::Serialization(e) => format!("Serialization error: {}", e),
+            Self::BuildBudget(e) => format!("Build budget exceeded: {}", e),
+            Self::InvalidText(e) => format!("Invalid text: {}", e),
         };
         write!(f, "{}", str_val)
     }
 }
 
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(_, e) => Some(e.get_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SearchError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err.kind(), ErrorSource::new(err))
+    }
+}
+
+/// An error from another library that caused a `SearchError`, shared so that the errors can
+/// be cloned and compared. Errors are equal if their descriptions are.
+#[derive(Debug, Clone)]
+pub struct ErrorSource(Arc<dyn std::error::Error + Send + Sync>);
+
+impl ErrorSource {
+    /// Wrap an error.
+    pub fn new<E: std::error::Error + Send + Sync + 'static>(err: E) -> Self {
+        Self(Arc::new(err))
+    }
+
+    /// Get a reference to the wrapped error, e.g. to downcast it.
+    pub fn get_ref(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self.0.as_ref()
+    }
+}
+
+impl std::fmt::Display for ErrorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq for ErrorSource {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for ErrorSource {}
+
 /// Result type for this library
 pub type SearchResult<T> = Result<T, SearchError>;
 
-/// A link between two nodes in the prefix tree
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Link(char, NodeId);
+/// An entry of a gazetteer dictionary: pattern, keyword and category (entity label).
+pub type GazetteerEntry = (String, Option<String>, Option<String>);
+
+/// A link between two nodes in the prefix tree. The ID of the node is stored in 32 bits to
+/// keep the links arena of the tree compact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Link(char, u32);
 
 impl Link {
     /// Get the character element of the link
@@ -46,180 +244,508 @@ impl Link {
     /// Get the Node ID element of the link
     #[inline]
     fn get_node_id(&self) -> NodeId {
-        self.1
+        self.1 as NodeId
     }
 }
 
+/// Value of the 32-bit node and table references that are not set
+const NONE_ID: u32 = u32::MAX;
+
+/// Convert an optional 32-bit reference into an index
+#[inline]
+fn opt_id(id: u32) -> Option<usize> {
+    (id != NONE_ID).then_some(id as usize)
+}
+
 /// Options to use when performing searches
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SearchOptions {
-    /// Whether to distinguish uppercase and lowercase characters.
+    /// Whether to distinguish uppercase and lowercase characters. Case-insensitive searches
+    /// compare the Unicode simple case folding of the patterns and texts.
     pub case_sensitive: bool,
 
-    /// Whether to return only matches that begin and end with word boundaries.
+    /// Whether to return only matches that begin and end with word boundaries. Characters of
+    /// scripts written without spaces between words (e.g. Chinese or Thai) are always
+    /// considered to be at a word boundary.
     pub check_bounds: bool,
+
+    /// Whether to find word boundaries with the Unicode word segmentation rules (UAX #29)
+    /// when checking bounds, instead of only looking at the characters next to the match. This
+    /// handles e.g. apostrophes, decimal numbers and Katakana words correctly. Requires the
+    /// `segmentation` feature, and is not applied in stream searches.
+    pub word_segmentation: bool,
+
+    /// Whether to treat hyphens and whitespace as optional separators. If set, these are removed
+    /// from the patterns and skipped in the searched text, so that "e-mail" matches "email",
+    /// "e-mail" and "e mail".
+    pub ignore_separators: bool,
+
+    /// Whether to ignore the separators between groups of digits in numbers (commas,
+    /// apostrophes, underscores and spaces), so that "10000" matches "10,000" and "10 000".
+    /// Periods are not ignored, since they are often used as decimal separators.
+    pub ignore_digit_separators: bool,
+
+    /// How whitespace in the patterns is matched. See `WhitespaceMatch`. Has no effect on
+    /// the whitespace removed by `ignore_separators`.
+    pub whitespace: WhitespaceMatch,
+
+    /// Character that matches any single character of the text when used in a pattern, e.g.
+    /// with `?`, "gr?y" matches "grey" and "gray". The wildcard can't be matched literally, and
+    /// should be a character left unchanged by the normalization of the patterns. When several
+    /// patterns match the same text, e.g. "colour" and "colo?r", only the one with the fewest
+    /// wildcards is reported. Each wildcard multiplies the size of the part of the tree after
+    /// it by the number of distinct characters of the dictionary, so wildcards are best used
+    /// in short dictionaries with small alphabets. Building the tree fails with a `BuildBudget`
+    /// error if the wildcards would add more than 2^20 nodes to it. No wildcard if None.
+    pub wildcard: Option<char>,
+
+    /// Whether to compute a confidence score for each match found. See `Match::score`.
+    pub compute_scores: bool,
+
+    /// Maximum number of patterns checked at each position of the text. When many patterns
+    /// end at the same position (e.g. with dictionaries of many nested suffixes), only the
+    /// longest ones are checked and the search is flagged as truncated. This bounds the work
+    /// done per character on adversarial inputs. No limit if None.
+    pub max_chain_depth: Option<usize>,
+
+    /// How overlapping matches are reported. See `MatchKind`. Stream searches always report
+    /// overlapping matches.
+    pub match_kind: MatchKind,
+
+    /// Whether to precompute a dense transition table for the automaton when building the
+    /// tree, for faster searches at the cost of memory. See `TrieRoot::compile_dfa`.
+    pub dense_dfa: bool,
+
+    /// Limits on the number of matches, length of the texts and time of each search. See
+    /// `SearchLimits`. No limits by default.
+    pub limits: SearchLimits,
+
+    /// Whether to compute the line and column of each match, reported by `Match::line_col`,
+    /// e.g. to report matches as `file:line:column`. Not applied in stream searches, nor to
+    /// the matches of `TrieRoot::find_suffix_matches`.
+    pub track_lines: bool,
+
+    /// Chain of transformations applied in order to the patterns and to the searched texts,
+    /// before the Unicode NFC normalization and the case folding of case-insensitive searches,
+    /// e.g. to strip accents. The ranges of the matches refer to the text as given to the
+    /// search. See `Normalizer`.
+    pub normalizers: Vec<Arc<dyn Normalizer>>,
 }
 
 impl Default for SearchOptions {
-    /// Default oprions: case sensitive search without checking word boundaries.
+    /// Default oprions: case sensitive search without checking word boundaries or ignoring
+    /// any kind of separators.
     fn default() -> Self {
         SearchOptions {
             case_sensitive: true,
             check_bounds: false,
+            word_segmentation: false,
+            ignore_separators: false,
+            ignore_digit_separators: false,
+            whitespace: WhitespaceMatch::Exact,
+            wildcard: None,
+            compute_scores: false,
+            max_chain_depth: None,
+            match_kind: MatchKind::Overlapping,
+            dense_dfa: false,
+            limits: SearchLimits::default(),
+            track_lines: false,
+            normalizers: Vec::new(),
         }
     }
 }
 
-/// Represents a node in the prefix tree for the Aho-Corasick structure
-#[derive(Debug)]
-pub struct Node {
-    value: Option<String>,
-    keyword: Option<String>,
-    nxt: Vec<Link>,
-    fail_to: Option<NodeId>,
-    dct_to: Option<NodeId>,
-    pattern_len: usize,
-}
-
-impl Default for Node {
-    /// Default instantiation - no value, keyword, empty links
-    fn default() -> Self {
-        Self {
-            value: None,
-            keyword: None,
-            nxt: Vec::new(),
-            fail_to: None,
-            dct_to: None,
-            pattern_len: 0,
-        }
-    }
-}
-
-impl Node {
-    /// Instantiate a new node to add to the prefix tree. If a value is provided, a DictNode will
-    /// be instantiated with that value. Otherwise, a MedNode will be created.
+impl SearchOptions {
+    /// Check that the options are consistent, returning a `SearchError::InvalidOptions` error
+    /// otherwise. Called when building a tree.
+    ///
+    /// The wildcard must be left unchanged by the normalization of the patterns, otherwise it
+    /// would never be found in them (e.g. an uppercase letter in a case-insensitive search, or
+    /// a separator that is ignored).
     ///
     /// Example
     /// ```rust
-    /// use ac_search_rs::trie::Node;
+    /// use ac_search_rs::trie::{SearchError, SearchOptions};
     ///
-    /// let node_1 = Node::new(Some(String::from("variant")), Some(String::from("Standard Variant")));
+    /// let options = SearchOptions {
+    ///     case_sensitive: false,
+    ///     wildcard: Some('?'),
+    ///     ..Default::default()
+    /// };
+    /// assert!(options.validate().is_ok());
     ///
-    /// // Keyword equal to the value to match
-    /// let node_2 = Node::new(Some(String::from("pattern")), None);
+    /// let options = SearchOptions {
+    ///     wildcard: Some('-'),
+    ///     ignore_separators: true,
+    ///     ..Default::default()
+    /// };
+    /// assert!(matches!(options.validate(), Err(SearchError::InvalidOptions(_))));
     /// ```
-    pub fn new(value: Option<String>, keyword: Option<String>) -> Self {
-        match value {
-            None => Self::default(),
-            Some(s) => {
-                let total_chars = s.chars().count();
-                Self {
-                    keyword: Some(keyword.unwrap_or_else(|| s.clone())),
-                    value: Some(s),
-                    nxt: Vec::new(),
-                    fail_to: None,
-                    dct_to: None,
-                    pattern_len: total_chars,
-                }
+    pub fn validate(&self) -> SearchResult<()> {
+        if let Some(wildcard) = self.wildcard {
+            let changed = [
+                format!("{wildcard}"),
+                format!("a{wildcard}a"),
+                format!("1{wildcard}1"),
+            ]
+            .into_iter()
+            .any(|probe| self.strip_pattern(&self.normalize_pattern(&probe)) != probe);
+            if changed {
+                return Err(SearchError::InvalidOptions(format!(
+                    "the wildcard {:?} is changed by the normalization of the patterns",
+                    wildcard
+                )));
             }
         }
+        Ok(())
+    }
+
+    /// Transform a character of a text as required by the options: case folding for
+    /// case-insensitive searches and flexible whitespace mapped to a space.
+    #[inline]
+    pub(crate) fn fold_char(&self, c: char) -> char {
+        let c = if self.case_sensitive {
+            c
+        } else {
+            normalize::fold_case(c)
+        };
+        self.whitespace.normalize(c)
+    }
+
+    /// Normalize a pattern as required by the options: the chain of normalizers, Unicode NFC
+    /// normalization and case folding for case-insensitive searches.
+    pub(crate) fn normalize_pattern(&self, pattern: &str) -> String {
+        if let Some((normalized, _)) = normalizer::apply_normalizers(&self.normalizers, pattern) {
+            return self.normalize_pattern_chars(&normalized);
+        }
+        self.normalize_pattern_chars(pattern)
     }
 
-    /// Add a link to the node. The link is pushed to the node's list of following nodes.
-    fn add_link(&mut self, link: Link) {
-        self.nxt.push(link);
+    /// Apply Unicode NFC normalization and case folding for case-insensitive searches to a
+    /// pattern.
+    fn normalize_pattern_chars(&self, pattern: &str) -> String {
+        if self.case_sensitive {
+            normalize::nfc(pattern.chars()).collect()
+        } else {
+            normalize::nfc(pattern.chars().map(normalize::fold_case)).collect()
+        }
     }
 
-    /// Set the node's failure node to the given node ID.
-    fn add_fail_node(&mut self, node_id: NodeId) {
-        self.fail_to.replace(node_id);
+    /// Remove the separators that are ignored according to the options from a normalized
+    /// pattern, and collapse its whitespace if matched flexibly. Digit separators are removed
+    /// first, since searches only skip those that lie between two digits of the text.
+    pub(crate) fn strip_pattern(&self, pattern: &str) -> String {
+        let stripped;
+        let pattern = if self.ignore_digit_separators {
+            stripped = strip_digit_separators(pattern);
+            stripped.as_str()
+        } else {
+            pattern
+        };
+        if self.ignore_separators {
+            pattern.chars().filter(|&c| !is_separator(c)).collect()
+        } else {
+            self.whitespace.normalize_pattern(pattern)
+        }
     }
+}
+
+/// Compact storage of a node of the prefix tree. The links of the node and the pattern ending
+/// at it are kept in the tables of the tree and referenced by index, so that every node has
+/// the same small size and no allocations of its own.
+#[derive(Debug, Clone, Copy)]
+struct NodeData {
+    /// Range of the links of the node in the links arena of the tree
+    links_start: u32,
+    links_len: u32,
 
-    /// Get the vector of following nodes
+    /// Failure and dictionary links, NONE_ID if not set
+    fail_to: u32,
+    dct_to: u32,
+
+    /// ID of the pattern ending at the node, NONE_ID if it is not a dictionary node
+    pattern: u32,
+
+    /// Length in characters of the pattern ending at the node
+    pattern_len: u32,
+}
+
+impl Default for NodeData {
+    /// Default instantiation - no pattern, empty links
+    fn default() -> Self {
+        Self {
+            links_start: 0,
+            links_len: 0,
+            fail_to: NONE_ID,
+            dct_to: NONE_ID,
+            pattern: NONE_ID,
+            pattern_len: 0,
+        }
+    }
+}
+
+/// A pattern of the dictionary, stored once in the pattern table of the tree.
+#[derive(Debug)]
+struct PatternEntry {
+    /// The normalized pattern
+    value: Box<str>,
+
+    /// Keyword of the pattern, None if it is the pattern itself
+    keyword: Option<Box<str>>,
+
+    /// Index of the category of the pattern in the categories table, NONE_ID if none
+    category: u32,
+
+    /// Node where the pattern ends
+    node_id: u32,
+}
+
+/// Represents a node in the prefix tree for the Aho-Corasick structure. This is a view of the
+/// node borrowed from the tree, which stores the nodes, their links and their patterns in
+/// shared tables.
+#[derive(Clone, Copy)]
+pub struct Node<'t> {
+    tree: &'t TrieRoot,
+    data: &'t NodeData,
+}
+
+impl std::fmt::Debug for Node<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Node")
+            .field("value_keyword", &self.value_keyword())
+            .field("category", &self.category())
+            .field("next_nodes", &self.next_nodes())
+            .field("fail_node", &self.fail_node())
+            .field("fail_dct", &self.fail_dct())
+            .finish()
+    }
+}
+
+impl<'t> Node<'t> {
+    /// Get the slice of following nodes
     #[inline]
-    pub fn next_nodes(&self) -> &Vec<Link> {
-        &self.nxt
+    pub fn next_nodes(&self) -> &'t [Link] {
+        let start = self.data.links_start as usize;
+        &self.tree.links[start..start + self.data.links_len as usize]
     }
 
     /// Get adjacent (failure) link of this node
     #[inline]
     pub fn fail_node(&self) -> Option<NodeId> {
-        self.fail_to
+        opt_id(self.data.fail_to)
     }
 
     /// Get the first dictionary node found by following the trie's failure links
     /// from this node.
     #[inline]
     pub fn fail_dct(&self) -> Option<NodeId> {
-        self.dct_to
+        opt_id(self.data.dct_to)
     }
 
     /// Get a link to a following node for a suffix starting with the given character
     ///
-    /// Use binary search to search for a link that has the given character. Returns None
-    /// if there is no following link indexed with the given character.
+    /// Use binary search to search for a link that has the given character. If there is
+    /// none, the link of the wildcard character is followed, if any. Returns None if there
+    /// is no following link for the given character.
     pub fn follow_link(&self, ch: char) -> Option<NodeId> {
-        if self.nxt.len() < 8 {
+        let found = self.follow_exact_link(ch);
+        match self.tree.options.wildcard {
+            Some(wildcard) if found.is_none() && ch != wildcard => self.follow_exact_link(wildcard),
+            _ => found,
+        }
+    }
+
+    /// Get the link to a following node indexed with the given character, if any.
+    #[inline]
+    fn follow_exact_link(&self, ch: char) -> Option<NodeId> {
+        let nxt = self.next_nodes();
+        if nxt.len() < 8 {
             // Small array: simple search
-            self.nxt
-                .iter()
+            nxt.iter()
                 .find(|&l| l.get_char() == ch)
                 .map(|l| l.get_node_id())
         } else {
             // Larger array: binary search
-            self.nxt
-                .binary_search_by_key(&ch, |l| l.get_char())
+            nxt.binary_search_by_key(&ch, |l| l.get_char())
                 .ok()
-                .map(|i| self.nxt[i].get_node_id())
+                .map(|i| nxt[i].get_node_id())
         }
     }
 
+    /// Get the ID of the pattern ending at the node, if it is a dictionary node.
+    #[inline]
+    pub fn pattern_id(&self) -> Option<PatternId> {
+        opt_id(self.data.pattern)
+    }
+
+    /// Get the node where the pattern ending at this node was inserted. It is the node itself,
+    /// except in the copies of the subtrees of wildcards. The settings of the patterns (e.g.
+    /// context rules) are stored by this node.
+    #[inline]
+    pub(crate) fn pattern_node_id(&self) -> Option<NodeId> {
+        self.pattern_entry().map(|p| p.node_id as NodeId)
+    }
+
+    /// Get the length in characters of the pattern ending at the node (0 if it is not a
+    /// dictionary node).
+    #[inline]
+    pub(crate) fn pattern_len(&self) -> usize {
+        self.data.pattern_len as usize
+    }
+
+    /// Get the entry of the pattern ending at the node in the pattern table of the tree
+    #[inline]
+    fn pattern_entry(&self) -> Option<&'t PatternEntry> {
+        self.pattern_id().map(|id| &self.tree.patterns[id])
+    }
+
     /// Get the value and keyword of the node. These are not None if the node is a dictionary node.
-    pub fn value_keyword(&self) -> Option<(&str, &str)> {
-        match (&self.value, &self.keyword) {
-            (Some(s), Some(t)) => Some((s, t)),
-            _ => None,
+    pub fn value_keyword(&self) -> Option<(&'t str, &'t str)> {
+        self.pattern_entry()
+            .map(|p| (&*p.value, p.keyword.as_deref().unwrap_or(&p.value)))
+    }
+
+    /// Get the category (entity label) of the node, if it is a dictionary node with a category.
+    #[inline]
+    pub fn category(&self) -> Option<&'t str> {
+        let category = opt_id(self.pattern_entry()?.category)?;
+        Some(&self.tree.categories[category])
+    }
+
+    /// Get the metadata of the node, if it is a dictionary node with metadata.
+    #[inline]
+    pub fn metadata(&self) -> Option<&'t str> {
+        if self.tree.metadata.is_empty() {
+            return None;
+        }
+        let node_id = self.pattern_entry()?.node_id as NodeId;
+        self.tree.metadata.get(&node_id).map(String::as_str)
+    }
+
+    /// Get the payload of the node, if it is a dictionary node with a payload.
+    #[inline]
+    pub(crate) fn payload(&self) -> Option<&'t Payload> {
+        if self.tree.payloads.is_empty() {
+            return None;
+        }
+        let node_id = self.pattern_entry()?.node_id as NodeId;
+        self.tree.payloads.get(&node_id)
+    }
+
+    /// Get the priority of the node, 0 unless it is a dictionary node with another priority.
+    #[inline]
+    pub(crate) fn priority(&self) -> i32 {
+        if self.tree.priorities.is_empty() {
+            return 0;
         }
+        self.pattern_entry()
+            .and_then(|p| self.tree.priorities.get(&(p.node_id as NodeId)))
+            .copied()
+            .unwrap_or_default()
     }
 }
 
 /// Represents the root of the Aho-Corasick prefix tree
+///
+/// The tree is `Send` and `Sync`: searches only need a shared reference, so a built tree can
+/// be searched from several threads at once. See `SharedTrie` to share it between threads.
 pub struct TrieRoot {
-    nodes: Vec<Node>,
+    nodes: Vec<NodeData>,
+
+    /// Links of all the nodes. The links of each node are contiguous and sorted by character.
+    links: Vec<Link>,
+
+    /// Patterns of the dictionary, by pattern ID
+    patterns: Vec<PatternEntry>,
+
+    /// Distinct categories of the patterns, referenced by index from the pattern table
+    categories: Vec<Box<str>>,
+
     options: SearchOptions,
     max_pattern_len: usize,
+    context_rules: HashMap<NodeId, Vec<ContextRule>>,
+    weights: HashMap<NodeId, f32>,
+
+    /// Patterns with uppercase characters in case-insensitive trees, stored to score matches
+    cased_patterns: HashMap<NodeId, String>,
+
+    /// Cased forms of the patterns of case-insensitive trees whose matches must have their
+    /// exact case. See `create_from_dictionary`.
+    exact_case: HashMap<NodeId, String>,
+
+    /// Patterns whose matches are checked for word bounds or not regardless of the
+    /// `check_bounds` option. See `create_from_dictionary`.
+    bounds_override: HashMap<NodeId, bool>,
+
+    /// Data reported with the matches of each pattern
+    metadata: HashMap<NodeId, String>,
+
+    /// Values of any type reported with the matches of each pattern. See
+    /// `set_pattern_payload`.
+    payloads: HashMap<NodeId, Payload>,
+
+    /// Priorities of the patterns used to resolve overlapping matches. See
+    /// `set_pattern_priority`.
+    priorities: HashMap<NodeId, i32>,
+
+    /// Record of the patterns that have matched, if tracking coverage
+    coverage: Option<coverage::Coverage>,
+
+    /// Substring searcher used instead of the automaton for single-pattern dictionaries. Boxed
+    /// because the searcher may be aligned for SIMD registers, which the allocator of Python
+    /// objects wrapping the tree does not guarantee.
+    single_pattern: Option<Box<single::SinglePattern>>,
+
+    /// Dense transition table of the automaton, if compiled
+    dfa: Option<dfa::DenseDfa>,
 }
 
 impl TrieRoot {
-    /// Instantiate a new, empty prefix tree. This should not be called directly, use
-    /// create_prefix_tree function instead.
+    /// Instantiate a new, empty prefix tree with the given search options. Patterns must
+    /// be added afterwards.
     fn new(options: SearchOptions) -> Self {
         Self {
             // Add root node
-            nodes: vec![Node::default()],
+            nodes: vec![NodeData::default()],
+            links: Vec::new(),
+            patterns: Vec::new(),
+            categories: Vec::new(),
             max_pattern_len: 0,
             options,
+            context_rules: HashMap::new(),
+            weights: HashMap::new(),
+            cased_patterns: HashMap::new(),
+            exact_case: HashMap::new(),
+            bounds_override: HashMap::new(),
+            metadata: HashMap::new(),
+            payloads: HashMap::new(),
+            priorities: HashMap::new(),
+            coverage: None,
+            single_pattern: None,
+            dfa: None,
         }
     }
 
     /// Get a node by its ID number. Returns error if the ID is out of bounds.
-    pub fn get_node(&self, node_id: NodeId) -> SearchResult<&Node> {
-        if node_id >= self.nodes.len() {
-            Err(SearchError::InvalidNodeId(node_id))
-        } else {
-            Ok(&self.nodes[node_id])
+    pub fn get_node(&self, node_id: NodeId) -> SearchResult<Node<'_>> {
+        match self.nodes.get(node_id) {
+            None => Err(SearchError::InvalidNodeId(node_id)),
+            Some(data) => Ok(Node { tree: self, data }),
         }
     }
 
     /// Get a node without bounds checking  - to use with guaranteed-safe Ids
     #[inline]
-    fn get_node_unchecked(&self, node_id: NodeId) -> &Node {
-        &self.nodes[node_id]
+    fn get_node_unchecked(&self, node_id: NodeId) -> Node<'_> {
+        Node {
+            tree: self,
+            data: &self.nodes[node_id],
+        }
     }
 
-    /// Get the vector of nodes
-    pub fn nodes_vec(&self) -> &Vec<Node> {
-        &self.nodes
+    /// Iterate over the nodes of the tree, in order of their IDs
+    pub fn nodes(&self) -> impl ExactSizeIterator<Item = Node<'_>> {
+        self.nodes.iter().map(|data| Node { tree: self, data })
     }
 
     /// Get the ID of the root node of the tree
@@ -229,8 +755,8 @@ impl TrieRoot {
     }
 
     /// Get the root node of the tree
-    pub fn root_node(&self) -> &Node {
-        &self.nodes[0]
+    pub fn root_node(&self) -> Node<'_> {
+        self.get_node_unchecked(0)
     }
 
     /// Get the total number of nodes in the prefix tree
@@ -238,62 +764,128 @@ impl TrieRoot {
         self.nodes.len()
     }
 
-    /// Add a new node to the tree and return its Id
-    fn add_node(&mut self, node: Node) -> NodeId {
-        self.nodes.push(node);
-        self.nodes.len() - 1
+    /// Get the length (in characters) of the longest pattern stored in the tree
+    #[inline]
+    pub fn max_pattern_len(&self) -> usize {
+        self.max_pattern_len
     }
 
-    /// Add a link from one node in the tree to another
-    fn add_link(&mut self, from: NodeId, to: NodeId, c: char, fail_link: bool) -> SearchResult<()> {
+    /// Get the options the tree was built with
+    #[inline]
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// Add a new node to the tree and return its Id. Returns an error if the tree already has
+    /// as many nodes as can be referenced by the links.
+    fn add_node(&mut self) -> SearchResult<NodeId> {
+        if self.nodes.len() >= NONE_ID as usize {
+            return Err(SearchError::BuildBudget(format!(
+                "the tree can't have more than {} nodes",
+                NONE_ID
+            )));
+        }
+        self.nodes.push(NodeData::default());
+        Ok(self.nodes.len() - 1)
+    }
+
+    /// Set the failure link of a node
+    fn set_fail_node(&mut self, from: NodeId, to: NodeId) -> SearchResult<()> {
         if to >= self.nodes.len() {
             return Err(SearchError::InvalidNodeId(to));
         }
-        if from >= self.nodes.len() {
-            return Err(SearchError::InvalidNodeId(from));
-        }
-        let from_node = &mut self.nodes[from];
-        if fail_link {
-            from_node.add_fail_node(to);
-        } else {
-            let lnk = Link(c, to);
-            from_node.add_link(lnk);
-        }
+        let from_node = self
+            .nodes
+            .get_mut(from)
+            .ok_or(SearchError::InvalidNodeId(from))?;
+        from_node.fail_to = to as u32;
         Ok(())
     }
 
-    /// Add a new pattern / string to the prefix tree.
+    /// Get the links of each node as separate lists, to add nodes and links to the tree.
+    fn unpack_links(&self) -> Vec<Vec<Link>> {
+        self.nodes()
+            .map(|node| node.next_nodes().to_vec())
+            .collect()
+    }
+
+    /// Store the lists of links of the nodes in the links arena, each node referencing its
+    /// range of links.
+    fn pack_links(&mut self, node_links: Vec<Vec<Link>>) {
+        self.links = Vec::with_capacity(node_links.iter().map(Vec::len).sum());
+        for (node, links) in self.nodes.iter_mut().zip(node_links) {
+            node.links_start = self.links.len() as u32;
+            node.links_len = links.len() as u32;
+            self.links.extend(links);
+        }
+    }
+
+    /// Add new patterns / strings to the prefix tree, with their pattern IDs. The IDs must
+    /// follow those of the patterns already in the tree, in any order.
     ///
-    /// Add the nodes corresponding to a new string to the prefix tree along with
-    /// their corresponding "following" links. Adjacent or "failure" links must be added
-    /// separately by calling the "compute_failure_links" function. This is only meant to
-    /// be used during creation of the trie structure.
-    fn add_pattern(&mut self, mut new_item: String, kw: Option<String>) -> SearchResult<()> {
-        // Normalize pattern to unicode NFC (combined)
-        new_item = new_item.nfc().collect();
-
-        let mut current_id = self.root_node_id();
-        let characters: Vec<char> = new_item.chars().collect();
-        if characters.len() > self.max_pattern_len {
-            self.max_pattern_len = characters.len();
-        }
-
-        for (i, &c) in characters.iter().enumerate() {
-            match self.get_node_unchecked(current_id).follow_link(c) {
-                Some(nid) => current_id = nid,
-                None => {
-                    // Next node not already present - add it to the trie
-                    let (val, key) = if i == characters.len() - 1 {
-                        (Some(new_item.clone()), kw.clone())
-                    } else {
-                        (None, None)
-                    };
-                    let node_id = self.add_node(Node::new(val, key));
-                    self.add_link(current_id, node_id, c, false)?;
-
-                    current_id = node_id;
+    /// Add the nodes corresponding to the new strings to the prefix tree along with
+    /// their corresponding "following" links, and the patterns to the pattern table.
+    /// Adjacent or "failure" links must be added separately by calling the
+    /// "compute_failure_links" function. This is only meant to be used while building the
+    /// trie structure or adding patterns to it.
+    fn add_patterns_with_ids(
+        &mut self,
+        entries: Vec<(GazetteerEntry, PatternId)>,
+    ) -> SearchResult<()> {
+        let mut node_links = self.unpack_links();
+        let mut inserted = Vec::with_capacity(entries.len());
+        for (entry, pattern_id) in entries {
+            let mut current_id = self.root_node_id();
+            for c in entry.0.chars() {
+                let links = &node_links[current_id];
+                match links.binary_search_by_key(&c, Link::get_char) {
+                    Ok(i) => current_id = links[i].get_node_id(),
+                    Err(i) => {
+                        // Next node not already present - add it to the trie
+                        let node_id = self.add_node()?;
+                        node_links[current_id].insert(i, Link(c, node_id as u32));
+                        node_links.push(Vec::new());
+                        current_id = node_id;
+                    }
                 }
             }
+            inserted.push((pattern_id, current_id, entry));
+        }
+        self.pack_links(node_links);
+
+        // Categories are shared by the patterns
+        let mut category_ids: HashMap<String, u32> = (0..)
+            .zip(&self.categories)
+            .map(|(i, c)| (c.to_string(), i))
+            .collect();
+        inserted.sort_unstable_by_key(|&(pattern_id, _, _)| pattern_id);
+        for (pattern_id, node_id, (pattern, keyword, category)) in inserted {
+            if pattern_id != self.patterns.len() {
+                return Err(SearchError::InvalidDictionary);
+            }
+            let pattern_len = pattern.chars().count();
+            self.max_pattern_len = self.max_pattern_len.max(pattern_len);
+
+            let category = match category {
+                None => NONE_ID,
+                Some(c) => *category_ids.entry(c).or_insert_with_key(|c| {
+                    self.categories.push(c.as_str().into());
+                    (self.categories.len() - 1) as u32
+                }),
+            };
+
+            // The node may already exist if the pattern is a prefix of another one
+            let node = &mut self.nodes[node_id];
+            node.pattern = pattern_id as u32;
+            node.pattern_len = pattern_len as u32;
+            self.patterns.push(PatternEntry {
+                keyword: keyword
+                    .filter(|kw| *kw != pattern)
+                    .map(String::into_boxed_str),
+                value: pattern.into_boxed_str(),
+                category,
+                node_id: node_id as u32,
+            });
         }
         Ok(())
     }
@@ -307,21 +899,20 @@ impl TrieRoot {
         // Initialize queue with (parent_id, child_id, edge_char) tuples
         let mut queue = VecDeque::with_capacity(self.total_nodes());
         for link in self.root_node().next_nodes() {
-            let Link(c, node_id) = link;
-            queue.push_back((self.root_node_id(), *node_id, *c));
+            queue.push_back((self.root_node_id(), link.get_node_id(), link.get_char()));
         }
 
         // Process each node in BFS order
         while let Some((parent_id, current_id, edge_char)) = queue.pop_front() {
             // Push children to queue
             let curr_node = self.get_node(current_id)?;
-            for Link(c, nid) in curr_node.next_nodes() {
-                queue.push_back((current_id, *nid, *c));
+            for link in curr_node.next_nodes() {
+                queue.push_back((current_id, link.get_node_id(), link.get_char()));
             }
 
             // Level 1 failure nodes point to root
             if parent_id == self.root_node_id() {
-                self.add_link(current_id, parent_id, edge_char, true)?;
+                self.set_fail_node(current_id, parent_id)?;
                 continue;
             }
 
@@ -335,13 +926,13 @@ impl TrieRoot {
                 match check.follow_link(edge_char) {
                     Some(nid) => {
                         // Found the node
-                        self.add_link(current_id, nid, '\0', true)?;
+                        self.set_fail_node(current_id, nid)?;
                         break;
                     }
                     None => {
                         // No node found
                         if check_id == self.root_node_id() {
-                            self.add_link(current_id, check_id, edge_char, true)?;
+                            self.set_fail_node(current_id, check_id)?;
                             break;
                         } else if let Some(nid) = check.fail_node() {
                             check_id = nid;
@@ -378,25 +969,66 @@ impl TrieRoot {
         Some(current)
     }
 
-    /// Sort the lists of next links for all the nodes in the tree. This should be called just
-    /// once when initializing. Also assigns the dictionary failure nodes.
-    fn finalize_links(&mut self) {
-        for node in self.nodes.iter_mut() {
-            node.nxt.sort();
+    /// Get the node where the given pattern of the dictionary ends. The pattern is normalized
+    /// as required by the search options. Returns None if the pattern is not in the dictionary.
+    pub(crate) fn pattern_node_id(&self, pattern: &str) -> Option<NodeId> {
+        let path = self
+            .options
+            .strip_pattern(&self.options.normalize_pattern(pattern));
+        self.node_by_path(&path)
+            .filter(|&nid| self.get_node_unchecked(nid).pattern_node_id() == Some(nid))
+    }
+
+    /// Get the (pattern, keyword) entry with the given ID, as reported by `Match::pattern_id`.
+    /// The pattern is normalized according to the search options. Returns None if there is no
+    /// pattern with that ID.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = vec![
+    ///     (String::from("NYC"), Some(String::from("New York"))),
+    ///     (String::from("Big Apple"), Some(String::from("New York"))),
+    /// ];
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let found = prefix_tree.find_text_matches("the Big Apple").unwrap();
+    /// assert_eq!(found[0].pattern_id(), 1);
+    /// assert_eq!(prefix_tree.pattern(1), Some(("Big Apple", "New York")));
+    /// assert_eq!(prefix_tree.pattern(2), None);
+    /// ```
+    pub fn pattern(&self, id: PatternId) -> Option<(&str, &str)> {
+        let entry = self.patterns.get(id)?;
+        self.get_node_unchecked(entry.node_id as NodeId)
+            .value_keyword()
+    }
+
+    /// Store the cased forms of the patterns, given by normalized pattern, to score matches.
+    fn set_cased_patterns(&mut self, cased_patterns: Vec<(String, String)>) {
+        for (path, cased) in cased_patterns {
+            if let Some(node_id) = self.node_by_path(&path) {
+                self.cased_patterns.insert(node_id, cased);
+            }
         }
+    }
 
+    /// Assign the dictionary failure nodes of all the nodes in the tree. This should be called
+    /// once the failure links are computed.
+    fn finalize_links(&mut self) {
         for i in 0..self.nodes.len() {
             if i == self.root_node_id() {
                 continue;
             }
 
             // Follow fail nodes until reaching root or a dictionary node
-            let mut curr_id = self.nodes[i].fail_node().unwrap();
+            self.nodes[i].dct_to = NONE_ID;
+            let mut curr_id = self.get_node_unchecked(i).fail_node().unwrap();
             while curr_id != self.root_node_id() {
                 let curr = self.get_node_unchecked(curr_id);
-                match curr.value_keyword() {
+                match curr.pattern_id() {
                     Some(_) => {
-                        self.nodes[i].dct_to.replace(curr_id);
+                        self.nodes[i].dct_to = curr_id as u32;
                         break;
                     }
                     None => {
@@ -408,6 +1040,38 @@ impl TrieRoot {
     }
 }
 
+/// Return whether the character is a separator that can be ignored when the
+/// `ignore_separators` option is set (hyphens and whitespace).
+#[inline]
+pub(crate) fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '\u{2010}' | '\u{2011}') || c.is_whitespace()
+}
+
+/// Return whether the character can separate groups of digits in a number, to be ignored
+/// when the `ignore_digit_separators` option is set.
+#[inline]
+pub(crate) fn is_digit_separator(c: char) -> bool {
+    matches!(
+        c,
+        ',' | '\'' | '_' | ' ' | '\u{a0}' | '\u{2009}' | '\u{202f}'
+    )
+}
+
+/// Remove the digit separators that lie between two digits in the given string.
+fn strip_digit_separators(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let between_digits = i > 0
+            && chars[i - 1].is_ascii_digit()
+            && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit());
+        if !(between_digits && is_digit_separator(c)) {
+            out.push(c);
+        }
+    }
+    out
+}
+
 /// Given a vector of strings, return a vector of (pattern, keyword).
 ///
 /// This instantiates the new vector by adding a None keyword to each element.
@@ -442,7 +1106,7 @@ pub fn add_keyword_slot(patterns: Vec<String>) -> Vec<(String, Option<String>)>
 ///     String::from("ab"),
 ///     String::from("cd"),
 /// ]);
-/// let opts = trie::SearchOptions{case_sensitive: false, check_bounds: true};
+/// let opts = trie::SearchOptions{case_sensitive: false, check_bounds: true, ..Default::default()};
 /// let prefix_tree = trie::create_prefix_tree(my_dictionary, Some(opts)).unwrap();
 ///
 /// // With keywords and variants to match different patterns to "Python"
@@ -454,7 +1118,38 @@ pub fn add_keyword_slot(patterns: Vec<String>) -> Vec<(String, Option<String>)>
 /// let prefix_tree = trie::create_prefix_tree(my_dictionary, None).unwrap();
 /// ```
 pub fn create_prefix_tree(
-    mut dictionary: Vec<(String, Option<String>)>,
+    dictionary: Vec<(String, Option<String>)>,
+    opts: Option<SearchOptions>,
+) -> SearchResult<TrieRoot> {
+    let entries = dictionary
+        .into_iter()
+        .map(|(pattern, keyword)| (pattern, keyword, None))
+        .collect();
+    create_gazetteer(entries, opts)
+}
+
+/// Instantiate a prefix tree for search from a dictionary of (pattern, keyword, category)
+/// entries. The category is an entity label (e.g. PERSON, ORG or DRUG) that is reported along
+/// with the matches of the pattern. Returns an error if the dictionary is empty or contains
+/// empty strings or duplicates.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let gazetteer = vec![
+///     (String::from("Ada Lovelace"), None, Some(String::from("PERSON"))),
+///     (String::from("Acme Corp."), Some(String::from("Acme")), Some(String::from("ORG"))),
+///     (String::from("aspirin"), None, Some(String::from("DRUG"))),
+/// ];
+/// let prefix_tree = trie::create_gazetteer(gazetteer, None).unwrap();
+///
+/// let matches = prefix_tree.find_text_matches(String::from("Acme Corp. sells aspirin")).unwrap();
+/// assert_eq!(matches[0].category(), Some("ORG"));
+/// assert_eq!(matches[1].category(), Some("DRUG"));
+/// ```
+pub fn create_gazetteer(
+    mut dictionary: Vec<GazetteerEntry>,
     opts: Option<SearchOptions>,
 ) -> SearchResult<TrieRoot> {
     if dictionary.is_empty() {
@@ -462,16 +1157,17 @@ pub fn create_prefix_tree(
     }
 
     let opts_obj = opts.unwrap_or_default();
-    if !opts_obj.case_sensitive {
-        // Case insensitive - convert all dictionary elements to lowercase
-        for item in &mut dictionary {
-            item.0 = item.0.to_lowercase();
-        }
-    }
+    opts_obj.validate()?;
+    let cased_patterns = normalize_entries(&opts_obj, &mut dictionary);
+    // Keep the position of each entry in the given dictionary
+    let mut dictionary: Vec<(GazetteerEntry, usize)> = dictionary.into_iter().zip(0..).collect();
     dictionary.sort();
 
-    // Validate dictionary - no duplicate patterns
-    for (item, next) in dictionary.iter().zip(&dictionary[1..]) {
+    // Validate dictionary - no empty or duplicate patterns
+    if dictionary[0].0.0.is_empty() {
+        return Err(SearchError::InvalidDictionary);
+    }
+    for ((item, _), (next, _)) in dictionary.iter().zip(&dictionary[1..]) {
         if item.0 == next.0 {
             return Err(SearchError::DuplicateNode);
         } else if item.0.is_empty() || next.0.is_empty() {
@@ -480,14 +1176,44 @@ pub fn create_prefix_tree(
     }
 
     let mut pt = TrieRoot::new(opts_obj);
-    for (pattern, keyword) in dictionary {
-        pt.add_pattern(pattern, keyword).unwrap();
-    }
+    pt.add_patterns_with_ids(dictionary)?;
+    pt.expand_wildcards()?;
     pt.compute_failure_links()?;
     pt.finalize_links();
+    pt.set_cased_patterns(cased_patterns);
+    pt.set_single_pattern();
+    pt.refresh_dfa();
+    pt.warn_pathological();
     Ok(pt)
 }
 
+/// Normalize the patterns of dictionary entries as required by the search options. Returns
+/// the (normalized, cased) forms of the patterns whose case must be kept to compute scores.
+fn normalize_entries(
+    opts: &SearchOptions,
+    dictionary: &mut [GazetteerEntry],
+) -> Vec<(String, String)> {
+    let keep_cased = opts.compute_scores && !opts.case_sensitive;
+    let mut cased_patterns = Vec::new();
+    for (pattern, keyword, _) in dictionary {
+        // Normalize pattern to unicode NFC (combined) and fold its case if case
+        // insensitive. If separators are removed, keep the normalized form as the keyword.
+        let normalized = opts.normalize_pattern(pattern);
+        let stripped = opts.strip_pattern(&normalized);
+        if keep_cased {
+            let cased = opts.strip_pattern(&normalize::nfc(pattern.chars()).collect::<String>());
+            if cased != stripped {
+                cased_patterns.push((stripped.clone(), cased));
+            }
+        }
+        if stripped != normalized {
+            keyword.get_or_insert(normalized);
+        }
+        *pattern = stripped;
+    }
+    cased_patterns
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -522,7 +1248,7 @@ mod tests {
 
         // Count dictionary nodes
         let mut dct_vals = Vec::new();
-        for node in pt.nodes {
+        for node in pt.nodes() {
             if let Some((value, _)) = node.value_keyword() {
                 dct_vals.push(value.to_string());
             }
@@ -555,7 +1281,7 @@ mod tests {
             None => panic!("Expected a dictionary node"),
             Some((value, _)) => {
                 assert_eq!("ab", value);
-                &ab_node.nxt
+                ab_node.next_nodes()
             }
         };
         assert_eq!(ab_nxt.len(), 1);
@@ -565,7 +1291,7 @@ mod tests {
         // Check 'c' node
         let c_node = pt.get_node(pt.node_by_path("c").unwrap()).unwrap();
         let c_nxt = match c_node.value_keyword() {
-            None => &c_node.nxt,
+            None => c_node.next_nodes(),
             Some(_) => panic!("Expected intermediate node"),
         };
         assert_eq!(c_nxt.len(), 2);
@@ -766,13 +1492,14 @@ mod tests {
             Some(SearchOptions {
                 case_sensitive: false,
                 check_bounds: false,
+                ..Default::default()
             }),
         )
         .unwrap();
 
         assert_eq!(pt.total_nodes(), 7);
         let mut total_dct = 0;
-        for node in pt.nodes {
+        for node in pt.nodes() {
             if let Some((value, _)) = node.value_keyword() {
                 total_dct += 1;
                 assert_eq!(value, value.to_lowercase());
@@ -793,6 +1520,7 @@ mod tests {
             Some(SearchOptions {
                 case_sensitive: false,
                 check_bounds: false,
+                ..Default::default()
             }),
         );
         res.unwrap();
@@ -824,6 +1552,7 @@ mod tests {
             Some(SearchOptions {
                 case_sensitive: false,
                 check_bounds: false,
+                ..Default::default()
             }),
         )
         .unwrap();
@@ -844,7 +1573,7 @@ mod tests {
         .unwrap();
 
         let mut total_dct = 0;
-        for node in &pt.nodes {
+        for node in pt.nodes() {
             if let Some(nid) = dbg!(node).fail_dct() {
                 total_dct += 1;
                 dbg!(pt.get_node_unchecked(nid)).value_keyword().unwrap();
@@ -877,7 +1606,209 @@ mod tests {
         let acd_node = pt.get_node_unchecked(acd_id);
 
         // Expect bcd -> cd, acd -> cd
-        assert_eq!(bcd_node.dct_to.unwrap(), cd_id);
-        assert_eq!(acd_node.dct_to.unwrap(), cd_id);
+        assert_eq!(bcd_node.fail_dct().unwrap(), cd_id);
+        assert_eq!(acd_node.fail_dct().unwrap(), cd_id);
+    }
+
+    #[test]
+    fn test_create_ignore_separators() {
+        let opts = || SearchOptions {
+            ignore_separators: true,
+            ..Default::default()
+        };
+        let pt = create_prefix_tree(
+            vec![
+                (String::from("e-mail"), None),
+                (String::from("a b-c"), Some(String::from("ABC"))),
+            ],
+            Some(opts()),
+        )
+        .unwrap();
+
+        let email = pt.get_node(pt.node_by_path("email").unwrap()).unwrap();
+        assert_eq!(email.value_keyword(), Some(("email", "e-mail")));
+        let abc = pt.get_node(pt.node_by_path("abc").unwrap()).unwrap();
+        assert_eq!(abc.value_keyword(), Some(("abc", "ABC")));
+
+        // Patterns that are equal once separators are removed
+        let res = create_prefix_tree(
+            add_keyword_slot(vec![String::from("e-mail"), String::from("email")]),
+            Some(opts()),
+        );
+        assert_eq!(res.err(), Some(SearchError::DuplicateNode));
+
+        // Pattern made up only of separators
+        let res = create_prefix_tree(add_keyword_slot(vec![String::from(" - ")]), Some(opts()));
+        assert_eq!(res.err(), Some(SearchError::InvalidDictionary));
+    }
+
+    #[test]
+    fn test_create_ignore_digit_separators() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("10,000"),
+                String::from("1 2, 3"),
+                String::from("20000"),
+            ]),
+            Some(SearchOptions {
+                ignore_digit_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let node = pt.get_node(pt.node_by_path("10000").unwrap()).unwrap();
+        assert_eq!(node.value_keyword(), Some(("10000", "10,000")));
+        let node = pt.get_node(pt.node_by_path("12, 3").unwrap()).unwrap();
+        assert_eq!(node.value_keyword(), Some(("12, 3", "1 2, 3")));
+        let node = pt.get_node(pt.node_by_path("20000").unwrap()).unwrap();
+        assert_eq!(node.value_keyword(), Some(("20000", "20000")));
+    }
+
+    #[test]
+    fn test_create_gazetteer() {
+        let pt = create_gazetteer(
+            vec![
+                (String::from("ab"), None, Some(String::from("ORG"))),
+                (String::from("abc"), Some(String::from("x")), None),
+                (String::from("cd"), None, Some(String::from("DRUG"))),
+            ],
+            None,
+        )
+        .unwrap();
+
+        let node = pt.get_node(pt.node_by_path("ab").unwrap()).unwrap();
+        assert_eq!(node.category(), Some("ORG"));
+        let node = pt.get_node(pt.node_by_path("abc").unwrap()).unwrap();
+        assert_eq!(node.category(), None);
+        let node = pt.get_node(pt.node_by_path("c").unwrap()).unwrap();
+        assert_eq!(node.category(), None);
+
+        let matches = pt.find_text_matches(String::from("abcd")).unwrap();
+        let categories: Vec<Option<&str>> = matches.iter().map(|m| m.category()).collect();
+        assert_eq!(categories, [Some("ORG"), None, Some("DRUG")]);
+
+        let duplicated = create_gazetteer(
+            vec![
+                (String::from("ab"), None, Some(String::from("ORG"))),
+                (String::from("ab"), None, Some(String::from("PERSON"))),
+            ],
+            None,
+        );
+        assert_eq!(duplicated.err(), Some(SearchError::DuplicateNode));
+    }
+
+    #[test]
+    fn test_compact_layout() {
+        assert_eq!(size_of::<NodeData>(), 24);
+        assert_eq!(size_of::<Link>(), 8);
+
+        let mut pt = create_gazetteer(
+            vec![
+                (
+                    String::from("ab"),
+                    Some(String::from("ab")),
+                    Some(String::from("ORG")),
+                ),
+                (
+                    String::from("abc"),
+                    Some(String::from("x")),
+                    Some(String::from("ORG")),
+                ),
+                (String::from("cd"), None, Some(String::from("DRUG"))),
+            ],
+            None,
+        )
+        .unwrap();
+        pt.add_patterns(vec![(String::from("bd"), None)]).unwrap();
+
+        // Patterns are stored once, with shared categories and keywords only if different
+        assert_eq!(pt.patterns.len(), 4);
+        assert_eq!(pt.categories.len(), 2);
+        assert!(pt.patterns[0].keyword.is_none());
+        assert_eq!(pt.pattern(1), Some(("abc", "x")));
+
+        // The links of each node are contiguous and sorted
+        assert_eq!(pt.links.len(), pt.total_nodes() - 1);
+        for node in pt.nodes() {
+            assert!(node.next_nodes().is_sorted());
+        }
+        assert_eq!(pt.find_text_matches("abcd bd").unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_invalid_options() {
+        let build = |wildcard: char, opts: SearchOptions| {
+            create_prefix_tree(
+                add_keyword_slot(vec![String::from("ab")]),
+                Some(SearchOptions {
+                    wildcard: Some(wildcard),
+                    ..opts
+                }),
+            )
+        };
+        for (wildcard, opts) in [
+            (
+                'X',
+                SearchOptions {
+                    case_sensitive: false,
+                    ..Default::default()
+                },
+            ),
+            (
+                ' ',
+                SearchOptions {
+                    ignore_separators: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                '_',
+                SearchOptions {
+                    ignore_digit_separators: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                '\t',
+                SearchOptions {
+                    whitespace: WhitespaceMatch::Flexible,
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let err = build(wildcard, opts).err().unwrap();
+            assert!(
+                matches!(err, SearchError::InvalidOptions(_)),
+                "{wildcard:?}"
+            );
+            assert!(err.to_string().starts_with("Invalid search options"));
+        }
+        assert!(build('X', SearchOptions::default()).is_ok());
+        assert!(build('_', SearchOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_error_from_io() {
+        use std::error::Error;
+
+        let io_err = || std::io::Error::new(std::io::ErrorKind::NotFound, "missing.tsv");
+        let err: SearchError = io_err().into();
+        assert_eq!(
+            err,
+            SearchError::Io(std::io::ErrorKind::NotFound, ErrorSource::new(io_err()))
+        );
+        assert_eq!(err.to_string(), "IO error: missing.tsv");
+
+        // The IO error is kept as the source
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+        assert!(SearchError::Timeout.source().is_none());
+
+        fn assert_boxable<E: Error + Send + Sync + 'static>(_: &E) {}
+        assert_boxable(&err);
     }
 }
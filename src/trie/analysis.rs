@@ -0,0 +1,183 @@
+use super::{NodeId, TrieRoot};
+
+/// Number of single-character patterns for the dictionary to be reported with a warning when
+/// building a tree. Fewer are only logged at the info level, since dictionaries often have a few.
+const SINGLE_CHAR_WARN_THRESHOLD: usize = 10;
+
+/// Number of patterns an entry must be a suffix of to be reported when building a tree.
+const SUFFIX_WARN_THRESHOLD: usize = 100;
+
+/// Number of patterns that can end at the same position of a text for the tree to be reported
+/// when building it.
+const CHAIN_WARN_THRESHOLD: usize = 16;
+
+/// Structural features of a dictionary that can make the number of matches explode, as
+/// returned by `TrieRoot::analyze`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryReport<'a> {
+    /// Patterns made of a single character, which match anywhere that character appears
+    pub single_char_patterns: Vec<&'a str>,
+
+    /// Patterns that are a suffix of at least the requested number of other patterns, along
+    /// with the number of patterns they are a suffix of, sorted by decreasing count. Every
+    /// match of the longer patterns is also a match of these.
+    pub common_suffixes: Vec<(&'a str, usize)>,
+
+    /// Maximum number of patterns that can end at the same position of a text, i.e. the length
+    /// of the longest chain of dictionary links
+    pub max_chain_depth: usize,
+
+    /// A pattern whose matches reach the maximum chain depth, if any
+    pub deepest_pattern: Option<&'a str>,
+}
+
+impl TrieRoot {
+    /// Analyze the dictionary of the tree, looking for patterns that can make the search output
+    /// explode: single-character patterns, patterns that are suffixes of many others (at least
+    /// `suffix_threshold`) and long chains of patterns ending at the same position.
+    ///
+    /// Some of these checks are run when building a tree, logging a warning if they fail.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![
+    ///     String::from("a"),
+    ///     String::from("ba"),
+    ///     String::from("cba"),
+    ///     String::from("xa"),
+    /// ]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let report = prefix_tree.analyze(2);
+    /// assert_eq!(report.single_char_patterns, ["a"]);
+    /// assert_eq!(report.common_suffixes, [("a", 3)]);
+    /// assert_eq!(report.max_chain_depth, 3);
+    /// assert_eq!(report.deepest_pattern, Some("cba"));
+    /// ```
+    pub fn analyze(&self, suffix_threshold: usize) -> DictionaryReport<'_> {
+        let mut depths: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut suffix_counts = vec![0; self.nodes.len()];
+        let mut report = DictionaryReport {
+            single_char_patterns: Vec::new(),
+            common_suffixes: Vec::new(),
+            max_chain_depth: 0,
+            deepest_pattern: None,
+        };
+
+        for (node_id, node) in self.nodes().enumerate() {
+            let Some((value, _)) = node.value_keyword() else {
+                continue;
+            };
+            if node.pattern_len() == 1 {
+                report.single_char_patterns.push(value);
+            }
+            let depth = self.chain_depth(node_id, &mut depths);
+            if depth > report.max_chain_depth {
+                report.max_chain_depth = depth;
+                report.deepest_pattern = Some(value);
+            }
+
+            let mut suffix_id = node.fail_dct();
+            while let Some(sid) = suffix_id {
+                suffix_counts[sid] += 1;
+                suffix_id = self.get_node_unchecked(sid).fail_dct();
+            }
+        }
+
+        for (node_id, &count) in suffix_counts.iter().enumerate() {
+            if count > 0
+                && count >= suffix_threshold
+                && let Some((value, _)) = self.get_node_unchecked(node_id).value_keyword()
+            {
+                report.common_suffixes.push((value, count));
+            }
+        }
+        report.single_char_patterns.sort_unstable();
+        report
+            .common_suffixes
+            .sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        report
+    }
+
+    /// Number of patterns ending at the given node, following its dictionary links. Depths
+    /// already computed are stored in `memo`.
+    fn chain_depth(&self, node_id: NodeId, memo: &mut [Option<usize>]) -> usize {
+        if let Some(depth) = memo[node_id] {
+            return depth;
+        }
+        let node = self.get_node_unchecked(node_id);
+        let own = usize::from(node.value_keyword().is_some());
+        let depth = own + node.fail_dct().map_or(0, |nid| self.chain_depth(nid, memo));
+        memo[node_id] = Some(depth);
+        depth
+    }
+
+    /// Log warnings about the features of the dictionary that can make the search output
+    /// explode, or an info message if it only has a few single-character patterns. Called when
+    /// building the tree.
+    pub(crate) fn warn_pathological(&self) {
+        if !log::log_enabled!(log::Level::Info) {
+            return;
+        }
+        let report = self.analyze(SUFFIX_WARN_THRESHOLD);
+        if let Some(example) = report.single_char_patterns.first() {
+            let level = if report.single_char_patterns.len() >= SINGLE_CHAR_WARN_THRESHOLD {
+                log::Level::Warn
+            } else {
+                log::Level::Info
+            };
+            log::log!(
+                level,
+                "Dictionary has {} single-character patterns, e.g. {:?}",
+                report.single_char_patterns.len(),
+                example
+            );
+        }
+        for (pattern, count) in report.common_suffixes.iter().take(10) {
+            log::warn!(
+                "Pattern {:?} is a suffix of {} other patterns",
+                pattern,
+                count
+            );
+        }
+        if report.max_chain_depth >= CHAIN_WARN_THRESHOLD {
+            log::warn!(
+                "Up to {} patterns can match at the same position (e.g. ending with {:?})",
+                report.max_chain_depth,
+                report.deepest_pattern.unwrap_or_default()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_analyze_nested_suffixes() {
+        let words: Vec<String> = (1..=20).map(|n| "a".repeat(n)).collect();
+        let pt = create_prefix_tree(add_keyword_slot(words), None).unwrap();
+
+        let report = pt.analyze(18);
+        assert_eq!(report.single_char_patterns, ["a"]);
+        assert_eq!(report.common_suffixes, [("a", 19), ("aa", 18)]);
+        assert_eq!(report.max_chain_depth, 20);
+        assert_eq!(report.deepest_pattern, Some("a".repeat(20).as_str()));
+    }
+
+    #[test]
+    fn test_analyze_clean_dictionary() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("abc"), String::from("bcd")]),
+            None,
+        )
+        .unwrap();
+        let report = pt.analyze(1);
+        assert!(report.single_char_patterns.is_empty());
+        assert!(report.common_suffixes.is_empty());
+        assert_eq!(report.max_chain_depth, 1);
+    }
+}
@@ -0,0 +1,308 @@
+use super::normalize::{PreparedText, is_nfc_boundary};
+use super::{Match, NodeId, ReadChar, TrieRoot};
+
+/// Characters added to the longest pattern to get the size of the first window of text read
+/// by `longest_prefix`
+pub(crate) const PREFIX_WINDOW_MARGIN: usize = 16;
+
+impl TrieRoot {
+    /// Find the longest pattern of the dictionary that matches the text starting exactly at the
+    /// given character position, if any. This is the primitive needed for maximal munch
+    /// tokenization, where the text is consumed one longest match at a time.
+    ///
+    /// Word bounds and context rules are checked as in a regular search, and ignored
+    /// separators may occur inside the match but not at its start. The position and the
+    /// range of the returned match refer to the characters of the given text.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![
+    ///     String::from("new"),
+    ///     String::from("new york"),
+    ///     String::from("york"),
+    /// ]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let text = String::from("in new york city");
+    /// let found = prefix_tree.longest_match_at(text.clone(), 3).unwrap();
+    /// assert_eq!((found.value(), found.char_range()), ("new york", (3, 11)));
+    /// assert!(prefix_tree.longest_match_at(text, 4).is_none());
+    /// ```
+    pub fn longest_match_at<T: AsRef<str>>(&self, text: T, start: usize) -> Option<Match<'_>> {
+        let prepared = self.prepare_text(text.as_ref());
+        self.anchored_matches(&prepared, start, true).0.pop()
+    }
+
+    /// Find the longest pattern of the dictionary that is a prefix of the text, if any. This
+    /// is the same as `longest_match_at(text, 0)`, but only normalizes and reads the start of
+    /// the text, so that a greedy tokenizer can consume a long text one token at a time.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![
+    ///     String::from("un"),
+    ///     String::from("unaffable"),
+    ///     String::from("aff"),
+    /// ]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let text = "unaffected ".repeat(1000);
+    /// let found = prefix_tree.longest_prefix(&text).unwrap();
+    /// assert_eq!((found.value(), found.char_range()), ("un", (0, 2)));
+    /// assert!(prefix_tree.longest_prefix("affable").is_some());
+    /// assert!(prefix_tree.longest_prefix("naff").is_none());
+    /// ```
+    pub fn longest_prefix(&self, text: &str) -> Option<Match<'_>> {
+        // Characters that can be read after the end of a match by the context rules
+        let lookahead = self.context_lookahead();
+        let mut window = self.max_pattern_len + lookahead + PREFIX_WINDOW_MARGIN;
+        loop {
+            // Cut the text where NFC normalization of the window agrees with that of the text
+            let Some(cut) = text
+                .char_indices()
+                .skip(window)
+                .find(|&(_, c)| is_nfc_boundary(c))
+                .map(|(b, _)| b)
+            else {
+                return self.longest_match_at(text, 0);
+            };
+            let prepared = self.prepare_text(&text[..cut]);
+            let (mut found, read) = self.anchored_matches(&prepared, 0, true);
+            if read + lookahead <= prepared.text.chars().count() {
+                return found.pop();
+            }
+            // The result might depend on characters after the window
+            window *= 2;
+        }
+    }
+
+    /// Find the patterns matching a prepared text at the given character position of the
+    /// original text, from the shortest to the longest, or only the longest one. Also returns
+    /// the number of characters of the prepared text read to find them, excluding the
+    /// lookahead of context rules.
+    pub(crate) fn anchored_matches<'a>(
+        &'a self,
+        prepared: &PreparedText,
+        start: usize,
+        longest_only: bool,
+    ) -> (Vec<Match<'a>>, usize) {
+        let chars: Vec<(usize, char)> = prepared.text.char_indices().collect();
+
+        // Position in the prepared text corresponding to the start in the original text
+        let first_pos = match &prepared.offsets {
+            None => start,
+            Some(offsets) => match offsets.iter().position(|&(orig, _)| orig >= start) {
+                Some(pos) => pos,
+                None => return (Vec::new(), chars.len()),
+            },
+        };
+        if first_pos >= chars.len() || prepared.original_range(first_pos, first_pos + 1).0 != start
+        {
+            return (Vec::new(), first_pos + 1);
+        }
+        let (first_byte, first_ch) = chars[first_pos];
+        let prev = first_pos.checked_sub(1).map(|i| chars[i].1);
+        if self.skip_char(prev, first_ch, chars.get(first_pos + 1).map(|&(_, c)| c)) {
+            return (Vec::new(), first_pos + 2);
+        }
+        let first = ReadChar {
+            ch: first_ch,
+            pos: first_pos,
+            byte: first_byte,
+            prev,
+        };
+
+        // Follow the tree edges from the root, remembering the acceptable patterns
+        let mut node_id = self.root_node_id();
+        let mut accepted: Vec<(NodeId, usize, usize)> = Vec::new();
+        let mut read = first_pos;
+        for pos in first_pos..chars.len() {
+            let (byte, ch) = chars[pos];
+            let next = chars.get(pos + 1).map(|&(_, c)| c);
+            read = pos + 2;
+            if pos > first_pos && self.skip_char(Some(chars[pos - 1].1), ch, next) {
+                continue;
+            }
+            let Some(next_id) = self.get_node_unchecked(node_id).follow_link(ch) else {
+                break;
+            };
+            node_id = next_id;
+            let Some(pattern_node) = self.get_node_unchecked(node_id).pattern_node_id() else {
+                continue;
+            };
+            if self.is_match_bounded(
+                pattern_node,
+                &first,
+                ch,
+                byte + ch.len_utf8(),
+                next,
+                Some(&prepared.text),
+            ) && self.context_allows(
+                pattern_node,
+                &prepared.text,
+                first_byte,
+                byte + ch.len_utf8(),
+            ) && self.exact_case_allows(
+                pattern_node,
+                prepared,
+                (first_pos, pos + 1),
+                first_byte..byte + ch.len_utf8(),
+            ) {
+                if longest_only {
+                    accepted.clear();
+                }
+                accepted.push((pattern_node, pos + 1, byte + ch.len_utf8()));
+            }
+        }
+
+        let mut found = Vec::with_capacity(accepted.len());
+        for (node_id, end, end_byte) in accepted {
+            let node = self.get_node_unchecked(node_id);
+            let Some((value, kw)) = node.value_keyword() else {
+                continue;
+            };
+            if let Some(coverage) = &self.coverage {
+                coverage.record(node_id);
+            }
+            found.push(
+                Match::new(value, kw, end, end - first_pos)
+                    .with_category(node.category())
+                    .with_metadata(node.metadata())
+                    .with_payload(node.payload())
+                    .with_priority(node.priority())
+                    .with_byte_range(first_byte, end_byte)
+                    .with_pattern_id(node.pattern_id().unwrap_or_default()),
+            );
+        }
+        self.finish_matches(&mut found, prepared);
+        (found, read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ContextRule, SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_longest_match_at() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("a"),
+                String::from("ab"),
+                String::from("abcd"),
+                String::from("bc"),
+            ]),
+            None,
+        )
+        .unwrap();
+        let hs = "xabcabcd";
+
+        let found: Vec<Option<&str>> = (0..=hs.len())
+            .map(|i| pt.longest_match_at(String::from(hs), i).map(|m| m.value()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                None,
+                Some("ab"),
+                Some("bc"),
+                None,
+                Some("abcd"),
+                Some("bc"),
+                None,
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_longest_match_at_options() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abc"),
+                String::from("é-x"),
+            ]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds: true,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        // The separator is ignored inside the match, and "abcd" is not word bounded
+        let found = pt.longest_match_at(String::from("AB-C abcd"), 0).unwrap();
+        assert_eq!((found.value(), found.char_range()), ("abc", (0, 4)));
+        assert!(pt.longest_match_at(String::from("AB-C abcd"), 5).is_none());
+
+        // Decomposed accent: the match covers both characters of the original text
+        #[cfg(feature = "unicode")]
+        {
+            let found = pt.longest_match_at(String::from("E\u{301} X"), 0).unwrap();
+            assert_eq!((found.value(), found.char_range()), ("éx", (0, 4)));
+            assert!(pt.longest_match_at(String::from("E\u{301} X"), 1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("a"),
+                String::from("ab"),
+                String::from("a b c d"),
+                String::from("éa"),
+                String::from("tag"),
+            ]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds: true,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        pt.add_context_rule("tag", ContextRule::NotFollowedBy("-".repeat(40)))
+            .unwrap();
+
+        let long_sep = format!("a{}b c d", " ".repeat(100));
+        let context = format!("tag{} x", "-".repeat(40));
+        let decomposed = format!("E\u{301}A{}", " x".repeat(100));
+        for text in [
+            "ab",
+            "a b c d e",
+            "abc",
+            "",
+            " ab",
+            long_sep.as_str(),
+            context.as_str(),
+            &context[..30],
+            decomposed.as_str(),
+        ] {
+            let expected = pt.longest_match_at(text, 0);
+            let found = pt.longest_prefix(text);
+            assert_eq!(
+                found
+                    .as_ref()
+                    .map(|m| (m.value(), m.char_range(), m.byte_range())),
+                expected
+                    .as_ref()
+                    .map(|m| (m.value(), m.char_range(), m.byte_range())),
+                "{text}"
+            );
+        }
+        assert_eq!(
+            pt.longest_prefix(&"a b c d ".repeat(1000)).unwrap().value(),
+            "abcd"
+        );
+        let found = pt.longest_prefix(&long_sep).unwrap();
+        assert_eq!((found.value(), found.char_range()), ("abcd", (0, 106)));
+    }
+}
@@ -0,0 +1,235 @@
+use super::{
+    GazetteerEntry, MatchKind, SearchLimits, SearchOptions, SearchResult, TrieRoot,
+    WhitespaceMatch, create_gazetteer,
+};
+
+/// Builder to configure and create a prefix tree, as an alternative to `create_prefix_tree`
+/// and `create_gazetteer`.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{MatchKind, TrieBuilder};
+///
+/// let prefix_tree = TrieBuilder::new()
+///     .case_insensitive(true)
+///     .word_bounds(true)
+///     .match_kind(MatchKind::LeftmostLongest)
+///     .add_pattern("new york")
+///     .add_pattern_with_keyword("NYC", "new york")
+///     .build()
+///     .unwrap();
+///
+/// let matches = prefix_tree.find_text_matches("New York, or nyc").unwrap();
+/// assert_eq!(matches.len(), 2);
+/// assert!(matches.iter().all(|m| m.keyword() == "new york"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrieBuilder {
+    entries: Vec<GazetteerEntry>,
+    options: SearchOptions,
+    track_coverage: bool,
+}
+
+impl TrieBuilder {
+    /// Create a builder with no patterns and the default search options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace all the search options.
+    pub fn options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether to ignore the case of the patterns and texts. See `SearchOptions::case_sensitive`.
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.options.case_sensitive = !yes;
+        self
+    }
+
+    /// Whether to report only matches at word boundaries. See `SearchOptions::check_bounds`.
+    pub fn word_bounds(mut self, yes: bool) -> Self {
+        self.options.check_bounds = yes;
+        self
+    }
+
+    /// Whether to find word boundaries with the Unicode word segmentation rules. See
+    /// `SearchOptions::word_segmentation`.
+    pub fn word_segmentation(mut self, yes: bool) -> Self {
+        self.options.word_segmentation = yes;
+        self
+    }
+
+    /// Whether to treat hyphens and whitespace as optional. See
+    /// `SearchOptions::ignore_separators`.
+    pub fn ignore_separators(mut self, yes: bool) -> Self {
+        self.options.ignore_separators = yes;
+        self
+    }
+
+    /// Whether to ignore the separators between groups of digits. See
+    /// `SearchOptions::ignore_digit_separators`.
+    pub fn ignore_digit_separators(mut self, yes: bool) -> Self {
+        self.options.ignore_digit_separators = yes;
+        self
+    }
+
+    /// How whitespace in the patterns is matched. See `WhitespaceMatch`.
+    pub fn whitespace(mut self, mode: WhitespaceMatch) -> Self {
+        self.options.whitespace = mode;
+        self
+    }
+
+    /// Character that matches any single character in the patterns. See
+    /// `SearchOptions::wildcard`.
+    pub fn wildcard(mut self, wildcard: Option<char>) -> Self {
+        self.options.wildcard = wildcard;
+        self
+    }
+
+    /// Whether to compute the scores of the matches. See `SearchOptions::compute_scores`.
+    pub fn compute_scores(mut self, yes: bool) -> Self {
+        self.options.compute_scores = yes;
+        self
+    }
+
+    /// Maximum number of patterns checked at each position of the text. See
+    /// `SearchOptions::max_chain_depth`.
+    pub fn max_chain_depth(mut self, depth: Option<usize>) -> Self {
+        self.options.max_chain_depth = depth;
+        self
+    }
+
+    /// Limits on the work done by each search. See `SearchLimits`.
+    pub fn limits(mut self, limits: SearchLimits) -> Self {
+        self.options.limits = limits;
+        self
+    }
+
+    /// How overlapping matches are reported. See `MatchKind`.
+    pub fn match_kind(mut self, kind: MatchKind) -> Self {
+        self.options.match_kind = kind;
+        self
+    }
+
+    /// Whether to compile the dense transition table of the automaton. See
+    /// `TrieRoot::compile_dfa`.
+    pub fn dense_dfa(mut self, yes: bool) -> Self {
+        self.options.dense_dfa = yes;
+        self
+    }
+
+    /// Whether to record which patterns match. See `TrieRoot::track_coverage`.
+    pub fn track_coverage(mut self, yes: bool) -> Self {
+        self.track_coverage = yes;
+        self
+    }
+
+    /// Add a pattern that is its own keyword.
+    pub fn add_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.entries.push((pattern.into(), None, None));
+        self
+    }
+
+    /// Add a pattern reported with the given keyword.
+    pub fn add_pattern_with_keyword(
+        mut self,
+        pattern: impl Into<String>,
+        keyword: impl Into<String>,
+    ) -> Self {
+        self.entries
+            .push((pattern.into(), Some(keyword.into()), None));
+        self
+    }
+
+    /// Add a (pattern, keyword, category) entry, as in `create_gazetteer`.
+    pub fn add_entry(mut self, entry: GazetteerEntry) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Add several patterns that are their own keywords.
+    pub fn add_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.entries
+            .extend(patterns.into_iter().map(|p| (p.into(), None, None)));
+        self
+    }
+
+    /// Build the prefix tree. Returns an error if there are no patterns, or some are empty
+    /// or duplicated.
+    pub fn build(self) -> SearchResult<TrieRoot> {
+        let mut pt = create_gazetteer(self.entries, Some(self.options))?;
+        if self.track_coverage {
+            pt.track_coverage();
+        }
+        Ok(pt)
+    }
+}
+
+impl TrieRoot {
+    /// Create a builder to configure a new prefix tree. See `TrieBuilder`.
+    pub fn builder() -> TrieBuilder {
+        TrieBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchError, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_builder_options() {
+        let pt = TrieRoot::builder()
+            .case_insensitive(true)
+            .word_bounds(true)
+            .word_segmentation(true)
+            .ignore_separators(true)
+            .compute_scores(true)
+            .max_chain_depth(Some(3))
+            .dense_dfa(true)
+            .track_coverage(true)
+            .add_patterns(["e-mail", "Mail"])
+            .add_entry((String::from("post"), None, Some(String::from("X"))))
+            .build()
+            .unwrap();
+
+        let opts = pt.options();
+        assert!(!opts.case_sensitive && opts.check_bounds && opts.ignore_separators);
+        assert!(opts.word_segmentation && opts.compute_scores && opts.dense_dfa);
+        assert_eq!(opts.max_chain_depth, Some(3));
+        assert!(pt.never_matched().is_some());
+        assert!(pt.is_dfa_compiled());
+        assert_eq!(pt.total_patterns(), 3);
+    }
+
+    #[test]
+    fn test_builder_same_as_create() {
+        let built = TrieBuilder::new()
+            .add_pattern("ab")
+            .add_pattern_with_keyword("abc", "ABC")
+            .build()
+            .unwrap();
+        let created = create_prefix_tree(
+            vec![
+                (String::from("ab"), None),
+                (String::from("abc"), Some(String::from("ABC"))),
+            ],
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            built.find_text_matches("xabc").unwrap(),
+            created.find_text_matches("xabc").unwrap()
+        );
+        assert_eq!(
+            TrieBuilder::new().build().err(),
+            Some(SearchError::InvalidDictionary)
+        );
+    }
+}
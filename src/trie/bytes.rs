@@ -0,0 +1,220 @@
+use super::{Match, SearchResult, TrieRoot, add_keyword_slot, create_prefix_tree};
+
+/// How to handle byte sequences that are not valid UTF-8 when searching raw bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidUtf8 {
+    /// Replace each invalid sequence with the replacement character (U+FFFD), so that no match
+    /// can span it.
+    #[default]
+    Replace,
+
+    /// Drop the invalid sequences, so that the text on both sides is searched as if it was
+    /// contiguous.
+    Skip,
+}
+
+/// Decode the given bytes as UTF-8, handling invalid sequences as requested. Returns the
+/// decoded text along with the range of bytes of the input each character comes from, or None
+/// if the characters correspond one to one to the bytes.
+fn decode_bytes(data: &[u8], invalid: InvalidUtf8) -> (String, Option<Vec<(usize, usize)>>) {
+    if data.is_ascii() {
+        // ASCII is valid UTF-8 with one byte per character
+        return (String::from_utf8(data.to_vec()).unwrap(), None);
+    }
+
+    let mut text = String::with_capacity(data.len());
+    let mut spans = Vec::with_capacity(data.len());
+    let mut offset = 0;
+    for chunk in data.utf8_chunks() {
+        for (i, c) in chunk.valid().char_indices() {
+            text.push(c);
+            spans.push((offset + i, offset + i + c.len_utf8()));
+        }
+        offset += chunk.valid().len();
+
+        let bad = chunk.invalid().len();
+        if bad > 0 && invalid == InvalidUtf8::Replace {
+            text.push(char::REPLACEMENT_CHARACTER);
+            spans.push((offset, offset + bad));
+        }
+        offset += bad;
+    }
+    (text, Some(spans))
+}
+
+/// Map each byte to the character with the same code point (Latin-1), so that the characters
+/// correspond one to one to the bytes.
+fn bytes_to_latin1(data: &[u8]) -> String {
+    data.iter().map(|&b| char::from(b)).collect()
+}
+
+impl TrieRoot {
+    /// Create a prefix tree to search for byte signatures, which need not be valid UTF-8 (e.g.
+    /// in binary protocol payloads or files with mixed encodings). Search it with
+    /// `find_byte_matches`.
+    ///
+    /// Each byte of the patterns is stored as the character with the same code point
+    /// (Latin-1), which is how the values of the matches are reported. The IDs of the patterns
+    /// are their positions in the given vector, so `Match::pattern_id` identifies the pattern
+    /// matched. The tree uses the default search options, so the bytes are matched exactly,
+    /// without checking word bounds.
+    pub fn from_byte_patterns(patterns: Vec<Vec<u8>>) -> SearchResult<Self> {
+        let patterns = patterns.iter().map(|p| bytes_to_latin1(p)).collect();
+        create_prefix_tree(add_keyword_slot(patterns), None)
+    }
+
+    /// Find all matches for the search dictionary in the given bytes, reading each byte as
+    /// one character, without assuming the data is UTF-8. The character and byte ranges of the
+    /// matches are both byte offsets into the data.
+    ///
+    /// This is meant for the trees built with `from_byte_patterns`. The patterns of other
+    /// trees are only found where they are ASCII; use `find_matches_lossy` to search mostly
+    /// UTF-8 data for text patterns instead.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::TrieRoot;
+    ///
+    /// let signatures = vec![b"\x89PNG".to_vec(), b"\xff\xd8\xff".to_vec(), b"%PDF".to_vec()];
+    /// let prefix_tree = TrieRoot::from_byte_patterns(signatures).unwrap();
+    ///
+    /// let data = b"\x00\x01\xff\xd8\xff\xe0 ... \x89PNG\r\n";
+    /// let found: Vec<(usize, (usize, usize))> = prefix_tree
+    ///     .find_byte_matches(data)
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(|m| (m.pattern_id(), m.char_range()))
+    ///     .collect();
+    /// assert_eq!(found, [(1, (2, 5)), (0, (11, 15))]);
+    /// ```
+    pub fn find_byte_matches<'a>(&'a self, data: &[u8]) -> SearchResult<Vec<Match<'a>>> {
+        let mut matches = self.find_text_matches(bytes_to_latin1(data))?;
+        for m in &mut matches {
+            (m.start_byte, m.end_byte) = (m.start, m.end);
+        }
+        Ok(matches)
+    }
+
+    /// Find all matches for the search dictionary in the given bytes, which may contain
+    /// invalid UTF-8 (e.g. log files or network captures). Invalid sequences are handled
+    /// according to `invalid`.
+    ///
+    /// Unlike the other searches, the character ranges of the matches are **byte** offsets into the
+    /// given data, the same as their byte ranges, since the character indices of a partially
+    /// invalid input are not well defined.
+    ///
+    /// This decodes the data as UTF-8 to search for text patterns. Use `find_byte_matches`
+    /// instead to search for the byte signatures of a tree built with `from_byte_patterns`.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, InvalidUtf8};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("error"), String::from("é")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let data = b"\xff\xfeerror: caf\xc3\xa9";
+    /// let matches = prefix_tree.find_matches_lossy(data, InvalidUtf8::Replace).unwrap();
+    /// assert_eq!(matches[0].char_range(), (2, 7));
+    /// assert_eq!(matches[1].char_range(), (12, 14));
+    /// ```
+    pub fn find_matches_lossy<'a>(
+        &'a self,
+        data: &[u8],
+        invalid: InvalidUtf8,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let (text, spans) = decode_bytes(data, invalid);
+        let mut matches = self.find_text_matches(text)?;
+        if let Some(spans) = spans {
+            for m in &mut matches {
+                (m.start, m.end) = (spans[m.start].0, spans[m.end - 1].1);
+                (m.start_byte, m.end_byte) = (m.start, m.end);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::MatchKind;
+    use super::*;
+    use std::ops::Range;
+
+    #[test]
+    fn test_decode_bytes() {
+        let (text, spans) = decode_bytes(b"abc", InvalidUtf8::Replace);
+        assert_eq!(text, "abc");
+        assert!(spans.is_none());
+
+        let data = b"a\xffb\xc3\xa9\xe2\x82";
+        let (text, spans) = decode_bytes(data, InvalidUtf8::Replace);
+        assert_eq!(text, "a\u{fffd}bé\u{fffd}");
+        assert_eq!(spans.unwrap(), [(0, 1), (1, 2), (2, 3), (3, 5), (5, 7)]);
+
+        let (text, spans) = decode_bytes(data, InvalidUtf8::Skip);
+        assert_eq!(text, "abé");
+        assert_eq!(spans.unwrap(), [(0, 1), (2, 3), (3, 5)]);
+    }
+
+    #[test]
+    fn test_search_byte_patterns() {
+        let patterns = vec![
+            b"\xca\xfe".to_vec(),
+            b"\x00\xff\x00".to_vec(),
+            b"AB".to_vec(),
+        ];
+        let pt = TrieRoot::from_byte_patterns(patterns).unwrap();
+        assert_eq!(pt.pattern(0), Some(("\u{ca}\u{fe}", "\u{ca}\u{fe}")));
+
+        let data = b"\xca\xfe\xba\xbe\x00\xff\x00\xff\x00 ab AB\xc3\xa9";
+        let found: Vec<(usize, (usize, usize), Range<usize>)> = pt
+            .find_byte_matches(data)
+            .unwrap()
+            .iter()
+            .map(|m| (m.pattern_id(), m.char_range(), m.byte_range()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (0, (0, 2), 0..2),
+                (1, (4, 7), 4..7),
+                (1, (6, 9), 6..9),
+                (2, (13, 15), 13..15),
+            ]
+        );
+        assert_eq!(pt.options().match_kind, MatchKind::Overlapping);
+
+        // Single pattern, searched without the automaton
+        let pt = TrieRoot::from_byte_patterns(vec![b"\xff\x00".to_vec()]).unwrap();
+        let ranges: Vec<Range<usize>> = pt
+            .find_byte_matches(b"\xff\xff\x00\xff")
+            .unwrap()
+            .iter()
+            .map(|m| m.byte_range())
+            .collect();
+        assert_eq!(ranges, vec![1..3]);
+
+        assert!(TrieRoot::from_byte_patterns(vec![b"a".to_vec(), b"a".to_vec()]).is_err());
+    }
+
+    #[test]
+    fn test_search_bytes() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("né")]),
+            None,
+        )
+        .unwrap();
+
+        let data = b"a\xffb ab n\xc3\xa9";
+        let ranges = |invalid| -> Vec<(usize, usize)> {
+            pt.find_matches_lossy(data, invalid)
+                .unwrap()
+                .iter()
+                .map(|m| m.char_range())
+                .collect()
+        };
+        assert_eq!(ranges(InvalidUtf8::Replace), [(4, 6), (7, 10)]);
+        assert_eq!(ranges(InvalidUtf8::Skip), [(0, 3), (4, 6), (7, 10)]);
+    }
+}
@@ -0,0 +1,159 @@
+use super::{Match, SearchError, SearchResult, TrieRoot};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// A flag to abandon searches in progress from another thread, e.g. when the request that
+/// started them times out. Clones of a token share the same flag, so one can be handed to the
+/// searches and another kept to cancel them.
+///
+/// Cancellation is cooperative: the token is checked periodically while scanning a text, and
+/// the searches return a `SearchError::Cancelled` error once it is set.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, CancelToken, SearchError};
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// let token = CancelToken::new();
+/// let found = prefix_tree.find_text_matches_cancellable("abab", &token).unwrap();
+/// assert_eq!(found.len(), 2);
+///
+/// token.clone().cancel();
+/// assert!(token.is_cancelled());
+/// let found = prefix_tree.find_text_matches_cancellable("abab", &token);
+/// assert_eq!(found.err(), Some(SearchError::Cancelled));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the searches checking this token or any of its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Return whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Conditions under which the scan of a text is abandoned before its end.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Interrupt<'c> {
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) cancel: Option<&'c CancelToken>,
+
+    /// End of the time allowed by the `time_limit` of the search limits, and the limit
+    time_limit: Option<(Instant, Duration)>,
+}
+
+impl<'c> Interrupt<'c> {
+    /// Stop the scan at the deadline, if any.
+    pub(crate) fn until(deadline: Option<Instant>) -> Self {
+        Self {
+            deadline,
+            ..Default::default()
+        }
+    }
+
+    /// Also stop the scan once the given time has passed from now, if any.
+    pub(crate) fn with_time_limit(self, limit: Option<Duration>) -> Self {
+        Self {
+            time_limit: limit.map(|l| (Instant::now() + l, l)),
+            ..self
+        }
+    }
+
+    /// Check whether the scan must stop, returning the error to report in that case.
+    #[inline]
+    pub(crate) fn check(&self) -> Option<SearchError> {
+        if self.cancel.is_some_and(CancelToken::is_cancelled) {
+            Some(SearchError::Cancelled)
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(SearchError::Timeout)
+        } else if let Some((end, limit)) = self.time_limit
+            && Instant::now() >= end
+        {
+            Some(SearchError::LimitExceeded(format!(
+                "time limit of {:?}",
+                limit
+            )))
+        } else {
+            None
+        }
+    }
+}
+
+impl TrieRoot {
+    /// Find all matches for the search dictionary in the given text, giving up with a
+    /// `SearchError::Cancelled` error if the token is cancelled before the search is done.
+    /// See `CancelToken`.
+    pub fn find_text_matches_cancellable<'a, T: AsRef<str>>(
+        &'a self,
+        text: T,
+        token: &CancelToken,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let interrupt = Interrupt {
+            cancel: Some(token),
+            ..Default::default()
+        };
+        self.find_matches_filtered(text.as_ref(), 0, interrupt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_cancel_search() {
+        let words = vec![String::from("ab"), String::from("cd")];
+        let pt = create_prefix_tree(add_keyword_slot(words.clone()), None).unwrap();
+        let single = create_prefix_tree(add_keyword_slot(vec![words[0].clone()]), None).unwrap();
+        let text = "ab cd ".repeat(100_000);
+
+        let token = CancelToken::new();
+        assert_eq!(
+            pt.find_text_matches_cancellable(&text, &token).unwrap(),
+            pt.find_text_matches(&text).unwrap()
+        );
+        let found = single.find_text_matches_cancellable(&text, &token).unwrap();
+        assert_eq!(found.len(), 100_000);
+
+        // Cancel while other threads are searching
+        let handles: Vec<_> = [&pt, &single]
+            .into_iter()
+            .map(|tree| {
+                let token = token.clone();
+                let text = &text;
+                move || loop {
+                    if let Err(e) = tree.find_text_matches_cancellable(text, &token) {
+                        return e;
+                    }
+                }
+            })
+            .collect();
+        thread::scope(|s| {
+            let running: Vec<_> = handles.into_iter().map(|h| s.spawn(h)).collect();
+            token.cancel();
+            for h in running {
+                assert_eq!(h.join().unwrap(), SearchError::Cancelled);
+            }
+        });
+        assert_eq!(
+            pt.find_text_matches_cancellable("", &token).unwrap(),
+            Vec::new()
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use super::{NodeId, SearchError, SearchResult, TrieRoot};
+
+/// A rule on the text surrounding a match, attached to a pattern of the dictionary. Matches of
+/// the pattern that break any of its rules are discarded during the scan.
+///
+/// The strings in the rules are normalized the same way as the patterns, so e.g. they are
+/// compared without regard to case in case-insensitive searches.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("tag"), String::from("app")]);
+/// let mut prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// prefix_tree
+///     .add_context_rule("tag", trie::ContextRule::NotPrecededBy(String::from("#")))
+///     .unwrap();
+/// prefix_tree
+///     .add_context_rule("app", trie::ContextRule::NotFollowedBy(String::from(".js")))
+///     .unwrap();
+///
+/// let matches = prefix_tree.find_text_matches(String::from("#tag tag app.js app")).unwrap();
+/// let ranges: Vec<(usize, usize)> = matches.iter().map(|m| m.char_range()).collect();
+/// assert_eq!(ranges, [(5, 8), (16, 19)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextRule {
+    /// Do not match if the match is immediately preceded by the given string
+    NotPrecededBy(String),
+
+    /// Do not match if the match is immediately followed by the given string
+    NotFollowedBy(String),
+}
+
+impl TrieRoot {
+    /// Attach a context rule to a pattern of the dictionary. A pattern can have several rules,
+    /// and its matches are only reported if they satisfy all of them.
+    ///
+    /// Returns an error if the pattern is not in the dictionary.
+    pub fn add_context_rule(&mut self, pattern: &str, rule: ContextRule) -> SearchResult<()> {
+        let node_id = self
+            .pattern_node_id(pattern)
+            .ok_or_else(|| SearchError::PatternNotFound(pattern.to_string()))?;
+
+        let rule = match rule {
+            ContextRule::NotPrecededBy(s) => {
+                ContextRule::NotPrecededBy(self.options.normalize_pattern(&s))
+            }
+            ContextRule::NotFollowedBy(s) => {
+                ContextRule::NotFollowedBy(self.options.normalize_pattern(&s))
+            }
+        };
+        self.context_rules.entry(node_id).or_default().push(rule);
+        Ok(())
+    }
+
+    /// Maximum number of characters after a match read by the context rules.
+    pub(crate) fn context_lookahead(&self) -> usize {
+        self.context_rules
+            .values()
+            .flatten()
+            .map(|rule| match rule {
+                ContextRule::NotPrecededBy(_) => 0,
+                ContextRule::NotFollowedBy(s) => s.chars().count(),
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Check whether a match of the pattern at the given node, spanning the given byte range of
+    /// the searched text, satisfies the context rules of the pattern.
+    #[inline]
+    pub(crate) fn context_allows(
+        &self,
+        node_id: NodeId,
+        text: &str,
+        start_byte: usize,
+        end_byte: usize,
+    ) -> bool {
+        if self.context_rules.is_empty() {
+            return true;
+        }
+        match self.context_rules.get(&node_id) {
+            None => true,
+            Some(rules) => rules.iter().all(|rule| match rule {
+                ContextRule::NotPrecededBy(s) => !text[..start_byte].ends_with(s.as_str()),
+                ContextRule::NotFollowedBy(s) => !text[end_byte..].starts_with(s.as_str()),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_context_rules() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("Tag"), String::from("tags")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        pt.add_context_rule("TAG", ContextRule::NotPrecededBy(String::from("#")))
+            .unwrap();
+        pt.add_context_rule("tag", ContextRule::NotFollowedBy(String::from("-X")))
+            .unwrap();
+
+        let matches = pt
+            .find_text_matches(String::from("#tag tag-x #tags tag TAG-y"))
+            .unwrap();
+        let found: Vec<(usize, usize)> = matches.iter().map(|m| m.char_range()).collect();
+        assert_eq!(found, [(12, 16), (17, 20), (21, 24)]);
+    }
+
+    #[test]
+    fn test_context_rule_missing_pattern() {
+        let mut pt = create_prefix_tree(add_keyword_slot(vec![String::from("abc")]), None).unwrap();
+        assert_eq!(
+            pt.add_context_rule("ab", ContextRule::NotPrecededBy(String::from("x"))),
+            Err(SearchError::PatternNotFound(String::from("ab")))
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use super::search::ScanState;
+use super::{MatchKind, ReadChar, SearchResult, TrieRoot};
+
+impl TrieRoot {
+    /// Count the matches of the search dictionary in the text, without collecting them. The
+    /// count is the number of matches `find_text_matches` would return.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("b")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// assert_eq!(prefix_tree.count_matches("ab xb").unwrap(), 3);
+    /// ```
+    pub fn count_matches<T: AsRef<str>>(&self, text: T) -> SearchResult<usize> {
+        // Leftmost matches can only be chosen once the overlapping ones are known
+        if self.options.match_kind != MatchKind::Overlapping {
+            return Ok(self.find_text_matches(text)?.len());
+        }
+        let mut count = 0;
+        self.scan_while(text.as_ref(), |found| {
+            count += found;
+            true
+        })?;
+        Ok(count)
+    }
+
+    /// Return whether the search dictionary has any match in the text. The search stops at
+    /// the first match found.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("spam")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// assert!(prefix_tree.contains_any("buy spam now").unwrap());
+    /// assert!(!prefix_tree.contains_any("hello").unwrap());
+    /// ```
+    pub fn contains_any<T: AsRef<str>>(&self, text: T) -> SearchResult<bool> {
+        let finished = self.scan_while(text.as_ref(), |found| found == 0)?;
+        Ok(!finished)
+    }
+
+    /// Scan a text, calling a function with the number of matches ending at each position
+    /// where some are found. The scan stops early if the function returns false. Returns
+    /// whether the whole text was scanned.
+    fn scan_while<F>(&self, text: &str, mut callback: F) -> SearchResult<bool>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let prepared = self.prepare_text(text);
+        let text = prepared.text.as_ref();
+        let mut state = ScanState::new(self);
+
+        // Matches ending at the current position, cleared after each character
+        let mut found = Vec::new();
+        let mut chars_iter = text.char_indices().enumerate().peekable();
+        let mut prev: Option<char> = None;
+        while let Some((pos, (byte, ch))) = chars_iter.next() {
+            let nxt_ch = chars_iter.peek().map(|&(_, (_, c))| c);
+            let read = ReadChar {
+                ch,
+                pos,
+                byte,
+                prev,
+            };
+            self.scan_char(&mut state, read, nxt_ch, text, 0, &mut found)?;
+            if !found.is_empty() {
+                let total = found.len();
+                found.clear();
+                if !callback(total) {
+                    return Ok(false);
+                }
+            }
+            prev = Some(ch);
+        }
+        if state.truncated {
+            log::warn!("Some matches were skipped due to the maximum chain depth");
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_count_same_as_search() {
+        let words = || {
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abc"),
+                String::from("bc"),
+                String::from("c"),
+            ])
+        };
+        for (check_bounds, match_kind) in [
+            (false, MatchKind::Overlapping),
+            (true, MatchKind::Overlapping),
+            (false, MatchKind::LeftmostLongest),
+        ] {
+            let pt = create_prefix_tree(
+                words(),
+                Some(SearchOptions {
+                    case_sensitive: false,
+                    check_bounds,
+                    match_kind,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+            for text in ["", "ABC", "xabc abc c", "bcbc"] {
+                let found = pt.find_text_matches(text).unwrap();
+                assert_eq!(pt.count_matches(text).unwrap(), found.len());
+                assert_eq!(pt.contains_any(text).unwrap(), !found.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_any_stops_early() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        let mut positions = 0;
+        let finished = pt
+            .scan_while("ab ab ab", |_| {
+                positions += 1;
+                false
+            })
+            .unwrap();
+        assert!(!finished);
+        assert_eq!(positions, 1);
+    }
+}
@@ -0,0 +1,111 @@
+use super::{NodeId, TrieRoot};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Record of the patterns of a prefix tree that have matched at least once, with one bit per
+/// node of the tree. Bits are set atomically, so the tree can still be shared by several
+/// threads while searching.
+#[derive(Debug)]
+pub(crate) struct Coverage {
+    hits: Vec<AtomicU64>,
+}
+
+impl Coverage {
+    fn new(total_nodes: usize) -> Self {
+        Self {
+            hits: (0..total_nodes.div_ceil(64))
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+        }
+    }
+
+    /// Number of bytes allocated on the heap by the record
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.hits.capacity() * size_of::<AtomicU64>()
+    }
+
+    /// Extend the record to a tree that has grown to the given number of nodes
+    pub(crate) fn resize(&mut self, total_nodes: usize) {
+        self.hits
+            .resize_with(total_nodes.div_ceil(64), || AtomicU64::new(0));
+    }
+
+    /// Mark the pattern ending at the given node as matched
+    #[inline]
+    pub(crate) fn record(&self, node_id: NodeId) {
+        let bit = 1 << (node_id % 64);
+        let word = &self.hits[node_id / 64];
+        // Avoid contention on the shared word for patterns that already matched
+        if word.load(Ordering::Relaxed) & bit == 0 {
+            word.fetch_or(bit, Ordering::Relaxed);
+        }
+    }
+
+    fn is_hit(&self, node_id: NodeId) -> bool {
+        self.hits[node_id / 64].load(Ordering::Relaxed) & (1 << (node_id % 64)) != 0
+    }
+}
+
+impl TrieRoot {
+    /// Start recording which patterns of the dictionary match in the searches done with this
+    /// tree. Calling it again clears the record.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("cd")]);
+    /// let mut prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    /// prefix_tree.track_coverage();
+    ///
+    /// prefix_tree.find_text_matches(String::from("xxab")).unwrap();
+    /// assert_eq!(prefix_tree.never_matched(), Some(vec![("cd", "cd")]));
+    /// ```
+    pub fn track_coverage(&mut self) {
+        self.coverage = Some(Coverage::new(self.nodes.len()));
+    }
+
+    /// Get the (pattern, keyword) entries that have not matched in any search since coverage
+    /// tracking was enabled, sorted by pattern. Returns None if coverage is not being tracked.
+    pub fn never_matched(&self) -> Option<Vec<(&str, &str)>> {
+        let coverage = self.coverage.as_ref()?;
+        let mut entries: Vec<(&str, &str)> = self
+            .nodes()
+            .enumerate()
+            .filter(|&(nid, _)| !coverage.is_hit(nid))
+            .filter_map(|(_, node)| node.value_keyword())
+            .collect();
+        entries.sort_unstable();
+        Some(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_coverage_tracking() {
+        let words: Vec<String> = (0..100).map(|i| format!("w{i}")).collect();
+        let mut pt = create_prefix_tree(add_keyword_slot(words), None).unwrap();
+        assert_eq!(pt.never_matched(), None);
+
+        pt.track_coverage();
+        assert_eq!(pt.never_matched().unwrap().len(), 100);
+
+        pt.find_text_matches(String::from("w1 w99 w5")).unwrap();
+        pt.find_text_matches(String::from("w42")).unwrap();
+        let missing = pt.never_matched().unwrap();
+        assert_eq!(missing.len(), 94);
+        assert!(!missing.contains(&("w9", "w9")));
+        assert!(!missing.contains(&("w42", "w42")));
+        assert!(missing.contains(&("w2", "w2")));
+
+        let mut searcher = pt.stream_searcher();
+        searcher.feed("w2").unwrap();
+        searcher.finish().unwrap();
+        assert_eq!(pt.never_matched().unwrap().len(), 93);
+
+        pt.track_coverage();
+        assert_eq!(pt.never_matched().unwrap().len(), 100);
+    }
+}
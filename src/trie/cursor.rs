@@ -0,0 +1,165 @@
+use super::{NodeId, SearchResult, TrieRoot};
+
+/// A state of the automaton, used to drive a search one character at a time with
+/// `TrieRoot::next_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct State(NodeId);
+
+impl State {
+    /// Get the ID of the node of the tree corresponding to this state
+    #[inline]
+    pub fn node_id(&self) -> NodeId {
+        self.0
+    }
+}
+
+/// A pattern ending at a state of the automaton.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateMatch<'a> {
+    /// The pattern, as stored in the tree
+    pub value: &'a str,
+
+    /// The keyword of the pattern
+    pub keyword: &'a str,
+
+    /// The category of the pattern, if any
+    pub category: Option<&'a str>,
+
+    /// The metadata of the pattern, if any
+    pub metadata: Option<&'a str>,
+
+    /// Length of the pattern in characters
+    pub length: usize,
+}
+
+/// Iterator over the patterns ending at a state, as returned by `TrieRoot::matches_at`.
+pub struct StateMatches<'a> {
+    trie: &'a TrieRoot,
+    node_id: Option<NodeId>,
+}
+
+impl<'a> Iterator for StateMatches<'a> {
+    type Item = StateMatch<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let root_id = self.trie.root_node_id();
+        while let Some(node_id) = self.node_id.filter(|&n| n != root_id) {
+            let node = self.trie.get_node_unchecked(node_id);
+            self.node_id = node.fail_dct();
+            if let Some((value, keyword)) = node.value_keyword() {
+                return Some(StateMatch {
+                    value,
+                    keyword,
+                    category: node.category(),
+                    metadata: node.metadata(),
+                    length: node.pattern_len(),
+                });
+            }
+        }
+        None
+    }
+}
+
+impl TrieRoot {
+    /// Get the initial state of the automaton, to start a search with `next_state`.
+    #[inline]
+    pub fn start_state(&self) -> State {
+        State(self.root_node_id())
+    }
+
+    /// Move the automaton from the given state after reading the next character of the text.
+    ///
+    /// This is a low-level API to drive the automaton from custom input loops. Characters are
+    /// case folded for case-insensitive trees, but are otherwise taken as given: the text
+    /// should be NFC normalized, and ignored separators must be skipped by the caller. Returns
+    /// an error if the state does not belong to this tree.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("b")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let mut state = prefix_tree.start_state();
+    /// for ch in "xab".chars() {
+    ///     state = prefix_tree.next_state(state, ch).unwrap();
+    /// }
+    /// let found: Vec<&str> = prefix_tree.matches_at(state).map(|m| m.value).collect();
+    /// assert_eq!(found, ["ab", "b"]);
+    /// ```
+    pub fn next_state(&self, state: State, ch: char) -> SearchResult<State> {
+        let node_id = self.get_node(state.0).map(|_| state.0)?;
+        Ok(State(
+            self.next_node_id(node_id, self.options.fold_char(ch))?,
+        ))
+    }
+
+    /// Iterate over the patterns that end at the given state, from longest to shortest.
+    ///
+    /// Word bounds and context rules are not checked, since these depend on the surrounding
+    /// text. Nothing is returned if the state does not belong to this tree.
+    pub fn matches_at(&self, state: State) -> StateMatches<'_> {
+        StateMatches {
+            trie: self,
+            node_id: self.get_node(state.0).ok().map(|_| state.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_cursor_matches_search() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abc"),
+                String::from("bc"),
+                String::from("c"),
+            ]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let text = "xAbCabc";
+        let mut state = pt.start_state();
+        let mut found = Vec::new();
+        for (pos, ch) in text.chars().enumerate() {
+            state = pt.next_state(state, ch).unwrap();
+            for m in pt.matches_at(state) {
+                found.push((pos + 1 - m.length, m.value));
+            }
+        }
+
+        let expected: Vec<(usize, &str)> = pt
+            .find_text_matches(String::from(text))
+            .unwrap()
+            .iter()
+            .map(|m| (m.char_range().0, m.value()))
+            .collect();
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_cursor_invalid_state() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        let other = create_prefix_tree(
+            add_keyword_slot(vec![String::from("abcdef"), String::from("xyz")]),
+            None,
+        )
+        .unwrap();
+
+        let mut state = other.start_state();
+        for ch in "abcde".chars() {
+            state = other.next_state(state, ch).unwrap();
+        }
+        assert!(pt.next_state(state, 'f').is_err());
+        assert_eq!(pt.matches_at(state).count(), 0);
+    }
+}
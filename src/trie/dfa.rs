@@ -0,0 +1,225 @@
+use super::{NodeId, SearchError, SearchResult, TrieRoot};
+use std::collections::VecDeque;
+
+/// Maximum size of the transition table of a dense DFA, in bytes
+const MAX_DFA_TABLE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Class of the characters that don't appear in any pattern
+const NO_CLASS: u32 = u32::MAX;
+
+/// Transition table with the next node of the automaton for every node and character class.
+///
+/// The characters that appear in the patterns are numbered into classes, and every other character
+/// leads back to the root, or is read as the wildcard if the patterns have one. With the table,
+/// each character of the text is processed with a single lookup instead of following failure links.
+#[derive(Debug)]
+pub(crate) struct DenseDfa {
+    /// Class of each ASCII character
+    ascii_classes: [u32; 128],
+
+    /// Non-ASCII characters of the patterns, sorted. Their classes follow the ASCII ones.
+    other_chars: Vec<char>,
+    ascii_count: usize,
+    num_classes: usize,
+
+    /// Class of the wildcard character of the patterns, followed by the characters that don't
+    /// appear in any pattern. None if there is no wildcard.
+    wildcard_class: Option<usize>,
+
+    /// Next node for each node and class, at `node_id * num_classes + class`
+    table: Vec<u32>,
+}
+
+impl DenseDfa {
+    /// Number of bytes allocated on the heap by the table
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.other_chars.capacity() * size_of::<char>() + self.table.capacity() * size_of::<u32>()
+    }
+
+    /// Get the class of a character, or None if it does not appear in any pattern
+    #[inline]
+    fn class(&self, ch: char) -> Option<usize> {
+        if ch.is_ascii() {
+            match self.ascii_classes[ch as usize] {
+                NO_CLASS => None,
+                class => Some(class as usize),
+            }
+        } else {
+            self.other_chars
+                .binary_search(&ch)
+                .ok()
+                .map(|i| self.ascii_count + i)
+        }
+    }
+
+    /// Get the node reached from the given node reading a character
+    #[inline]
+    pub(crate) fn next(&self, node_id: NodeId, ch: char) -> NodeId {
+        match self.class(ch).or(self.wildcard_class) {
+            None => 0,
+            Some(class) => self.table[node_id * self.num_classes + class] as NodeId,
+        }
+    }
+}
+
+impl TrieRoot {
+    /// Precompute the transitions of the automaton for every node and character of the
+    /// patterns, so that searches never follow failure links. This speeds up searches at the
+    /// cost of memory proportional to the number of nodes times the number of distinct
+    /// characters of the dictionary, so it suits dictionaries with small alphabets (e.g.
+    /// mostly ASCII). It can also be enabled with the `dense_dfa` search option.
+    ///
+    /// Returns an error if the table would exceed 256 MiB, in which case the tree keeps
+    /// searching without it.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("bc")]);
+    /// let mut prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    /// prefix_tree.compile_dfa().unwrap();
+    ///
+    /// assert!(prefix_tree.is_dfa_compiled());
+    /// assert_eq!(prefix_tree.find_text_matches("abc").unwrap().len(), 2);
+    /// ```
+    pub fn compile_dfa(&mut self) -> SearchResult<()> {
+        self.dfa = None;
+        let mut chars: Vec<char> = self.links.iter().map(|l| l.get_char()).collect();
+        chars.sort_unstable();
+        chars.dedup();
+
+        let ascii_count = chars.partition_point(char::is_ascii);
+        let num_classes = chars.len();
+        let table_bytes = self
+            .nodes
+            .len()
+            .saturating_mul(num_classes)
+            .saturating_mul(size_of::<u32>());
+        if table_bytes > MAX_DFA_TABLE_BYTES || self.nodes.len() > u32::MAX as usize {
+            return Err(SearchError::BuildBudget(format!(
+                "dense DFA would take {} bytes, more than the limit of {}",
+                table_bytes, MAX_DFA_TABLE_BYTES
+            )));
+        }
+
+        let mut ascii_classes = [NO_CLASS; 128];
+        for (class, &c) in chars[..ascii_count].iter().enumerate() {
+            ascii_classes[c as usize] = class as u32;
+        }
+        let mut dfa = DenseDfa {
+            ascii_classes,
+            other_chars: chars[ascii_count..].to_vec(),
+            ascii_count,
+            num_classes,
+            wildcard_class: None,
+            table: vec![0; self.nodes.len() * num_classes],
+        };
+        dfa.wildcard_class = self.options.wildcard.and_then(|c| dfa.class(c));
+
+        // Fill the rows in BFS order: the transitions missing from a node are those of its
+        // failure node, which is shallower and so already filled
+        let root_id = self.root_node_id();
+        let mut queue = VecDeque::from([root_id]);
+        while let Some(node_id) = queue.pop_front() {
+            let row = node_id * num_classes;
+            if node_id != root_id {
+                let fail_id = self
+                    .get_node_unchecked(node_id)
+                    .fail_node()
+                    .ok_or(SearchError::MissingLink(node_id))?;
+                let fail_row = fail_id * num_classes;
+                dfa.table.copy_within(fail_row..fail_row + num_classes, row);
+            }
+            for link in self.get_node_unchecked(node_id).next_nodes() {
+                let class = dfa.class(link.get_char()).unwrap();
+                dfa.table[row + class] = link.get_node_id() as u32;
+                queue.push_back(link.get_node_id());
+            }
+        }
+
+        self.dfa = Some(dfa);
+        self.options.dense_dfa = true;
+        Ok(())
+    }
+
+    /// Whether the dense transition table of the automaton has been computed. See
+    /// `compile_dfa`.
+    pub fn is_dfa_compiled(&self) -> bool {
+        self.dfa.is_some()
+    }
+
+    /// Compute the dense transition table if required by the `dense_dfa` search option, e.g.
+    /// after building the tree or changing it. Logs a warning if the table is too large.
+    pub(crate) fn refresh_dfa(&mut self) {
+        if !self.options.dense_dfa {
+            return;
+        }
+        if let Err(e) = self.compile_dfa() {
+            log::warn!("Searching without dense DFA: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_dfa_same_transitions() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("abcd"),
+                String::from("bce"),
+                String::from("cé"),
+                String::from("éa"),
+                String::from("a"),
+            ]),
+            None,
+        )
+        .unwrap();
+        let count = |pt: &TrieRoot, text: &str| pt.find_text_matches(text).unwrap().len();
+        let texts = ["abcdé", "xbcéabce", "éaéa bcd", ""];
+        let before: Vec<_> = texts.iter().map(|t| count(&pt, t)).collect();
+
+        let nfa: Vec<(NodeId, char)> = (0..pt.total_nodes())
+            .flat_map(|nid| "abcdeéx".chars().map(move |c| (nid, c)))
+            .collect();
+        let nfa_next: Vec<NodeId> = nfa
+            .iter()
+            .map(|&(nid, c)| pt.next_node_id(nid, c).unwrap())
+            .collect();
+
+        pt.compile_dfa().unwrap();
+        assert!(pt.options().dense_dfa);
+        let dfa = pt.dfa.as_ref().unwrap();
+        for (&(nid, c), &next) in nfa.iter().zip(&nfa_next) {
+            assert_eq!(dfa.next(nid, c), next, "Node {nid} char {c:?}");
+        }
+        let after: Vec<_> = texts.iter().map(|t| count(&pt, t)).collect();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn test_dfa_option() {
+        let opts = |dense_dfa| {
+            Some(SearchOptions {
+                dense_dfa,
+                case_sensitive: false,
+                ..Default::default()
+            })
+        };
+        let words = || add_keyword_slot(vec![String::from("ab"), String::from("Bc")]);
+        let dense = create_prefix_tree(words(), opts(true)).unwrap();
+        let sparse = create_prefix_tree(words(), opts(false)).unwrap();
+        assert!(dense.is_dfa_compiled());
+        assert!(!sparse.is_dfa_compiled());
+        for text in ["ABC", "xabxbc", "aabbcc"] {
+            assert_eq!(
+                dense.find_text_matches(text).unwrap(),
+                sparse.find_text_matches(text).unwrap()
+            );
+        }
+    }
+}
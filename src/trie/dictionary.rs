@@ -0,0 +1,634 @@
+use super::normalize::{self, PreparedText};
+use super::{
+    GazetteerEntry, Match, NodeId, SearchError, SearchOptions, SearchResult, TrieRoot,
+    create_gazetteer,
+};
+use std::{fs, ops::Range, path::Path, str::FromStr};
+
+/// Columns of a TSV or CSV dictionary, in their order when the file has no header
+const COLUMNS: [&str; 6] = [
+    "pattern",
+    "keyword",
+    "category",
+    "case_sensitive",
+    "metadata",
+    "check_bounds",
+];
+
+/// An entry of a dictionary with the settings of its pattern, as read by `read_dictionary`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DictionaryEntry {
+    /// The pattern to find. It is matched literally: no characters have a special meaning.
+    pub pattern: String,
+
+    /// The keyword reported for the matches. The pattern is its own keyword if None.
+    pub keyword: Option<String>,
+
+    /// Category (entity label) of the pattern
+    pub category: Option<String>,
+
+    /// Whether to match the pattern with its exact case, overriding the `case_sensitive`
+    /// search option for this pattern
+    pub case_sensitive: Option<bool>,
+
+    /// Whether the matches of the pattern must be word bounded, overriding the `check_bounds`
+    /// search option for this pattern. Useful to require bounds only for short, ambiguous
+    /// patterns.
+    pub check_bounds: Option<bool>,
+
+    /// Arbitrary data reported with the matches of the pattern. See `Match::metadata`.
+    pub metadata: Option<String>,
+}
+
+impl DictionaryEntry {
+    /// Instantiate an entry for a pattern, without any other settings.
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GazetteerEntry> for DictionaryEntry {
+    fn from((pattern, keyword, category): GazetteerEntry) -> Self {
+        Self {
+            pattern,
+            keyword,
+            category,
+            ..Default::default()
+        }
+    }
+}
+
+/// Format of a dictionary file read by `read_dictionary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DictionaryFormat {
+    /// Tab separated values. Fields can't contain tabs or line breaks.
+    #[default]
+    Tsv,
+
+    /// Comma separated values. Fields with commas, quotes or line breaks must be quoted.
+    Csv,
+
+    /// A JSON array of pattern strings or objects with the fields of the entries. Requires
+    /// the `json` feature.
+    Json,
+}
+
+impl DictionaryFormat {
+    /// Get a format from its name ("tsv", "csv" or "json"). Returns None if the name is
+    /// unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tsv" => Some(Self::Tsv),
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Get the format of a file from its extension. Files with an unknown extension are read
+    /// as TSV.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| Self::from_name(&ext.to_ascii_lowercase()))
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for DictionaryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or_else(|| format!("Unknown dictionary format: {}", s))
+    }
+}
+
+/// Error for an invalid dictionary file
+fn invalid(line: usize, reason: &str) -> SearchError {
+    SearchError::DictionaryFile(format!("line {}: {}", line, reason))
+}
+
+/// Parse the value of a boolean column of a TSV or CSV dictionary.
+fn parse_flag(line: usize, column: &str, value: Option<&str>) -> SearchResult<Option<bool>> {
+    match value {
+        None => Ok(None),
+        Some("true") => Ok(Some(true)),
+        Some("false") => Ok(Some(false)),
+        Some(v) => Err(invalid(line, &format!("invalid {} {:?}", column, v))),
+    }
+}
+
+/// Split CSV data into records, with the line where each one starts.
+fn csv_records(data: &str) -> SearchResult<Vec<(usize, Vec<String>)>> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let (mut line, mut start_line) = (1, 1);
+    let mut quoted = false;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            '\n' if quoted => {
+                field.push(c);
+                line += 1;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                fields.push(std::mem::take(&mut field));
+                records.push((start_line, std::mem::take(&mut fields)));
+                line += 1;
+                start_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(invalid(start_line, "unterminated quoted field"));
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
+
+/// Split TSV data into records, with the line of each one.
+fn tsv_records(data: &str) -> Vec<(usize, Vec<String>)> {
+    data.lines()
+        .zip(1..)
+        .map(|(l, line)| (line, l.split('\t').map(String::from).collect()))
+        .collect()
+}
+
+/// Check whether a record of a TSV or CSV file is a header: at least two distinct column
+/// names, starting with the `pattern` column.
+fn is_header(fields: &[String]) -> bool {
+    fields.len() > 1
+        && fields[0] == COLUMNS[0]
+        && fields
+            .iter()
+            .enumerate()
+            .all(|(i, name)| COLUMNS.contains(&name.as_str()) && !fields[..i].contains(name))
+}
+
+/// Build the entries of a dictionary from the records of a TSV or CSV file. Blank records
+/// are skipped.
+fn entries_from_records(records: Vec<(usize, Vec<String>)>) -> SearchResult<Vec<DictionaryEntry>> {
+    let mut records = records
+        .into_iter()
+        .filter(|(_, fields)| fields.iter().any(|f| !f.is_empty()))
+        .peekable();
+
+    // The columns are named in the header, if any. A first record that doesn't only list
+    // distinct column names is an entry, e.g. for the pattern "pattern".
+    let mut columns = COLUMNS.to_vec();
+    if let Some((_, header)) = records.next_if(|(_, fields)| is_header(fields)) {
+        columns = header
+            .iter()
+            .map(|name| *COLUMNS.iter().find(|&c| c == name).unwrap())
+            .collect();
+    }
+
+    let mut entries = Vec::new();
+    for (line, fields) in records {
+        if fields.len() > columns.len() {
+            return Err(invalid(line, "too many fields"));
+        }
+        let mut entry = DictionaryEntry::default();
+        for (&column, value) in columns.iter().zip(fields) {
+            let value = Some(value).filter(|v| !v.is_empty());
+            match column {
+                "pattern" => entry.pattern = value.unwrap_or_default(),
+                "keyword" => entry.keyword = value,
+                "category" => entry.category = value,
+                "case_sensitive" => {
+                    entry.case_sensitive = parse_flag(line, column, value.as_deref())?
+                }
+                "check_bounds" => entry.check_bounds = parse_flag(line, column, value.as_deref())?,
+                _ => entry.metadata = value,
+            }
+        }
+        if entry.pattern.is_empty() {
+            return Err(invalid(line, "empty pattern"));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Build the entries of a dictionary from a JSON array.
+#[cfg(feature = "json")]
+fn entries_from_json(data: &str) -> SearchResult<Vec<DictionaryEntry>> {
+    use serde_json::Value;
+
+    let to_err = |e: serde_json::Error| SearchError::DictionaryFile(e.to_string());
+    let Value::Array(items) = serde_json::from_str(data).map_err(to_err)? else {
+        return Err(SearchError::DictionaryFile(String::from(
+            "expected an array of entries",
+        )));
+    };
+    let mut entries = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        let invalid =
+            |reason: &str| SearchError::DictionaryFile(format!("entry {}: {}", i, reason));
+        let fields = match item {
+            Value::String(pattern) => {
+                entries.push(DictionaryEntry::new(pattern));
+                continue;
+            }
+            Value::Object(fields) => fields,
+            _ => return Err(invalid("expected a string or an object")),
+        };
+        let mut entry = DictionaryEntry::default();
+        for (key, value) in fields {
+            let text = match (key.as_str(), value) {
+                (_, Value::Null) => continue,
+                ("case_sensitive", Value::Bool(b)) => {
+                    entry.case_sensitive = Some(b);
+                    continue;
+                }
+                ("check_bounds", Value::Bool(b)) => {
+                    entry.check_bounds = Some(b);
+                    continue;
+                }
+                (_, Value::String(s)) => s,
+                _ => return Err(invalid(&format!("invalid field {:?}", key))),
+            };
+            match key.as_str() {
+                "pattern" => entry.pattern = text,
+                "keyword" => entry.keyword = Some(text),
+                "category" => entry.category = Some(text),
+                "metadata" => entry.metadata = Some(text),
+                _ => return Err(invalid(&format!("invalid field {:?}", key))),
+            }
+        }
+        if entry.pattern.is_empty() {
+            return Err(invalid("empty pattern"));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Parse the entries of a dictionary in the given format.
+///
+/// TSV and CSV dictionaries have a record per entry, with the columns `pattern`, `keyword`,
+/// `category`, `case_sensitive` (`true` or `false`), `metadata` and `check_bounds` (`true` or
+/// `false`). A record can leave out the trailing columns, and empty fields are left unset. The
+/// columns can be given in another order, or some left out, with a first record listing the
+/// names of at least two distinct columns, starting with `pattern`. Any other first record is
+/// an entry, so a dictionary can start with the pattern "pattern". JSON dictionaries are arrays
+/// of pattern strings or objects with the same fields.
+///
+/// Patterns are taken literally, so they don't need any escaping beyond the quoting of the
+/// CSV and JSON formats.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, DictionaryFormat};
+///
+/// let data = "pattern\tmetadata\tcase_sensitive\nWHO\tQ7817\ttrue\nworld health\tQ7817\n";
+/// let entries = trie::parse_dictionary(data, DictionaryFormat::Tsv).unwrap();
+/// assert_eq!(entries[0].pattern, "WHO");
+/// assert_eq!(entries[0].case_sensitive, Some(true));
+/// assert_eq!(entries[1].metadata.as_deref(), Some("Q7817"));
+/// ```
+pub fn parse_dictionary(
+    data: &str,
+    format: DictionaryFormat,
+) -> SearchResult<Vec<DictionaryEntry>> {
+    match format {
+        DictionaryFormat::Tsv => entries_from_records(tsv_records(data)),
+        DictionaryFormat::Csv => entries_from_records(csv_records(data)?),
+        #[cfg(feature = "json")]
+        DictionaryFormat::Json => entries_from_json(data),
+        #[cfg(not(feature = "json"))]
+        DictionaryFormat::Json => Err(SearchError::DictionaryFile(String::from(
+            "JSON dictionaries require the json feature",
+        ))),
+    }
+}
+
+/// Read a dictionary file in the given format, or in the format given by its extension
+/// (`.tsv`, `.csv` or `.json`) if None. See `parse_dictionary` for the formats.
+pub fn read_dictionary<P: AsRef<Path>>(
+    path: P,
+    format: Option<DictionaryFormat>,
+) -> SearchResult<Vec<DictionaryEntry>> {
+    let format = format.unwrap_or_else(|| DictionaryFormat::from_path(&path));
+    let data = fs::read_to_string(path).map_err(|e| SearchError::DictionaryFile(e.to_string()))?;
+    parse_dictionary(&data, format)
+}
+
+/// Instantiate a prefix tree for search from dictionary entries, applying their settings.
+///
+/// Patterns with a case sensitivity other than that of the search options are matched as
+/// follows: if some pattern is case-insensitive, the whole tree is, and the matches of the
+/// case-sensitive patterns are only reported if their text has the exact case of the pattern.
+/// Their keyword is the pattern as written, unless given. This is checked in the searches of
+/// whole texts, but not in stream searches or with cursors. Patterns with a `check_bounds`
+/// setting are checked for word bounds (or not) in all searches, regardless of the search
+/// options. Otherwise, returns an error in the same cases as `create_gazetteer`.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, DictionaryEntry};
+///
+/// let entries = vec![
+///     DictionaryEntry {
+///         pattern: String::from("WHO"),
+///         case_sensitive: Some(true),
+///         metadata: Some(String::from("Q7817")),
+///         ..Default::default()
+///     },
+///     DictionaryEntry::new("health"),
+/// ];
+/// let opts = trie::SearchOptions{case_sensitive: false, ..Default::default()};
+/// let prefix_tree = trie::create_from_dictionary(entries, Some(opts)).unwrap();
+///
+/// let matches = prefix_tree.find_text_matches("who knows? WHO, Health").unwrap();
+/// let found: Vec<_> = matches.iter().map(|m| (m.keyword(), m.metadata())).collect();
+/// assert_eq!(found, [("WHO", Some("Q7817")), ("health", None)]);
+/// ```
+pub fn create_from_dictionary(
+    entries: Vec<DictionaryEntry>,
+    options: Option<SearchOptions>,
+) -> SearchResult<TrieRoot> {
+    let mut options = options.unwrap_or_default();
+    let exact: Vec<bool> = entries
+        .iter()
+        .map(|e| e.case_sensitive.unwrap_or(options.case_sensitive))
+        .collect();
+    options.case_sensitive = exact.iter().all(|&e| e);
+
+    let mut extras = Vec::with_capacity(entries.len());
+    let mut dictionary = Vec::with_capacity(entries.len());
+    for (entry, exact) in entries.into_iter().zip(exact) {
+        let cased = (exact && !options.case_sensitive).then(|| {
+            options.strip_pattern(&normalize::nfc(entry.pattern.chars()).collect::<String>())
+        });
+        // Matches with an exact case are reported with the pattern as written by default
+        let keyword = match cased {
+            Some(_) => entry.keyword.or_else(|| Some(entry.pattern.clone())),
+            None => entry.keyword,
+        };
+        let bounds = entry.check_bounds.filter(|&b| b != options.check_bounds);
+        extras.push((cased, bounds, entry.metadata));
+        dictionary.push((entry.pattern, keyword, entry.category));
+    }
+
+    let mut pt = create_gazetteer(dictionary, Some(options))?;
+    for (pattern_id, (cased, bounds, metadata)) in extras.into_iter().enumerate() {
+        let node_id = pt.patterns[pattern_id].node_id as NodeId;
+        if let Some(cased) = cased {
+            pt.exact_case.insert(node_id, cased);
+        }
+        if let Some(bounds) = bounds {
+            pt.bounds_override.insert(node_id, bounds);
+        }
+        if let Some(metadata) = metadata {
+            pt.metadata.insert(node_id, metadata);
+        }
+    }
+    Ok(pt)
+}
+
+impl TrieRoot {
+    /// Check whether a match of the pattern at the given node, spanning the given ranges of
+    /// characters and bytes of a prepared text, has the exact case of the pattern if it must.
+    pub(crate) fn exact_case_allows(
+        &self,
+        node_id: NodeId,
+        prepared: &PreparedText,
+        chars: (usize, usize),
+        bytes: Range<usize>,
+    ) -> bool {
+        let Some(cased) = self.exact_case.get(&node_id) else {
+            return true;
+        };
+        let found = match prepared.original {
+            None => &prepared.text[bytes],
+            Some(original) => {
+                let (start, end) = prepared.original_range(chars.0, chars.1);
+                let (start_byte, end_byte) = prepared.original_byte_range(start, end);
+                &original[start_byte..end_byte]
+            }
+        };
+        let found = self
+            .options
+            .strip_pattern(&normalize::nfc(found.chars()).collect::<String>());
+        found == *cased
+    }
+
+    /// Discard the matches found in a prepared text that don't have the exact case of their
+    /// pattern, for the patterns that require it.
+    pub(crate) fn retain_exact_case(&self, matches: &mut Vec<Match>, prepared: &PreparedText) {
+        if self.exact_case.is_empty() {
+            return;
+        }
+        matches.retain(|m| {
+            let node_id = self.patterns[m.pattern_id].node_id as NodeId;
+            self.exact_case_allows(node_id, prepared, m.char_range(), m.byte_range())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::MatchKind;
+    use super::*;
+
+    #[test]
+    fn test_parse_dictionary() {
+        let tsv = "apple\tAPPLE\tFRUIT\tfalse\t{\"id\": 1}\n\n\"x\"\tx,y\n";
+        let entries = parse_dictionary(tsv, DictionaryFormat::Tsv).unwrap();
+        assert_eq!(
+            entries,
+            [
+                DictionaryEntry {
+                    pattern: String::from("apple"),
+                    keyword: Some(String::from("APPLE")),
+                    category: Some(String::from("FRUIT")),
+                    case_sensitive: Some(false),
+                    check_bounds: None,
+                    metadata: Some(String::from("{\"id\": 1}")),
+                },
+                DictionaryEntry {
+                    pattern: String::from("\"x\""),
+                    keyword: Some(String::from("x,y")),
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let csv = "pattern,metadata\r\n\"a, \"\"b\"\"\",\"multi\nline\"\nc,\n";
+        let entries = parse_dictionary(csv, DictionaryFormat::Csv).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pattern, "a, \"b\"");
+        assert_eq!(entries[0].metadata.as_deref(), Some("multi\nline"));
+        assert_eq!(entries[1], DictionaryEntry::new("c"));
+
+        for (data, format, line) in [
+            ("a\tb\tc\tmaybe", DictionaryFormat::Tsv, 1),
+            ("a\n\tkw", DictionaryFormat::Tsv, 2),
+            ("a,b,c,true,m,false,x", DictionaryFormat::Csv, 1),
+            ("pattern\tcheck_bounds\na\tyes", DictionaryFormat::Tsv, 2),
+            ("a\n\"b", DictionaryFormat::Csv, 2),
+        ] {
+            let err = parse_dictionary(data, format).unwrap_err();
+            let expected = format!("line {}: ", line);
+            assert!(matches!(err, SearchError::DictionaryFile(e) if e.starts_with(&expected)));
+        }
+
+        // First records that are not only column names are entries
+        for data in [
+            "pattern\nword\n",
+            "pattern\tPATTERN\nword\n",
+            "pattern\tcolor\nword\n",
+        ] {
+            let entries = parse_dictionary(data, DictionaryFormat::Tsv).unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries[0].pattern, "pattern");
+            assert_eq!(entries[1], DictionaryEntry::new("word"));
+        }
+        let entries = parse_dictionary("pattern,pattern\n", DictionaryFormat::Csv).unwrap();
+        assert_eq!(entries[0].keyword.as_deref(), Some("pattern"));
+
+        assert_eq!(
+            DictionaryFormat::from_path("dct.CSV"),
+            DictionaryFormat::Csv
+        );
+        assert_eq!(
+            DictionaryFormat::from_path("dct.txt"),
+            DictionaryFormat::Tsv
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_parse_json_dictionary() {
+        let json = r#"["a", {"pattern": "b", "case_sensitive": true, "check_bounds": false, "metadata": "m"}]"#;
+        let entries = parse_dictionary(json, DictionaryFormat::Json).unwrap();
+        assert_eq!(entries[0], DictionaryEntry::new("a"));
+        assert_eq!(entries[1].case_sensitive, Some(true));
+        assert_eq!(entries[1].check_bounds, Some(false));
+        assert_eq!(entries[1].metadata.as_deref(), Some("m"));
+
+        for json in [
+            r#"{"a": 1}"#,
+            r#"[1]"#,
+            r#"[{"pattern": "a", "x": "y"}]"#,
+            "[{}]",
+        ] {
+            assert!(parse_dictionary(json, DictionaryFormat::Json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_dictionary_case_overrides() {
+        let entries = vec![
+            DictionaryEntry {
+                pattern: String::from("US"),
+                metadata: Some(String::from("country")),
+                ..Default::default()
+            },
+            DictionaryEntry {
+                pattern: String::from("e-Mail"),
+                case_sensitive: Some(true),
+                ..Default::default()
+            },
+            DictionaryEntry {
+                pattern: String::from("straße"),
+                case_sensitive: Some(false),
+                ..Default::default()
+            },
+        ];
+        let pt = create_from_dictionary(
+            entries,
+            Some(SearchOptions {
+                ignore_separators: true,
+                match_kind: MatchKind::LeftmostLongest,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert!(!pt.options().case_sensitive);
+
+        let text = "us US email eMail e Mail STRASSE STRAẞE";
+        let found: Vec<(&str, Option<&str>)> = pt
+            .find_text_matches(text)
+            .unwrap()
+            .iter()
+            .map(|m| (m.matched_text(text), m.metadata()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("US", Some("country")),
+                ("eMail", None),
+                ("e Mail", None),
+                ("STRAẞE", None)
+            ]
+        );
+        assert_eq!(pt.longest_match_at(text, 0), None);
+        assert_eq!(
+            pt.longest_match_at(text, 3).unwrap().metadata(),
+            Some("country")
+        );
+
+        let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+        assert_eq!(restored.find_text_matches(text).unwrap().len(), 4);
+        assert_eq!(
+            restored.find_text_matches(text).unwrap()[0].metadata(),
+            Some("country")
+        );
+    }
+
+    #[test]
+    fn test_dictionary_bounds_overrides() {
+        let tsv = "pattern\tcheck_bounds\nUS\ttrue\nunited states\nfoo\tfalse\n";
+        let entries = parse_dictionary(tsv, DictionaryFormat::Tsv).unwrap();
+        assert_eq!(entries[0].check_bounds, Some(true));
+        assert_eq!(entries[1].check_bounds, None);
+
+        let text = "USA, us, the united statesman";
+        for check_bounds in [false, true] {
+            let pt = create_from_dictionary(
+                entries.clone(),
+                Some(SearchOptions {
+                    case_sensitive: false,
+                    check_bounds,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+            let mut expected = vec!["us"];
+            if !check_bounds {
+                expected.push("united states");
+            }
+            let found: Vec<&str> = pt
+                .find_text_matches(text)
+                .unwrap()
+                .iter()
+                .map(|m| m.matched_text(text))
+                .collect();
+            assert_eq!(found, expected);
+            assert_eq!(pt.find_text_matches("foobar").unwrap().len(), 1);
+            assert_eq!(pt.longest_match_at(text, 0), None);
+
+            let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+            assert_eq!(restored.find_text_matches(text).unwrap().len(), found.len());
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use super::TrieRoot;
+use std::collections::BTreeMap;
+
+/// Differences between the dictionaries of two prefix trees, as returned by `TrieRoot::diff`.
+///
+/// Patterns are compared in their normalized form, i.e. as stored in the trees. All the lists
+/// are sorted by pattern.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TrieDiff<'a> {
+    /// Entries (pattern, keyword) only present in the new tree
+    pub added: Vec<(&'a str, &'a str)>,
+
+    /// Entries (pattern, keyword) only present in the old tree
+    pub removed: Vec<(&'a str, &'a str)>,
+
+    /// Patterns present in both trees with a different keyword: (pattern, old keyword,
+    /// new keyword)
+    pub changed: Vec<(&'a str, &'a str, &'a str)>,
+}
+
+impl TrieDiff<'_> {
+    /// Return whether both dictionaries have exactly the same entries
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl TrieRoot {
+    /// Get the (pattern, keyword) entries of the dictionary stored in the tree, sorted by
+    /// pattern. Patterns are returned in their normalized form.
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        let mut entries: Vec<(&str, &str)> = self
+            .nodes()
+            .filter_map(|node| node.value_keyword())
+            .collect();
+        entries.sort_unstable();
+        entries
+    }
+
+    /// Compare the dictionary of this tree with that of another one, returning the entries
+    /// that were added, removed or changed in `other` with respect to `self`.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let old = trie::create_prefix_tree(
+    ///     vec![(String::from("abc"), None), (String::from("py3"), Some(String::from("Python")))],
+    ///     None,
+    /// ).unwrap();
+    /// let new = trie::create_prefix_tree(
+    ///     vec![(String::from("py3"), Some(String::from("Python 3"))), (String::from("xyz"), None)],
+    ///     None,
+    /// ).unwrap();
+    ///
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.added, [("xyz", "xyz")]);
+    /// assert_eq!(diff.removed, [("abc", "abc")]);
+    /// assert_eq!(diff.changed, [("py3", "Python", "Python 3")]);
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a TrieRoot) -> TrieDiff<'a> {
+        let mut new_entries: BTreeMap<&str, &str> = other.entries().into_iter().collect();
+        let mut diff = TrieDiff::default();
+        for (pattern, keyword) in self.entries() {
+            match new_entries.remove(pattern) {
+                None => diff.removed.push((pattern, keyword)),
+                Some(new_kw) if new_kw != keyword => diff.changed.push((pattern, keyword, new_kw)),
+                Some(_) => {}
+            }
+        }
+        diff.added = new_entries.into_iter().collect();
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_trie_diff() {
+        let old = create_prefix_tree(
+            vec![
+                (String::from("ab"), None),
+                (String::from("abc"), Some(String::from("x"))),
+                (String::from("cd"), None),
+            ],
+            None,
+        )
+        .unwrap();
+        let new = create_prefix_tree(
+            vec![
+                (String::from("a"), None),
+                (String::from("abc"), Some(String::from("y"))),
+                (String::from("cd"), None),
+            ],
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(old.entries(), [("ab", "ab"), ("abc", "x"), ("cd", "cd")]);
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, [("a", "a")]);
+        assert_eq!(diff.removed, [("ab", "ab")]);
+        assert_eq!(diff.changed, [("abc", "x", "y")]);
+    }
+
+    #[test]
+    fn test_trie_diff_normalized() {
+        let old = create_prefix_tree(add_keyword_slot(vec![String::from("abc")]), None).unwrap();
+        let new = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ABC")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert!(old.diff(&new).is_empty());
+    }
+}
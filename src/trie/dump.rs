@@ -0,0 +1,210 @@
+use super::TrieRoot;
+use std::fmt;
+use std::io;
+
+impl fmt::Debug for TrieRoot {
+    /// Summary of the tree: sizes and options. Use `TrieRoot::dump` to see the nodes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrieRoot")
+            .field("total_nodes", &self.total_nodes())
+            .field("total_patterns", &self.total_patterns())
+            .field("max_pattern_len", &self.max_pattern_len)
+            .field("options", &self.options)
+            .field("context_rules", &self.context_rules.len())
+            .field("weights", &self.weights.len())
+            .field("track_coverage", &self.coverage.is_some())
+            .field("dense_dfa", &self.dfa.is_some())
+            .finish()
+    }
+}
+
+impl TrieRoot {
+    /// Get the number of patterns stored in the tree
+    pub fn total_patterns(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Write a readable table of the nodes of the tree, with one line per node containing its
+    /// ID, failure link, dictionary link, pattern and keyword (if a dictionary node) and the
+    /// edges to the following nodes. Meant for debugging small trees.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("b")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let mut out = Vec::new();
+    /// prefix_tree.dump(&mut out).unwrap();
+    /// let table = String::from_utf8(out).unwrap();
+    /// assert!(table.lines().any(|l| l.contains("\"ab\"")));
+    /// ```
+    pub fn dump<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{:?}", self)?;
+        writeln!(
+            writer,
+            "{:>6} {:>6} {:>6}  {:<24} edges",
+            "id", "fail", "dict", "pattern -> keyword"
+        )?;
+
+        let link_str = |link: Option<usize>| link.map_or(String::from("-"), |l| l.to_string());
+        for (node_id, node) in self.nodes().enumerate() {
+            let entry = match node.value_keyword() {
+                Some((value, kw)) => format!("{:?} -> {:?}", value, kw),
+                None => String::new(),
+            };
+            let edges: Vec<String> = node
+                .next_nodes()
+                .iter()
+                .map(|l| format!("{:?}:{}", l.get_char(), l.get_node_id()))
+                .collect();
+            writeln!(
+                writer,
+                "{:>6} {:>6} {:>6}  {:<24} {}",
+                node_id,
+                link_str(node.fail_node()),
+                link_str(node.fail_dct()),
+                entry,
+                edges.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the automaton in the Graphviz DOT format, to draw it with e.g. `dot -Tsvg`. Meant
+    /// for debugging small trees.
+    ///
+    /// Nodes are labelled with their ID, and dictionary nodes are drawn with a double circle
+    /// and also labelled with their pattern. The edges to the following nodes are black and
+    /// labelled with their character, failure links are red and dashed, and dictionary links
+    /// are blue and dotted. The failure links to the root are included.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("b")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let dot = prefix_tree.to_dot();
+    /// assert!(dot.starts_with("digraph trie {"));
+    /// assert!(dot.contains("0 -> 1 [label=\"a\"];"));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph trie {\n    rankdir=LR;\n    node [shape=circle];\n");
+        for (node_id, node) in self.nodes().enumerate() {
+            match node.value_keyword() {
+                Some((value, _)) => out.push_str(&format!(
+                    "    {} [label=\"{}\\n{}\", shape=doublecircle];\n",
+                    node_id,
+                    node_id,
+                    escape_dot(value)
+                )),
+                None => out.push_str(&format!("    {} [label=\"{}\"];\n", node_id, node_id)),
+            }
+        }
+        for (node_id, node) in self.nodes().enumerate() {
+            for link in node.next_nodes() {
+                out.push_str(&format!(
+                    "    {} -> {} [label=\"{}\"];\n",
+                    node_id,
+                    link.get_node_id(),
+                    escape_dot(&link.get_char().to_string())
+                ));
+            }
+            if let Some(fail_id) = node.fail_node() {
+                out.push_str(&format!(
+                    "    {} -> {} [color=red, style=dashed];\n",
+                    node_id, fail_id
+                ));
+            }
+            if let Some(dct_id) = node.fail_dct() {
+                out.push_str(&format!(
+                    "    {} -> {} [color=blue, style=dotted];\n",
+                    node_id, dct_id
+                ));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Escape a string to quote it in a DOT label
+fn escape_dot(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_debug_and_dump() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("b")]),
+            None,
+        )
+        .unwrap();
+        assert_eq!(pt.total_patterns(), 2);
+
+        let summary = format!("{:?}", pt);
+        assert!(summary.contains("total_nodes: 4"));
+        assert!(summary.contains("total_patterns: 2"));
+
+        let mut out = Vec::new();
+        pt.dump(&mut out).unwrap();
+        let table = String::from_utf8(out).unwrap();
+        // Summary, header and one line per node
+        assert_eq!(table.lines().count(), 2 + pt.total_nodes());
+
+        let ab = pt.node_by_path("ab").unwrap();
+        let b = pt.node_by_path("b").unwrap();
+        let ab_line = table.lines().nth(2 + ab).unwrap();
+        let fields: Vec<&str> = ab_line.split_whitespace().collect();
+        assert_eq!(fields[..3], [ab.to_string(), b.to_string(), b.to_string()]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("b"),
+                String::from("\"q"),
+            ]),
+            None,
+        )
+        .unwrap();
+        let dot = pt.to_dot();
+        assert!(dot.starts_with("digraph trie {\n"));
+        assert!(dot.ends_with("}\n"));
+
+        let lines: Vec<&str> = dot.lines().collect();
+        let count = |attrs: &str| lines.iter().filter(|l| l.contains(attrs)).count();
+        let goto_edges = pt.nodes().map(|n| n.next_nodes().len()).sum::<usize>();
+        assert_eq!(count("-> ") - count("color="), goto_edges);
+        assert_eq!(count("[color=red, style=dashed]"), pt.total_nodes() - 1);
+        assert_eq!(count("shape=doublecircle"), 3);
+
+        let (ab, b) = (
+            pt.node_by_path("ab").unwrap(),
+            pt.node_by_path("b").unwrap(),
+        );
+        assert!(lines.contains(&format!("    {ab} -> {b} [color=blue, style=dotted];").as_str()));
+        assert!(dot.contains("[label=\"\\\"\"]"));
+        assert!(dot.contains("\\n\\\"q\", shape=doublecircle]"));
+    }
+}
@@ -0,0 +1,134 @@
+use super::{Match, SearchResult, TrieRoot};
+pub use encoding_rs::Encoding;
+use std::iter;
+
+/// Decode the given bytes with an encoding, without BOM sniffing. Returns the decoded text
+/// along with the range of bytes of the input each character comes from, or None if the
+/// characters correspond one to one to the bytes.
+fn decode_with_spans(
+    data: &[u8],
+    encoding: &'static Encoding,
+) -> (String, Option<Vec<(usize, usize)>>) {
+    if encoding.is_single_byte() || (encoding.is_ascii_compatible() && data.is_ascii()) {
+        let (text, _) = encoding.decode_without_bom_handling(data);
+        return (text.into_owned(), None);
+    }
+
+    // Feed the decoder one byte at a time, to find the bytes of each character. The
+    // characters output at once (e.g. a replacement character followed by the next one after
+    // a malformed sequence) are all given the bytes read since the previous output.
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(data.len());
+    let mut spans = Vec::with_capacity(data.len());
+    let mut seq_start = 0;
+    for i in 0..=data.len() {
+        let (src, last) = match data.get(i) {
+            Some(b) => (std::slice::from_ref(b), false),
+            None => (&[][..], true),
+        };
+        text.reserve(decoder.max_utf8_buffer_length(src.len()).unwrap_or(16));
+        let before = text.len();
+        let _ = decoder.decode_to_string(src, &mut text, last);
+
+        let emitted = text[before..].chars().count();
+        if emitted > 0 {
+            let seq_end = (i + 1).min(data.len());
+            spans.extend(iter::repeat_n((seq_start, seq_end), emitted));
+            seq_start = seq_end;
+        }
+    }
+    (text, Some(spans))
+}
+
+impl TrieRoot {
+    /// Find all matches for the search dictionary in bytes of text in the given encoding (e.g.
+    /// Windows-1252 or Shift_JIS), decoding them on the fly. Requires the `encoding` feature.
+    ///
+    /// The character ranges of the matches are character indices into the decoded text, as in
+    /// `find_text_matches`, and their byte ranges are offsets into the given bytes. Malformed
+    /// sequences are replaced with U+FFFD. The encodings are those of the `encoding_rs` crate,
+    /// which can be looked up by name with `Encoding::for_label`.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, Encoding};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("café")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let windows_1252 = Encoding::for_label(b"windows-1252").unwrap();
+    /// let matches = prefix_tree.find_matches_encoded(b"un caf\xe9", windows_1252).unwrap();
+    /// assert_eq!(matches[0].char_range(), (3, 7));
+    /// assert_eq!(matches[0].byte_range(), 3..7);
+    /// ```
+    pub fn find_matches_encoded<'a>(
+        &'a self,
+        data: &[u8],
+        encoding: &'static Encoding,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let (text, spans) = decode_with_spans(data, encoding);
+        let mut matches = self.find_text_matches(text)?;
+        for m in &mut matches {
+            (m.start_byte, m.end_byte) = match &spans {
+                None => (m.start, m.end),
+                Some(spans) => (spans[m.start].0, spans[m.end - 1].1),
+            };
+        }
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, WINDOWS_1252};
+
+    #[test]
+    fn test_decode_with_spans() {
+        let (text, spans) = decode_with_spans(b"caf\xe9 \x80", WINDOWS_1252);
+        assert_eq!(text, "café €");
+        assert!(spans.is_none());
+
+        let (data, _, _) = SHIFT_JIS.encode("aタワー");
+        let (text, spans) = decode_with_spans(&data, SHIFT_JIS);
+        assert_eq!(text, "aタワー");
+        assert_eq!(spans.unwrap(), [(0, 1), (1, 3), (3, 5), (5, 7)]);
+
+        // Truncated multi-byte sequence at the end
+        let (text, spans) = decode_with_spans(b"ab\x83", SHIFT_JIS);
+        assert_eq!(text, "ab\u{fffd}");
+        assert_eq!(spans.unwrap(), [(0, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn test_search_encoded() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("東京"), String::from("tower")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let text = "Tokyo 東京 TOWER";
+        let (data, _, _) = SHIFT_JIS.encode(text);
+        let found = pt.find_matches_encoded(&data, SHIFT_JIS).unwrap();
+        let expected = pt.find_text_matches(text).unwrap();
+        assert_eq!(found.len(), 2);
+        for (m, e) in found.iter().zip(&expected) {
+            assert_eq!(m.char_range(), e.char_range());
+        }
+        assert_eq!(
+            &data[found[0].byte_range()],
+            &SHIFT_JIS.encode("東京").0[..]
+        );
+        assert_eq!(found[1].byte_range(), 11..16);
+
+        let found = pt
+            .find_matches_encoded(b"TOWER \xff", WINDOWS_1252)
+            .unwrap();
+        assert_eq!(found[0].byte_range(), 0..5);
+    }
+}
@@ -0,0 +1,181 @@
+use super::{Match, SearchError, SearchResult, TrieRoot};
+use std::iter;
+
+/// A text container that can be searched with `TrieRoot::find_haystack_matches`.
+///
+/// The text is provided as a sequence of string pieces, so that containers that don't store
+/// their text contiguously (e.g. ropes used by text editors) can be searched without copying it
+/// into a single string.
+pub trait Haystack {
+    /// Iterator over the pieces of the text
+    type Chunks<'h>: Iterator<Item = &'h str>
+    where
+        Self: 'h;
+
+    /// Get the pieces of the text, in order. Returns an error if the container does not hold a
+    /// valid text.
+    fn chunks(&self) -> SearchResult<Self::Chunks<'_>>;
+}
+
+impl Haystack for str {
+    type Chunks<'h> = iter::Once<&'h str>;
+
+    fn chunks(&self) -> SearchResult<Self::Chunks<'_>> {
+        Ok(iter::once(self))
+    }
+}
+
+impl Haystack for String {
+    type Chunks<'h> = iter::Once<&'h str>;
+
+    fn chunks(&self) -> SearchResult<Self::Chunks<'_>> {
+        Ok(iter::once(self.as_str()))
+    }
+}
+
+/// Bytes are searched only if they are valid UTF-8. See `TrieRoot::find_matches_lossy` to
+/// search data that may contain invalid sequences.
+impl Haystack for [u8] {
+    type Chunks<'h> = iter::Once<&'h str>;
+
+    fn chunks(&self) -> SearchResult<Self::Chunks<'_>> {
+        std::str::from_utf8(self).map(iter::once).map_err(|e| {
+            SearchError::InvalidText(format!("invalid UTF-8 at byte {}", e.valid_up_to()))
+        })
+    }
+}
+
+impl<H: Haystack + ?Sized> Haystack for &H {
+    type Chunks<'h>
+        = H::Chunks<'h>
+    where
+        Self: 'h;
+
+    fn chunks(&self) -> SearchResult<Self::Chunks<'_>> {
+        (**self).chunks()
+    }
+}
+
+#[cfg(feature = "rope")]
+impl Haystack for ropey::Rope {
+    type Chunks<'h> = ropey::iter::Chunks<'h>;
+
+    fn chunks(&self) -> SearchResult<Self::Chunks<'_>> {
+        Ok(ropey::Rope::chunks(self))
+    }
+}
+
+impl TrieRoot {
+    /// Find all matches for the search dictionary in a text container.
+    ///
+    /// A text stored in a single piece is searched as with `find_text_matches`. Texts split in
+    /// several pieces (e.g. large ropes) are searched incrementally with a `StreamSearcher`,
+    /// without joining the pieces; in that case, as for any stream search, the text is not
+    /// normalized and context rules are not evaluated.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("abc")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// assert_eq!(prefix_tree.find_haystack_matches("xabc").unwrap().len(), 1);
+    /// assert_eq!(prefix_tree.find_haystack_matches(b"abc abc".as_slice()).unwrap().len(), 2);
+    /// assert!(prefix_tree.find_haystack_matches(b"ab\xff".as_slice()).is_err());
+    /// ```
+    pub fn find_haystack_matches<H: Haystack + ?Sized>(
+        &self,
+        haystack: &H,
+    ) -> SearchResult<Vec<Match<'_>>> {
+        let mut chunks = haystack.chunks()?;
+        let Some(first) = chunks.next() else {
+            return Ok(Vec::new());
+        };
+        let Some(second) = chunks.next() else {
+            return self.find_text_matches(first);
+        };
+
+        let mut searcher = self.stream_searcher();
+        let mut matches = searcher.feed(first)?;
+        for chunk in iter::once(second).chain(chunks) {
+            matches.append(&mut searcher.feed(chunk)?);
+        }
+        matches.append(&mut searcher.finish()?);
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    /// Text split in fixed pieces
+    struct Pieces(Vec<String>);
+
+    impl Pieces {
+        fn new(pieces: &[&str]) -> Self {
+            Self(pieces.iter().map(|p| p.to_string()).collect())
+        }
+    }
+
+    impl Haystack for Pieces {
+        type Chunks<'h> = iter::Map<std::slice::Iter<'h, String>, fn(&String) -> &str>;
+
+        fn chunks(&self) -> SearchResult<Self::Chunks<'_>> {
+            Ok(self.0.iter().map(String::as_str))
+        }
+    }
+
+    #[test]
+    fn test_haystack_containers() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("né")]),
+            Some(SearchOptions {
+                check_bounds: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "ab xab né";
+        let expected = pt.find_text_matches(text).unwrap();
+        assert_eq!(expected.len(), 2);
+
+        assert_eq!(pt.find_haystack_matches(text).unwrap(), expected);
+        assert_eq!(
+            pt.find_haystack_matches(&text.to_string()).unwrap(),
+            expected
+        );
+        assert_eq!(pt.find_haystack_matches(text.as_bytes()).unwrap(), expected);
+        assert_eq!(
+            pt.find_haystack_matches(&Pieces::new(&["a", "b x", "", "ab n", "é"]))
+                .unwrap(),
+            expected
+        );
+        assert!(
+            pt.find_haystack_matches(&Pieces::new(&[]))
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            pt.find_haystack_matches(b"ab\xc3".as_slice()),
+            Err(SearchError::InvalidText(String::from(
+                "invalid UTF-8 at byte 2"
+            )))
+        );
+    }
+
+    #[cfg(feature = "rope")]
+    #[test]
+    fn test_haystack_rope() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("abc")]), None).unwrap();
+        let text = "abc ".repeat(5000);
+        let rope = ropey::Rope::from_str(&text);
+        assert!(rope.chunks().count() > 1);
+        assert_eq!(
+            pt.find_haystack_matches(&rope).unwrap(),
+            pt.find_text_matches(text).unwrap()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use super::{GazetteerEntry, SearchError, SearchResult, TrieRoot, normalize_entries};
+use std::collections::HashSet;
+
+impl TrieRoot {
+    /// Add new (pattern, keyword) entries to the tree after it was built, instead of building
+    /// it again from the whole dictionary. The patterns are normalized according to the search
+    /// options of the tree, as in `create_prefix_tree`.
+    ///
+    /// The failure and dictionary links of the automaton are recomputed, which takes time
+    /// proportional to the size of the tree, so prefer adding patterns in batches. Context
+    /// rules, weights and coverage records of the existing patterns are kept. Returns an error
+    /// if a pattern is empty or already in the tree, in which case the tree is left unchanged.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("abc")]);
+    /// let mut prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    /// assert_eq!(prefix_tree.find_text_matches("ab cd abc").unwrap().len(), 1);
+    ///
+    /// prefix_tree
+    ///     .add_patterns(vec![(String::from("ab"), None), (String::from("cd"), None)])
+    ///     .unwrap();
+    /// assert_eq!(prefix_tree.find_text_matches("ab cd abc").unwrap().len(), 4);
+    /// ```
+    pub fn add_patterns(&mut self, patterns: Vec<(String, Option<String>)>) -> SearchResult<()> {
+        if patterns.is_empty() {
+            return Ok(());
+        }
+        let mut entries: Vec<GazetteerEntry> = patterns
+            .into_iter()
+            .map(|(pattern, keyword)| (pattern, keyword, None))
+            .collect();
+        let cased_patterns = normalize_entries(&self.options, &mut entries);
+
+        // Validate the new patterns before changing the tree
+        let mut seen = HashSet::with_capacity(entries.len());
+        for (pattern, _, _) in &entries {
+            if pattern.is_empty() {
+                return Err(SearchError::InvalidDictionary);
+            }
+            let in_tree = self
+                .node_by_path(pattern)
+                .is_some_and(|nid| self.get_node_unchecked(nid).pattern_node_id() == Some(nid));
+            if in_tree || !seen.insert(pattern.as_str()) {
+                return Err(SearchError::DuplicateNode);
+            }
+        }
+
+        // New patterns come after the existing ones in the dictionary
+        let first_id = self.patterns.len();
+        self.add_patterns_with_ids(entries.into_iter().zip(first_id..).collect())?;
+        self.expand_wildcards()?;
+        self.compute_failure_links()?;
+        self.finalize_links();
+        self.set_cased_patterns(cased_patterns);
+        if let Some(coverage) = &mut self.coverage {
+            coverage.resize(self.nodes.len());
+        }
+        self.set_single_pattern();
+        self.refresh_dfa();
+        self.warn_pathological();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_add_patterns_same_as_build() {
+        let words = |w: &[&str]| add_keyword_slot(w.iter().map(|s| s.to_string()).collect());
+        let opts = || {
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            })
+        };
+        let mut pt = create_prefix_tree(words(&["abcd", "bc"]), opts()).unwrap();
+        pt.add_patterns(words(&["AB", "c", "abcde", "x"])).unwrap();
+        pt.add_patterns(Vec::new()).unwrap();
+        let full =
+            create_prefix_tree(words(&["abcd", "bc", "ab", "c", "abcde", "x"]), opts()).unwrap();
+
+        assert_eq!(pt.total_nodes(), full.total_nodes());
+        assert_eq!(pt.total_patterns(), 6);
+        for id in 0..6 {
+            assert_eq!(pt.pattern(id), full.pattern(id));
+        }
+        for text in ["abcde", "xABCx bcd", "ab c"] {
+            assert_eq!(
+                pt.find_text_matches(text).unwrap(),
+                full.find_text_matches(text).unwrap(),
+                "Text {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_patterns_errors() {
+        let mut pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        pt.track_coverage();
+        for patterns in [vec!["ab"], vec!["x", "x"], vec!["x", ""]] {
+            assert!(
+                pt.add_patterns(add_keyword_slot(
+                    patterns.iter().map(|p| p.to_string()).collect()
+                ))
+                .is_err()
+            );
+        }
+        // Unchanged tree, still using the single pattern searcher
+        assert_eq!(pt.total_nodes(), 3);
+        assert!(pt.single_pattern.is_some());
+
+        pt.add_patterns(add_keyword_slot(vec![String::from("b")]))
+            .unwrap();
+        assert!(pt.single_pattern.is_none());
+        assert_eq!(pt.find_text_matches("abb").unwrap().len(), 3);
+        assert_eq!(pt.never_matched(), Some(Vec::new()));
+    }
+}
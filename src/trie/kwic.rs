@@ -0,0 +1,168 @@
+use super::{Match, SearchResult, TrieRoot};
+
+/// A match along with the text surrounding it, as shown in keyword-in-context (KWIC) indexes.
+/// Produced by `TrieRoot::find_matches_with_context` or `with_context`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct MatchInContext<'a, 't> {
+    /// The match found
+    found: Match<'a>,
+
+    /// Text preceding the match, up to the size of the window
+    before: &'t str,
+
+    /// Text of the match
+    matched: &'t str,
+
+    /// Text following the match, up to the size of the window
+    after: &'t str,
+}
+
+impl<'a, 't> MatchInContext<'a, 't> {
+    /// Get the match
+    pub fn get_match(&self) -> &Match<'a> {
+        &self.found
+    }
+
+    /// Consume the object, returning the match
+    pub fn into_match(self) -> Match<'a> {
+        self.found
+    }
+
+    /// Get the text preceding the match
+    pub fn before(&self) -> &'t str {
+        self.before
+    }
+
+    /// Get the text of the match, as written in the searched text
+    pub fn matched_text(&self) -> &'t str {
+        self.matched
+    }
+
+    /// Get the text following the match
+    pub fn after(&self) -> &'t str {
+        self.after
+    }
+}
+
+/// Attach to each match the text around it in the searched text: up to `window_chars`
+/// characters before and after the match. The matches must have been found in the given text.
+///
+/// Only the characters in the windows are traversed, so this is cheap even for long texts.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("fox")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let text = "the quick brown fox jumps";
+/// let matches = prefix_tree.find_text_matches(text).unwrap();
+///
+/// let found = trie::with_context(text, matches, 6);
+/// assert_eq!(found[0].before(), "brown ");
+/// assert_eq!(found[0].matched_text(), "fox");
+/// assert_eq!(found[0].after(), " jumps");
+/// ```
+pub fn with_context<'a, 't>(
+    text: &'t str,
+    matches: Vec<Match<'a>>,
+    window_chars: usize,
+) -> Vec<MatchInContext<'a, 't>> {
+    matches
+        .into_iter()
+        .map(|found| {
+            let range = found.byte_range();
+            let start = match window_chars {
+                0 => range.start,
+                n => text[..range.start]
+                    .char_indices()
+                    .rev()
+                    .nth(n - 1)
+                    .map_or(0, |(i, _)| i),
+            };
+            let end = text[range.end..]
+                .char_indices()
+                .nth(window_chars)
+                .map_or(text.len(), |(i, _)| range.end + i);
+            MatchInContext {
+                before: &text[start..range.start],
+                matched: &text[range.clone()],
+                after: &text[range.end..end],
+                found,
+            }
+        })
+        .collect()
+}
+
+impl TrieRoot {
+    /// Find all matches for the search dictionary in the text, as with `find_text_matches`,
+    /// along with up to `window_chars` characters of text before and after each of them.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("café")]);
+    /// let opts = trie::SearchOptions{case_sensitive: false, ..Default::default()};
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+    ///
+    /// let found = prefix_tree.find_matches_with_context("Un CAFÉ noir", 3).unwrap();
+    /// assert_eq!(
+    ///     (found[0].before(), found[0].matched_text(), found[0].after()),
+    ///     ("Un ", "CAFÉ", " no")
+    /// );
+    /// ```
+    pub fn find_matches_with_context<'a, 't>(
+        &'a self,
+        text: &'t str,
+        window_chars: usize,
+    ) -> SearchResult<Vec<MatchInContext<'a, 't>>> {
+        let matches = self.find_text_matches(text)?;
+        Ok(with_context(text, matches, window_chars))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_matches_with_context() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("ñé")]),
+            None,
+        )
+        .unwrap();
+        let text = "ab xñé—yz ab";
+        let snippets = |window| -> Vec<(&str, &str, &str)> {
+            pt.find_matches_with_context(text, window)
+                .unwrap()
+                .iter()
+                .map(|m| (m.before(), m.matched_text(), m.after()))
+                .collect()
+        };
+
+        assert_eq!(
+            snippets(0),
+            [("", "ab", ""), ("", "ñé", ""), ("", "ab", "")]
+        );
+        assert_eq!(
+            snippets(2),
+            [("", "ab", " x"), (" x", "ñé", "—y"), ("z ", "ab", "")]
+        );
+        assert_eq!(
+            snippets(100),
+            [
+                ("", "ab", " xñé—yz ab"),
+                ("ab x", "ñé", "—yz ab"),
+                ("ab xñé—yz ", "ab", "")
+            ]
+        );
+        let found = pt.find_matches_with_context(text, 1).unwrap();
+        assert_eq!(found[1].get_match().char_range(), (4, 6));
+        assert_eq!(
+            found[1].get_match(),
+            &pt.find_text_matches(text).unwrap()[1]
+        );
+    }
+}
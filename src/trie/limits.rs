@@ -0,0 +1,128 @@
+use super::{SearchError, SearchResult};
+use std::time::Duration;
+
+/// Limits on the work done by each search, set with the `limits` search option. A search that
+/// exceeds any of them is abandoned with a `SearchError::LimitExceeded` error, which protects
+/// services searching untrusted texts from pathological inputs, e.g. texts producing millions
+/// of overlapping matches.
+///
+/// The limits apply to the searches of whole texts (`find_text_matches` and its variants,
+/// `search` and the parallel searches), not to stream searches.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, SearchError, SearchLimits, SearchOptions};
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("a"), String::from("aa")]);
+/// let options = SearchOptions {
+///     limits: SearchLimits {
+///         max_matches: Some(10),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// let prefix_tree = trie::create_prefix_tree(dictionary, Some(options)).unwrap();
+///
+/// assert_eq!(prefix_tree.find_text_matches("aaa").unwrap().len(), 5);
+/// let res = prefix_tree.find_text_matches("a".repeat(100));
+/// assert!(matches!(res, Err(SearchError::LimitExceeded(_))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchLimits {
+    /// Maximum number of matches found in a text, including the overlapping matches discarded
+    /// according to the `match_kind` option. No limit if None.
+    pub max_matches: Option<usize>,
+
+    /// Maximum number of characters of the texts searched. No limit if None.
+    pub max_text_chars: Option<usize>,
+
+    /// Maximum time spent scanning each text. It is checked periodically, so the search may
+    /// run slightly past it. No limit if None.
+    pub time_limit: Option<Duration>,
+}
+
+impl SearchLimits {
+    /// Check that a text to search is not longer than allowed.
+    pub(crate) fn check_text(&self, text: &str) -> SearchResult<()> {
+        match self.max_text_chars {
+            // Texts are never longer in characters than in bytes
+            Some(max) if text.len() > max && text.chars().count() > max => Err(
+                SearchError::LimitExceeded(format!("text longer than {} characters", max)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that the number of matches found so far is not larger than allowed.
+    #[inline]
+    pub(crate) fn check_matches(&self, total: usize) -> SearchResult<()> {
+        match self.max_matches {
+            Some(max) if total > max => Err(SearchError::LimitExceeded(format!(
+                "more than {} matches",
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MatchKind, SearchOptions, TrieRoot, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    fn limited_tree(patterns: &[&str], limits: SearchLimits) -> TrieRoot {
+        create_prefix_tree(
+            add_keyword_slot(patterns.iter().map(|p| p.to_string()).collect()),
+            Some(SearchOptions {
+                limits,
+                match_kind: MatchKind::LeftmostLongest,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let limits = SearchLimits {
+            max_matches: Some(5),
+            max_text_chars: Some(12),
+            ..Default::default()
+        };
+        let exceeded = |res: SearchResult<usize>| matches!(res, Err(SearchError::LimitExceeded(_)));
+        for pt in [
+            limited_tree(&["a", "aa"], limits),
+            limited_tree(&["a"], limits),
+        ] {
+            // Overlapping matches count towards the limit
+            let count = |text: &str| pt.find_text_matches(text).map(|m| m.len());
+            assert_eq!(
+                count("aa").unwrap(),
+                if pt.total_patterns() == 2 { 1 } else { 2 }
+            );
+            assert!(exceeded(count("aaaaaa")));
+            assert!(exceeded(count("bbbbbbbbbbbbbbb")));
+            assert_eq!(count("ééééééééééé").unwrap(), 0);
+
+            let parallel = pt.find_text_matches_parallel("a-a-a-a-a-a", Some(2));
+            assert!(exceeded(parallel.map(|m| m.len())));
+            assert!(exceeded(pt.search(String::from("aaaaaa")).map(|o| o.len())));
+        }
+
+        let restored = TrieRoot::from_bytes(&limited_tree(&["a"], limits).to_bytes()).unwrap();
+        assert_eq!(restored.options().limits, limits);
+
+        let pt = limited_tree(
+            &["ab"],
+            SearchLimits {
+                time_limit: Some(Duration::ZERO),
+                ..Default::default()
+            },
+        );
+        assert!(exceeded(pt.find_text_matches("ab").map(|m| m.len())));
+        assert!(pt.find_text_matches("").unwrap().is_empty());
+        let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+        assert_eq!(restored.options().limits.time_limit, Some(Duration::ZERO));
+    }
+}
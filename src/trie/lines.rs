@@ -0,0 +1,127 @@
+use super::Match;
+
+/// Computes the line and column of the start of matches in a text, counting the newlines
+/// once as the matches advance through it. Matches are mostly found in order, so each one
+/// only requires reading the text since the previous one.
+pub(crate) struct LineTracker {
+    /// Byte offset and character index up to which the text has been read
+    byte: usize,
+    pos: usize,
+
+    /// Line number of the position read (starting at 1), and byte offset and character
+    /// index of the start of the line
+    line: usize,
+    line_byte: usize,
+    line_pos: usize,
+}
+
+impl LineTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            byte: 0,
+            pos: 0,
+            line: 1,
+            line_byte: 0,
+            line_pos: 0,
+        }
+    }
+
+    /// Set the line and column of the given matches of the text. The ranges of the matches
+    /// must refer to the text.
+    pub(crate) fn locate(&mut self, matches: &mut [Match], text: &str) {
+        for m in matches {
+            m.line_col = Some(self.line_col(text, m.start_byte, m.start));
+        }
+    }
+
+    /// Get the line and column (starting at 1, in characters) of the character at the given
+    /// byte offset and character index of the text.
+    fn line_col(&mut self, text: &str, byte: usize, pos: usize) -> (usize, usize) {
+        if byte >= self.byte {
+            for (offset, ch) in text[self.byte..byte].char_indices() {
+                self.pos += 1;
+                if ch == '\n' {
+                    self.line += 1;
+                    self.line_byte = self.byte + offset + 1;
+                    self.line_pos = self.pos;
+                }
+            }
+            self.byte = byte;
+        } else if byte < self.line_byte {
+            // A match starting on a previous line, e.g. found after a shorter one within it.
+            // Only the text from the start of its line is read.
+            let newlines = text.as_bytes()[byte..self.line_byte]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            let line_start = text[..byte].rfind('\n').map_or(0, |i| i + 1);
+            return (
+                self.line - newlines,
+                text[line_start..byte].chars().count() + 1,
+            );
+        }
+        (self.line, pos - self.line_pos + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_line_col() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("b\nc"),
+                String::from("cé"),
+                String::from("a b\ncé"),
+            ]),
+            Some(SearchOptions {
+                track_lines: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "é ab\ncé a b\ncé\n\nab";
+        let expected = [
+            ("ab", (1, 3)),
+            ("b\nc", (1, 4)),
+            ("cé", (2, 1)),
+            ("b\nc", (2, 6)),
+            // Found after a match starting later on the same line
+            ("a b\ncé", (2, 4)),
+            ("cé", (3, 1)),
+            ("ab", (5, 1)),
+        ];
+        let located = |found: Vec<_>| -> Vec<_> {
+            found
+                .iter()
+                .map(|m: &super::Match| (m.value(), m.line_col().unwrap()))
+                .collect()
+        };
+        assert_eq!(located(pt.find_text_matches(text).unwrap()), expected);
+        assert_eq!(located(pt.match_iter(text).collect()), expected);
+        assert_eq!(
+            pt.longest_match_at(text, 8).unwrap().line_col(),
+            Some((2, 4))
+        );
+
+        // Found after a match starting on the next line
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("y"), String::from("x\nyz")]),
+            Some(SearchOptions {
+                track_lines: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            located(pt.find_text_matches("é\néx\nyz").unwrap()),
+            [("y", (3, 1)), ("x\nyz", (2, 2))]
+        );
+
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        assert_eq!(pt.find_text_matches("ab").unwrap()[0].line_col(), None);
+    }
+}
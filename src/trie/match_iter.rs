@@ -0,0 +1,274 @@
+use super::lines::LineTracker;
+use super::match_kind::LeftmostSelector;
+use super::normalize::PreparedText;
+use super::search::ScanState;
+use super::{Match, MatchKind, ReadChar, SearchResult, TrieRoot};
+use std::collections::VecDeque;
+
+/// Lazy iterator over the matches of a text, created with `TrieRoot::match_iter`.
+///
+/// The text is scanned only as far as needed to produce the next match, so stopping the
+/// iteration early skips the rest of the text. Matches are produced in order of their end
+/// position, as with `find_text_matches`.
+pub struct MatchIter<'a, 't> {
+    trie: &'a TrieRoot,
+    prepared: PreparedText<'t>,
+    state: ScanState,
+
+    /// Byte offset and index of the next character of the prepared text to read
+    byte: usize,
+    pos: usize,
+    prev: Option<char>,
+
+    /// Whether the whole text has been scanned
+    done: bool,
+
+    /// Selector of the matches to report, if the match kind is not overlapping
+    selector: Option<LeftmostSelector<'a>>,
+
+    /// Characters of the original text, if needed to compute the scores of the matches
+    original_chars: Option<Vec<char>>,
+
+    /// Tracker of the lines of the original text, if needed
+    lines: Option<LineTracker>,
+
+    /// Matches found and not yet returned
+    pending: VecDeque<Match<'a>>,
+    found: Vec<Match<'a>>,
+}
+
+impl<'a, 't> MatchIter<'a, 't> {
+    /// Create an iterator over the matches of the given text
+    pub(crate) fn new(trie: &'a TrieRoot, text: &'t str) -> Self {
+        let prepared = trie.prepare_text(text);
+        let original_chars = trie.options.compute_scores.then(|| {
+            let original = prepared.original.unwrap_or(&prepared.text);
+            original.chars().collect()
+        });
+        Self {
+            trie,
+            prepared,
+            state: ScanState::new(trie),
+            byte: 0,
+            pos: 0,
+            prev: None,
+            done: false,
+            selector: (trie.options.match_kind != MatchKind::Overlapping)
+                .then(|| LeftmostSelector::new(trie.options.match_kind)),
+            original_chars,
+            lines: trie.options.track_lines.then(LineTracker::new),
+            pending: VecDeque::new(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Get the next match, scanning the text until one is found. Returns None once the whole
+    /// text has been scanned.
+    pub(crate) fn next_match(&mut self) -> SearchResult<Option<Match<'a>>> {
+        while self.pending.is_empty() && !self.done {
+            let text = self.prepared.text.as_ref();
+            let mut rest = text[self.byte..].chars();
+            let horizon = match rest.next() {
+                None => {
+                    self.done = true;
+                    if self.state.truncated {
+                        log::warn!("Some matches were skipped due to the maximum chain depth");
+                    }
+                    usize::MAX
+                }
+                Some(ch) => {
+                    let read = ReadChar {
+                        ch,
+                        pos: self.pos,
+                        byte: self.byte,
+                        prev: self.prev,
+                    };
+                    self.trie.scan_char(
+                        &mut self.state,
+                        read,
+                        rest.next(),
+                        text,
+                        0,
+                        &mut self.found,
+                    )?;
+                    self.byte += ch.len_utf8();
+                    self.pos += 1;
+                    self.prev = Some(ch);
+                    self.state.horizon(self.trie.max_pattern_len)
+                }
+            };
+            if let Some(selector) = &mut self.selector {
+                selector.push(&mut self.found);
+                selector.select(horizon, &mut self.found);
+            }
+
+            if !self.found.is_empty() {
+                self.trie.remap_matches(&mut self.found, &self.prepared);
+                if let Some(chars) = &self.original_chars {
+                    self.trie.score_matches_in(&mut self.found, chars);
+                }
+                if let Some(lines) = &mut self.lines {
+                    let original = self.prepared.original.unwrap_or(&self.prepared.text);
+                    lines.locate(&mut self.found, original);
+                }
+                self.pending.extend(self.found.drain(..));
+            }
+        }
+        Ok(self.pending.pop_front())
+    }
+}
+
+impl<'a> Iterator for MatchIter<'a, '_> {
+    type Item = Match<'a>;
+
+    /// Get the next match. The iteration ends early if the automaton of the tree is found to
+    /// be inconsistent, which is logged as an error.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match().unwrap_or_else(|e| {
+            log::error!("Search stopped: {}", e);
+            self.done = true;
+            None
+        })
+    }
+}
+
+impl TrieRoot {
+    /// Iterate lazily over the matches of the search dictionary in the given text, instead of
+    /// collecting them all into a vector. The text is transformed according to the search
+    /// options (e.g. lowercased) upfront if needed, but only scanned as the iterator advances.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("cd")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let mut matches = prefix_tree.match_iter("xx cd ab cd");
+    /// assert_eq!(matches.next().unwrap().char_range(), (3, 5));
+    /// assert_eq!(matches.count(), 2);
+    ///
+    /// let first_ab = prefix_tree.match_iter("cd ab ab").find(|m| m.value() == "ab");
+    /// assert_eq!(first_ab.unwrap().char_range(), (3, 5));
+    /// ```
+    pub fn match_iter<'t>(&self, text: &'t str) -> MatchIter<'_, 't> {
+        MatchIter::new(self, text)
+    }
+
+    /// Find the first match of the search dictionary in the given text, stopping the scan as
+    /// soon as it is found, e.g. to check texts for banned terms. Returns the same match as
+    /// the first one of `find_text_matches`, or None if there are no matches.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("spam"), String::from("ham")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let first = prefix_tree.find_first_match("ham and spam").unwrap();
+    /// assert_eq!(first.map(|m| m.value()), Some("ham"));
+    /// assert!(prefix_tree.find_first_match("eggs").unwrap().is_none());
+    /// ```
+    pub fn find_first_match<'a>(&'a self, text: &str) -> SearchResult<Option<Match<'a>>> {
+        MatchIter::new(self, text).next_match()
+    }
+
+    /// Find the first `n` matches of the search dictionary in the given text, stopping the
+    /// scan once they are found. Returns the same matches as the first `n` of
+    /// `find_text_matches`, or all of them if there are fewer.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let found = prefix_tree.find_first_n_matches("ab ab ab", 2).unwrap();
+    /// let ranges: Vec<(usize, usize)> = found.iter().map(|m| m.char_range()).collect();
+    /// assert_eq!(ranges, [(0, 2), (3, 5)]);
+    /// ```
+    pub fn find_first_n_matches<'a>(
+        &'a self,
+        text: &str,
+        n: usize,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let mut matches = MatchIter::new(self, text);
+        let mut found = Vec::new();
+        while found.len() < n
+            && let Some(m) = matches.next_match()?
+        {
+            found.push(m);
+        }
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MatchKind, SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_match_iter_same_as_search() {
+        for (case_sensitive, compute_scores) in [(true, false), (false, true)] {
+            let pt = create_prefix_tree(
+                add_keyword_slot(vec![
+                    String::from("ab"),
+                    String::from("abc"),
+                    String::from("bc"),
+                    String::from("é"),
+                ]),
+                Some(SearchOptions {
+                    case_sensitive,
+                    compute_scores,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+            for text in ["", "abc", "xABC abcé", "ÀbC e\u{301} abc"] {
+                let expected = pt.find_text_matches(text).unwrap();
+                let found: Vec<_> = pt.match_iter(text).collect();
+                assert_eq!(found, expected, "Text {text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_first_matches() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("abc"),
+                String::from("bc"),
+                String::from("b"),
+            ]),
+            Some(SearchOptions {
+                match_kind: MatchKind::LeftmostLongest,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "xbc abc b";
+        let expected = pt.find_text_matches(text).unwrap();
+        assert_eq!(
+            pt.find_first_match(text).unwrap().as_ref(),
+            expected.first()
+        );
+        assert_eq!(pt.find_first_match("xyz").unwrap(), None);
+        for n in 0..5 {
+            let found = pt.find_first_n_matches(text, n).unwrap();
+            assert_eq!(found, expected[..n.min(expected.len())]);
+        }
+    }
+
+    #[test]
+    fn test_match_iter_lazy() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        let text = "ab ".repeat(1000);
+        let mut matches = pt.match_iter(&text);
+        assert_eq!(matches.next().unwrap().char_range(), (0, 2));
+        // Only the first characters have been read
+        assert!(matches.pos < 5);
+        assert_eq!(matches.count(), 999);
+    }
+}
@@ -0,0 +1,173 @@
+use super::{Match, TrieRoot};
+
+/// How the matches found in a text are reported when they overlap. Set with the `match_kind`
+/// search option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchKind {
+    /// Report every occurrence of every pattern, including overlapping ones.
+    #[default]
+    Overlapping,
+
+    /// Report non-overlapping matches: scanning from left to right, the longest pattern
+    /// starting at the leftmost position is taken, and the search continues after it. This
+    /// is the usual behaviour for tokenization and replacement.
+    LeftmostLongest,
+
+    /// Report non-overlapping matches as with `LeftmostLongest`, but taking the pattern that
+    /// comes first in the dictionary among those starting at the leftmost position.
+    LeftmostFirst,
+}
+
+impl MatchKind {
+    /// Get a match kind from its name in snake case (e.g. "leftmost_longest"), as used by the
+    /// Python bindings and the search service. Returns None if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "overlapping" => Some(Self::Overlapping),
+            "leftmost_longest" => Some(Self::LeftmostLongest),
+            "leftmost_first" => Some(Self::LeftmostFirst),
+            _ => None,
+        }
+    }
+}
+
+/// Selects the non-overlapping matches of a leftmost match kind among the matches found while
+/// scanning a text.
+///
+/// Matches can be added as they are found. A match is selected once the matches starting at
+/// or before it are all known, i.e. once its start is before the position from which the
+/// matches found later can start.
+pub(crate) struct LeftmostSelector<'a> {
+    kind: MatchKind,
+
+    /// Matches that may still be selected
+    candidates: Vec<Match<'a>>,
+
+    /// End of the last match selected. Matches starting before it are discarded.
+    selected_end: usize,
+}
+
+impl<'a> LeftmostSelector<'a> {
+    pub(crate) fn new(kind: MatchKind) -> Self {
+        Self {
+            kind,
+            candidates: Vec::new(),
+            selected_end: 0,
+        }
+    }
+
+    /// Add the given matches as candidates, leaving the vector empty.
+    pub(crate) fn push(&mut self, found: &mut Vec<Match<'a>>) {
+        let selected_end = self.selected_end;
+        self.candidates
+            .extend(found.drain(..).filter(|m| m.start >= selected_end));
+    }
+
+    /// Move the matches that can be selected to the output vector, given the position from
+    /// which the matches added later can start.
+    pub(crate) fn select(&mut self, horizon: usize, out: &mut Vec<Match<'a>>) {
+        while let Some(start) = self.candidates.iter().map(|m| m.start).min() {
+            if start >= horizon {
+                break;
+            }
+            let leftmost = self
+                .candidates
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.start == start);
+            let best = match self.kind {
+                MatchKind::LeftmostFirst => leftmost.min_by_key(|(_, m)| m.pattern_id),
+                _ => leftmost.max_by_key(|(_, m)| m.end),
+            };
+            let Some((best_idx, _)) = best else {
+                break;
+            };
+
+            let chosen = self.candidates.swap_remove(best_idx);
+            self.selected_end = chosen.end;
+            self.candidates.retain(|m| m.start >= chosen.end);
+            out.push(chosen);
+        }
+    }
+}
+
+impl TrieRoot {
+    /// Keep the matches reported according to the `match_kind` search option among all the
+    /// (possibly overlapping) matches found in a text.
+    pub(crate) fn select_matches<'a>(&self, matches: Vec<Match<'a>>) -> Vec<Match<'a>> {
+        self.select_matches_kind(matches, self.options.match_kind)
+    }
+
+    /// Keep the matches reported according to the given match kind.
+    pub(crate) fn select_matches_kind<'a>(
+        &self,
+        mut matches: Vec<Match<'a>>,
+        kind: MatchKind,
+    ) -> Vec<Match<'a>> {
+        if kind == MatchKind::Overlapping {
+            return matches;
+        }
+        let mut selector = LeftmostSelector::new(kind);
+        selector.push(&mut matches);
+        selector.select(usize::MAX, &mut matches);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    /// Search the text returning the ranges of the matches, checking that the eager and lazy
+    /// searches agree
+    fn search_ranges(patterns: &[&str], kind: MatchKind, text: &str) -> Vec<(usize, usize)> {
+        let pt = create_prefix_tree(
+            add_keyword_slot(patterns.iter().map(|p| p.to_string()).collect()),
+            Some(SearchOptions {
+                match_kind: kind,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let found = pt.find_text_matches(text).unwrap();
+        let lazy: Vec<Match> = pt.match_iter(text).collect();
+        assert_eq!(found, lazy);
+        found.iter().map(|m| m.char_range()).collect()
+    }
+
+    #[test]
+    fn test_leftmost_longest() {
+        let patterns = ["abcd", "b", "bcd", "ab", "cdef", "x"];
+        assert_eq!(
+            search_ranges(&patterns, MatchKind::LeftmostLongest, "abcdef xabcx"),
+            [(0, 4), (7, 8), (8, 10), (11, 12)]
+        );
+        assert_eq!(
+            search_ranges(&patterns, MatchKind::LeftmostLongest, "bcdef"),
+            [(0, 3)]
+        );
+        assert_eq!(
+            search_ranges(&["aa"], MatchKind::LeftmostLongest, "aaaaa"),
+            [(0, 2), (2, 4)]
+        );
+        assert_eq!(
+            search_ranges(&patterns, MatchKind::Overlapping, "abcd").len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_leftmost_first() {
+        // "ab" comes before "abcd" in the dictionary, so it is preferred
+        let patterns = ["b", "ab", "abcd", "cd"];
+        assert_eq!(
+            search_ranges(&patterns, MatchKind::LeftmostFirst, "abcd bcd"),
+            [(0, 2), (2, 4), (5, 6), (6, 8)]
+        );
+        assert_eq!(
+            search_ranges(&["abcd", "ab", "cd"], MatchKind::LeftmostFirst, "abcd"),
+            [(0, 4)]
+        );
+    }
+}
@@ -0,0 +1,432 @@
+use super::{TrieRoot, normalizer};
+use std::borrow::Cow;
+use std::cell::OnceCell;
+#[cfg(feature = "unicode")]
+use unicode_normalization::{UnicodeNormalization, char as uchar, is_nfc};
+
+/// Apply Unicode NFC normalization to a sequence of characters. Without the `unicode`
+/// feature, the characters are returned unchanged.
+#[cfg(feature = "unicode")]
+pub(crate) fn nfc(chars: impl Iterator<Item = char>) -> impl Iterator<Item = char> {
+    chars.nfc()
+}
+
+/// Apply Unicode NFC normalization to a sequence of characters. Without the `unicode`
+/// feature, the characters are returned unchanged.
+#[cfg(not(feature = "unicode"))]
+pub(crate) fn nfc(chars: impl Iterator<Item = char>) -> impl Iterator<Item = char> {
+    chars
+}
+
+/// Check whether a string is in NFC form. Always true without the `unicode` feature.
+#[cfg(not(feature = "unicode"))]
+#[inline]
+fn is_nfc(_s: &str) -> bool {
+    true
+}
+
+/// Check whether a text can be cut before a character without changing the NFC normalization
+/// of the part before it: the character is a starter that never composes with a preceding
+/// one. Always true without the `unicode` feature.
+#[cfg(feature = "unicode")]
+pub(crate) fn is_nfc_boundary(c: char) -> bool {
+    c.is_ascii()
+        || (uchar::canonical_combining_class(c) == 0
+            && unicode_normalization::is_nfc_quick(std::iter::once(c))
+                == unicode_normalization::IsNormalized::Yes)
+}
+
+/// Check whether a text can be cut before a character without changing the NFC normalization
+/// of the part before it: the character is a starter that never composes with a preceding
+/// one. Always true without the `unicode` feature.
+#[cfg(not(feature = "unicode"))]
+#[inline]
+pub(crate) fn is_nfc_boundary(_c: char) -> bool {
+    true
+}
+
+/// Range of characters of an original text each character of a transformed text comes from
+pub(crate) type CharOffsets = Vec<(usize, usize)>;
+
+/// A text transformed according to the search options, ready to be searched.
+///
+/// Searching requires the text to be normalized the same way as the patterns: the chain of
+/// normalizers of the options, Unicode NFC normalization and, for case-insensitive searches, case
+/// folding. NFC normalization can change the number of characters of the text, so when this happens
+/// a map from each character of the prepared text to the range of characters of the original text
+/// it comes from is built.
+#[derive(Debug)]
+pub(crate) struct PreparedText<'t> {
+    /// The transformed text. Borrows the original text if no transformations were needed.
+    pub(crate) text: Cow<'t, str>,
+
+    /// Range of characters of the original text each character of the prepared text comes
+    /// from. None if the characters of both texts correspond one to one.
+    pub(crate) offsets: Option<Vec<(usize, usize)>>,
+
+    /// The original text, if the transformations changed it
+    pub(crate) original: Option<&'t str>,
+
+    /// Byte offset of each character of the original text, plus its length. Only built if
+    /// needed to locate matches in a transformed text, and left empty for ASCII texts.
+    original_bytes: OnceCell<Option<Vec<usize>>>,
+}
+
+impl PreparedText<'_> {
+    /// Map a range of characters of the prepared text to the corresponding range of
+    /// characters in the original text.
+    pub(crate) fn original_range(&self, start: usize, end: usize) -> (usize, usize) {
+        match &self.offsets {
+            None => (start, end),
+            Some(offsets) => (offsets[start].0, offsets[end - 1].1),
+        }
+    }
+
+    /// Map a range of characters of the original text to the corresponding range of bytes.
+    pub(crate) fn original_byte_range(&self, start: usize, end: usize) -> (usize, usize) {
+        let original = self.original.unwrap_or(&self.text);
+        let bounds = self.original_bytes.get_or_init(|| {
+            (!original.is_ascii()).then(|| {
+                original
+                    .char_indices()
+                    .map(|(i, _)| i)
+                    .chain(std::iter::once(original.len()))
+                    .collect()
+            })
+        });
+        match bounds {
+            None => (start, end),
+            Some(bounds) => (bounds[start], bounds[end]),
+        }
+    }
+}
+
+/// Apply NFC normalization to a stream of characters, keeping track of the characters of the
+/// original text they come from. Each item of the input is a character together with the
+/// index of the original character it comes from.
+#[cfg(feature = "unicode")]
+pub(crate) fn nfc_with_offsets(
+    chars: impl Iterator<Item = (char, usize)>,
+    capacity: usize,
+) -> (String, Vec<(usize, usize)>) {
+    compose_with_offsets(chars, capacity, false)
+}
+
+/// Apply NFKC normalization to a stream of characters, keeping track of the characters of
+/// the original text they come from, as `nfc_with_offsets`.
+#[cfg(feature = "unicode")]
+pub(crate) fn nfkc_with_offsets(
+    chars: impl Iterator<Item = (char, usize)>,
+    capacity: usize,
+) -> (String, Vec<(usize, usize)>) {
+    compose_with_offsets(chars, capacity, true)
+}
+
+/// Apply NFC or, if `compat` is set, NFKC normalization to a stream of characters, keeping
+/// track of the characters of the original text they come from.
+///
+/// The input is split into segments that can be normalized independently: a segment ends
+/// before a starter character (canonical combining class 0) that does not compose with the
+/// preceding one. All the characters output for a segment map to the whole range of original
+/// characters the segment comes from.
+#[cfg(feature = "unicode")]
+fn compose_with_offsets(
+    chars: impl Iterator<Item = (char, usize)>,
+    capacity: usize,
+    compat: bool,
+) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(capacity);
+    let mut offsets = Vec::with_capacity(capacity);
+    let mut segment = String::new();
+    let mut seg_range = (0, 0);
+
+    let mut flush = |segment: &mut String, range: (usize, usize)| {
+        let mut push = |c| {
+            text.push(c);
+            offsets.push(range);
+        };
+        if compat {
+            segment.nfkc().for_each(&mut push);
+        } else {
+            segment.nfc().for_each(&mut push);
+        }
+        segment.clear();
+    };
+    let last_composed = |segment: &str| {
+        if compat {
+            segment.nfkc().last()
+        } else {
+            segment.nfc().last()
+        }
+    };
+
+    for (c, orig_idx) in chars {
+        let starts_segment = uchar::canonical_combining_class(c) == 0
+            && last_composed(&segment).is_none_or(|last| uchar::compose(last, c).is_none());
+        if starts_segment && !segment.is_empty() {
+            flush(&mut segment, seg_range);
+            seg_range = (orig_idx, orig_idx);
+        } else if segment.is_empty() {
+            seg_range = (orig_idx, orig_idx);
+        }
+        segment.push(c);
+        seg_range.1 = orig_idx + 1;
+    }
+    flush(&mut segment, seg_range);
+    (text, offsets)
+}
+
+/// Collect a stream of characters keeping track of the characters of the original text they
+/// come from. Without the `unicode` feature no normalization is applied.
+#[cfg(not(feature = "unicode"))]
+pub(crate) fn nfc_with_offsets(
+    chars: impl Iterator<Item = (char, usize)>,
+    capacity: usize,
+) -> (String, Vec<(usize, usize)>) {
+    let mut text = String::with_capacity(capacity);
+    let mut offsets = Vec::with_capacity(capacity);
+    for (c, orig_idx) in chars {
+        text.push(c);
+        offsets.push((orig_idx, orig_idx + 1));
+    }
+    (text, offsets)
+}
+
+/// Map a character to its Unicode simple case folding, to compare texts ignoring case.
+///
+/// Unlike lowercasing, which turns e.g. 'İ' into two characters, simple case folding maps
+/// every character to a single one, so the characters of a folded text correspond one to one
+/// to those of the original. It matches the lowercase form of most characters, except for
+/// the variant forms of some letters (e.g. 'ſ' or final 'ς'), which fold to their usual
+/// lowercase form, and Cherokee, which folds to uppercase.
+#[inline]
+pub(crate) fn fold_case(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+    let code = c as u32;
+    match c {
+        // Characters without a simple case folding
+        'İ' | '\u{13A0}'..='\u{13F5}' => c,
+        '\u{13F8}'..='\u{13FD}' => char::from_u32(code - 8).unwrap(),
+        '\u{AB70}'..='\u{ABBF}' => char::from_u32(code - 0xAB70 + 0x13A0).unwrap(),
+        'ſ' => 's',
+        '\u{345}' | '\u{1FBE}' => 'ι',
+        'ς' => 'σ',
+        'ϐ' => 'β',
+        'ϑ' => 'θ',
+        'ϕ' => 'φ',
+        'ϖ' => 'π',
+        'ϰ' => 'κ',
+        'ϱ' => 'ρ',
+        'ϵ' => 'ε',
+        'ẛ' => 'ṡ',
+        '\u{1C80}' => 'в',
+        '\u{1C81}' => 'д',
+        '\u{1C82}' => 'о',
+        '\u{1C83}' => 'с',
+        '\u{1C84}' | '\u{1C85}' => 'т',
+        '\u{1C86}' => 'ъ',
+        '\u{1C87}' => 'ѣ',
+        '\u{1C88}' => '\u{A64B}',
+        _ => {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(low), None) => low,
+                _ => c,
+            }
+        }
+    }
+}
+
+/// Check whether case folding would change the text
+#[inline]
+fn needs_folding(text: &str) -> bool {
+    if text.is_ascii() {
+        return text.bytes().any(|b| b.is_ascii_uppercase());
+    }
+    text.chars().any(|c| fold_case(c) != c)
+}
+
+impl TrieRoot {
+    /// Apply the transformations required by the search options to a text before searching it.
+    /// The text is only copied if it is changed by the transformations.
+    pub(crate) fn prepare_text<'t>(&self, text: &'t str) -> PreparedText<'t> {
+        let Some((normalized, chain_offsets)) =
+            normalizer::apply_normalizers(&self.options.normalizers, text)
+        else {
+            return match self.fold_and_compose(text) {
+                None => PreparedText {
+                    text: Cow::Borrowed(text),
+                    offsets: None,
+                    original: None,
+                    original_bytes: OnceCell::new(),
+                },
+                Some((prepared, offsets)) => PreparedText {
+                    text: Cow::Owned(prepared),
+                    offsets,
+                    original: Some(text),
+                    original_bytes: OnceCell::new(),
+                },
+            };
+        };
+
+        // Map the characters of the prepared text to the original through the output of the
+        // normalizers
+        let (prepared, offsets) = match self.fold_and_compose(&normalized) {
+            None => (normalized, chain_offsets),
+            Some((prepared, None)) => (prepared, chain_offsets),
+            Some((prepared, Some(offsets))) => {
+                let offsets = offsets
+                    .into_iter()
+                    .map(|(start, end)| (chain_offsets[start].0, chain_offsets[end - 1].1))
+                    .collect();
+                (prepared, offsets)
+            }
+        };
+        PreparedText {
+            text: Cow::Owned(prepared),
+            offsets: Some(offsets),
+            original: Some(text),
+            original_bytes: OnceCell::new(),
+        }
+    }
+
+    /// Apply the case folding, whitespace mapping and NFC normalization required by the search
+    /// options to a text. Returns the transformed text and the range of characters of the
+    /// given text each of its characters comes from, if they don't correspond one to one, or
+    /// None if the text is left unchanged.
+    fn fold_and_compose(&self, text: &str) -> Option<(String, Option<CharOffsets>)> {
+        let folded = ((!self.options.case_sensitive && needs_folding(text))
+            || self.options.whitespace.needs_normalizing(text))
+        .then(|| {
+            text.chars()
+                .map(|c| self.options.fold_char(c))
+                .collect::<String>()
+        });
+
+        // Fast path: case folding and the mapping of whitespace keep the characters of the
+        // text, so they correspond to those of the original unless NFC normalization changes
+        // them
+        let searched = folded.as_deref().unwrap_or(text);
+        if is_nfc(searched) {
+            return folded.map(|folded| (folded, None));
+        }
+        let (prepared, offsets) = nfc_with_offsets(searched.chars().zip(0..), searched.len());
+        Some((prepared, Some(offsets)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    fn sample_tree(case_sensitive: bool) -> TrieRoot {
+        create_prefix_tree(
+            add_keyword_slot(vec![String::from("x")]),
+            Some(SearchOptions {
+                case_sensitive,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_prepare_no_offsets() {
+        let prepared = sample_tree(true).prepare_text("Ábc déf");
+        assert_eq!(prepared.text, "Ábc déf");
+        assert!(prepared.offsets.is_none());
+
+        let prepared = sample_tree(false).prepare_text("Ábc DÉF");
+        assert_eq!(prepared.text, "ábc déf");
+        assert!(prepared.offsets.is_none());
+
+        // Texts that don't change are not copied
+        for (case_sensitive, text) in [(true, "Ábc"), (false, "ábc"), (false, "abc 12")] {
+            let prepared = sample_tree(case_sensitive).prepare_text(text);
+            assert!(matches!(prepared.text, Cow::Borrowed(_)));
+            assert!(prepared.original.is_none());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn test_prepare_nfc_offsets() {
+        // Decomposed 'é' and Hangul jamo
+        let prepared = sample_tree(true).prepare_text("ae\u{301}x\u{1100}\u{1161}\u{11a8}y");
+        assert_eq!(prepared.text, "aéx\u{ac01}y");
+        assert_eq!(
+            prepared.offsets.as_deref(),
+            Some(&[(0, 1), (1, 3), (3, 4), (4, 7), (7, 8)][..])
+        );
+        assert_eq!(prepared.original_range(1, 3), (1, 4));
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn test_prepare_folded_offsets() {
+        let prepared = sample_tree(false).prepare_text("İE\u{301}");
+        assert_eq!(prepared.text, "İé");
+        assert_eq!(prepared.offsets.as_deref(), Some(&[(0, 1), (1, 3)][..]));
+    }
+
+    #[test]
+    fn test_ranges_refer_to_original() {
+        use super::super::{CollapseWhitespace, Lowercase, WhitespaceMatch};
+        use std::sync::Arc;
+
+        // Characters whose transformed form has another length in bytes or characters
+        let text = "\u{212a}elvin ſtraſse \u{3000}NEW\u{3000}\u{3000}York cafe\u{301} İstanbul";
+        let patterns = || {
+            add_keyword_slot(vec![
+                String::from("kelvin"),
+                String::from("ſtraſse"),
+                String::from("new york"),
+                String::from("café"),
+                String::from("İstanbul"),
+            ])
+        };
+        for opts in [
+            SearchOptions {
+                case_sensitive: false,
+                whitespace: WhitespaceMatch::Flexible,
+                ..Default::default()
+            },
+            SearchOptions {
+                normalizers: vec![Arc::new(CollapseWhitespace), Arc::new(Lowercase)],
+                ..Default::default()
+            },
+        ] {
+            let pt = create_prefix_tree(patterns(), Some(opts)).unwrap();
+            let found = pt.find_text_matches(text).unwrap();
+            let chars: Vec<char> = text.chars().collect();
+            for m in &found {
+                let (start, end) = m.char_range();
+                let from_chars: String = chars[start..end].iter().collect();
+                assert_eq!(m.matched_text(text), from_chars);
+            }
+            #[cfg(feature = "unicode")]
+            assert_eq!(found.len(), 5);
+
+            assert_eq!(pt.match_iter(text).collect::<Vec<_>>(), found);
+            assert_eq!(pt.find_text_matches_parallel(text, Some(2)).unwrap(), found);
+            let mut searcher = pt.stream_searcher();
+            let mut streamed = searcher.feed(text).unwrap();
+            streamed.append(&mut searcher.finish().unwrap());
+            assert_eq!(streamed, found);
+        }
+    }
+
+    #[test]
+    fn test_fold_case() {
+        let folded: String = "AbÉİſΣς\u{13F8}\u{AB70}".chars().map(fold_case).collect();
+        assert_eq!(folded, "abéİsσσ\u{13F0}\u{13A0}");
+
+        // Texts with variant forms are folded even if already lowercase
+        let prepared = sample_tree(false).prepare_text("straſse");
+        assert_eq!(prepared.text, "strasse");
+        assert!(prepared.offsets.is_none());
+    }
+}
@@ -0,0 +1,327 @@
+use super::normalize;
+use std::fmt::Debug;
+use std::sync::Arc;
+#[cfg(feature = "unicode")]
+use unicode_normalization::char as uchar;
+
+/// A transformation applied to the patterns and to the searched texts, set with the
+/// `normalizers` search option. The matches found in a transformed text are reported with
+/// their ranges in the text as given to the search, so a normalizer must tell which
+/// characters of its input each character of its output comes from.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, Normalizer, SearchOptions};
+/// use std::sync::Arc;
+///
+/// /// Removes the zero-width spaces of the text
+/// #[derive(Debug)]
+/// struct StripZeroWidth;
+///
+/// impl Normalizer for StripZeroWidth {
+///     fn name(&self) -> &str {
+///         "strip_zero_width"
+///     }
+///
+///     fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>) {
+///         for (i, &c) in chars.iter().enumerate() {
+///             if c != '\u{200b}' {
+///                 out.push((c, (i, i + 1)));
+///             }
+///         }
+///     }
+/// }
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("password")]);
+/// let opts = SearchOptions {
+///     normalizers: vec![Arc::new(StripZeroWidth)],
+///     ..Default::default()
+/// };
+/// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+///
+/// let text = "my pass\u{200b}word";
+/// let matches = prefix_tree.find_text_matches(text).unwrap();
+/// assert_eq!(matches[0].matched_text(text), "pass\u{200b}word");
+/// ```
+pub trait Normalizer: Debug + Send + Sync {
+    /// Name of the normalizer. The built-in normalizers are saved by name with the tree (see
+    /// `normalizer_from_name`), so other normalizers should not use their names.
+    fn name(&self) -> &str;
+
+    /// Transform a sequence of characters, pushing each character of the output together
+    /// with the range of indices of the input characters it comes from. The ranges must not
+    /// be empty, and must not decrease from one output character to the next.
+    fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>);
+
+    /// Check whether a text can be cut before a character without changing the output of the
+    /// normalizer for the part before it. Stream searches hold back the text received after
+    /// the last such character until more is known. The default suits normalizers that
+    /// transform each character on its own.
+    fn is_boundary(&self, _c: char) -> bool {
+        true
+    }
+}
+
+/// Unicode NFC normalization (canonical composition), e.g. "e" followed by a combining
+/// acute accent becomes "é". Searches always apply it after the other normalizers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nfc;
+
+impl Normalizer for Nfc {
+    fn name(&self) -> &str {
+        "nfc"
+    }
+
+    fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>) {
+        let (text, offsets) = normalize::nfc_with_offsets(chars.iter().copied().zip(0..), 0);
+        out.extend(text.chars().zip(offsets));
+    }
+
+    fn is_boundary(&self, c: char) -> bool {
+        normalize::is_nfc_boundary(c)
+    }
+}
+
+/// Unicode NFKC normalization (compatibility composition), which also replaces characters
+/// by their usual equivalent, e.g. "ﬁ" by "fi", full-width letters by ASCII ones and "²" by
+/// "2". Requires the `unicode` feature.
+#[cfg(feature = "unicode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nfkc;
+
+#[cfg(feature = "unicode")]
+impl Normalizer for Nfkc {
+    fn name(&self) -> &str {
+        "nfkc"
+    }
+
+    fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>) {
+        let (text, offsets) = normalize::nfkc_with_offsets(chars.iter().copied().zip(0..), 0);
+        out.extend(text.chars().zip(offsets));
+    }
+
+    fn is_boundary(&self, c: char) -> bool {
+        c.is_ascii()
+            || (uchar::canonical_combining_class(c) == 0
+                && unicode_normalization::is_nfkc_quick(std::iter::once(c))
+                    == unicode_normalization::IsNormalized::Yes)
+    }
+}
+
+/// Conversion to lowercase. Unlike the case folding of case-insensitive searches, this may
+/// turn a character into several, e.g. "İ" into "i̇".
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lowercase;
+
+impl Normalizer for Lowercase {
+    fn name(&self) -> &str {
+        "lowercase"
+    }
+
+    fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>) {
+        for (i, c) in chars.iter().enumerate() {
+            out.extend(c.to_lowercase().map(|low| (low, (i, i + 1))));
+        }
+    }
+}
+
+/// Removal of the accents and other combining marks, e.g. "crème brûlée" becomes "creme
+/// brulee". Characters are decomposed to remove the marks. Requires the `unicode` feature.
+#[cfg(feature = "unicode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripAccents;
+
+#[cfg(feature = "unicode")]
+impl Normalizer for StripAccents {
+    fn name(&self) -> &str {
+        "strip_accents"
+    }
+
+    fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>) {
+        for (i, &c) in chars.iter().enumerate() {
+            uchar::decompose_canonical(c, |d| {
+                if !uchar::is_combining_mark(d) {
+                    out.push((d, (i, i + 1)));
+                }
+            });
+        }
+    }
+}
+
+/// Replacement of each run of whitespace characters by a single space.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CollapseWhitespace;
+
+impl Normalizer for CollapseWhitespace {
+    fn name(&self) -> &str {
+        "collapse_whitespace"
+    }
+
+    fn normalize(&self, chars: &[char], out: &mut Vec<(char, (usize, usize))>) {
+        for (i, &c) in chars.iter().enumerate() {
+            if !c.is_whitespace() {
+                out.push((c, (i, i + 1)));
+            } else if let Some((' ', range)) = out.last_mut()
+                && range.1 == i
+                && chars[i - 1].is_whitespace()
+            {
+                range.1 = i + 1;
+            } else {
+                out.push((' ', (i, i + 1)));
+            }
+        }
+    }
+
+    fn is_boundary(&self, c: char) -> bool {
+        !c.is_whitespace()
+    }
+}
+
+/// Get a built-in normalizer from its name, e.g. "strip_accents". Returns None if the name is
+/// unknown, or if the normalizer requires a feature that is not enabled.
+pub fn normalizer_from_name(name: &str) -> Option<Arc<dyn Normalizer>> {
+    match name {
+        "nfc" => Some(Arc::new(Nfc)),
+        #[cfg(feature = "unicode")]
+        "nfkc" => Some(Arc::new(Nfkc)),
+        "lowercase" => Some(Arc::new(Lowercase)),
+        #[cfg(feature = "unicode")]
+        "strip_accents" => Some(Arc::new(StripAccents)),
+        "collapse_whitespace" => Some(Arc::new(CollapseWhitespace)),
+        _ => None,
+    }
+}
+
+/// Apply a chain of normalizers to a text. Returns the transformed text and the range of
+/// characters of the original text each of its characters comes from, or None if the text
+/// is left unchanged.
+pub(crate) fn apply_normalizers(
+    normalizers: &[Arc<dyn Normalizer>],
+    text: &str,
+) -> Option<(String, Vec<(usize, usize)>)> {
+    if normalizers.is_empty() {
+        return None;
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    let mut offsets: Vec<(usize, usize)> = (0..chars.len()).map(|i| (i, i + 1)).collect();
+    let mut out = Vec::with_capacity(chars.len());
+    for normalizer in normalizers {
+        out.clear();
+        normalizer.normalize(&chars, &mut out);
+        chars.clear();
+        let mut mapped = Vec::with_capacity(out.len());
+        for &(c, (start, end)) in &out {
+            chars.push(c);
+            mapped.push((offsets[start].0, offsets[end - 1].1));
+        }
+        offsets = mapped;
+    }
+
+    let normalized: String = chars.into_iter().collect();
+    (normalized != text).then_some((normalized, offsets))
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "unicode")]
+    use super::super::{SearchOptions, TrieRoot, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    fn normalized(
+        normalizer: impl Normalizer + 'static,
+        text: &str,
+    ) -> (String, Vec<(usize, usize)>) {
+        apply_normalizers(&[Arc::new(normalizer)], text).unwrap_or_else(|| {
+            let n = text.chars().count();
+            (text.to_string(), (0..n).map(|i| (i, i + 1)).collect())
+        })
+    }
+
+    #[test]
+    fn test_normalizers() {
+        assert_eq!(
+            normalized(CollapseWhitespace, "a \t\nb c  "),
+            (
+                String::from("a b c "),
+                vec![(0, 1), (1, 4), (4, 5), (5, 6), (6, 7), (7, 9)]
+            )
+        );
+        assert_eq!(
+            normalized(Lowercase, "Aİ"),
+            (String::from("ai\u{307}"), vec![(0, 1), (1, 2), (1, 2)])
+        );
+        assert!(apply_normalizers(&[Arc::new(Lowercase)], "abc").is_none());
+        assert!(apply_normalizers(&[], "ABC").is_none());
+
+        #[cfg(feature = "unicode")]
+        {
+            assert_eq!(
+                normalized(StripAccents, "Ée\u{301}\u{ac01}").0,
+                "Ee\u{1100}\u{1161}\u{11a8}"
+            );
+            assert_eq!(
+                normalized(Nfkc, "ﬁe\u{301}²"),
+                (String::from("fié2"), vec![(0, 1), (0, 1), (1, 3), (3, 4)])
+            );
+            assert_eq!(normalized(Nfc, "e\u{301}").0, "é");
+        }
+
+        // Offsets are composed along the chain
+        let chain: Vec<Arc<dyn Normalizer>> = vec![
+            Arc::new(CollapseWhitespace),
+            normalizer_from_name("lowercase").unwrap(),
+        ];
+        assert_eq!(
+            apply_normalizers(&chain, "A  İ"),
+            Some((
+                String::from("a i\u{307}"),
+                vec![(0, 1), (1, 3), (3, 4), (3, 4)]
+            ))
+        );
+        assert!(normalizer_from_name("upper").is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn test_search_with_normalizers() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("Crème Brûlée"), String::from("ﬁnal")]),
+            Some(SearchOptions {
+                normalizers: vec![
+                    Arc::new(Nfkc),
+                    Arc::new(StripAccents),
+                    Arc::new(CollapseWhitespace),
+                    Arc::new(Lowercase),
+                ],
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "Une CREME\n  brulee, e\u{301}tait FINAL";
+        let found: Vec<_> = pt
+            .find_text_matches(text)
+            .unwrap()
+            .iter()
+            .map(|m| (m.keyword(), m.char_range(), m.matched_text(text)))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("creme brulee", (4, 18), "CREME\n  brulee"),
+                ("final", (27, 32), "FINAL")
+            ]
+        );
+
+        // Built-in normalizers are kept when the tree is serialized
+        let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+        assert_eq!(
+            restored.find_text_matches(text).unwrap(),
+            pt.find_text_matches(text).unwrap()
+        );
+
+        // Settings of other patterns are found with the same normalization
+        let mut pt = pt;
+        pt.set_pattern_priority("creme brulee", 1).unwrap();
+        assert_eq!(pt.find_text_matches(text).unwrap()[0].priority(), 1);
+    }
+}
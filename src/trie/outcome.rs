@@ -0,0 +1,271 @@
+use super::cancel::Interrupt;
+use super::{Match, SearchError, SearchResult, TrieRoot};
+use std::borrow::Cow;
+use std::time::Instant;
+
+/// The result of a search, holding the matches along with the text that was actually searched.
+///
+/// The search options may require transforming the text before searching it (e.g. lowercasing
+/// for case-insensitive searches). The outcome keeps the transformed text alive so the matched
+/// substrings can be recovered safely, along with the original text so the matches can be
+/// reported as they appeared in the input.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("straße")]);
+/// let opts = trie::SearchOptions{case_sensitive: false, ..Default::default()};
+/// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+///
+/// let outcome = prefix_tree.search(String::from("Die STRASSE, die Straße")).unwrap();
+/// assert_eq!(outcome.len(), 1);
+/// assert_eq!(outcome.matched_text(0), Some("straße"));
+/// assert_eq!(outcome.haystack(), "die strasse, die straße");
+/// assert_eq!(outcome.original_text(0), Some("Straße"));
+/// ```
+#[derive(Debug)]
+pub struct SearchOutcome<'a> {
+    haystack: String,
+    matches: Vec<Match<'a>>,
+
+    /// The input text, if it differs from the searched haystack
+    original: Option<String>,
+
+    /// Range of characters of the haystack covered by each match, if these differ from the
+    /// ranges in the original text.
+    searched_ranges: Option<Vec<(usize, usize)>>,
+
+    /// Whether patterns were left unchecked at some position due to `max_chain_depth`
+    truncated: bool,
+
+    /// Whether the search stopped before the end of the text due to its deadline
+    timed_out: bool,
+}
+
+impl<'a> SearchOutcome<'a> {
+    /// The text as it was seen by the search automaton.
+    pub fn haystack(&self) -> &str {
+        &self.haystack
+    }
+
+    /// The text as it was given to the search, before any transformations.
+    pub fn original_haystack(&self) -> &str {
+        self.original.as_deref().unwrap_or(&self.haystack)
+    }
+
+    /// The matches found in the text.
+    pub fn matches(&self) -> &[Match<'a>] {
+        &self.matches
+    }
+
+    /// Total number of matches found
+    pub fn len(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Return whether no matches were found
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    /// Return whether some patterns were left unchecked at some position of the text due to
+    /// the `max_chain_depth` search option, so that matches may be missing.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Return whether the search was stopped before the end of the text because its deadline
+    /// was exceeded, so that only the matches found up to that point are reported.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Consume the outcome and return the vector of matches.
+    pub fn into_matches(self) -> Vec<Match<'a>> {
+        self.matches
+    }
+
+    /// Get the substring of the searched text covered by the i-th match. Returns None if
+    /// there is no match with that index.
+    pub fn matched_text(&self, i: usize) -> Option<&str> {
+        let (start, end) = match &self.searched_ranges {
+            None => self.matches.get(i)?.char_range(),
+            Some(ranges) => *ranges.get(i)?,
+        };
+        self.slice(start, end)
+    }
+
+    /// Get the substring of the searched text between the given character indices. Returns
+    /// None if the range is empty, reversed or out of bounds.
+    pub fn slice(&self, start_char: usize, end_char: usize) -> Option<&str> {
+        slice_chars(&self.haystack, start_char, end_char)
+    }
+
+    /// Get the substring of the original text covered by the i-th match, i.e. the match
+    /// as it appeared in the input (for example, with its original casing).
+    pub fn original_text(&self, i: usize) -> Option<&str> {
+        self.original_haystack()
+            .get(self.matches.get(i)?.byte_range())
+    }
+}
+
+/// Get the substring of the text between the given character indices. Returns None if the
+/// range is empty, reversed or out of bounds.
+fn slice_chars(text: &str, start_char: usize, end_char: usize) -> Option<&str> {
+    if start_char >= end_char {
+        return None;
+    }
+    if text.is_ascii() {
+        return text.get(start_char..end_char);
+    }
+
+    let mut bounds = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()));
+    let start_byte = bounds.nth(start_char)?;
+    let end_byte = bounds.nth(end_char - start_char - 1)?;
+    Some(&text[start_byte..end_byte])
+}
+
+impl TrieRoot {
+    /// Find all matches for the search dictionary in the given text, keeping the searched text
+    /// together with the matches.
+    pub fn search<'a>(&'a self, text: String) -> SearchResult<SearchOutcome<'a>> {
+        self.search_outcome(text, None)
+    }
+
+    /// Find the matches for the search dictionary in the given text as in `search`, stopping
+    /// once the deadline is exceeded. In that case the matches found so far are returned and
+    /// the outcome is flagged with `timed_out`, which bounds the time spent on untrusted or
+    /// unexpectedly large texts.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("cd")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    /// let outcome = prefix_tree.search_until(String::from("ab cd"), deadline).unwrap();
+    /// assert!(!outcome.timed_out());
+    /// assert_eq!(outcome.len(), 2);
+    ///
+    /// let outcome = prefix_tree.search_until(String::from("ab cd"), Instant::now()).unwrap();
+    /// assert!(outcome.timed_out());
+    /// ```
+    pub fn search_until<'a>(
+        &'a self,
+        text: String,
+        deadline: Instant,
+    ) -> SearchResult<SearchOutcome<'a>> {
+        self.search_outcome(text, Some(deadline))
+    }
+
+    /// Search the text keeping it together with the matches, stopping at the deadline if any.
+    fn search_outcome<'a>(
+        &'a self,
+        text: String,
+        deadline: Option<Instant>,
+    ) -> SearchResult<SearchOutcome<'a>> {
+        let prepared = self.prepare_text(&text);
+        let output = self.scan_text(&prepared, 0, Interrupt::until(deadline))?;
+        let timed_out = match output.interrupted {
+            None => false,
+            Some(SearchError::Timeout) => true,
+            Some(e) => return Err(e),
+        };
+        let mut matches = output.matches;
+
+        let searched_ranges = prepared
+            .offsets
+            .is_some()
+            .then(|| matches.iter().map(|m| m.char_range()).collect());
+        self.finish_matches(&mut matches, &prepared);
+
+        // Keep the input text as the haystack if it was searched as is
+        let (haystack, original) = match prepared.text {
+            Cow::Owned(searched) => (searched, Some(text)),
+            Cow::Borrowed(_) => (text, None),
+        };
+        Ok(SearchOutcome {
+            haystack,
+            matches,
+            original,
+            searched_ranges,
+            truncated: output.truncated,
+            timed_out,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_outcome_matched_text() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("Éa"), String::from("b-c")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let outcome = pt.search(String::from("xÉA b c ÉaÉa")).unwrap();
+        assert_eq!(outcome.len(), 4);
+        let found: Vec<&str> = (0..outcome.len())
+            .map(|i| outcome.matched_text(i).unwrap())
+            .collect();
+        assert_eq!(found, ["éa", "b c", "éa", "éa"]);
+        assert_eq!(outcome.matched_text(4), None);
+
+        assert_eq!(outcome.slice(0, 12), Some("xéa b c éaéa"));
+        assert_eq!(outcome.slice(11, 12), Some("a"));
+        assert_eq!(outcome.slice(11, 13), None);
+        assert_eq!(outcome.slice(3, 3), None);
+    }
+
+    #[test]
+    fn test_outcome_ascii_slices() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        let outcome = pt.search(String::from("xxab")).unwrap();
+        assert_eq!(outcome.matched_text(0), Some("ab"));
+        assert_eq!(outcome.slice(2, 5), None);
+        assert_eq!(outcome.into_matches().len(), 1);
+    }
+
+    #[test]
+    fn test_outcome_original_text() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("abc"), String::from("İx")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        // No change in the number of characters
+        let outcome = pt.search(String::from("x ABC aBc")).unwrap();
+        assert_eq!(outcome.original_haystack(), "x ABC aBc");
+        assert_eq!(outcome.original_text(0), Some("ABC"));
+        assert_eq!(outcome.original_text(1), Some("aBc"));
+        assert_eq!(outcome.original_text(2), None);
+
+        // 'İ' is case folded to itself, not lowercased to two characters
+        let outcome = pt.search(String::from("İX abc İx")).unwrap();
+        assert_eq!(outcome.len(), 3);
+        assert_eq!(outcome.matched_text(0), Some("İx"));
+        assert_eq!(outcome.matches()[0].char_range(), (0, 2));
+        assert_eq!(outcome.original_text(0), Some("İX"));
+        assert_eq!(outcome.original_text(1), Some("abc"));
+        assert_eq!(outcome.original_text(2), Some("İx"));
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Match, SearchError, SearchResult, TrieRoot};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// How to choose among overlapping matches in `resolve_overlaps`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlapStrategy {
+    /// Keep the longest matches: matches are taken from the longest to the shortest, skipping
+    /// those that overlap one already taken. Among matches of the same length, the leftmost
+    /// one is preferred.
+    #[default]
+    KeepLongest,
+
+    /// Keep the leftmost matches: matches are taken from left to right, skipping those that
+    /// overlap one already taken. Among matches with the same start, the one that comes first
+    /// in the input is preferred.
+    KeepFirst,
+
+    /// Keep the matches of the patterns with the highest priority: matches are taken from
+    /// the highest to the lowest priority, skipping those that overlap one already taken.
+    /// Among matches of the same priority, the longest one is preferred, then the leftmost.
+    /// See `TrieRoot::set_pattern_priority`.
+    KeepHighestPriority,
+
+    /// Keep all the matches, including overlapping ones.
+    KeepAll,
+}
+
+/// Remove the overlapping matches from the result of a search, keeping a set of
+/// non-overlapping matches chosen with the given strategy. The matches returned are sorted by
+/// their position in the text, except with `OverlapStrategy::KeepAll`, which returns them
+/// unchanged.
+///
+/// Unlike the `match_kind` search option, this can be applied after an overlapping search,
+/// e.g. to keep both the overlapping matches and the non-overlapping ones.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, OverlapStrategy, resolve_overlaps};
+///
+/// let dictionary = trie::add_keyword_slot(vec![
+///     String::from("new york"),
+///     String::from("york city"),
+///     String::from("city"),
+/// ]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let text = "new york city";
+///
+/// let found = prefix_tree.find_text_matches(text).unwrap();
+/// let longest = resolve_overlaps(found, OverlapStrategy::KeepLongest);
+/// let values: Vec<&str> = longest.iter().map(|m| m.value()).collect();
+/// assert_eq!(values, ["york city"]);
+///
+/// let found = prefix_tree.find_text_matches(text).unwrap();
+/// let first = resolve_overlaps(found, OverlapStrategy::KeepFirst);
+/// let values: Vec<&str> = first.iter().map(|m| m.value()).collect();
+/// assert_eq!(values, ["new york", "city"]);
+/// ```
+pub fn resolve_overlaps(mut matches: Vec<Match<'_>>, strategy: OverlapStrategy) -> Vec<Match<'_>> {
+    match strategy {
+        OverlapStrategy::KeepAll => return matches,
+        OverlapStrategy::KeepLongest => {
+            matches.sort_by_key(|m| (Reverse(m.end - m.start), m.start))
+        }
+        OverlapStrategy::KeepFirst => matches.sort_by_key(|m| m.start),
+        OverlapStrategy::KeepHighestPriority => {
+            matches.sort_by_key(|m| (Reverse(m.priority), Reverse(m.end - m.start), m.start))
+        }
+    }
+
+    // Ranges of the matches kept, by start. They never overlap, so the only one that can
+    // overlap a new match is the last one starting before its end.
+    let mut kept_ranges = BTreeMap::new();
+    let mut kept = Vec::new();
+    for m in matches {
+        let overlaps = kept_ranges
+            .range(..m.end)
+            .next_back()
+            .is_some_and(|(_, &end)| end > m.start);
+        if !overlaps {
+            kept_ranges.insert(m.start, m.end);
+            kept.push(m);
+        }
+    }
+    kept.sort_by_key(|m| m.start);
+    kept
+}
+
+impl TrieRoot {
+    /// Set the priority of a pattern of the dictionary, used to choose among overlapping
+    /// matches with `OverlapStrategy::KeepHighestPriority`, e.g. so that a product name wins
+    /// over the generic terms it contains. Patterns have a priority of 0 by default.
+    ///
+    /// Returns an error if the pattern is not in the dictionary.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, OverlapStrategy, resolve_overlaps};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![
+    ///     String::from("apple"),
+    ///     String::from("apple pie"),
+    ///     String::from("pie crust"),
+    /// ]);
+    /// let mut prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    /// prefix_tree.set_pattern_priority("pie crust", 10).unwrap();
+    ///
+    /// let found = prefix_tree.find_text_matches("apple pie crust").unwrap();
+    /// let kept = resolve_overlaps(found, OverlapStrategy::KeepHighestPriority);
+    /// let values: Vec<&str> = kept.iter().map(|m| m.value()).collect();
+    /// assert_eq!(values, ["apple", "pie crust"]);
+    /// ```
+    pub fn set_pattern_priority(&mut self, pattern: &str, priority: i32) -> SearchResult<()> {
+        let node_id = self
+            .pattern_node_id(pattern)
+            .ok_or_else(|| SearchError::PatternNotFound(pattern.to_string()))?;
+        if priority == 0 {
+            self.priorities.remove(&node_id);
+        } else {
+            self.priorities.insert(node_id, priority);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    fn resolved_ranges(text: &str, strategy: OverlapStrategy) -> Vec<(usize, usize)> {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abcd"),
+                String::from("bcdef"),
+                String::from("ef"),
+                String::from("f"),
+            ]),
+            None,
+        )
+        .unwrap();
+        let found = pt.find_text_matches(text).unwrap();
+        resolve_overlaps(found, strategy)
+            .iter()
+            .map(|m| (m.start, m.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_overlaps() {
+        assert_eq!(
+            resolved_ranges("abcdefg", OverlapStrategy::KeepLongest),
+            [(1, 6)]
+        );
+        assert_eq!(
+            resolved_ranges("abcdefg", OverlapStrategy::KeepFirst),
+            [(0, 2), (4, 6)]
+        );
+        assert_eq!(
+            resolved_ranges("abcdefg", OverlapStrategy::KeepAll).len(),
+            5
+        );
+        assert_eq!(
+            resolved_ranges("ab ef abcd", OverlapStrategy::KeepLongest),
+            [(0, 2), (3, 5), (6, 10)]
+        );
+        assert!(resolved_ranges("", OverlapStrategy::KeepLongest).is_empty());
+
+        // Without priorities, the longest matches are kept
+        assert_eq!(
+            resolved_ranges("abcdefg", OverlapStrategy::KeepHighestPriority),
+            [(1, 6)]
+        );
+    }
+
+    #[test]
+    fn test_resolve_by_priority() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abcd"),
+                String::from("bcdef"),
+                String::from("ef"),
+                String::from("f"),
+            ]),
+            None,
+        )
+        .unwrap();
+        pt.set_pattern_priority("ab", 2).unwrap();
+        pt.set_pattern_priority("f", 2).unwrap();
+        pt.set_pattern_priority("ef", 1).unwrap();
+        assert!(pt.set_pattern_priority("abc", 1).is_err());
+
+        let ranges = |pt: &TrieRoot| -> Vec<(usize, usize, i32)> {
+            let found = pt.find_text_matches("abcdefg").unwrap();
+            resolve_overlaps(found, OverlapStrategy::KeepHighestPriority)
+                .iter()
+                .map(|m| (m.start, m.end, m.priority()))
+                .collect()
+        };
+        assert_eq!(ranges(&pt), [(0, 2, 2), (5, 6, 2)]);
+
+        // Ties are broken by length
+        pt.set_pattern_priority("f", 1).unwrap();
+        assert_eq!(ranges(&pt), [(0, 2, 2), (4, 6, 1)]);
+
+        // Priorities are kept when the tree is serialized
+        let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+        assert_eq!(ranges(&restored), ranges(&pt));
+
+        pt.set_pattern_priority("ab", 0).unwrap();
+        assert_eq!(ranges(&pt), [(0, 4, 0), (4, 6, 1)]);
+    }
+}
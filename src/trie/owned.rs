@@ -0,0 +1,224 @@
+use super::{Match, PatternId, Payload, PayloadSlot, Score};
+use std::any::Any;
+use std::ops::Range;
+
+/// A match that owns its value, keyword, category and metadata (and shares its payload), so that it
+/// can outlive the prefix tree that found it, be sent to other threads or be stored in a cache. It
+/// is created from a `Match` with `Match::into_owned` and has the same accessors.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, OwnedMatch};
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("spam")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// let owned: Vec<OwnedMatch> = prefix_tree
+///     .find_text_matches("spam and spam")
+///     .unwrap()
+///     .into_iter()
+///     .map(|m| m.into_owned())
+///     .collect();
+/// drop(prefix_tree);
+///
+/// let handle = std::thread::spawn(move || owned.iter().map(|m| m.char_range()).count());
+/// assert_eq!(handle.join().unwrap(), 2);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, PartialOrd, Ord, Hash)]
+pub struct OwnedMatch {
+    /// Index of first character in the match
+    start: usize,
+
+    /// Value of the matched dictionary entry
+    value: Box<str>,
+
+    /// The corresponding keyword / standard form of the match
+    kw: Box<str>,
+
+    /// 1 + index of last character in the match
+    end: usize,
+
+    /// Confidence score of the match, if computed
+    score: Option<Score>,
+
+    /// Category (entity label) of the matched pattern, if any
+    category: Option<Box<str>>,
+
+    /// Metadata of the matched pattern, if any
+    metadata: Option<Box<str>>,
+
+    /// Payload of the matched pattern, if any
+    payload: PayloadSlot<Payload>,
+
+    /// Byte offsets of the start and end of the match in the searched text
+    start_byte: usize,
+    end_byte: usize,
+
+    /// ID of the matched pattern
+    pattern_id: PatternId,
+
+    /// Priority of the matched pattern
+    priority: i32,
+
+    /// Line and column of the start of the match, if tracked
+    line_col: Option<(usize, usize)>,
+}
+
+impl OwnedMatch {
+    /// Return the value of the matched dictionary entry. This may differ from the text of the
+    /// match (e.g. in case-insensitive searches), which is given by `matched_text`.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Return the value of the associated keyword of the match
+    pub fn keyword(&self) -> &str {
+        &self.kw
+    }
+
+    /// Return the range of characters the match spans.
+    pub fn char_range(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+
+    /// Return the range of bytes the match spans in the searched text.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start_byte..self.end_byte
+    }
+
+    /// Return the text of the match, as written in the searched text. Panics if the match is
+    /// out of the bounds of the given text.
+    pub fn matched_text<'t>(&self, text: &'t str) -> &'t str {
+        &text[self.byte_range()]
+    }
+
+    /// Return the ID of the matched pattern.
+    pub fn pattern_id(&self) -> PatternId {
+        self.pattern_id
+    }
+
+    /// Return the priority of the matched pattern.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Return the line and column of the start of the match, if tracked.
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        self.line_col
+    }
+
+    /// Return the category (entity label) of the matched pattern, if any.
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    /// Return the metadata of the matched pattern, if any.
+    pub fn metadata(&self) -> Option<&str> {
+        self.metadata.as_deref()
+    }
+
+    /// Return the payload of the matched pattern, if it has one of type `T`.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.0.as_ref()?.downcast_ref()
+    }
+
+    /// Return the confidence score of the match, if computed.
+    pub fn score(&self) -> Option<f32> {
+        self.score.map(|s| s.0)
+    }
+
+    /// Borrow the match as a `Match`, e.g. to pass it to the functions that render or
+    /// filter matches.
+    pub fn as_match(&self) -> Match<'_> {
+        let mut m = Match::new(&self.value, &self.kw, self.end, self.end - self.start)
+            .with_byte_range(self.start_byte, self.end_byte)
+            .with_category(self.category.as_deref())
+            .with_metadata(self.metadata.as_deref())
+            .with_payload(self.payload.0.as_ref())
+            .with_pattern_id(self.pattern_id)
+            .with_priority(self.priority);
+        m.score = self.score;
+        m.line_col = self.line_col;
+        m
+    }
+}
+
+impl Match<'_> {
+    /// Copy the strings borrowed from the tree into an `OwnedMatch`, which doesn't borrow
+    /// the tree.
+    pub fn into_owned(self) -> OwnedMatch {
+        OwnedMatch::from(&self)
+    }
+}
+
+impl From<&Match<'_>> for OwnedMatch {
+    fn from(m: &Match<'_>) -> Self {
+        Self {
+            start: m.start,
+            value: m.value().into(),
+            kw: m.keyword().into(),
+            end: m.end,
+            score: m.score,
+            category: m.category().map(Box::from),
+            metadata: m.metadata().map(Box::from),
+            payload: PayloadSlot(m.payload.0.cloned()),
+            start_byte: m.start_byte,
+            end_byte: m.end_byte,
+            pattern_id: m.pattern_id,
+            priority: m.priority,
+            line_col: m.line_col,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, create_gazetteer};
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_into_owned() {
+        let pt = create_gazetteer(
+            vec![
+                (String::from("paris"), None, Some(String::from("CITY"))),
+                (
+                    String::from("france"),
+                    Some(String::from("FR")),
+                    Some(String::from("COUNTRY")),
+                ),
+            ],
+            Some(SearchOptions {
+                case_sensitive: false,
+                compute_scores: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "Paris, France";
+        let found = pt.find_text_matches(text).unwrap();
+        let owned: Vec<OwnedMatch> = found.iter().map(OwnedMatch::from).collect();
+        for (m, o) in found.iter().zip(&owned) {
+            assert_eq!(o.as_match(), *m);
+            assert_eq!(o.value(), m.value());
+            assert_eq!(o.keyword(), m.keyword());
+            assert_eq!(o.char_range(), m.char_range());
+            assert_eq!(o.byte_range(), m.byte_range());
+            assert_eq!(o.category(), m.category());
+            assert_eq!(o.metadata(), m.metadata());
+            assert_eq!(o.score(), m.score());
+            assert_eq!(o.pattern_id(), m.pattern_id());
+            assert_eq!(o.priority(), m.priority());
+        }
+        drop(found);
+        drop(pt);
+
+        // Owned matches outlive the tree and can be sent to other threads and hashed
+        let owned = std::thread::spawn(move || owned).join().unwrap();
+        assert_eq!(owned[1].keyword(), "FR");
+        assert_eq!(owned[1].category(), Some("COUNTRY"));
+        assert_eq!(owned[1].matched_text(text), "France");
+        let cache: HashSet<OwnedMatch> = owned.iter().cloned().chain(owned.clone()).collect();
+        assert_eq!(cache.len(), 2);
+    }
+}
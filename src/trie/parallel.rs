@@ -0,0 +1,302 @@
+use super::cancel::Interrupt;
+use super::search::{DEADLINE_CHECK_INTERVAL, ScanState};
+use super::{Match, ReadChar, SearchResult, TrieRoot};
+use crate::multi_proc;
+
+/// Minimum number of bytes of text searched by each thread, so that small texts are not split
+/// into chunks too small to be worth a thread
+const MIN_CHUNK_BYTES: usize = 64 * 1024;
+
+/// A piece of a prepared text searched by one thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Chunk {
+    /// Byte offset and character index from which the automaton starts reading. This is
+    /// before the start of the chunk, so that matches that begin in the previous chunk and
+    /// end in this one are found.
+    scan_byte: usize,
+    scan_pos: usize,
+
+    /// Character index of the start of the chunk. Only the matches ending after it are kept.
+    start_pos: usize,
+
+    /// Byte offset of the end of the chunk
+    end_byte: usize,
+}
+
+impl TrieRoot {
+    /// Find all matches for the search dictionary in a single large text, splitting it into
+    /// chunks searched in parallel. The matches are the same as those of `find_text_matches`,
+    /// in the same order. Uses the number of threads given, or the default of `multi_proc`.
+    ///
+    /// Each chunk is scanned starting a little before it, so that the matches that span two
+    /// chunks are found once. Texts shorter than 64 KiB per thread are split into fewer
+    /// chunks, since they are faster to search on a single thread.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("abc"), String::from("cd")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let text = "abcd ".repeat(100_000);
+    /// let matches = prefix_tree.find_text_matches_parallel(&text, Some(4)).unwrap();
+    /// assert_eq!(matches.len(), 200_000);
+    /// assert_eq!(matches, prefix_tree.find_text_matches(&text).unwrap());
+    /// ```
+    pub fn find_text_matches_parallel<'a>(
+        &'a self,
+        text: &str,
+        num_threads: Option<usize>,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let threads = match num_threads {
+            Some(n) if n > 0 => n,
+            _ => multi_proc::get_total_threads().min(multi_proc::max_threads()),
+        };
+        let total_chunks = (text.len() / MIN_CHUNK_BYTES).clamp(1, threads);
+        self.find_matches_chunked(text, total_chunks, Some(threads))
+    }
+
+    /// Find the matches in each of several texts, searching them in parallel in a rayon thread
+    /// pool with the default number of threads of `multi_proc`. The results are returned in the
+    /// same order as the texts. Requires the `rayon` feature.
+    ///
+    /// As soon as the search of a text fails, the remaining texts are skipped and the error
+    /// is returned.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("cd")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let texts = vec![String::from("abcd"), String::from("xyz"), String::from("ab")];
+    /// let results = prefix_tree.par_search_many(&texts).unwrap();
+    /// let counts: Vec<usize> = results.iter().map(|r| r.len()).collect();
+    /// assert_eq!(counts, [2, 0, 1]);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_search_many<'a>(&'a self, texts: &[String]) -> SearchResult<Vec<Vec<Match<'a>>>> {
+        multi_proc::try_parallel_apply(
+            texts.iter().collect(),
+            |text| self.find_text_matches(text),
+            None,
+        )
+    }
+
+    /// Find all matches in a text, splitting it into the given number of chunks searched in
+    /// parallel.
+    fn find_matches_chunked<'a>(
+        &'a self,
+        text: &str,
+        total_chunks: usize,
+        num_threads: Option<usize>,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let limits = &self.options.limits;
+        limits.check_text(text)?;
+        let interrupt = Interrupt::default().with_time_limit(limits.time_limit);
+        let prepared = self.prepare_text(text);
+        let searched: &str = &prepared.text;
+        let chunks = self.split_chunks(searched, total_chunks);
+        let found = multi_proc::try_parallel_apply(
+            chunks,
+            |chunk| self.scan_chunk(searched, chunk, interrupt),
+            num_threads,
+        )?;
+
+        let mut truncated = false;
+        let mut matches = Vec::new();
+        for (chunk_matches, chunk_truncated) in found {
+            matches.extend(chunk_matches);
+            truncated |= chunk_truncated;
+        }
+        if truncated {
+            log::warn!("Some matches were skipped due to the maximum chain depth");
+        }
+        limits.check_matches(matches.len())?;
+        self.retain_exact_case(&mut matches, &prepared);
+        let mut matches = self.select_matches(matches);
+        self.finish_matches(&mut matches, &prepared);
+        Ok(matches)
+    }
+
+    /// Split a prepared text into chunks of about the same number of bytes.
+    fn split_chunks(&self, text: &str, total_chunks: usize) -> Vec<Chunk> {
+        let mut chunks = Vec::with_capacity(total_chunks);
+        let mut start_byte = 0;
+        let mut start_pos = 0;
+        for i in 1..=total_chunks {
+            let mut end_byte = text.len() * i / total_chunks;
+            while !text.is_char_boundary(end_byte) {
+                end_byte += 1;
+            }
+            if end_byte == start_byte {
+                continue;
+            }
+            let (scan_byte, warm_chars) = self.warm_up_start(text, start_byte);
+            chunks.push(Chunk {
+                scan_byte,
+                scan_pos: start_pos - warm_chars,
+                start_pos,
+                end_byte,
+            });
+            start_pos += text[start_byte..end_byte].chars().count();
+            start_byte = end_byte;
+        }
+        chunks
+    }
+
+    /// Find where to start reading a text so that the automaton has read the characters of
+    /// any match that ends after the given byte offset. Returns the byte offset and the
+    /// number of characters before the given one.
+    ///
+    /// The characters skipped by the automaton (e.g. ignored separators) don't count towards
+    /// the length of a pattern, so they are not counted either.
+    fn warm_up_start(&self, text: &str, byte: usize) -> (usize, usize) {
+        let mut needed = self.max_pattern_len.saturating_sub(1);
+        let mut next = text[byte..].chars().next();
+        let mut before = text[..byte].char_indices().rev().peekable();
+        let mut start = (byte, 0);
+        while needed > 0
+            && let Some((b, ch)) = before.next()
+        {
+            let prev = before.peek().map(|&(_, c)| c);
+            if !self.skip_char(prev, ch, next) {
+                needed -= 1;
+            }
+            start = (b, start.1 + 1);
+            next = Some(ch);
+        }
+        start
+    }
+
+    /// Find the (possibly overlapping) matches that end in a chunk of a prepared text. Also
+    /// returns whether some patterns were left unchecked due to `max_chain_depth`. Fails if
+    /// the scan is interrupted.
+    fn scan_chunk<'a>(
+        &'a self,
+        text: &str,
+        chunk: Chunk,
+        interrupt: Interrupt,
+    ) -> SearchResult<(Vec<Match<'a>>, bool)> {
+        let mut state = ScanState::new(self);
+        let mut matches = Vec::new();
+
+        let mut prev = text[..chunk.scan_byte].chars().next_back();
+        let after = text[chunk.end_byte..].chars().next();
+        let mut chars_iter = text[chunk.scan_byte..chunk.end_byte]
+            .char_indices()
+            .peekable();
+        let mut pos = chunk.scan_pos;
+        while let Some((offset, ch)) = chars_iter.next() {
+            if (pos - chunk.scan_pos).is_multiple_of(DEADLINE_CHECK_INTERVAL)
+                && let Some(e) = interrupt.check()
+            {
+                return Err(e);
+            }
+            let nxt_ch = chars_iter.peek().map(|&(_, c)| c).or(after);
+            let read = ReadChar {
+                ch,
+                pos,
+                byte: chunk.scan_byte + offset,
+                prev,
+            };
+            self.scan_char(&mut state, read, nxt_ch, text, 0, &mut matches)?;
+            prev = Some(ch);
+            pos += 1;
+        }
+
+        // Matches ending before the chunk belong to the previous one
+        matches.retain(|m| m.end > chunk.start_pos);
+        Ok((matches, state.truncated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MatchKind, SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_chunked_same_as_search() {
+        let words = || {
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abcab"),
+                String::from("bcé"),
+                String::from("e-mail"),
+                String::from("1000"),
+            ])
+        };
+        let text = "xabcabcé EMAIL e mail 1,000 abcabcab ÉBCÉ abcé".repeat(3);
+        for (check_bounds, ignore, match_kind) in [
+            (false, false, MatchKind::Overlapping),
+            (true, true, MatchKind::Overlapping),
+            (false, true, MatchKind::LeftmostLongest),
+        ] {
+            let pt = create_prefix_tree(
+                words(),
+                Some(SearchOptions {
+                    case_sensitive: false,
+                    check_bounds,
+                    ignore_separators: ignore,
+                    ignore_digit_separators: ignore,
+                    match_kind,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+            let expected = pt.find_text_matches(&text).unwrap();
+            for total_chunks in [1, 2, 7, 40, text.len()] {
+                let found = pt
+                    .find_matches_chunked(&text, total_chunks, Some(3))
+                    .unwrap();
+                assert_eq!(found, expected, "{total_chunks} chunks");
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_search_many() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("bc")]),
+            None,
+        )
+        .unwrap();
+        let texts: Vec<String> = (0..200).map(|i| "abc ".repeat(i % 7)).collect();
+        let results = pt.par_search_many(&texts).unwrap();
+        assert_eq!(results.len(), texts.len());
+        for (text, found) in texts.iter().zip(&results) {
+            assert_eq!(found, &pt.find_text_matches(text).unwrap());
+        }
+        assert!(pt.par_search_many(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("abcd")]), None).unwrap();
+        let chunks = pt.split_chunks("aé-bcdéf", 3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks[1],
+            Chunk {
+                scan_byte: 0,
+                scan_pos: 0,
+                start_pos: 2,
+                end_byte: 6,
+            }
+        );
+        assert_eq!(
+            chunks[2],
+            Chunk {
+                scan_byte: 3,
+                scan_pos: 2,
+                start_pos: 5,
+                end_byte: 10,
+            }
+        );
+        assert!(pt.split_chunks("", 4).is_empty());
+    }
+}
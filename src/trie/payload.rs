@@ -0,0 +1,181 @@
+use super::create_prefix_tree;
+use super::{NodeId, PatternId, SearchError, SearchOptions, SearchResult, TrieRoot};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// A value of any type attached to a pattern of the dictionary and reported with its matches,
+/// e.g. an entity ID or a struct of tags. See `TrieRoot::set_pattern_payload`.
+pub type Payload = Arc<dyn Any + Send + Sync>;
+
+/// The payload of a match, if its pattern has one. Payloads are ignored when comparing,
+/// hashing and printing matches: they are determined by the pattern ID, which is compared.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct PayloadSlot<P>(pub(crate) Option<P>);
+
+impl<P> PartialEq for PayloadSlot<P> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<P> Eq for PayloadSlot<P> {}
+
+impl<P> PartialOrd for PayloadSlot<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P> Ord for PayloadSlot<P> {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl<P> Hash for PayloadSlot<P> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl<P> fmt::Debug for PayloadSlot<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(..)"),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+/// Instantiate a prefix tree from a dictionary of (pattern, keyword, payload) entries. The
+/// payload of each pattern is reported with its matches by `Match::payload`, so that data
+/// such as entity IDs can be carried without a separate map keyed by keyword.
+///
+/// Payloads are not saved with the tree by `TrieRoot::to_bytes`.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Entity {
+///     id: u32,
+///     tags: Vec<&'static str>,
+/// }
+///
+/// let dictionary = vec![
+///     (String::from("Paris"), None, Entity { id: 90, tags: vec!["city"] }),
+///     (String::from("Seine"), None, Entity { id: 1471, tags: vec!["river"] }),
+/// ];
+/// let prefix_tree = trie::create_with_payloads(dictionary, None).unwrap();
+///
+/// let matches = prefix_tree.find_text_matches("The Seine flows through Paris").unwrap();
+/// let ids: Vec<u32> = matches.iter().map(|m| m.payload::<Entity>().unwrap().id).collect();
+/// assert_eq!(ids, [1471, 90]);
+/// assert_eq!(matches[0].payload::<String>(), None);
+/// ```
+pub fn create_with_payloads<T: Any + Send + Sync>(
+    dictionary: Vec<(String, Option<String>, T)>,
+    opts: Option<SearchOptions>,
+) -> SearchResult<TrieRoot> {
+    let (entries, payloads): (Vec<_>, Vec<_>) = dictionary
+        .into_iter()
+        .map(|(pattern, keyword, payload)| ((pattern, keyword), payload))
+        .unzip();
+    let mut pt = create_prefix_tree(entries, opts)?;
+    for (pattern_id, payload) in payloads.into_iter().enumerate() {
+        let node_id = pt.patterns[pattern_id].node_id as NodeId;
+        pt.payloads.insert(node_id, Arc::new(payload));
+    }
+    Ok(pt)
+}
+
+impl TrieRoot {
+    /// Attach a payload to a pattern of the dictionary, replacing its previous one. The
+    /// payload is reported with the matches of the pattern by `Match::payload`. Payloads are
+    /// not saved with the tree by `to_bytes`.
+    ///
+    /// Returns an error if the pattern is not in the dictionary.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ACME")]);
+    /// let mut prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    /// prefix_tree.set_pattern_payload("ACME", 42_u64).unwrap();
+    /// assert!(prefix_tree.set_pattern_payload("Initech", 7_u64).is_err());
+    ///
+    /// let matches = prefix_tree.find_text_matches("ACME Corp.").unwrap();
+    /// assert_eq!(matches[0].payload::<u64>(), Some(&42));
+    /// ```
+    pub fn set_pattern_payload<T: Any + Send + Sync>(
+        &mut self,
+        pattern: &str,
+        payload: T,
+    ) -> SearchResult<()> {
+        let node_id = self
+            .pattern_node_id(pattern)
+            .ok_or_else(|| SearchError::PatternNotFound(pattern.to_string()))?;
+        self.payloads.insert(node_id, Arc::new(payload));
+        Ok(())
+    }
+
+    /// Get the payload of the pattern with the given ID, if it has one of type `T`.
+    pub fn pattern_payload<T: Any>(&self, pattern_id: PatternId) -> Option<&T> {
+        let node_id = self.patterns.get(pattern_id)?.node_id as NodeId;
+        self.payloads.get(&node_id)?.downcast_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payloads() {
+        let mut pt = create_with_payloads(
+            vec![
+                (String::from("Ab"), None, 2_u32),
+                (String::from("bc"), Some(String::from("BC")), 3),
+            ],
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let payloads = |pt: &TrieRoot| -> Vec<_> {
+            pt.find_text_matches("abc")
+                .unwrap()
+                .iter()
+                .map(|m| m.payload::<u32>().copied())
+                .collect()
+        };
+        assert_eq!(payloads(&pt), [Some(2), Some(3)]);
+        assert_eq!(pt.pattern_payload::<u32>(0), Some(&2));
+        assert_eq!(pt.pattern_payload::<u64>(0), None);
+        assert_eq!(pt.pattern_payload::<u32>(2), None);
+
+        // Payloads of another type
+        pt.set_pattern_payload("BC", "x").unwrap();
+        assert_eq!(payloads(&pt), [Some(2), None]);
+        let found = pt.find_text_matches("bc").unwrap();
+        assert_eq!(found[0].payload::<&str>(), Some(&"x"));
+
+        // Payloads are kept by owned matches and by the other search methods
+        let owned = found.into_iter().next().unwrap().into_owned();
+        drop(pt);
+        assert_eq!(owned.payload::<&str>(), Some(&"x"));
+        assert_eq!(owned.as_match().payload::<&str>(), Some(&"x"));
+
+        // Single-pattern dictionaries
+        let pt = create_with_payloads(vec![(String::from("abc"), None, 'x')], None).unwrap();
+        assert_eq!(
+            pt.find_text_matches("abc").unwrap()[0].payload(),
+            Some(&'x')
+        );
+        assert_eq!(pt.longest_match_at("abc", 0).unwrap().payload(), Some(&'x'));
+    }
+}
@@ -0,0 +1,591 @@
+use super::{
+    ContextRule, Link, MatchKind, NONE_ID, NodeData, NodeId, PatternEntry, SearchError,
+    SearchLimits, SearchOptions, SearchResult, TrieRoot, WhitespaceMatch, normalizer_from_name,
+    opt_id,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Identifier written at the start of serialized prefix trees
+const TRIE_MAGIC: &[u8; 4] = b"ACTR";
+
+/// Version of the prefix tree binary format
+const TRIE_VERSION: u8 = 12;
+
+/// Value used to encode a missing number or string in a serialized tree
+const NONE_U64: u64 = u64::MAX;
+
+/// Helper to write the binary representation of a prefix tree
+struct ByteWriter {
+    out: Vec<u8>,
+}
+
+impl ByteWriter {
+    fn write_u8(&mut self, num: u8) {
+        self.out.push(num);
+    }
+
+    fn write_u64(&mut self, num: u64) {
+        self.out.extend_from_slice(&num.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, num: usize) {
+        self.write_u64(num as u64);
+    }
+
+    fn write_opt_usize(&mut self, num: Option<usize>) {
+        self.write_u64(num.map_or(NONE_U64, |n| n as u64));
+    }
+
+    fn write_str(&mut self, s: &str) {
+        self.write_usize(s.len());
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    fn write_opt_str(&mut self, s: Option<&str>) {
+        match s {
+            None => self.write_u64(NONE_U64),
+            Some(s) => self.write_str(s),
+        }
+    }
+}
+
+/// Helper to read the binary representation of a prefix tree
+struct ByteReader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> ByteReader<'d> {
+    fn take(&mut self, n: usize) -> SearchResult<&'d [u8]> {
+        if self.data.len() < n {
+            return Err(invalid("unexpected end of data"));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn read_u8(&mut self) -> SearchResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_bool(&mut self) -> SearchResult<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(invalid(&format!("invalid boolean {}", b))),
+        }
+    }
+
+    fn read_u64(&mut self) -> SearchResult<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_opt_usize(&mut self) -> SearchResult<Option<usize>> {
+        match self.read_u64()? {
+            NONE_U64 => Ok(None),
+            num => usize::try_from(num)
+                .map(Some)
+                .map_err(|_| invalid("number out of range")),
+        }
+    }
+
+    fn read_usize(&mut self) -> SearchResult<usize> {
+        self.read_opt_usize()?
+            .ok_or_else(|| invalid("missing number"))
+    }
+
+    /// Read a node ID, checking that it is lower than the number of nodes
+    fn read_node_id(&mut self, total_nodes: usize) -> SearchResult<NodeId> {
+        let node_id = self.read_usize()?;
+        if node_id >= total_nodes {
+            return Err(invalid(&format!("invalid node ID {}", node_id)));
+        }
+        Ok(node_id)
+    }
+
+    fn read_opt_str(&mut self) -> SearchResult<Option<String>> {
+        let Some(len) = self.read_opt_usize()? else {
+            return Ok(None);
+        };
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|_| invalid("invalid UTF-8 string"))
+    }
+
+    fn read_str(&mut self) -> SearchResult<String> {
+        self.read_opt_str()?
+            .ok_or_else(|| invalid("missing string"))
+    }
+}
+
+/// Error for invalid serialized trees
+fn invalid(reason: &str) -> SearchError {
+    SearchError::Serialization(format!("invalid prefix tree data: {}", reason))
+}
+
+impl TrieRoot {
+    /// Serialize the tree into a compact binary representation, including its search options,
+    /// context rules, pattern weights and priorities, metadata and per-pattern settings. Use
+    /// `from_bytes` to rebuild it without recomputing the automaton. Normalizers are saved by name,
+    /// so only trees with the built-in ones can be rebuilt. Coverage records are not kept, only
+    /// whether coverage is tracked, and the dense transition table is recomputed on load if the
+    /// `dense_dfa` option is set.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ByteWriter { out: Vec::new() };
+        writer.out.extend_from_slice(TRIE_MAGIC);
+        writer.write_u8(TRIE_VERSION);
+
+        let opts = &self.options;
+        for flag in [
+            opts.case_sensitive,
+            opts.check_bounds,
+            opts.word_segmentation,
+            opts.ignore_separators,
+            opts.ignore_digit_separators,
+            opts.compute_scores,
+            opts.dense_dfa,
+            self.coverage.is_some(),
+            opts.track_lines,
+        ] {
+            writer.write_u8(u8::from(flag));
+        }
+        writer.write_opt_usize(opts.max_chain_depth);
+        writer.write_u8(match opts.match_kind {
+            MatchKind::Overlapping => 0,
+            MatchKind::LeftmostLongest => 1,
+            MatchKind::LeftmostFirst => 2,
+        });
+        writer.write_u8(match opts.whitespace {
+            WhitespaceMatch::Exact => 0,
+            WhitespaceMatch::Flexible => 1,
+            WhitespaceMatch::FlexibleMultiline => 2,
+        });
+        writer.write_opt_usize(opts.wildcard.map(|c| c as usize));
+        writer.write_opt_usize(opts.limits.max_matches);
+        writer.write_opt_usize(opts.limits.max_text_chars);
+        writer.write_opt_usize(
+            opts.limits
+                .time_limit
+                .map(|d| usize::try_from(d.as_nanos()).unwrap_or(usize::MAX)),
+        );
+        writer.write_usize(opts.normalizers.len());
+        for normalizer in &opts.normalizers {
+            writer.write_str(normalizer.name());
+        }
+        writer.write_usize(self.max_pattern_len);
+
+        writer.write_usize(self.nodes.len());
+        for node in self.nodes() {
+            writer.write_opt_usize(node.fail_node());
+            writer.write_opt_usize(node.fail_dct());
+            writer.write_opt_usize(node.pattern_id());
+            writer.write_usize(node.next_nodes().len());
+            for link in node.next_nodes() {
+                writer
+                    .out
+                    .extend_from_slice(&u32::from(link.get_char()).to_le_bytes());
+                writer.write_usize(link.get_node_id());
+            }
+        }
+
+        writer.write_usize(self.categories.len());
+        for category in &self.categories {
+            writer.write_str(category);
+        }
+        writer.write_usize(self.patterns.len());
+        for pattern in &self.patterns {
+            writer.write_str(&pattern.value);
+            writer.write_opt_str(pattern.keyword.as_deref());
+            writer.write_opt_usize(opt_id(pattern.category));
+        }
+
+        // Maps are written sorted by node, so that the output is deterministic
+        let mut rules: Vec<_> = self.context_rules.iter().collect();
+        rules.sort_unstable_by_key(|(node_id, _)| **node_id);
+        writer.write_usize(rules.len());
+        for (node_id, node_rules) in rules {
+            writer.write_usize(*node_id);
+            writer.write_usize(node_rules.len());
+            for rule in node_rules {
+                let (kind, s) = match rule {
+                    ContextRule::NotPrecededBy(s) => (0, s),
+                    ContextRule::NotFollowedBy(s) => (1, s),
+                };
+                writer.write_u8(kind);
+                writer.write_str(s);
+            }
+        }
+
+        let mut weights: Vec<_> = self.weights.iter().collect();
+        weights.sort_unstable_by_key(|(node_id, _)| **node_id);
+        writer.write_usize(weights.len());
+        for (node_id, weight) in weights {
+            writer.write_usize(*node_id);
+            writer.out.extend_from_slice(&weight.to_le_bytes());
+        }
+
+        for map in [&self.cased_patterns, &self.exact_case, &self.metadata] {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(node_id, _)| **node_id);
+            writer.write_usize(entries.len());
+            for (node_id, s) in entries {
+                writer.write_usize(*node_id);
+                writer.write_str(s);
+            }
+        }
+
+        let mut bounds: Vec<_> = self.bounds_override.iter().collect();
+        bounds.sort_unstable_by_key(|(node_id, _)| **node_id);
+        writer.write_usize(bounds.len());
+        for (node_id, check) in bounds {
+            writer.write_usize(*node_id);
+            writer.write_u8(*check as u8);
+        }
+
+        let mut priorities: Vec<_> = self.priorities.iter().collect();
+        priorities.sort_unstable_by_key(|(node_id, _)| **node_id);
+        writer.write_usize(priorities.len());
+        for (node_id, priority) in priorities {
+            writer.write_usize(*node_id);
+            writer.out.extend_from_slice(&priority.to_le_bytes());
+        }
+        writer.out
+    }
+
+    /// Rebuild a tree serialized with `to_bytes`. Returns an error if the data is invalid.
+    pub fn from_bytes(data: &[u8]) -> SearchResult<Self> {
+        let mut reader = ByteReader { data };
+        if reader.take(4)? != TRIE_MAGIC {
+            return Err(invalid("not a serialized prefix tree"));
+        }
+        let version = reader.read_u8()?;
+        if version != TRIE_VERSION {
+            return Err(invalid(&format!("unsupported version {}", version)));
+        }
+
+        let mut flags = [false; 9];
+        for flag in &mut flags {
+            *flag = reader.read_bool()?;
+        }
+        let [
+            case_sensitive,
+            check_bounds,
+            word_segmentation,
+            ignore_separators,
+            ignore_digit_separators,
+            compute_scores,
+            dense_dfa,
+            track_coverage,
+            track_lines,
+        ] = flags;
+        let max_chain_depth = reader.read_opt_usize()?;
+        let match_kind = match reader.read_u8()? {
+            0 => MatchKind::Overlapping,
+            1 => MatchKind::LeftmostLongest,
+            2 => MatchKind::LeftmostFirst,
+            k => return Err(invalid(&format!("unknown match kind {}", k))),
+        };
+        let whitespace = match reader.read_u8()? {
+            0 => WhitespaceMatch::Exact,
+            1 => WhitespaceMatch::Flexible,
+            2 => WhitespaceMatch::FlexibleMultiline,
+            k => return Err(invalid(&format!("unknown whitespace mode {}", k))),
+        };
+        let wildcard = match reader.read_opt_usize()? {
+            None => None,
+            Some(c) => Some(
+                u32::try_from(c)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid("invalid wildcard"))?,
+            ),
+        };
+        let limits = SearchLimits {
+            max_matches: reader.read_opt_usize()?,
+            max_text_chars: reader.read_opt_usize()?,
+            time_limit: reader
+                .read_opt_usize()?
+                .map(|nanos| Duration::from_nanos(nanos as u64)),
+        };
+        let mut normalizers = Vec::new();
+        for _ in 0..reader.read_usize()? {
+            let name = reader.read_str()?;
+            normalizers.push(
+                normalizer_from_name(&name)
+                    .ok_or_else(|| invalid(&format!("unknown normalizer {:?}", name)))?,
+            );
+        }
+        let mut pt = TrieRoot::new(SearchOptions {
+            case_sensitive,
+            check_bounds,
+            word_segmentation,
+            ignore_separators,
+            ignore_digit_separators,
+            whitespace,
+            wildcard,
+            compute_scores,
+            max_chain_depth,
+            match_kind,
+            dense_dfa,
+            limits,
+            track_lines,
+            normalizers,
+        });
+        pt.max_pattern_len = reader.read_usize()?;
+
+        let total_nodes = reader.read_usize()?;
+        if total_nodes == 0 {
+            return Err(invalid("the tree has no nodes"));
+        }
+        if total_nodes >= NONE_ID as usize {
+            return Err(invalid("too many nodes"));
+        }
+        pt.nodes = Vec::with_capacity(total_nodes.min(reader.data.len()));
+        for node_id in 0..total_nodes {
+            let fail_to = reader.read_opt_usize()?;
+            let dct_to = reader.read_opt_usize()?;
+            let pattern = reader.read_opt_usize()?;
+            if [fail_to, dct_to]
+                .iter()
+                .flatten()
+                .any(|&nid| nid >= total_nodes)
+                || (node_id != 0 && fail_to.is_none())
+                || pattern.is_some_and(|id| id >= NONE_ID as usize)
+            {
+                return Err(invalid(&format!("invalid links of node {}", node_id)));
+            }
+
+            let total_links = reader.read_usize()?;
+            let links_start = pt.links.len();
+            for _ in 0..total_links {
+                let c = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+                let c = char::from_u32(c).ok_or_else(|| invalid("invalid character"))?;
+                pt.links
+                    .push(Link(c, reader.read_node_id(total_nodes)? as u32));
+            }
+            if !pt.links[links_start..].is_sorted() {
+                return Err(invalid(&format!("unsorted links of node {}", node_id)));
+            }
+            let to_id = |id: Option<usize>| id.map_or(NONE_ID, |id| id as u32);
+            pt.nodes.push(NodeData {
+                links_start: links_start as u32,
+                links_len: total_links as u32,
+                fail_to: to_id(fail_to),
+                dct_to: to_id(dct_to),
+                pattern: to_id(pattern),
+                pattern_len: 0,
+            });
+        }
+
+        let total_categories = reader.read_usize()?;
+        for _ in 0..total_categories.min(reader.data.len()) {
+            pt.categories.push(reader.read_str()?.into_boxed_str());
+        }
+        let total_patterns = reader.read_usize()?;
+        for _ in 0..total_patterns.min(reader.data.len()) {
+            let value = reader.read_str()?;
+            let keyword = reader.read_opt_str()?;
+            let category = match reader.read_opt_usize()? {
+                Some(c) if c >= pt.categories.len() => {
+                    return Err(invalid(&format!("invalid category {}", c)));
+                }
+                c => c.map_or(NONE_ID, |c| c as u32),
+            };
+            pt.patterns.push(PatternEntry {
+                value: value.into_boxed_str(),
+                keyword: keyword.map(String::into_boxed_str),
+                category,
+                node_id: NONE_ID,
+            });
+        }
+        if pt.categories.len() != total_categories || pt.patterns.len() != total_patterns {
+            return Err(invalid("unexpected end of data"));
+        }
+
+        // Each pattern ends at a single node, unless it is copied after a wildcard
+        for (node_id, node) in pt.nodes.iter_mut().enumerate() {
+            let Some(id) = opt_id(node.pattern) else {
+                continue;
+            };
+            match pt.patterns.get_mut(id) {
+                Some(pattern) if pattern.node_id == NONE_ID || wildcard.is_some() => {
+                    pattern.node_id = node_id as u32;
+                    node.pattern_len = pattern.value.chars().count() as u32;
+                }
+                _ => return Err(invalid(&format!("invalid pattern of node {}", node_id))),
+            }
+        }
+        if pt.patterns.iter().any(|p| p.node_id == NONE_ID) {
+            return Err(invalid("pattern without a node"));
+        }
+        if wildcard.is_some() && pt.find_wildcard_pattern_nodes().is_none() {
+            return Err(invalid("pattern not at the end of its path"));
+        }
+
+        let total_rules = reader.read_usize()?;
+        for _ in 0..total_rules {
+            let node_id = reader.read_node_id(total_nodes)?;
+            let node_rules = pt.context_rules.entry(node_id).or_default();
+            for _ in 0..reader.read_usize()? {
+                node_rules.push(match reader.read_u8()? {
+                    0 => ContextRule::NotPrecededBy(reader.read_str()?),
+                    1 => ContextRule::NotFollowedBy(reader.read_str()?),
+                    k => return Err(invalid(&format!("unknown context rule {}", k))),
+                });
+            }
+        }
+
+        let total_weights = reader.read_usize()?;
+        pt.weights = HashMap::with_capacity(total_weights.min(reader.data.len() / 12));
+        for _ in 0..total_weights {
+            let node_id = reader.read_node_id(total_nodes)?;
+            let weight = f32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+            pt.weights.insert(node_id, weight);
+        }
+
+        for map in [&mut pt.cased_patterns, &mut pt.exact_case, &mut pt.metadata] {
+            for _ in 0..reader.read_usize()? {
+                let node_id = reader.read_node_id(total_nodes)?;
+                map.insert(node_id, reader.read_str()?);
+            }
+        }
+        for _ in 0..reader.read_usize()? {
+            let node_id = reader.read_node_id(total_nodes)?;
+            pt.bounds_override.insert(node_id, reader.read_bool()?);
+        }
+        for _ in 0..reader.read_usize()? {
+            let node_id = reader.read_node_id(total_nodes)?;
+            let priority = i32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+            pt.priorities.insert(node_id, priority);
+        }
+        if !reader.data.is_empty() {
+            return Err(invalid("unexpected data after the tree"));
+        }
+
+        pt.set_single_pattern();
+        pt.refresh_dfa();
+        if track_coverage {
+            pt.track_coverage();
+        }
+        Ok(pt)
+    }
+
+    /// Save the tree to a file, to load it later with `load` instead of building it again
+    /// from the dictionary. See `to_bytes`.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, TrieRoot};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("cd")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let path = std::env::temp_dir().join("ac_search_doc_tree.bin");
+    /// prefix_tree.save(&path).unwrap();
+    /// let loaded = TrieRoot::load(&path).unwrap();
+    /// assert_eq!(loaded.find_text_matches("xab cd").unwrap().len(), 2);
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> SearchResult<()> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Load a tree from a file written with `save`.
+    pub fn load<P: AsRef<Path>>(path: P) -> SearchResult<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_gazetteer, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_trie_round_trip() {
+        let mut pt = create_gazetteer(
+            vec![
+                (String::from("Ab"), None, Some(String::from("X"))),
+                (String::from("abc"), Some(String::from("ABC")), None),
+                (String::from("b-c"), None, None),
+                (String::from("é"), None, None),
+            ],
+            Some(SearchOptions {
+                case_sensitive: false,
+                ignore_separators: true,
+                compute_scores: true,
+                max_chain_depth: Some(4),
+                match_kind: MatchKind::LeftmostFirst,
+                dense_dfa: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        pt.add_context_rule("abc", ContextRule::NotFollowedBy(String::from("d")))
+            .unwrap();
+        pt.set_pattern_weight("bc", 0.5).unwrap();
+        pt.track_coverage();
+
+        let data = pt.to_bytes();
+        assert_eq!(data, pt.to_bytes());
+        let loaded = TrieRoot::from_bytes(&data).unwrap();
+        assert_eq!(loaded.to_bytes(), data);
+        assert_eq!(loaded.total_nodes(), pt.total_nodes());
+        assert_eq!(loaded.options().match_kind, MatchKind::LeftmostFirst);
+        assert!(loaded.never_matched().is_some());
+        assert!(loaded.is_dfa_compiled());
+
+        for text in ["xAbc abcd b c É", "AB ab", ""] {
+            assert_eq!(
+                loaded.find_text_matches(text).unwrap(),
+                pt.find_text_matches(text).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_trie_file() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("abc")]), None).unwrap();
+        let path = std::env::temp_dir().join(format!("ac_search_test_{}.bin", std::process::id()));
+        pt.save(&path).unwrap();
+        let loaded = TrieRoot::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The single pattern fast path is restored
+        assert!(loaded.single_pattern.is_some());
+        assert_eq!(loaded.find_text_matches("abcabc").unwrap().len(), 2);
+        assert!(matches!(TrieRoot::load(&path), Err(SearchError::Io(..))));
+    }
+
+    #[test]
+    fn test_trie_invalid_data() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        let data = pt.to_bytes();
+        for bad in [&data[..data.len() - 1], &data[1..], b"ACTR\x09".as_slice()] {
+            assert!(matches!(
+                TrieRoot::from_bytes(bad),
+                Err(SearchError::Serialization(_))
+            ));
+        }
+
+        // Link from "a" to a node out of bounds
+        let ab_id = pt.node_by_path("ab").unwrap();
+        let mut link = u32::from('b').to_le_bytes().to_vec();
+        link.extend_from_slice(&(ab_id as u64).to_le_bytes());
+        let at = data.windows(12).position(|w| w == link).unwrap() + 4;
+        let mut bad = data.clone();
+        bad[at..at + 8].copy_from_slice(&(pt.total_nodes() as u64).to_le_bytes());
+        assert_eq!(
+            TrieRoot::from_bytes(&bad).err(),
+            Some(SearchError::Serialization(format!(
+                "invalid prefix tree data: invalid node ID {}",
+                pt.total_nodes()
+            )))
+        );
+    }
+}
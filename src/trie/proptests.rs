@@ -0,0 +1,137 @@
+//! Property-based tests comparing the matches found by the prefix tree with those of a
+//! brute-force search of each pattern in the text.
+use super::{MatchKind, PatternId, SearchOptions, TrieRoot, add_keyword_slot, create_prefix_tree};
+use proptest::collection::btree_set;
+use proptest::prelude::*;
+
+/// Start and end byte offsets and pattern IDs of matches
+type Found = Vec<(usize, usize, PatternId)>;
+
+fn build(patterns: &[String], opts: SearchOptions) -> TrieRoot {
+    create_prefix_tree(add_keyword_slot(patterns.to_vec()), Some(opts)).unwrap()
+}
+
+/// Matches found by the tree, sorted
+fn found(tree: &TrieRoot, text: &str) -> Found {
+    let mut found: Found = tree
+        .find_text_matches(text)
+        .unwrap()
+        .iter()
+        .map(|m| (m.byte_range().start, m.byte_range().end, m.pattern_id()))
+        .collect();
+    found.sort();
+    found
+}
+
+/// All the occurrences of the patterns in the text, sorted. `match_indices` skips the
+/// occurrences overlapping the previous one, so the search is restarted from the character
+/// following the start of each one.
+fn naive_matches(patterns: &[String], text: &str) -> Found {
+    let mut found = Vec::new();
+    for (pattern_id, pattern) in patterns.iter().enumerate() {
+        let mut from = 0;
+        while let Some((i, _)) = text[from..].match_indices(pattern.as_str()).next() {
+            let start = from + i;
+            found.push((start, start + pattern.len(), pattern_id));
+            from = start + text[start..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Non-overlapping matches taken from left to right among all the occurrences: at each
+/// position, the longest one or the one of the first pattern in the dictionary.
+fn naive_leftmost(all: &Found, longest: bool) -> Found {
+    let mut out: Found = Vec::new();
+    let mut pos = 0;
+    for &(start, _, _) in all {
+        if start < pos {
+            continue;
+        }
+        let candidates = all.iter().filter(|(s, _, _)| *s == start);
+        let best = if longest {
+            candidates.max_by_key(|(_, end, _)| *end)
+        } else {
+            candidates.min_by_key(|(_, _, id)| *id)
+        };
+        let best = *best.unwrap();
+        pos = best.1;
+        out.push(best);
+    }
+    out
+}
+
+/// Whether a character next to a match prevents it from being bounded
+fn is_word_char(c: Option<char>) -> bool {
+    c.is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+proptest! {
+    #[test]
+    fn prop_overlapping_matches(
+        patterns in btree_set("[abé]{1,4}", 1..8),
+        text in "[abé ]{0,60}",
+    ) {
+        let patterns: Vec<String> = patterns.into_iter().collect();
+        let tree = build(&patterns, SearchOptions::default());
+        let expected = naive_matches(&patterns, &text);
+        prop_assert_eq!(found(&tree, &text), expected.clone());
+        prop_assert_eq!(tree.count_matches(&text).unwrap(), expected.len());
+    }
+
+    #[test]
+    fn prop_case_insensitive_matches(
+        patterns in btree_set("[abé]{1,4}", 1..8),
+        text in "[aAbBéÉ ]{0,60}",
+    ) {
+        let patterns: Vec<String> = patterns.into_iter().collect();
+        let tree = build(&patterns, SearchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        });
+        // Lowercasing these characters keeps their length in bytes
+        prop_assert_eq!(found(&tree, &text), naive_matches(&patterns, &text.to_lowercase()));
+    }
+
+    #[test]
+    fn prop_bounded_matches(
+        patterns in btree_set("[ab_ .]{1,4}", 1..8),
+        text in "[ab_ .]{0,60}",
+    ) {
+        let patterns: Vec<String> = patterns.into_iter().collect();
+        let tree = build(&patterns, SearchOptions {
+            check_bounds: true,
+            ..Default::default()
+        });
+        let expected: Found = naive_matches(&patterns, &text)
+            .into_iter()
+            .filter(|&(start, end, _)| {
+                let before = text[..start].chars().next_back();
+                let after = text[end..].chars().next();
+                !is_word_char(before) && !is_word_char(after)
+            })
+            .collect();
+        prop_assert_eq!(found(&tree, &text), expected);
+    }
+
+    #[test]
+    fn prop_leftmost_matches(
+        patterns in btree_set("[abé]{1,4}", 1..8)
+            .prop_map(|set| set.into_iter().collect::<Vec<String>>())
+            .prop_shuffle(),
+        text in "[abé ]{0,60}",
+    ) {
+        let all = naive_matches(&patterns, &text);
+        for (match_kind, longest) in [
+            (MatchKind::LeftmostLongest, true),
+            (MatchKind::LeftmostFirst, false),
+        ] {
+            let tree = build(&patterns, SearchOptions {
+                match_kind,
+                ..Default::default()
+            });
+            prop_assert_eq!(found(&tree, &text), naive_leftmost(&all, longest));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use super::Match;
+
+/// A region of a text grouping nearby matches, as produced by `merge_regions`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct MatchRegion<'a> {
+    /// Index of the first character of the region
+    start: usize,
+
+    /// 1 + index of the last character of the region
+    end: usize,
+
+    /// Matches in the region, sorted by position
+    matches: Vec<Match<'a>>,
+}
+
+impl<'a> MatchRegion<'a> {
+    /// Get the range of characters covered by the region, from the start of its first match to
+    /// the end of the last one (exclusive)
+    pub fn char_range(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+
+    /// Get the matches contained in the region, sorted by position
+    pub fn matches(&self) -> &[Match<'a>] {
+        &self.matches
+    }
+
+    /// Consume the region, returning its matches
+    pub fn into_matches(self) -> Vec<Match<'a>> {
+        self.matches
+    }
+}
+
+/// Group matches into regions, merging those that overlap or are separated by at most
+/// `max_gap` characters. This is the usual step after a search to highlight texts, extract
+/// snippets around the matches, or group alerts.
+///
+/// The regions are returned sorted by position, and don't overlap or touch each other within
+/// the allowed gap.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("red"), String::from("fox")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let text = "a red fox and a red hen";
+/// let matches = prefix_tree.find_text_matches(text.to_string()).unwrap();
+///
+/// let regions = trie::merge_regions(matches, 1);
+/// assert_eq!(regions.len(), 2);
+/// assert_eq!(regions[0].char_range(), (2, 9));
+/// assert_eq!(regions[0].matches().len(), 2);
+/// assert_eq!(regions[1].char_range(), (16, 19));
+/// ```
+pub fn merge_regions(mut matches: Vec<Match<'_>>, max_gap: usize) -> Vec<MatchRegion<'_>> {
+    matches.sort_by_key(|m| (m.start, m.end));
+
+    let mut regions: Vec<MatchRegion> = Vec::new();
+    for m in matches {
+        match regions.last_mut() {
+            Some(region) if m.start <= region.end + max_gap => {
+                region.end = region.end.max(m.end);
+                region.matches.push(m);
+            }
+            _ => regions.push(MatchRegion {
+                start: m.start,
+                end: m.end,
+                matches: vec![m],
+            }),
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_merge_regions() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("abc"),
+                String::from("bcd"),
+                String::from("x"),
+            ]),
+            None,
+        )
+        .unwrap();
+        let text = "abcd  x   x";
+        let ranges = |gap| -> Vec<((usize, usize), usize)> {
+            let matches = pt.find_text_matches(text).unwrap();
+            merge_regions(matches, gap)
+                .iter()
+                .map(|r| (r.char_range(), r.matches().len()))
+                .collect()
+        };
+
+        // Overlapping matches are always merged
+        assert_eq!(ranges(0), [((0, 4), 2), ((6, 7), 1), ((10, 11), 1)]);
+        assert_eq!(ranges(2), [((0, 7), 3), ((10, 11), 1)]);
+        assert_eq!(ranges(3), [((0, 11), 4)]);
+        assert!(merge_regions(Vec::new(), 1).is_empty());
+    }
+
+    #[test]
+    fn test_merge_regions_sorts_matches() {
+        let matches = vec![
+            Match::new("c", "c", 6, 1),
+            Match::new("a", "a", 1, 1),
+            Match::new("bb", "bb", 3, 2),
+        ];
+        let regions = merge_regions(matches, 0);
+        assert_eq!(regions.len(), 2);
+        let values: Vec<&str> = regions[0].matches().iter().map(|m| m.value()).collect();
+        assert_eq!(values, ["a", "bb"]);
+        assert_eq!(regions[1].char_range(), (5, 6));
+        assert_eq!(
+            regions
+                .into_iter()
+                .next()
+                .unwrap()
+                .into_matches()
+                .last()
+                .unwrap()
+                .char_range(),
+            (1, 3)
+        );
+    }
+}
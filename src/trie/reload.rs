@@ -0,0 +1,196 @@
+use super::{
+    DictionaryEntry, DictionaryFormat, GazetteerEntry, SearchOptions, SearchResult, TrieRoot,
+    create_from_dictionary, read_dictionary,
+};
+use arc_swap::ArcSwap;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+/// Where to load the dictionary of a `ReloadableTrie` from.
+#[derive(Debug, Clone)]
+pub enum DictionarySource {
+    /// The entries of the dictionary
+    Entries(Vec<GazetteerEntry>),
+
+    /// A TSV file, read with `read_dictionary`
+    TsvFile(PathBuf),
+}
+
+impl DictionarySource {
+    /// Build a prefix tree from the dictionary with the given options
+    fn build(self, options: SearchOptions) -> SearchResult<TrieRoot> {
+        let entries = match self {
+            Self::Entries(entries) => entries.into_iter().map(DictionaryEntry::from).collect(),
+            Self::TsvFile(path) => read_dictionary(path, Some(DictionaryFormat::Tsv))?,
+        };
+        create_from_dictionary(entries, Some(options))
+    }
+}
+
+impl From<Vec<GazetteerEntry>> for DictionarySource {
+    fn from(entries: Vec<GazetteerEntry>) -> Self {
+        Self::Entries(entries)
+    }
+}
+
+impl From<Vec<(String, Option<String>)>> for DictionarySource {
+    fn from(entries: Vec<(String, Option<String>)>) -> Self {
+        Self::Entries(entries.into_iter().map(|(p, k)| (p, k, None)).collect())
+    }
+}
+
+impl From<PathBuf> for DictionarySource {
+    fn from(path: PathBuf) -> Self {
+        Self::TsvFile(path)
+    }
+}
+
+impl From<&Path> for DictionarySource {
+    fn from(path: &Path) -> Self {
+        Self::TsvFile(path.to_path_buf())
+    }
+}
+
+/// A handle to a prefix tree that can be rebuilt from an updated dictionary while it is being
+/// used for searches.
+///
+/// Searches are run on a snapshot of the tree obtained with `load`. Reloading builds the new
+/// tree without blocking the readers and then swaps it atomically, so searches in progress
+/// keep using the previous tree and later ones use the new tree. The rebuilt tree uses the
+/// same search options. Context rules and pattern weights are not carried over, since the
+/// patterns may have changed.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, ReloadableTrie};
+/// use std::sync::Arc;
+///
+/// let handle = Arc::new(
+///     ReloadableTrie::new(trie::add_keyword_slot(vec![String::from("old")]), None).unwrap(),
+/// );
+/// let snapshot = handle.load();
+///
+/// handle
+///     .reload_from(trie::add_keyword_slot(vec![String::from("new")]))
+///     .join()
+///     .unwrap()
+///     .unwrap();
+///
+/// let text = String::from("old and new");
+/// assert_eq!(snapshot.find_text_matches(text.clone()).unwrap()[0].value(), "old");
+/// assert_eq!(handle.load().find_text_matches(text).unwrap()[0].value(), "new");
+/// ```
+pub struct ReloadableTrie {
+    current: ArcSwap<TrieRoot>,
+    options: SearchOptions,
+}
+
+impl ReloadableTrie {
+    /// Build the initial prefix tree from the given dictionary source.
+    pub fn new(
+        source: impl Into<DictionarySource>,
+        opts: Option<SearchOptions>,
+    ) -> SearchResult<Self> {
+        let options = opts.unwrap_or_default();
+        let trie = source.into().build(options.clone())?;
+        Ok(Self {
+            current: ArcSwap::from_pointee(trie),
+            options,
+        })
+    }
+
+    /// Get the current prefix tree. The snapshot remains valid after a reload.
+    pub fn load(&self) -> Arc<TrieRoot> {
+        self.current.load_full()
+    }
+
+    /// Rebuild the prefix tree from the given source on the calling thread, then replace the
+    /// current one. On error, the current tree is kept.
+    pub fn reload_blocking(&self, source: impl Into<DictionarySource>) -> SearchResult<()> {
+        let trie = source.into().build(self.options.clone())?;
+        self.current.store(Arc::new(trie));
+        Ok(())
+    }
+
+    /// Rebuild the prefix tree from the given source on a background thread, then replace the
+    /// current one. The returned handle can be joined to get the result of the reload.
+    pub fn reload_from(
+        self: &Arc<Self>,
+        source: impl Into<DictionarySource>,
+    ) -> JoinHandle<SearchResult<()>> {
+        let handle = Arc::clone(self);
+        let source = source.into();
+        thread::spawn(move || handle.reload_blocking(source))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchError, add_keyword_slot};
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_reload_from_tsv_file() {
+        let path = std::env::temp_dir().join("ac_search_rs_test_reload.tsv");
+        fs::write(
+            &path,
+            "pattern\tkeyword\tcategory\n\nPython3\tPython\nAcme\tAcme Corp.\tORG\n",
+        )
+        .unwrap();
+        let handle = ReloadableTrie::new(path.as_path(), None).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let tree = handle.load();
+        let found: Vec<_> = tree
+            .find_text_matches("Acme sells Python3")
+            .unwrap()
+            .iter()
+            .map(|m| (m.keyword(), m.category()))
+            .collect();
+        assert_eq!(found, [("Acme Corp.", Some("ORG")), ("Python", None)]);
+        assert!(matches!(
+            handle.reload_blocking(path),
+            Err(SearchError::DictionaryFile(_))
+        ));
+        assert!(Arc::ptr_eq(&tree, &handle.load()));
+    }
+
+    #[test]
+    fn test_reload_trie() {
+        let handle = Arc::new(
+            ReloadableTrie::new(
+                add_keyword_slot(vec![String::from("ab")]),
+                Some(SearchOptions {
+                    case_sensitive: false,
+                    ..Default::default()
+                }),
+            )
+            .unwrap(),
+        );
+        let old = handle.load();
+
+        // Failed reloads keep the current tree
+        assert_eq!(
+            handle.reload_blocking(add_keyword_slot(vec![])),
+            Err(SearchError::InvalidDictionary)
+        );
+        assert!(Arc::ptr_eq(&old, &handle.load()));
+
+        handle
+            .reload_from(add_keyword_slot(vec![String::from("CD")]))
+            .join()
+            .unwrap()
+            .unwrap();
+        let text = String::from("AB CD");
+        let new = handle.load();
+        assert_eq!(
+            new.find_text_matches(text.clone()).unwrap()[0].value(),
+            "cd"
+        );
+        assert_eq!(old.find_text_matches(text).unwrap()[0].value(), "ab");
+    }
+}
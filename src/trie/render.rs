@@ -0,0 +1,120 @@
+use super::Match;
+
+/// A row of markers drawn under a line of text
+struct MarkerRow {
+    chars: Vec<char>,
+}
+
+impl MarkerRow {
+    /// Whether the row is free from the given column onwards, leaving a space after the
+    /// previous marker.
+    fn fits(&self, column: usize) -> bool {
+        self.chars.is_empty() || self.chars.len() < column
+    }
+
+    /// Draw a marker spanning the given columns, followed by an optional label
+    fn draw(&mut self, start: usize, end: usize, label: Option<&str>) {
+        self.chars.resize(start, ' ');
+        self.chars.extend(std::iter::repeat_n('^', end - start));
+        if let Some(label) = label {
+            self.chars.push(' ');
+            self.chars.extend(label.chars());
+        }
+    }
+}
+
+/// Render a text with the given matches marked with carets below them, each followed by the
+/// keyword of the match, to be displayed in a monospace font. This is meant for debugging
+/// output.
+///
+/// Each line of the text is followed by the rows needed to mark the matches in it without
+/// overlaps. Matches that span several lines are marked on each of them, with the keyword
+/// shown on the last one. The character ranges of the matches must refer to the given text.
+/// Each character is assumed to take a single column, which may not hold for wide characters
+/// (e.g. CJK) or tabs.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("cat"), String::from("at")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let text = "the cat\nsat";
+/// let matches = prefix_tree.find_text_matches(text.to_string()).unwrap();
+///
+/// let rendered = trie::render_matches(text, &matches);
+/// assert_eq!(rendered, "the cat\n    ^^^ cat\n     ^^ at\nsat\n ^^ at\n");
+/// ```
+pub fn render_matches(text: &str, matches: &[Match]) -> String {
+    let mut sorted: Vec<&Match> = matches.iter().collect();
+    sorted.sort_by_key(|m| (m.start, std::cmp::Reverse(m.end)));
+
+    let mut out = String::with_capacity(2 * text.len());
+    let mut line_start = 0;
+    for raw_line in text.split('\n') {
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        let line_end = line_start + line.chars().count();
+        out.push_str(line);
+        out.push('\n');
+
+        let mut rows: Vec<MarkerRow> = Vec::new();
+        for m in &sorted {
+            if m.start >= line_end || m.end <= line_start {
+                continue;
+            }
+            let start = m.start.max(line_start) - line_start;
+            let end = m.end.min(line_end) - line_start;
+            let label = (m.end <= line_end).then(|| m.keyword());
+            match rows.iter_mut().find(|r| r.fits(start)) {
+                Some(row) => row.draw(start, end, label),
+                None => {
+                    let mut row = MarkerRow { chars: Vec::new() };
+                    row.draw(start, end, label);
+                    rows.push(row);
+                }
+            }
+        }
+        for row in rows {
+            out.extend(row.chars);
+            out.push('\n');
+        }
+
+        // Skip the line break, and the carriage return if there was one
+        line_start += raw_line.chars().count() + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_render_overlaps() {
+        let pt = create_prefix_tree(
+            vec![
+                (String::from("abc"), Some(String::from("X"))),
+                (String::from("bcd"), None),
+                (String::from("e"), None),
+            ],
+            None,
+        )
+        .unwrap();
+        let text = "abcd e\r\n\nde";
+        let matches = pt.find_text_matches(text).unwrap();
+        assert_eq!(
+            render_matches(text, &matches),
+            "abcd e\n^^^ X\n ^^^ bcd\n     ^ e\n\nde\n ^ e\n"
+        );
+    }
+
+    #[test]
+    fn test_render_multiline_match() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("b\nc")]), None).unwrap();
+        let text = "ab\ncd";
+        let matches = pt.find_text_matches(text).unwrap();
+        assert_eq!(render_matches(text, &matches), "ab\n ^\ncd\n^ b\nc\n");
+        assert_eq!(render_matches(text, &[]), "ab\ncd\n");
+    }
+}
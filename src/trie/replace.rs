@@ -0,0 +1,133 @@
+use super::{Match, MatchKind, SearchResult, TrieRoot};
+
+impl TrieRoot {
+    /// Replace each match of the dictionary in the text with its keyword, e.g. to rewrite the
+    /// entities of a text in their canonical form.
+    ///
+    /// Overlapping matches can't all be replaced, so the matches are selected as set by the
+    /// `match_kind` search option, using `MatchKind::LeftmostLongest` if it is
+    /// `MatchKind::Overlapping`.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = vec![
+    ///     (String::from("NYC"), Some(String::from("New York City"))),
+    ///     (String::from("New York"), Some(String::from("New York City"))),
+    ///     (String::from("the big apple"), Some(String::from("New York City"))),
+    /// ];
+    /// let opts = trie::SearchOptions{case_sensitive: false, ..Default::default()};
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+    ///
+    /// let replaced = prefix_tree.replace_all("The Big Apple, aka NYC").unwrap();
+    /// assert_eq!(replaced, "New York City, aka New York City");
+    /// ```
+    pub fn replace_all(&self, text: &str) -> SearchResult<String> {
+        self.replace_all_with(text, |m, _| m.keyword())
+    }
+
+    /// Replace each match of the dictionary in the text with the string returned by the given
+    /// function, which receives the match and the text it replaces. The matches are selected
+    /// as in `replace_all`.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("secret")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let redacted = prefix_tree
+    ///     .replace_all_with("a secret plan", |_, found| "*".repeat(found.len()))
+    ///     .unwrap();
+    /// assert_eq!(redacted, "a ****** plan");
+    /// ```
+    pub fn replace_all_with<'a, F, R>(
+        &'a self,
+        text: &str,
+        mut replacement: F,
+    ) -> SearchResult<String>
+    where
+        F: FnMut(&Match<'a>, &str) -> R,
+        R: AsRef<str>,
+    {
+        let kind = match self.options.match_kind {
+            MatchKind::Overlapping => MatchKind::LeftmostLongest,
+            kind => kind,
+        };
+        let matches = self.select_matches_kind(self.find_text_matches(text)?, kind);
+
+        let mut out = String::with_capacity(text.len());
+        let mut copied = 0;
+        for m in &matches {
+            let range = m.byte_range();
+            out.push_str(&text[copied..range.start]);
+            out.push_str(replacement(m, &text[range.clone()]).as_ref());
+            copied = range.end;
+        }
+        out.push_str(&text[copied..]);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_replace_all() {
+        let pt = create_prefix_tree(
+            vec![
+                (String::from("colour"), Some(String::from("color"))),
+                (String::from("colours"), Some(String::from("colors"))),
+                (String::from("ours"), Some(String::from("OURS"))),
+                (String::from("é"), Some(String::from("e"))),
+            ],
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            pt.replace_all("Colours: ours, colour é!").unwrap(),
+            "colors: OURS, color e!"
+        );
+        assert_eq!(pt.replace_all("").unwrap(), "");
+        assert_eq!(pt.replace_all("nothing").unwrap(), "nothing");
+
+        // Decomposed characters are replaced entirely
+        #[cfg(feature = "unicode")]
+        assert_eq!(pt.replace_all("ñe\u{301}x").unwrap(), "ñex");
+    }
+
+    #[test]
+    fn test_replace_all_match_kind() {
+        let dictionary = || {
+            vec![
+                (String::from("ab"), Some(String::from("1"))),
+                (String::from("abc"), Some(String::from("2"))),
+            ]
+        };
+        let longest = create_prefix_tree(dictionary(), None).unwrap();
+        assert_eq!(longest.replace_all("abc ab").unwrap(), "2 1");
+
+        let first = create_prefix_tree(
+            dictionary(),
+            Some(SearchOptions {
+                match_kind: MatchKind::LeftmostFirst,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(first.replace_all("abc ab").unwrap(), "1c 1");
+
+        let spans = longest
+            .replace_all_with("xabx", |m, found| format!("[{}:{}]", found, m.keyword()))
+            .unwrap();
+        assert_eq!(spans, "x[ab:1]x");
+    }
+}
@@ -0,0 +1,109 @@
+use super::Match;
+use std::collections::HashMap;
+
+/// Group matches by their keyword, keeping the order in which they are given within each
+/// group.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = vec![
+///     (String::from("NYC"), Some(String::from("New York"))),
+///     (String::from("New York"), None),
+///     (String::from("Paris"), None),
+/// ];
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let matches = prefix_tree.find_text_matches("New York, Paris, NYC").unwrap();
+///
+/// let groups = trie::group_by_keyword(&matches);
+/// assert_eq!(groups.len(), 2);
+/// let ranges: Vec<_> = groups["New York"].iter().map(|m| m.char_range()).collect();
+/// assert_eq!(ranges, [(0, 8), (17, 20)]);
+/// ```
+pub fn group_by_keyword<'m, 'a>(matches: &'m [Match<'a>]) -> HashMap<&'a str, Vec<&'m Match<'a>>> {
+    let mut groups: HashMap<&str, Vec<&Match>> = HashMap::new();
+    for m in matches {
+        groups.entry(m.keyword()).or_default().push(m);
+    }
+    groups
+}
+
+/// Count the matches of each keyword.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("spam"), String::from("eggs")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let matches = prefix_tree.find_text_matches("spam, eggs and spam").unwrap();
+///
+/// let counts = trie::keyword_counts(&matches);
+/// assert_eq!(counts["spam"], 2);
+/// assert_eq!(counts["eggs"], 1);
+/// ```
+pub fn keyword_counts<'a>(matches: &[Match<'a>]) -> HashMap<&'a str, usize> {
+    let mut counts = HashMap::new();
+    for m in matches {
+        *counts.entry(m.keyword()).or_default() += 1;
+    }
+    counts
+}
+
+/// Count the matches of each keyword, returning the keywords sorted by decreasing count, and
+/// alphabetically for equal counts.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("b"), String::from("a")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+/// let matches = prefix_tree.find_text_matches("b a b a c b").unwrap();
+///
+/// assert_eq!(trie::most_common_keywords(&matches), [("b", 3), ("a", 2)]);
+/// ```
+pub fn most_common_keywords<'a>(matches: &[Match<'a>]) -> Vec<(&'a str, usize)> {
+    let mut counts: Vec<(&str, usize)> = keyword_counts(matches).into_iter().collect();
+    counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_keyword_reports() {
+        let pt = create_prefix_tree(
+            vec![
+                (String::from("ab"), Some(String::from("X"))),
+                (String::from("b"), Some(String::from("X"))),
+                (String::from("c"), None),
+            ],
+            None,
+        )
+        .unwrap();
+        let matches = pt.find_text_matches("abc cab").unwrap();
+
+        let groups = group_by_keyword(&matches);
+        let mut keywords: Vec<&str> = groups.keys().copied().collect();
+        keywords.sort();
+        assert_eq!(keywords, ["X", "c"]);
+        let ranges: Vec<(usize, usize)> = groups["X"].iter().map(|m| m.char_range()).collect();
+        assert_eq!(ranges, [(0, 2), (1, 2), (5, 7), (6, 7)]);
+
+        assert_eq!(
+            keyword_counts(&matches),
+            HashMap::from([("X", 4), ("c", 2)])
+        );
+        assert_eq!(most_common_keywords(&matches), [("X", 4), ("c", 2)]);
+
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("z")]), None).unwrap();
+        let matches = pt.find_text_matches("abc").unwrap();
+        assert!(group_by_keyword(&matches).is_empty());
+        assert!(most_common_keywords(&matches).is_empty());
+    }
+}
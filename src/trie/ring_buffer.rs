@@ -1,32 +1,70 @@
+use std::collections::VecDeque;
+
 /// A Circular buffer to keep the last "capacity" items that have been pushed to it.
-#[derive(Debug)]
-pub struct RingBuffer<T: Copy> {
-    values: Vec<T>,
+///
+/// Items are indexed from oldest to newest: index 0 is always the oldest item still in the
+/// buffer (the front) and index `len() - 1` the most recently pushed one (the back),
+/// regardless of whether the buffer has wrapped around.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::RingBuffer;
+///
+/// let mut buffer = RingBuffer::new(3);
+/// for i in 0..5 {
+///     buffer.push(i);
+/// }
+/// assert_eq!(buffer.front(), Some(&2));
+/// assert_eq!(buffer.back(), Some(&4));
+/// assert_eq!(*buffer.get_item(1), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    values: VecDeque<T>,
     capacity: usize,
-    pos: usize,
 }
 
-impl<T: Copy> RingBuffer<T> {
+impl<T> RingBuffer<T> {
     /// Instantiate a new buffer with the given capacity.
     pub fn new(capacity: usize) -> Self {
         Self {
-            values: Vec::with_capacity(capacity),
+            values: VecDeque::with_capacity(capacity),
             capacity,
-            pos: 0,
         }
     }
 
     /// Push a new element onto the buffer.
     ///
-    /// Adds a new element to the ring buffer. If the buffer is at capacity, the
-    /// oldest element in it will be removed.
+    /// Adds a new element to the back of the ring buffer. If the buffer is at capacity, the
+    /// oldest element in it will be removed. A buffer with zero capacity discards all items.
     pub fn push(&mut self, item: T) {
-        if self.values.len() < self.capacity {
-            self.values.push(item);
-        } else {
-            self.values[self.pos] = item;
-            self.pos = (self.pos + 1) % self.capacity;
+        if self.capacity == 0 {
+            return;
+        }
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
         }
+        self.values.push_back(item);
+    }
+
+    /// Remove and return the oldest element in the buffer, if any.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.values.pop_front()
+    }
+
+    /// Remove all the elements from the buffer.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Remove all the elements from the buffer, returning them from oldest to newest.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.values.drain(..)
+    }
+
+    /// Iterate over the elements in the buffer, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.values.iter()
     }
 
     /// The number of elements currently stored on the buffer.
@@ -41,11 +79,70 @@ impl<T: Copy> RingBuffer<T> {
         self.values.is_empty()
     }
 
-    /// Get the item at the given index. Panics if the index is out of bounds.
-    pub fn get_item(&self, index: usize) -> T {
+    /// The oldest element in the buffer, if any.
+    #[inline]
+    pub fn front(&self) -> Option<&T> {
+        self.values.front()
+    }
+
+    /// The most recently pushed element in the buffer, if any.
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        self.values.back()
+    }
+
+    /// Get the item at the given index, counting from the oldest item in the buffer.
+    /// Panics if the index is out of bounds.
+    pub fn get_item(&self, index: usize) -> &T {
         if index >= self.len() {
             panic!("Index out of bounds");
         }
-        self.values[(index + self.pos) % self.capacity]
+        &self.values[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_owned_items() {
+        let mut buffer = RingBuffer::new(2);
+        for s in ["a", "b", "c"] {
+            buffer.push(String::from(s));
+        }
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.get_item(0), "b");
+
+        assert_eq!(buffer.pop_front().as_deref(), Some("b"));
+        buffer.push(String::from("d"));
+        assert_eq!(buffer.drain().collect::<Vec<_>>(), ["c", "d"]);
+        assert!(buffer.is_empty());
+
+        buffer.push(String::from("e"));
+        buffer.clear();
+        assert!(buffer.pop_front().is_none());
+    }
+
+    #[test]
+    fn test_ring_buffer_order() {
+        let mut buffer = RingBuffer::new(3);
+        assert!(buffer.is_empty());
+        assert_eq!((buffer.front(), buffer.back()), (None, None));
+
+        // Index 0 is the oldest item before and after wrapping around
+        for i in 0..8usize {
+            buffer.push(i);
+            let oldest = i.saturating_sub(2);
+            assert_eq!(*buffer.get_item(0), oldest);
+            assert_eq!(buffer.front(), Some(&oldest));
+            assert_eq!(buffer.back(), Some(&i));
+            assert_eq!(*buffer.get_item(buffer.len() - 1), i);
+        }
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [5, 6, 7]);
+
+        let mut empty = RingBuffer::new(0);
+        empty.push(1);
+        assert!(empty.is_empty());
     }
 }
@@ -0,0 +1,132 @@
+use super::{Match, SearchError, SearchResult, TrieRoot, is_boundary, normalize};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// Confidence score of a match, wrapped so that matches can still be compared and sorted.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Score(pub(crate) f32);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for Score {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Consistent with the total order, under which scores are equal iff their bits are
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl TrieRoot {
+    /// Set the weight of a pattern of the dictionary, used to compute the scores of its
+    /// matches. Patterns have a weight of 1 by default.
+    ///
+    /// Returns an error if the pattern is not in the dictionary.
+    pub fn set_pattern_weight(&mut self, pattern: &str, weight: f32) -> SearchResult<()> {
+        let node_id = self
+            .pattern_node_id(pattern)
+            .ok_or_else(|| SearchError::PatternNotFound(pattern.to_string()))?;
+        self.weights.insert(node_id, weight);
+        Ok(())
+    }
+
+    /// Compute the confidence scores of the matches found in the given text. The ranges of the
+    /// matches must refer to the characters of the text.
+    ///
+    /// The score is the weight of the pattern times the average of three signals between 0
+    /// and 1:
+    /// - Length: `1 - 1 / length` for a pattern of `length` characters, so that longer
+    ///   patterns are less likely to be spurious matches.
+    /// - Case: 1 if the match has exactly the same case as the pattern, 0 otherwise. This is
+    ///   always 1 in case-sensitive searches.
+    /// - Bounds: 0.5 for each end of the match that lies on a word boundary.
+    pub(crate) fn score_matches(&self, matches: &mut [Match], text: &str) {
+        let chars: Vec<char> = text.chars().collect();
+        self.score_matches_in(matches, &chars);
+    }
+
+    /// Compute the confidence scores of the matches found in a text given as a slice of
+    /// characters, as in `score_matches`.
+    pub(crate) fn score_matches_in(&self, matches: &mut [Match], chars: &[char]) {
+        for m in matches {
+            let node_id = self.node_by_path(m.value());
+            let weight = node_id
+                .and_then(|nid| self.weights.get(&nid))
+                .copied()
+                .unwrap_or(1.0);
+
+            let length = 1.0 - 1.0 / m.value().chars().count() as f32;
+            let case = if self.options.case_sensitive {
+                1.0
+            } else {
+                let found: String = normalize::nfc(chars[m.start..m.end].iter().copied()).collect();
+                let expected = node_id
+                    .and_then(|nid| self.cased_patterns.get(&nid))
+                    .map_or(m.value(), |s| s.as_str());
+                f32::from(u8::from(self.options.strip_pattern(&found) == expected))
+            };
+            let before = is_boundary(m.start.checked_sub(1).map(|i| chars[i]), chars[m.start]);
+            let after = is_boundary(chars.get(m.end).copied(), chars[m.end - 1]);
+            let bounds = 0.5 * f32::from(u8::from(before) + u8::from(after));
+
+            m.score = Some(Score(weight * (length + case + bounds) / 3.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_match_scores() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("New York"), String::from("ab")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                compute_scores: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        pt.set_pattern_weight("AB", 0.5).unwrap();
+
+        let matches = pt
+            .find_text_matches(String::from("new york, New York. xab"))
+            .unwrap();
+        let scores: Vec<f32> = matches.iter().map(|m| m.score().unwrap()).collect();
+        assert_eq!(scores.len(), 3);
+        assert!((scores[0] - (0.875 + 1.0) / 3.0).abs() < 1e-6);
+        assert!((scores[1] - (0.875 + 2.0) / 3.0).abs() < 1e-6);
+        assert!((scores[2] - 0.5 * (0.5 + 1.0 + 0.5) / 3.0).abs() < 1e-6);
+
+        assert_eq!(
+            pt.set_pattern_weight("abc", 2.0),
+            Err(SearchError::PatternNotFound(String::from("abc")))
+        );
+    }
+
+    #[test]
+    fn test_no_scores_by_default() {
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        let matches = pt.find_text_matches(String::from("ab")).unwrap();
+        assert_eq!(matches[0].score(), None);
+    }
+}
@@ -1,4 +1,16 @@
-use super::{RingBuffer, SearchError, SearchResult, TrieRoot};
+use super::cancel::Interrupt;
+use super::lines::LineTracker;
+use super::normalize::PreparedText;
+use super::{
+    MatchIter, NodeId, PatternId, Payload, PayloadSlot, RingBuffer, Score, SearchError,
+    SearchResult, TrieRoot, WhitespaceMatch, is_digit_separator, is_separator,
+};
+use std::any::Any;
+use std::ops::Range;
+use std::time::Instant;
+
+/// Number of characters scanned between checks of the search deadline and cancellation
+pub(crate) const DEADLINE_CHECK_INTERVAL: usize = 4096;
 
 /// Return whether the given character is a "word character", i.e. a Unicode
 /// alphanumeric character, a number or an underscore.
@@ -10,47 +22,123 @@ fn is_word_char(c: char) -> bool {
 /// Represents a match found in a text.
 ///
 /// The match contains the index of the start and end characters of the match, so that
-/// `haystack_chars[start:end]` should be equal to the character vector of the "value". Note
-/// that matches are done on a character level, not a byte level, so indexing the string directly
-/// may not yield the expected result.
+/// `haystack_chars[start:end]` should be equal to the character vector of the "value". Note that
+/// matches are done on a character level, not a byte level, so indexing the string directly may not
+/// yield the expected result; use `byte_range` for that instead. When separators are ignored, the
+/// range spans the text variant that was found, including any separators within it. If normalizing
+/// the text (Unicode NFC) changes its number of characters, the range refers to the characters of
+/// the text as it was given to the search.
 ///
 /// **CAVEAT**
 /// Matches cannot outlive the TrieRoot object that created them. This is because the values and
-/// keywords are references to those stored in the Trie to avoid excessive cloning. Matches must be
-/// processed / consumed immediately after search.
+/// keywords are references to those stored in the Trie to avoid excessive cloning. Use
+/// `into_owned` to convert them into an `OwnedMatch` to keep them after the tree is dropped.
 #[derive(PartialEq, Eq, Debug, PartialOrd, Ord)]
 pub struct Match<'a> {
     /// Index of first character in the match
-    start: usize,
+    pub(crate) start: usize,
 
-    /// The match value substring that was actually found
+    /// Value of the matched dictionary entry
     value: &'a str,
 
     /// The corresponding keyword / standard form of the match
     kw: &'a str,
 
     /// 1 + index of last character in the match
-    end: usize,
+    pub(crate) end: usize,
+
+    /// Confidence score of the match, if computed
+    pub(crate) score: Option<Score>,
+
+    /// Category (entity label) of the matched pattern, if any
+    category: Option<&'a str>,
+
+    /// Metadata of the matched pattern, if any
+    metadata: Option<&'a str>,
+
+    /// Payload of the matched pattern, if any
+    pub(crate) payload: PayloadSlot<&'a Payload>,
+
+    /// Byte offsets of the start and end of the match in the searched text
+    pub(crate) start_byte: usize,
+    pub(crate) end_byte: usize,
+
+    /// ID of the matched pattern
+    pub(crate) pattern_id: PatternId,
+
+    /// Priority of the matched pattern
+    pub(crate) priority: i32,
+
+    /// Line and column of the start of the match, if tracked
+    pub(crate) line_col: Option<(usize, usize)>,
 }
 
 impl<'a> Match<'a> {
     /// Instantiate a new match from a value and 1 + index of the last character in the match.
+    /// The byte range of the match is set equal to its character range, as in an ASCII text,
+    /// and its pattern ID to 0.
     pub fn new(value: &'a str, kw: &'a str, end_pos: usize, length: usize) -> Self {
         Self {
             start: end_pos - length,
             end: end_pos,
             kw,
             value,
+            score: None,
+            category: None,
+            metadata: None,
+            payload: PayloadSlot(None),
+            start_byte: end_pos - length,
+            end_byte: end_pos,
+            pattern_id: 0,
+            priority: 0,
+            line_col: None,
         }
     }
 
-    /// Return the value stored in the match.
-    pub fn value(&self) -> &str {
+    /// Set the ID of the matched pattern.
+    pub(crate) fn with_pattern_id(mut self, pattern_id: PatternId) -> Self {
+        self.pattern_id = pattern_id;
+        self
+    }
+
+    /// Set the range of bytes of the match.
+    pub(crate) fn with_byte_range(mut self, start_byte: usize, end_byte: usize) -> Self {
+        (self.start_byte, self.end_byte) = (start_byte, end_byte);
+        self
+    }
+
+    /// Set the category of the match.
+    pub(crate) fn with_category(mut self, category: Option<&'a str>) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Set the metadata of the match.
+    pub(crate) fn with_metadata(mut self, metadata: Option<&'a str>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Set the priority of the match.
+    pub(crate) fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the payload of the match.
+    pub(crate) fn with_payload(mut self, payload: Option<&'a Payload>) -> Self {
+        self.payload = PayloadSlot(payload);
+        self
+    }
+
+    /// Return the value of the matched dictionary entry. This may differ from the text of the
+    /// match (e.g. in case-insensitive searches), which is given by `matched_text`.
+    pub fn value(&self) -> &'a str {
         self.value
     }
 
     /// Return the value of the associated keyword of the match
-    pub fn keyword(&self) -> &str {
+    pub fn keyword(&self) -> &'a str {
         self.kw
     }
 
@@ -58,22 +146,255 @@ impl<'a> Match<'a> {
     pub fn char_range(&self) -> (usize, usize) {
         (self.start, self.end)
     }
+
+    /// Return the range of bytes the match spans in the searched text, so that the text of
+    /// the match can be sliced directly.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("café")]);
+    /// let opts = trie::SearchOptions{case_sensitive: false, ..Default::default()};
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+    ///
+    /// let text = "Un CAFÉ noir";
+    /// let matches = prefix_tree.find_text_matches(text).unwrap();
+    /// assert_eq!(matches[0].char_range(), (3, 7));
+    /// assert_eq!(&text[matches[0].byte_range()], "CAFÉ");
+    /// ```
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start_byte..self.end_byte
+    }
+
+    /// Return the text of the match, as written in the searched text. This keeps the case and
+    /// form of the original, even if the search folds the case or normalizes the text. Panics
+    /// if the match is out of the bounds of the given text.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("kelvin")]);
+    /// let opts = trie::SearchOptions{case_sensitive: false, ..Default::default()};
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+    ///
+    /// let text = "0 \u{212a}elvin";
+    /// let matches = prefix_tree.find_text_matches(text).unwrap();
+    /// assert_eq!(matches[0].value(), "kelvin");
+    /// assert_eq!(matches[0].matched_text(text), "\u{212a}elvin");
+    /// ```
+    pub fn matched_text<'t>(&self, text: &'t str) -> &'t str {
+        &text[self.byte_range()]
+    }
+
+    /// Return the ID of the matched pattern, i.e. the position of its entry in the dictionary
+    /// the tree was built from. See `TrieRoot::pattern`.
+    pub fn pattern_id(&self) -> PatternId {
+        self.pattern_id
+    }
+
+    /// Return the category (entity label) of the matched pattern, if the dictionary entry has
+    /// one. See `create_gazetteer`.
+    pub fn category(&self) -> Option<&'a str> {
+        self.category
+    }
+
+    /// Return the metadata of the matched pattern, if its dictionary entry has any. See
+    /// `create_from_dictionary`.
+    pub fn metadata(&self) -> Option<&'a str> {
+        self.metadata
+    }
+
+    /// Return the payload of the matched pattern, if it has one of type `T`. See
+    /// `TrieRoot::set_pattern_payload`.
+    pub fn payload<T: Any>(&self) -> Option<&'a T> {
+        self.payload.0?.downcast_ref()
+    }
+
+    /// Return the priority of the matched pattern, 0 by default. See
+    /// `TrieRoot::set_pattern_priority`.
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Return the line and column of the start of the match in the searched text, if the
+    /// `track_lines` search option is set. Both start at 1, and the column is counted in
+    /// characters.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("TODO")]);
+    /// let opts = trie::SearchOptions{track_lines: true, ..Default::default()};
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+    ///
+    /// let text = "fn main() {\n    // TODO: greet\n}\n";
+    /// let matches = prefix_tree.find_text_matches(text).unwrap();
+    /// assert_eq!(matches[0].line_col(), Some((2, 8)));
+    /// ```
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        self.line_col
+    }
+
+    /// Return the confidence score of the match. This is only computed if the
+    /// `compute_scores` search option is set, and ranges from 0 to the weight of the pattern
+    /// (1 by default). See `TrieRoot::set_pattern_weight`.
+    pub fn score(&self) -> Option<f32> {
+        self.score.map(|s| s.0)
+    }
+}
+
+/// A character read by the automaton, along with the information needed to map matches
+/// back to the searched text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ReadChar {
+    /// The character that was read
+    pub(crate) ch: char,
+
+    /// Index of the character in the searched text
+    pub(crate) pos: usize,
+
+    /// Byte offset of the character in the searched text
+    pub(crate) byte: usize,
+
+    /// The character preceding it in the searched text, if any
+    pub(crate) prev: Option<char>,
+}
+
+/// State of the automaton of a prefix tree while scanning a text.
+pub(crate) struct ScanState {
+    /// Current node of the automaton
+    node_id: NodeId,
+
+    /// The last characters read by the automaton
+    buffer: RingBuffer<ReadChar>,
+
+    /// Whether patterns were left unchecked at some position due to `max_chain_depth`
+    pub(crate) truncated: bool,
+}
+
+impl ScanState {
+    /// Get the smallest position of the text at which the matches found from now on can
+    /// start, given the length of the longest pattern of the tree.
+    pub(crate) fn horizon(&self, max_pattern_len: usize) -> usize {
+        // The first character of a later match is one of the last characters read or a
+        // character not read yet
+        let keep = max_pattern_len.saturating_sub(1);
+        match self.buffer.len().checked_sub(keep) {
+            Some(i) if i < self.buffer.len() => self.buffer.get_item(i).pos,
+            Some(_) => self.buffer.back().map_or(0, |c| c.pos + 1),
+            None => self.buffer.front().map_or(0, |c| c.pos),
+        }
+    }
+
+    /// Initial state to scan a text with the given prefix tree
+    pub(crate) fn new(trie: &TrieRoot) -> Self {
+        Self {
+            node_id: trie.root_node_id(),
+            buffer: RingBuffer::new(trie.max_pattern_len),
+            truncated: false,
+        }
+    }
+}
+
+/// Matches found by scanning a prepared text.
+pub(crate) struct ScanOutput<'a> {
+    /// The matches found, in order of their end position
+    pub(crate) matches: Vec<Match<'a>>,
+
+    /// Whether patterns were left unchecked at some position due to `max_chain_depth`
+    pub(crate) truncated: bool,
+
+    /// The reason why the scan stopped before the end of the text, if it did (the deadline was
+    /// exceeded or the search was cancelled)
+    pub(crate) interrupted: Option<SearchError>,
 }
 
-/// Check if a match is word bounded. That is, check if the preceding and following characters
-/// are not word-characters.
-fn is_word_bounded(m: &Match, buffer: &RingBuffer<char>, next_char: Option<char>) -> bool {
-    let pat_len = m.end - m.start;
-    let left = m.start == 0 || (!is_word_char(buffer.get_item(buffer.len() - pat_len - 1)));
-    let right = match next_char {
-        None => true,
-        Some(ch) => !is_word_char(ch),
+/// Return whether the character belongs to a script written without spaces between words
+/// (Chinese, Japanese kana, Thai, Lao, Myanmar and Khmer).
+#[inline]
+fn is_unsegmented_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0E00..=0x0EFF // Thai, Lao
+            | 0x1000..=0x109F // Myanmar
+            | 0x1780..=0x17FF // Khmer
+            | 0x2E80..=0x2FDF // CJK radicals
+            | 0x3005..=0x3007 // CJK iteration and number marks
+            | 0x3021..=0x3029 // Hangzhou numerals
+            | 0x3040..=0x30FF // Hiragana, Katakana
+            | 0x31F0..=0x31FF // Katakana extensions
+            | 0x3400..=0x4DBF // CJK extension A
+            | 0x4E00..=0x9FFF // CJK unified ideographs
+            | 0xF900..=0xFAFF // CJK compatibility ideographs
+            | 0xFF66..=0xFF9F // Halfwidth Katakana
+            | 0x20000..=0x323AF // CJK extensions B-H
+    )
+}
+
+/// Check if there is a word boundary between a character at the edge of a match and the one
+/// next to it outside of the match.
+///
+/// There is a boundary if the outer character is missing or is not a word character. Scripts
+/// that do not separate words with spaces are always considered to be at a boundary, since
+/// their word bounds cannot be determined from the neighbouring characters.
+#[inline]
+pub(crate) fn is_boundary(outer: Option<char>, inner: char) -> bool {
+    outer.is_none_or(|c| !is_word_char(c) || is_unsegmented_char(c) || is_unsegmented_char(inner))
+}
+
+/// Check if a match is word bounded, given its first and last characters and the character
+/// following it.
+pub(crate) fn is_word_bounded(first: &ReadChar, last: char, next_char: Option<char>) -> bool {
+    is_boundary(first.prev, first.ch) && is_boundary(next_char, last)
+}
+
+/// Maximum number of bytes segmented on each side of a position to check if it is a word
+/// boundary, when there is no whitespace closer to it
+#[cfg(feature = "segmentation")]
+const SEGMENT_WINDOW_BYTES: usize = 64;
+
+/// Check if there is a word boundary at a byte offset of a text, following the Unicode word
+/// segmentation rules (UAX #29).
+///
+/// Only the text around the offset is segmented, from the closest whitespace before it to the
+/// closest one after it, since the rules never look across whitespace.
+#[cfg(feature = "segmentation")]
+fn is_segment_boundary(text: &str, byte: usize) -> bool {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let Some(ch) = text[byte..].chars().next() else {
+        return true;
     };
-    left && right
+    if byte == 0 {
+        return true;
+    }
+    let mut lower = byte.saturating_sub(SEGMENT_WINDOW_BYTES);
+    while !text.is_char_boundary(lower) {
+        lower += 1;
+    }
+    let mut upper = (byte + ch.len_utf8() + SEGMENT_WINDOW_BYTES).min(text.len());
+    while !text.is_char_boundary(upper) {
+        upper += 1;
+    }
+    let start = text[lower..byte]
+        .rfind(|c: char| c.is_ascii_whitespace())
+        .map_or(lower, |i| lower + i);
+    let after = byte + ch.len_utf8();
+    let end = text[after..upper]
+        .find(|c: char| c.is_ascii_whitespace())
+        .map_or(upper, |i| after + i);
+    text[start..end]
+        .split_word_bound_indices()
+        .any(|(i, _)| start + i == byte)
 }
 
 impl TrieRoot {
-    /// Find all matches for the search dictionary in the given text.
+    /// Find all matches for the search dictionary in the given text, which can be borrowed
+    /// (`&str`) or owned (`String`). The text is only copied if the search options require
+    /// transforming it (e.g. lowercasing a text with uppercase characters).
     ///
     /// Example:
     /// ```rust
@@ -88,7 +409,7 @@ impl TrieRoot {
     ///     String::from("Cxaabb"),
     /// ]);
     /// let search_tree = trie::create_prefix_tree(search_dictionary, None).unwrap();
-    /// let haystack = String::from("This is a string with some nonsense to check: abbaaCxa bCdbCxbb");
+    /// let haystack = "This is a string with some nonsense to check: abbaaCxa bCdbCxbb";
     /// let matches = search_tree.find_text_matches(haystack).unwrap();
     ///
     /// for m in matches {
@@ -97,74 +418,384 @@ impl TrieRoot {
     ///    println!("Found matching string '{value}' in characters {start}-{end}");
     /// }
     /// ```
-    pub fn find_text_matches<'a>(&'a self, mut text: String) -> SearchResult<Vec<Match<'a>>> {
-        let mut char_buffer = RingBuffer::new(self.max_pattern_len + 2);
-        if !self.options.case_sensitive {
-            text = text.to_lowercase();
-        };
+    pub fn find_text_matches<'a, T: AsRef<str>>(&'a self, text: T) -> SearchResult<Vec<Match<'a>>> {
+        self.find_matches_filtered(text.as_ref(), 0, Interrupt::default())
+    }
 
-        let mut matches: Vec<Match> = Vec::new();
-        let root_id = self.root_node_id();
+    /// Find all matches for the search dictionary in the given text, ignoring the patterns
+    /// shorter than `min_len` characters.
+    ///
+    /// This allows using the same prefix tree for queries that require different levels of
+    /// precision without rebuilding it. The length of a pattern is its number of characters
+    /// after normalization, not counting the separators that are ignored by the search options.
+    ///
+    /// Example:
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("abcd")]);
+    /// let search_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let matches = search_tree.find_text_matches_min_len(String::from("abcd ab"), 3).unwrap();
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].value(), "abcd");
+    /// ```
+    pub fn find_text_matches_min_len<'a, T: AsRef<str>>(
+        &'a self,
+        text: T,
+        min_len: usize,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        self.find_matches_filtered(text.as_ref(), min_len, Interrupt::default())
+    }
+
+    /// Find all matches for the search dictionary in the given text, giving up if the search
+    /// is not done by the given deadline. Returns a `SearchError::Timeout` error in that case.
+    ///
+    /// The deadline is checked periodically while scanning the text, so the search may run
+    /// slightly past it. The transformations applied to the text before scanning it (e.g.
+    /// lowercasing) are not interrupted.
+    ///
+    /// Example:
+    /// ```rust
+    /// use ac_search_rs::trie::{self, SearchError};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+    /// let search_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(1);
+    /// let matches = search_tree.find_text_matches_until(String::from("abab"), deadline).unwrap();
+    /// assert_eq!(matches.len(), 2);
+    ///
+    /// let expired = search_tree.find_text_matches_until(String::from("abab"), Instant::now());
+    /// assert_eq!(expired.err(), Some(SearchError::Timeout));
+    /// ```
+    pub fn find_text_matches_until<'a, T: AsRef<str>>(
+        &'a self,
+        text: T,
+        deadline: Instant,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        self.find_matches_filtered(text.as_ref(), 0, Interrupt::until(Some(deadline)))
+    }
 
-        let mut curr_id = root_id;
-        let mut current = self.root_node();
+    /// Find all matches in the text, ignoring the patterns shorter than `min_len` characters
+    /// and giving up with an error if the search is interrupted before it is done.
+    pub(crate) fn find_matches_filtered<'a>(
+        &'a self,
+        text: &str,
+        min_len: usize,
+        interrupt: Interrupt,
+    ) -> SearchResult<Vec<Match<'a>>> {
+        let prepared = self.prepare_text(text);
+        let mut output = self.scan_text(&prepared, min_len, interrupt)?;
+        if let Some(e) = output.interrupted {
+            return Err(e);
+        }
+        if output.truncated {
+            log::warn!("Some matches were skipped due to the maximum chain depth");
+        }
+        self.finish_matches(&mut output.matches, &prepared);
+        Ok(output.matches)
+    }
+
+    /// Map the ranges of the matches found in a prepared text back to the original text, and
+    /// compute their scores and lines if required by the search options.
+    pub(crate) fn finish_matches(&self, matches: &mut [Match], prepared: &PreparedText) {
+        self.remap_matches(matches, prepared);
+        let original = prepared.original.unwrap_or(&prepared.text);
+        if self.options.compute_scores {
+            self.score_matches(matches, original);
+        }
+        if self.options.track_lines {
+            LineTracker::new().locate(matches, original);
+        }
+    }
 
-        let mut chars_iter = text.chars().peekable();
-        let mut idx: usize = 0;
-        while let Some(ch) = chars_iter.next() {
-            // Buffer updates
-            if self.options.check_bounds {
-                char_buffer.push(ch);
+    /// Call a function for each match in the text as soon as it is found, instead of
+    /// collecting all the matches first. The search stops early if the function returns
+    /// false. Returns whether the whole text was searched.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let mut first = None;
+    /// let finished = prefix_tree
+    ///     .for_each_match(String::from("xab ab ab"), |m| {
+    ///         first = Some(m.char_range());
+    ///         false
+    ///     })
+    ///     .unwrap();
+    /// assert!(!finished);
+    /// assert_eq!(first, Some((1, 3)));
+    /// ```
+    pub fn for_each_match<'a, T, F>(&'a self, text: T, mut callback: F) -> SearchResult<bool>
+    where
+        T: AsRef<str>,
+        F: FnMut(Match<'a>) -> bool,
+    {
+        let mut matches = MatchIter::new(self, text.as_ref());
+        while let Some(m) = matches.next_match()? {
+            if !callback(m) {
+                return Ok(false);
             }
+        }
+        Ok(true)
+    }
 
-            // Node does not have link with the required char - try failovers
-            // until node found or root reached
-            while curr_id != root_id
-                && let None = current.follow_link(ch)
+    /// Map the ranges of the matches found in a prepared text back to the original text.
+    #[inline]
+    pub(crate) fn remap_matches(&self, matches: &mut [Match], prepared: &PreparedText) {
+        if prepared.original.is_some() {
+            for m in matches.iter_mut() {
+                (m.start, m.end) = prepared.original_range(m.start, m.end);
+                (m.start_byte, m.end_byte) = prepared.original_byte_range(m.start, m.end);
+            }
+        }
+    }
+
+    /// Find all matches in a text that has already been prepared for search, ignoring the patterns
+    /// shorter than `min_len` characters. Also returns whether the matches were truncated at some
+    /// position due to the `max_chain_depth` option. The scan stops once the deadline is exceeded
+    /// or the search is cancelled, returning the matches found so far. The matches are selected
+    /// according to the `match_kind` search option, after discarding those without the exact case
+    /// their pattern requires.
+    pub(crate) fn scan_text<'a>(
+        &'a self,
+        prepared: &PreparedText,
+        min_len: usize,
+        interrupt: Interrupt,
+    ) -> SearchResult<ScanOutput<'a>> {
+        let limits = &self.options.limits;
+        limits.check_text(prepared.original.unwrap_or(&prepared.text))?;
+        let interrupt = interrupt.with_time_limit(limits.time_limit);
+        let text = prepared.text.as_ref();
+        let mut output = match &self.single_pattern {
+            Some(single) => self.scan_single(single, text, min_len, interrupt)?,
+            None => self.scan_automaton(text, min_len, interrupt)?,
+        };
+        self.retain_exact_case(&mut output.matches, prepared);
+        output.matches = self.select_matches(output.matches);
+        Ok(output)
+    }
+
+    /// Find all the (possibly overlapping) matches in a prepared text with the automaton of
+    /// the tree. See `scan_text`.
+    fn scan_automaton<'a>(
+        &'a self,
+        text: &str,
+        min_len: usize,
+        interrupt: Interrupt,
+    ) -> SearchResult<ScanOutput<'a>> {
+        let mut state = ScanState::new(self);
+        let mut matches: Vec<Match> = Vec::new();
+
+        let mut chars_iter = text.char_indices().enumerate().peekable();
+        let mut prev: Option<char> = None;
+        while let Some((pos, (byte, ch))) = chars_iter.next() {
+            if pos % DEADLINE_CHECK_INTERVAL == 0
+                && let Some(e) = interrupt.check()
             {
-                match current.fail_node() {
-                    None => return Err(SearchError::MissingLink(curr_id)),
-                    Some(nid) => {
-                        curr_id = nid;
-                        current = self.get_node_unchecked(nid);
-                    }
-                }
+                return Ok(ScanOutput {
+                    matches,
+                    truncated: state.truncated,
+                    interrupted: Some(e),
+                });
             }
+            let nxt_ch: Option<char> = chars_iter.peek().map(|&(_, (_, c))| c);
+            let read = ReadChar {
+                ch,
+                pos,
+                byte,
+                prev,
+            };
+            self.scan_char(&mut state, read, nxt_ch, text, min_len, &mut matches)?;
+            self.options.limits.check_matches(matches.len())?;
+            prev = Some(ch);
+        }
+
+        Ok(ScanOutput {
+            matches,
+            truncated: state.truncated,
+            interrupted: None,
+        })
+    }
 
-            // Move to node if edge available. Now we are at a node with the
-            // right last character or at root.
-            if let Some(nid) = current.follow_link(ch) {
-                curr_id = nid;
-                current = self.get_node_unchecked(nid);
+    /// Check if a match of the pattern at the given node is word bounded as required by the
+    /// search options or the pattern's own setting, given its first and last characters, its
+    /// end byte and the character following it. The searched text is needed to apply word
+    /// segmentation; without it, only the neighbouring characters are checked.
+    #[inline]
+    #[cfg_attr(not(feature = "segmentation"), allow(unused_variables))]
+    pub(crate) fn is_match_bounded(
+        &self,
+        node_id: NodeId,
+        first: &ReadChar,
+        last: char,
+        end_byte: usize,
+        next_char: Option<char>,
+        text: Option<&str>,
+    ) -> bool {
+        let check = match self.bounds_override.get(&node_id) {
+            Some(&check) => check,
+            None => self.options.check_bounds,
+        };
+        if !check {
+            return true;
+        }
+        match text {
+            #[cfg(feature = "segmentation")]
+            Some(text) if self.options.word_segmentation => {
+                is_segment_boundary(text, first.byte) && is_segment_boundary(text, end_byte)
             }
+            _ => is_word_bounded(first, last, next_char),
+        }
+    }
 
-            // Check for matches
-            let mut check_id = curr_id;
-            while check_id != root_id {
-                let check = self.get_node_unchecked(check_id);
-                if let Some((value, keyword)) = check.value_keyword() {
-                    let m = Match::new(value, keyword, idx + 1, check.pattern_len);
-                    let nxt_ch: Option<char> = chars_iter.peek().copied();
+    /// Process the next character of a prepared text, adding the matches that end at it to
+    /// the output vector. `next` is the character following it in the text.
+    #[inline]
+    pub(crate) fn scan_char<'a>(
+        &'a self,
+        state: &mut ScanState,
+        read: ReadChar,
+        next: Option<char>,
+        text: &str,
+        min_len: usize,
+        out: &mut Vec<Match<'a>>,
+    ) -> SearchResult<()> {
+        if !self.skip_char(read.prev, read.ch, next) {
+            state.buffer.push(read);
+            state.node_id = self.next_node_id(state.node_id, read.ch)?;
+            state.truncated |= self.push_node_matches(
+                state.node_id,
+                &state.buffer,
+                next,
+                Some(text),
+                min_len,
+                out,
+            );
+        }
+        Ok(())
+    }
 
-                    if (!self.options.check_bounds) || is_word_bounded(&m, &char_buffer, nxt_ch) {
-                        matches.push(m);
-                    }
+    /// Return whether a character of the text must be skipped by the automaton according to
+    /// the search options, given the characters preceding and following it.
+    #[inline]
+    pub(crate) fn skip_char(&self, prev: Option<char>, ch: char, next: Option<char>) -> bool {
+        (self.options.ignore_separators && is_separator(ch))
+            || (ch == ' ' && prev == Some(' ') && self.options.whitespace != WhitespaceMatch::Exact)
+            || (self.options.ignore_digit_separators
+                && is_digit_separator(ch)
+                && prev.is_some_and(|c| c.is_ascii_digit())
+                && next.is_some_and(|c| c.is_ascii_digit()))
+    }
+
+    /// Move from the given node to the next one given the next character of the text.
+    ///
+    /// If the node does not have a link with the required character, failure links are followed
+    /// until a node with the link is found or the root is reached, unless the dense transition
+    /// table of the automaton has been compiled.
+    pub(crate) fn next_node_id(&self, mut curr_id: NodeId, ch: char) -> SearchResult<NodeId> {
+        if let Some(dfa) = &self.dfa {
+            return Ok(dfa.next(curr_id, ch));
+        }
+        let root_id = self.root_node_id();
+        let mut current = self.get_node_unchecked(curr_id);
+        while curr_id != root_id
+            && let None = current.follow_link(ch)
+        {
+            match current.fail_node() {
+                None => return Err(SearchError::MissingLink(curr_id)),
+                Some(nid) => {
+                    curr_id = nid;
+                    current = self.get_node_unchecked(nid);
                 }
-                check_id = check.fail_dct().unwrap_or(root_id);
             }
-            idx += 1;
         }
 
-        Ok(matches)
+        // Move to node if edge available. Now we are at a node with the
+        // right last character or at root.
+        Ok(current.follow_link(ch).unwrap_or(curr_id))
+    }
+
+    /// Add the matches that end at the given node to the output vector.
+    ///
+    /// The dictionary links are followed from the node to find all the patterns that end
+    /// at the current position of the text. `buffer` holds the last characters read by the
+    /// automaton and `next_char` is the character following them in the text. These are used
+    /// to find the range of the matches and check word bounds. If the full searched text is
+    /// given, the context rules of the patterns are checked as well. Patterns shorter than
+    /// `min_len` characters are ignored. Returns true if some patterns were left unchecked due
+    /// to the `max_chain_depth` option.
+    pub(crate) fn push_node_matches<'a>(
+        &'a self,
+        node_id: NodeId,
+        buffer: &RingBuffer<ReadChar>,
+        next_char: Option<char>,
+        text: Option<&str>,
+        min_len: usize,
+        out: &mut Vec<Match<'a>>,
+    ) -> bool {
+        let root_id = self.root_node_id();
+        let last = *buffer.back().expect("Buffer is empty");
+        let max_depth = self.options.max_chain_depth.unwrap_or(usize::MAX);
+        let mut depth = 0;
+        let mut check_id = node_id;
+        while check_id != root_id {
+            let check = self.get_node_unchecked(check_id);
+            if let Some((value, kw)) = check.value_keyword() {
+                // Dictionary links lead to ever shorter patterns
+                if check.pattern_len() < min_len {
+                    break;
+                }
+                if depth == max_depth {
+                    return true;
+                }
+                depth += 1;
+                let first = *buffer.get_item(buffer.len() - check.pattern_len());
+                let end_byte = last.byte + last.ch.len_utf8();
+                let pattern_node = check.pattern_node_id().unwrap_or(check_id);
+                let bounded =
+                    self.is_match_bounded(pattern_node, &first, last.ch, end_byte, next_char, text);
+                let allowed =
+                    text.is_none_or(|t| self.context_allows(pattern_node, t, first.byte, end_byte));
+                if bounded && allowed {
+                    if let Some(coverage) = &self.coverage {
+                        coverage.record(pattern_node);
+                    }
+                    out.push(Match {
+                        start: first.pos,
+                        end: last.pos + 1,
+                        value,
+                        kw,
+                        score: None,
+                        category: check.category(),
+                        metadata: check.metadata(),
+                        payload: PayloadSlot(check.payload()),
+                        start_byte: first.byte,
+                        end_byte,
+                        pattern_id: check.pattern_id().unwrap_or_default(),
+                        priority: check.priority(),
+                        line_col: None,
+                    });
+                }
+            }
+            check_id = check.fail_dct().unwrap_or(root_id);
+        }
+        false
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::normalize::nfc;
     use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
     use super::*;
     use rand::{Rng, distr::Alphanumeric};
-    use unicode_normalization::UnicodeNormalization;
 
     /// Make a sample tree for the dictionary {ab, abc, cd}
     fn sample_tree_1() -> TrieRoot {
@@ -192,7 +823,7 @@ mod tests {
         let pref_tree = sample_tree_1();
         let sample = "123 a ab c d cd bc abc";
 
-        let mut matches = dbg!(pref_tree.find_text_matches(sample.to_string()).unwrap());
+        let mut matches = dbg!(pref_tree.find_text_matches(sample).unwrap());
         matches.sort();
         // Expect 4 matches
         assert_eq!(matches.len(), 4);
@@ -219,10 +850,88 @@ mod tests {
     fn test_search_no_matches() {
         let pref_tree = sample_tree_1();
         let sample = "123 x, y aBcD wXyAb dc";
-        let matches = dbg!(pref_tree.find_text_matches(sample.to_string()).unwrap());
+        let matches = dbg!(pref_tree.find_text_matches(sample).unwrap());
         assert!(matches.is_empty());
     }
 
+    #[test]
+    fn test_match_byte_range() {
+        #[cfg(feature = "unicode")]
+        let texts = ["\u{212a}ELVIN, une École", "ÉCOLE kelvin", "ñ e\u{301}cole"];
+        #[cfg(not(feature = "unicode"))]
+        let texts = ["\u{212a}ELVIN, une École", "ÉCOLE kelvin"];
+
+        for patterns in [vec!["kelvin", "école"], vec!["école"]] {
+            let pt = create_prefix_tree(
+                add_keyword_slot(patterns.iter().map(|p| p.to_string()).collect()),
+                Some(SearchOptions {
+                    case_sensitive: false,
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+            for text in &texts {
+                let char_slice = |m: &Match| -> String {
+                    let (start, end) = m.char_range();
+                    text.chars().skip(start).take(end - start).collect()
+                };
+                let matches = pt.find_text_matches(text).unwrap();
+                assert!(!matches.is_empty());
+                for m in matches
+                    .iter()
+                    .chain(&pt.match_iter(text).collect::<Vec<_>>())
+                {
+                    assert_eq!(m.matched_text(text), char_slice(m), "Text {text:?}");
+                }
+                let first = pt
+                    .longest_match_at(text, matches[0].char_range().0)
+                    .unwrap();
+                assert_eq!(first.byte_range(), matches[0].byte_range());
+            }
+        }
+
+        // Stream matches are located from the start of the stream
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("né")]), None).unwrap();
+        let mut searcher = pt.stream_searcher();
+        let mut matches = searcher.feed("a né n").unwrap();
+        matches.append(&mut searcher.feed("é").unwrap());
+        matches.append(&mut searcher.finish().unwrap());
+        let ranges: Vec<Range<usize>> = matches.iter().map(|m| m.byte_range()).collect();
+        assert_eq!(ranges, [2..5, 6..9]);
+    }
+
+    #[test]
+    fn test_match_pattern_id() {
+        let dictionary = vec![
+            (String::from("abc"), Some(String::from("X"))),
+            (String::from("ab"), Some(String::from("X"))),
+            (String::from("Bc"), None),
+        ];
+        let pt = create_prefix_tree(
+            dictionary,
+            Some(SearchOptions {
+                case_sensitive: false,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let ids: Vec<PatternId> = pt
+            .find_text_matches("xabc")
+            .unwrap()
+            .iter()
+            .map(|m| m.pattern_id())
+            .collect();
+        assert_eq!(ids, [1, 0, 2]);
+        assert_eq!(pt.pattern(0), Some(("abc", "X")));
+        assert_eq!(pt.pattern(2), Some(("bc", "bc")));
+        assert_eq!(pt.pattern(3), None);
+
+        let single = create_prefix_tree(add_keyword_slot(vec![String::from("bc")]), None).unwrap();
+        assert_eq!(single.find_text_matches("abc").unwrap()[0].pattern_id(), 0);
+        assert_eq!(pt.longest_match_at("ab", 0).unwrap().pattern_id(), 1);
+    }
+
     #[test]
     fn test_search_random_string() {
         let haystack = random_string(8192);
@@ -308,6 +1017,7 @@ mod tests {
             Some(SearchOptions {
                 check_bounds: false,
                 case_sensitive: false,
+                ..Default::default()
             }),
         )
         .unwrap();
@@ -349,16 +1059,17 @@ mod tests {
             Some(SearchOptions {
                 case_sensitive: true,
                 check_bounds: true,
+                ..Default::default()
             }),
         )
         .unwrap();
 
         // No word bounds around patterns
-        let matches = dbg!(pt.find_text_matches("abp pabc bcdefg abhx cab".to_string())).unwrap();
+        let matches = dbg!(pt.find_text_matches("abp pabc bcdefg abhx cab")).unwrap();
         assert_eq!(matches.len(), 0);
 
         // Word bounds around patterns
-        let mut matches = dbg!(pt.find_text_matches("abc. -bcd- AB def".to_string())).unwrap();
+        let mut matches = dbg!(pt.find_text_matches("abc. -bcd- AB def")).unwrap();
         assert_eq!(matches.len(), 3);
         matches.sort();
 
@@ -380,11 +1091,12 @@ mod tests {
             Some(SearchOptions {
                 case_sensitive: true,
                 check_bounds: true,
+                ..Default::default()
             }),
         )
         .unwrap();
 
-        let hs = "abc-ábc: xyzo!xyzò äbc-".nfc().collect();
+        let hs: String = nfc("abc-ábc: xyzo!xyzò äbc-".chars()).collect();
         let matches = dbg!(pt.find_text_matches(hs)).unwrap();
         assert_eq!(matches.len(), 4);
 
@@ -408,10 +1120,11 @@ mod tests {
             Some(SearchOptions {
                 case_sensitive: false,
                 check_bounds: true,
+                ..Default::default()
             }),
         )
         .unwrap();
-        let hs = "TEXT: ÁBC_3 Yöyyiaa, ABc, XYzò YÖyyi".nfc().collect();
+        let hs: String = nfc("TEXT: ÁBC_3 Yöyyiaa, ABc, XYzò YÖyyi".chars()).collect();
         let matches = pt.find_text_matches(hs).unwrap();
         assert_eq!(matches.len(), 3);
 
@@ -419,4 +1132,305 @@ mod tests {
         assert_eq!(matches[1].kw, "xyzo-accent");
         assert_eq!(matches[2].kw, "Yoyyi");
     }
+
+    #[test]
+    fn test_search_ignore_separators() {
+        let dct = vec![
+            (String::from("e-mail"), None),
+            (String::from("New York"), Some(String::from("NY"))),
+        ];
+        let pt = create_prefix_tree(
+            dct,
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds: true,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let hs = "Email, e-mail or e mail: to NEWYORK, new-york, new\nyork. Not remail";
+        let matches = dbg!(pt.find_text_matches(hs)).unwrap();
+        assert_eq!(matches.len(), 6);
+
+        let hs_chars: Vec<char> = hs.chars().collect();
+        let found: Vec<String> = matches
+            .iter()
+            .map(|m| hs_chars[m.start..m.end].iter().collect())
+            .collect();
+        assert_eq!(
+            found,
+            [
+                "Email",
+                "e-mail",
+                "e mail",
+                "NEWYORK",
+                "new-york",
+                "new\nyork"
+            ]
+        );
+        for m in &matches[..3] {
+            assert_eq!(m.value(), "email");
+            assert_eq!(m.keyword(), "e-mail");
+        }
+        for m in &matches[3..] {
+            assert_eq!(m.value(), "newyork");
+            assert_eq!(m.keyword(), "NY");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn test_search_offsets_unnormalized() {
+        let dct = vec![
+            (String::from("café"), None),
+            (String::from("İstanbul"), Some(String::from("Istanbul"))),
+        ];
+        let pt = create_prefix_tree(
+            dct,
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        // Decomposed accents and characters with multi-character lowercase forms, which are
+        // case folded to a single character
+        let hs = "Cafe\u{301} in İSTANBUL, cafe\u{301}s";
+        let matches = dbg!(pt.find_text_matches(hs)).unwrap();
+        assert_eq!(matches.len(), 2);
+
+        let hs_chars: Vec<char> = hs.chars().collect();
+        let found: Vec<String> = matches
+            .iter()
+            .map(|m| hs_chars[m.start..m.end].iter().collect())
+            .collect();
+        assert_eq!(found, ["Cafe\u{301}", "İSTANBUL"]);
+        assert_eq!(matches[1].keyword(), "Istanbul");
+    }
+
+    #[test]
+    fn test_search_ignore_digit_separators() {
+        let dct = vec![
+            (String::from("10000"), None),
+            (String::from("1,500"), Some(String::from("1500"))),
+        ];
+        let pt = create_prefix_tree(
+            dct,
+            Some(SearchOptions {
+                check_bounds: true,
+                ignore_digit_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let hs = "10,000 10 000 10000 1500 1'500, 10.000 110,000 10,0000";
+        let matches = dbg!(pt.find_text_matches(hs)).unwrap();
+        let hs_chars: Vec<char> = hs.chars().collect();
+        let found: Vec<String> = matches
+            .iter()
+            .map(|m| hs_chars[m.start..m.end].iter().collect())
+            .collect();
+        assert_eq!(found, ["10,000", "10 000", "10000", "1500", "1'500"]);
+        assert_eq!(matches[4].keyword(), "1500");
+
+        // Separators next to digit separators, which are not between two digits
+        for (pattern, opts) in [
+            (
+                "1, 000",
+                SearchOptions {
+                    ignore_separators: true,
+                    ignore_digit_separators: true,
+                    ..Default::default()
+                },
+            ),
+            (
+                "1  000",
+                SearchOptions {
+                    whitespace: WhitespaceMatch::Flexible,
+                    ignore_digit_separators: true,
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let pt = create_prefix_tree(add_keyword_slot(vec![pattern.to_string()]), Some(opts))
+                .unwrap();
+            let matches = pt.find_text_matches(pattern).unwrap();
+            assert_eq!(matches.len(), 1);
+            assert_eq!(matches[0].char_range(), (0, 6));
+        }
+    }
+
+    #[test]
+    fn test_search_bounded_unsegmented_scripts() {
+        let dct = vec![
+            (String::from("北京"), Some(String::from("Beijing"))),
+            (String::from("ภาษา"), Some(String::from("language"))),
+            (String::from("abc"), None),
+        ];
+        let pt = create_prefix_tree(
+            dct,
+            Some(SearchOptions {
+                check_bounds: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let hs = "我爱北京天安门. ภาษาไทย. 東京abc, xabc";
+        let matches = dbg!(pt.find_text_matches(hs)).unwrap();
+        let keywords: Vec<&str> = matches.iter().map(|m| m.keyword()).collect();
+        assert_eq!(keywords, ["Beijing", "language", "abc"]);
+    }
+
+    #[cfg(feature = "segmentation")]
+    #[test]
+    fn test_search_word_segmentation() {
+        let words =
+            |words: &[&str]| add_keyword_slot(words.iter().map(|w| w.to_string()).collect());
+        let opts = |word_segmentation| SearchOptions {
+            check_bounds: true,
+            word_segmentation,
+            ..Default::default()
+        };
+        let dct = ["don", "3", "cat", "北京", "テス"];
+        let hs = "I don't, 3.14 cats. A cat! 北京 テスト 3";
+        for (word_segmentation, expected) in [
+            (false, vec!["don", "3", "cat", "北京", "テス", "3"]),
+            (true, vec!["cat", "北京", "3"]),
+        ] {
+            let pt = create_prefix_tree(words(&dct), Some(opts(word_segmentation))).unwrap();
+            let values: Vec<&str> = pt
+                .find_text_matches(hs)
+                .unwrap()
+                .iter()
+                .map(|m| m.value())
+                .collect();
+            assert_eq!(values, expected, "Segmentation: {word_segmentation}");
+        }
+
+        // Single pattern trees and anchored matches check bounds in the same way
+        let pt = create_prefix_tree(words(&["don"]), Some(opts(true))).unwrap();
+        assert!(pt.find_text_matches("don't").unwrap().is_empty());
+        assert_eq!(pt.find_text_matches("don, don't").unwrap().len(), 1);
+        assert!(pt.longest_match_at("don't", 0).is_none());
+    }
+
+    #[test]
+    fn test_search_min_len() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("a"),
+                String::from("ab"),
+                String::from("b-cd"),
+                String::from("abcd"),
+            ]),
+            Some(SearchOptions {
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let hs = String::from("abcd ab bcd");
+        assert_eq!(pt.find_text_matches(hs.clone()).unwrap().len(), 7);
+
+        let values: Vec<&str> = pt
+            .find_text_matches_min_len(hs.clone(), 3)
+            .unwrap()
+            .iter()
+            .map(|m| m.value())
+            .collect();
+        assert_eq!(values, ["abcd", "bcd", "bcd"]);
+
+        assert!(pt.find_text_matches_min_len(hs, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_max_chain_depth() {
+        let dct = add_keyword_slot(vec![
+            String::from("a"),
+            String::from("aa"),
+            String::from("aaa"),
+            String::from("aaaa"),
+        ]);
+        let pt = create_prefix_tree(
+            dct,
+            Some(SearchOptions {
+                max_chain_depth: Some(2),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+        let found: Vec<&str> = pt
+            .find_text_matches(String::from("aaa"))
+            .unwrap()
+            .iter()
+            .map(|m| m.value())
+            .collect();
+        assert_eq!(found, ["a", "aa", "a", "aaa", "aa"]);
+
+        let outcome = pt.search(String::from("aaa")).unwrap();
+        assert!(outcome.truncated());
+        assert!(!pt.search(String::from("aab")).unwrap().truncated());
+    }
+
+    #[test]
+    fn test_search_deadline() {
+        let pt = sample_tree_1();
+        let hs = "abc".repeat(DEADLINE_CHECK_INTERVAL);
+
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        let found = pt.find_text_matches_until(hs.clone(), deadline).unwrap();
+        assert_eq!(found.len(), 2 * DEADLINE_CHECK_INTERVAL);
+
+        let res = pt.find_matches_filtered(&hs, 0, Interrupt::until(Some(Instant::now())));
+        assert!(matches!(res, Err(SearchError::Timeout)));
+    }
+
+    #[test]
+    fn test_for_each_match() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("straße"), String::from("ab")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                compute_scores: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let hs = "İ STRAßE ab Ab";
+
+        let mut found = Vec::new();
+        let finished = pt
+            .for_each_match(String::from(hs), |m| {
+                found.push((m.char_range(), m.value(), m.score()));
+                true
+            })
+            .unwrap();
+        assert!(finished);
+
+        let expected: Vec<_> = pt
+            .find_text_matches(String::from(hs))
+            .unwrap()
+            .iter()
+            .map(|m| (m.char_range(), m.value(), m.score()))
+            .collect();
+        assert_eq!(found, expected);
+
+        let mut total = 0;
+        let finished = pt
+            .for_each_match(String::from(hs), |_| {
+                total += 1;
+                total < 2
+            })
+            .unwrap();
+        assert!(!finished);
+        assert_eq!(total, 2);
+    }
 }
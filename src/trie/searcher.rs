@@ -0,0 +1,126 @@
+use super::{Match, SearchResult, TrieRoot};
+
+/// Common interface of the search automatons.
+///
+/// Code that only needs to find the patterns of a dictionary in texts can be written against
+/// this trait instead of `TrieRoot`, so that other backends (e.g. more compact or faster
+/// automatons) can be swapped in without changing it. Only `find_matches` and
+/// `total_patterns` are required; the other methods have default implementations based on
+/// them that backends can override with faster ones.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, Searcher};
+///
+/// fn flag_texts<S: Searcher>(searcher: &S, texts: &[&str]) -> Vec<bool> {
+///     texts.iter().map(|t| searcher.contains_any(t).unwrap()).collect()
+/// }
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("spam")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// assert_eq!(flag_texts(&prefix_tree, &["buy spam", "hello"]), [true, false]);
+/// ```
+pub trait Searcher {
+    /// Find all matches for the search dictionary in the text.
+    fn find_matches<'a>(&'a self, text: &str) -> SearchResult<Vec<Match<'a>>>;
+
+    /// Total number of patterns of the search dictionary.
+    fn total_patterns(&self) -> usize;
+
+    /// Count the matches of the search dictionary in the text. The count is the number of
+    /// matches `find_matches` would return.
+    fn count_matches(&self, text: &str) -> SearchResult<usize> {
+        Ok(self.find_matches(text)?.len())
+    }
+
+    /// Return whether the search dictionary has any match in the text.
+    fn contains_any(&self, text: &str) -> SearchResult<bool> {
+        Ok(!self.find_matches(text)?.is_empty())
+    }
+}
+
+impl Searcher for TrieRoot {
+    fn find_matches<'a>(&'a self, text: &str) -> SearchResult<Vec<Match<'a>>> {
+        self.find_text_matches(text)
+    }
+
+    fn total_patterns(&self) -> usize {
+        TrieRoot::total_patterns(self)
+    }
+
+    fn count_matches(&self, text: &str) -> SearchResult<usize> {
+        TrieRoot::count_matches(self, text)
+    }
+
+    fn contains_any(&self, text: &str) -> SearchResult<bool> {
+        TrieRoot::contains_any(self, text)
+    }
+}
+
+impl<S: Searcher + ?Sized> Searcher for &S {
+    fn find_matches<'a>(&'a self, text: &str) -> SearchResult<Vec<Match<'a>>> {
+        (**self).find_matches(text)
+    }
+
+    fn total_patterns(&self) -> usize {
+        (**self).total_patterns()
+    }
+
+    fn count_matches(&self, text: &str) -> SearchResult<usize> {
+        (**self).count_matches(text)
+    }
+
+    fn contains_any(&self, text: &str) -> SearchResult<bool> {
+        (**self).contains_any(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MatchKind, SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    /// A backend implementing only the required methods
+    struct Naive(TrieRoot);
+
+    impl Searcher for Naive {
+        fn find_matches<'a>(&'a self, text: &str) -> SearchResult<Vec<Match<'a>>> {
+            self.0.find_text_matches(text)
+        }
+
+        fn total_patterns(&self) -> usize {
+            self.0.total_patterns()
+        }
+    }
+
+    #[test]
+    fn test_searcher_backends() {
+        let build = || {
+            create_prefix_tree(
+                add_keyword_slot(vec![String::from("ab"), String::from("b")]),
+                Some(SearchOptions {
+                    match_kind: MatchKind::LeftmostLongest,
+                    ..Default::default()
+                }),
+            )
+            .unwrap()
+        };
+        let pt = build();
+        let naive = Naive(build());
+        let searchers: [&dyn Searcher; 2] = [&pt, &naive];
+        for searcher in searchers {
+            assert_eq!(searcher.total_patterns(), 2);
+            assert_eq!(searcher.count_matches("ab xb").unwrap(), 2);
+            assert!(searcher.contains_any("xb").unwrap());
+            assert!(!searcher.contains_any("xyz").unwrap());
+            let values: Vec<&str> = searcher
+                .find_matches("ab xb")
+                .unwrap()
+                .iter()
+                .map(|m| m.value())
+                .collect();
+            assert_eq!(values, ["ab", "b"]);
+        }
+    }
+}
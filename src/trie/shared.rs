@@ -0,0 +1,128 @@
+use super::{Match, SearchResult, Searcher, TrieRoot};
+use std::{ops::Deref, sync::Arc};
+
+// Searches only take a shared reference to the tree, and the only state they update (the
+// coverage record) is atomic, so a tree can be searched from several threads at once.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<TrieRoot>();
+    assert_send_sync::<SharedTrie>();
+};
+
+/// A cheaply cloneable handle to a prefix tree, to share a single built tree between threads,
+/// e.g. the request handlers of a web server. Cloning the handle doesn't copy the tree.
+///
+/// The handle dereferences to the tree, so all the search methods of `TrieRoot` can be called
+/// on it. The tree can't be modified while it is shared; use `ReloadableTrie` to replace it
+/// with a rebuilt one.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, SharedTrie};
+/// use std::thread;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("spam"), String::from("eggs")]);
+/// let shared = SharedTrie::new(trie::create_prefix_tree(dictionary, None).unwrap());
+///
+/// let handles: Vec<_> = ["spam and eggs", "eggs", "ham"]
+///     .into_iter()
+///     .map(|text| {
+///         let trie = shared.clone();
+///         thread::spawn(move || trie.find_text_matches(text).unwrap().len())
+///     })
+///     .collect();
+/// let counts: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+/// assert_eq!(counts, [2, 1, 0]);
+/// ```
+#[derive(Clone)]
+pub struct SharedTrie(Arc<TrieRoot>);
+
+impl SharedTrie {
+    /// Wrap a prefix tree to share it.
+    pub fn new(trie: TrieRoot) -> Self {
+        Self(Arc::new(trie))
+    }
+
+    /// Get the reference-counted pointer to the tree.
+    pub fn as_arc(&self) -> &Arc<TrieRoot> {
+        &self.0
+    }
+
+    /// Get the tree back if this is its only handle, or the handle itself otherwise.
+    pub fn try_unwrap(self) -> Result<TrieRoot, Self> {
+        Arc::try_unwrap(self.0).map_err(Self)
+    }
+}
+
+impl Deref for SharedTrie {
+    type Target = TrieRoot;
+
+    fn deref(&self) -> &TrieRoot {
+        &self.0
+    }
+}
+
+impl From<TrieRoot> for SharedTrie {
+    fn from(trie: TrieRoot) -> Self {
+        Self::new(trie)
+    }
+}
+
+impl From<Arc<TrieRoot>> for SharedTrie {
+    fn from(trie: Arc<TrieRoot>) -> Self {
+        Self(trie)
+    }
+}
+
+impl Searcher for SharedTrie {
+    fn find_matches<'a>(&'a self, text: &str) -> SearchResult<Vec<Match<'a>>> {
+        self.0.find_text_matches(text)
+    }
+
+    fn total_patterns(&self) -> usize {
+        self.0.total_patterns()
+    }
+
+    fn count_matches(&self, text: &str) -> SearchResult<usize> {
+        self.0.count_matches(text)
+    }
+
+    fn contains_any(&self, text: &str) -> SearchResult<bool> {
+        self.0.contains_any(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_shared_trie_threads() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("cd")]),
+            None,
+        )
+        .unwrap();
+        pt.track_coverage();
+        let shared = SharedTrie::from(pt);
+
+        thread::scope(|s| {
+            for text in ["xab", "abab", "ab cd"] {
+                let trie = shared.clone();
+                s.spawn(move || assert!(!trie.find_text_matches(text).unwrap().is_empty()));
+            }
+        });
+        assert_eq!(shared.never_matched(), Some(Vec::new()));
+        assert_eq!(Searcher::count_matches(&shared, "ab cd ab").unwrap(), 3);
+
+        let other = shared.clone();
+        let shared = shared.try_unwrap().unwrap_err();
+        drop(other);
+        assert_eq!(
+            shared.try_unwrap().ok().map(|t| t.total_patterns()),
+            Some(2)
+        );
+    }
+}
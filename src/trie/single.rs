@@ -0,0 +1,202 @@
+use super::{
+    Match, NodeId, ReadChar, SearchResult, TrieRoot, WhitespaceMatch,
+    cancel::Interrupt,
+    search::{DEADLINE_CHECK_INTERVAL, ScanOutput},
+};
+use memchr::memmem;
+
+/// Substring searcher used instead of the automaton when the dictionary has a single pattern.
+pub(crate) struct SinglePattern {
+    /// Node of the pattern in the tree
+    node_id: NodeId,
+
+    /// Searcher for the pattern bytes
+    finder: memmem::Finder<'static>,
+}
+
+impl SinglePattern {
+    /// Number of bytes allocated on the heap by the searcher, including its box
+    pub(crate) fn heap_bytes(&self) -> usize {
+        size_of::<Self>() + self.finder.needle().len()
+    }
+}
+
+impl TrieRoot {
+    /// Enable the substring search fast path if the tree has a single pattern and the search
+    /// options allow it. Ignored separators can't be handled by a plain substring search, so
+    /// the automaton is used with those options.
+    pub(crate) fn set_single_pattern(&mut self) {
+        self.single_pattern = None;
+        if self.options.ignore_separators
+            || self.options.ignore_digit_separators
+            || self.options.whitespace != WhitespaceMatch::Exact
+            || self.options.wildcard.is_some()
+        {
+            return;
+        }
+        if let [pattern] = self.patterns.as_slice() {
+            self.single_pattern = Some(Box::new(SinglePattern {
+                node_id: pattern.node_id as NodeId,
+                finder: memmem::Finder::new(pattern.value.as_bytes()).into_owned(),
+            }));
+        }
+    }
+
+    /// Find all the (possibly overlapping) occurrences of the single pattern of the tree in a
+    /// prepared text, producing the same output as `scan_text`.
+    pub(crate) fn scan_single<'a>(
+        &'a self,
+        single: &SinglePattern,
+        text: &str,
+        min_len: usize,
+        interrupt: Interrupt,
+    ) -> SearchResult<ScanOutput<'a>> {
+        let node = self.get_node_unchecked(single.node_id);
+        let mut output = ScanOutput {
+            matches: Vec::new(),
+            truncated: false,
+            interrupted: None,
+        };
+        let Some((value, kw)) = node.value_keyword() else {
+            return Ok(output);
+        };
+        if node.pattern_len() < min_len {
+            return Ok(output);
+        }
+        let truncated_all = self.options.max_chain_depth == Some(0);
+        if !text.is_empty() {
+            output.interrupted = interrupt.check();
+            if output.interrupted.is_some() {
+                return Ok(output);
+            }
+        }
+
+        // Character position of `char_byte`, advanced incrementally over the text
+        let (mut char_pos, mut char_byte) = (0, 0);
+        let mut from = 0;
+        let mut found_count: usize = 0;
+        while let Some(offset) = single.finder.find(&text.as_bytes()[from..]) {
+            found_count += 1;
+            if found_count.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+                output.interrupted = interrupt.check();
+                if output.interrupted.is_some() {
+                    return Ok(output);
+                }
+            }
+            if truncated_all {
+                output.truncated = true;
+                return Ok(output);
+            }
+
+            let start_byte = from + offset;
+            let end_byte = start_byte + value.len();
+            char_pos += text[char_byte..start_byte].chars().count();
+            char_byte = start_byte;
+
+            let first_ch = text[start_byte..].chars().next().unwrap_or_default();
+            let first = ReadChar {
+                ch: first_ch,
+                pos: char_pos,
+                byte: start_byte,
+                prev: text[..start_byte].chars().next_back(),
+            };
+            let last = text[..end_byte].chars().next_back().unwrap_or_default();
+            let next = text[end_byte..].chars().next();
+            let bounded =
+                self.is_match_bounded(single.node_id, &first, last, end_byte, next, Some(text));
+            if bounded && self.context_allows(single.node_id, text, start_byte, end_byte) {
+                if let Some(coverage) = &self.coverage {
+                    coverage.record(single.node_id);
+                }
+                output.matches.push(
+                    Match::new(value, kw, char_pos + node.pattern_len(), node.pattern_len())
+                        .with_category(node.category())
+                        .with_metadata(node.metadata())
+                        .with_payload(node.payload())
+                        .with_priority(node.priority())
+                        .with_byte_range(start_byte, end_byte)
+                        .with_pattern_id(node.pattern_id().unwrap_or_default()),
+                );
+                self.options.limits.check_matches(output.matches.len())?;
+            }
+            from = start_byte + first_ch.len_utf8();
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ContextRule, SearchOptions, add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_single_pattern_fast_path() {
+        for (pattern, text, check_bounds) in [
+            ("aa", "aaaa baa", false),
+            ("ab", "ab xab ab-ab", true),
+            ("né", "néné ñné né", false),
+            ("né", "néné ñné né", true),
+            ("x", "", false),
+        ] {
+            let opts = SearchOptions {
+                check_bounds,
+                case_sensitive: false,
+                ..Default::default()
+            };
+            let single = create_prefix_tree(
+                add_keyword_slot(vec![String::from(pattern)]),
+                Some(opts.clone()),
+            )
+            .unwrap();
+            assert!(single.single_pattern.is_some());
+
+            // Same tree searched with the automaton
+            let mut automaton =
+                create_prefix_tree(add_keyword_slot(vec![String::from(pattern)]), Some(opts))
+                    .unwrap();
+            automaton.single_pattern = None;
+
+            let found = single.find_text_matches(String::from(text)).unwrap();
+            let expected = automaton.find_text_matches(String::from(text)).unwrap();
+            assert_eq!(found, expected, "Pattern {pattern:?} in {text:?}");
+        }
+    }
+
+    #[test]
+    fn test_single_pattern_options() {
+        let mut pt = create_prefix_tree(add_keyword_slot(vec![String::from("ab")]), None).unwrap();
+        pt.add_context_rule("ab", ContextRule::NotPrecededBy(String::from("#")))
+            .unwrap();
+        let found = pt.find_text_matches(String::from("#ab ab")).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].char_range(), (4, 6));
+        assert!(
+            pt.find_text_matches_min_len(String::from("ab"), 3)
+                .unwrap()
+                .is_empty()
+        );
+
+        let separators = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab")]),
+            Some(SearchOptions {
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert!(separators.single_pattern.is_none());
+        assert_eq!(
+            separators
+                .find_text_matches(String::from("a-b"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_tree_alignment() {
+        // Python objects wrapping the tree are only aligned to 16 bytes
+        assert!(std::mem::align_of::<super::super::TrieRoot>() <= 16);
+    }
+}
@@ -0,0 +1,152 @@
+use super::{ContextRule, Link, NodeData, NodeId, PatternEntry, TrieRoot};
+use std::collections::{HashMap, VecDeque};
+
+/// Size and shape of a built prefix tree, as returned by `TrieRoot::stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrieStats {
+    /// Number of nodes, including the root
+    pub total_nodes: usize,
+
+    /// Number of edges between nodes, not counting the failure and dictionary links
+    pub total_edges: usize,
+
+    /// Number of patterns of the dictionary
+    pub total_patterns: usize,
+
+    /// Depth of the deepest node, i.e. the length in characters of the longest path from the
+    /// root
+    pub max_depth: usize,
+
+    /// Average depth of the nodes, with the root at depth 0
+    pub avg_depth: f64,
+
+    /// Largest number of edges leaving a single node
+    pub max_fan_out: usize,
+
+    /// Estimate of the number of bytes allocated on the heap by the tree, including the
+    /// patterns, per-pattern settings and compiled tables, but not the allocator overhead
+    pub heap_bytes: usize,
+}
+
+impl TrieRoot {
+    /// Get statistics of the size and shape of the tree, e.g. to plan the memory needed by
+    /// large dictionaries.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![
+    ///     String::from("ab"),
+    ///     String::from("abc"),
+    ///     String::from("b"),
+    /// ]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let stats = prefix_tree.stats();
+    /// assert_eq!((stats.total_nodes, stats.total_edges, stats.total_patterns), (5, 4, 3));
+    /// assert_eq!((stats.max_depth, stats.max_fan_out), (3, 2));
+    /// assert!(stats.heap_bytes > 0);
+    /// ```
+    pub fn stats(&self) -> TrieStats {
+        let mut depths = vec![0; self.nodes.len()];
+        let mut queue = VecDeque::from([self.root_node_id()]);
+        let mut max_fan_out = 0;
+        while let Some(node_id) = queue.pop_front() {
+            let next_nodes = self.get_node_unchecked(node_id).next_nodes();
+            max_fan_out = max_fan_out.max(next_nodes.len());
+            for link in next_nodes {
+                depths[link.get_node_id()] = depths[node_id] + 1;
+                queue.push_back(link.get_node_id());
+            }
+        }
+
+        TrieStats {
+            total_nodes: self.nodes.len(),
+            total_edges: self.links.len(),
+            total_patterns: self.patterns.len(),
+            max_depth: depths.iter().copied().max().unwrap_or(0),
+            avg_depth: depths.iter().sum::<usize>() as f64 / depths.len() as f64,
+            max_fan_out,
+            heap_bytes: self.heap_bytes(),
+        }
+    }
+
+    /// Estimate the number of bytes allocated on the heap by the tree
+    fn heap_bytes(&self) -> usize {
+        let patterns: usize = self
+            .patterns
+            .iter()
+            .map(|p| p.value.len() + p.keyword.as_ref().map_or(0, |k| k.len()))
+            .sum();
+        let categories: usize = self.categories.iter().map(|c| c.len()).sum();
+        let rules = |rules: &Vec<ContextRule>| {
+            rules.capacity() * size_of::<ContextRule>()
+                + rules
+                    .iter()
+                    .map(|rule| match rule {
+                        ContextRule::NotPrecededBy(s) | ContextRule::NotFollowedBy(s) => {
+                            s.capacity()
+                        }
+                    })
+                    .sum::<usize>()
+        };
+
+        self.nodes.capacity() * size_of::<NodeData>()
+            + self.links.capacity() * size_of::<Link>()
+            + self.patterns.capacity() * size_of::<PatternEntry>()
+            + patterns
+            + self.categories.capacity() * size_of::<Box<str>>()
+            + categories
+            + map_heap_bytes(&self.context_rules, rules)
+            + map_heap_bytes(&self.weights, |_| 0)
+            + map_heap_bytes(&self.cased_patterns, String::capacity)
+            + map_heap_bytes(&self.exact_case, String::capacity)
+            + map_heap_bytes(&self.bounds_override, |_| 0)
+            + map_heap_bytes(&self.metadata, String::capacity)
+            + map_heap_bytes(&self.payloads, |_| 0)
+            + map_heap_bytes(&self.priorities, |_| 0)
+            + self.coverage.as_ref().map_or(0, |c| c.heap_bytes())
+            + self.single_pattern.as_ref().map_or(0, |s| s.heap_bytes())
+            + self.dfa.as_ref().map_or(0, |d| d.heap_bytes())
+    }
+}
+
+/// Estimate the number of bytes allocated on the heap by a map of node settings: its table,
+/// with a control byte per entry, and the heap data of its values.
+fn map_heap_bytes<V>(map: &HashMap<NodeId, V>, value_bytes: impl Fn(&V) -> usize) -> usize {
+    map.capacity() * (size_of::<(NodeId, V)>() + 1) + map.values().map(value_bytes).sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+
+    #[test]
+    fn test_stats() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abc"),
+                String::from("b"),
+                String::from("bd"),
+            ]),
+            None,
+        )
+        .unwrap();
+        let stats = pt.stats();
+        assert_eq!(stats.total_nodes, 6);
+        assert_eq!(stats.total_edges, 5);
+        assert_eq!(stats.total_patterns, 4);
+        assert_eq!(stats.max_depth, 3);
+        assert_eq!(stats.max_fan_out, 2);
+        // Depths 0, 1, 2, 3, 1, 2
+        assert!((stats.avg_depth - 9.0 / 6.0).abs() < 1e-9);
+        assert!(stats.heap_bytes >= 6 * 24 + 5 * 8);
+
+        // Compiled tables are counted
+        pt.compile_dfa().unwrap();
+        assert!(pt.stats().heap_bytes > stats.heap_bytes);
+        assert_eq!(pt.stats().total_nodes, stats.total_nodes);
+    }
+}
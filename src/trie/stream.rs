@@ -0,0 +1,737 @@
+use super::normalize::is_nfc_boundary;
+use super::{
+    Match, NodeId, ReadChar, RingBuffer, SearchError, SearchResult, TrieRoot, WhitespaceMatch,
+};
+use std::cell::OnceCell;
+use std::io;
+
+/// Identifier written at the start of serialized checkpoints
+const CHECKPOINT_MAGIC: &[u8; 4] = b"ACCK";
+
+/// Version of the checkpoint binary format
+const CHECKPOINT_VERSION: u8 = 4;
+
+/// Value used to encode a missing held character in a serialized checkpoint
+const NO_CHAR: u32 = u32::MAX;
+
+/// Parameters of the FNV-1a hash used to fingerprint the trees in checkpoints
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Number of bytes read at a time by `TrieRoot::find_stream_matches`
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Incremental searcher that receives the text in successive pieces.
+///
+/// This is useful to scan inputs that are too large to load in memory at once. The text can be
+/// split at any character; matches crossing the boundaries between pieces are found as if the
+/// whole text had been searched in a single call. Match positions are relative to the start of
+/// the full stream.
+///
+/// The text is normalized as in the other searches, so the characters received after the last
+/// point where it can be cut without changing its normalization (e.g. a letter that may be
+/// followed by a combining accent, see also `Normalizer::is_boundary`) are held back until more
+/// text is known. The last character processed is also held back until the next one is known,
+/// since it is needed to check word bounds. Call `finish` once the input is exhausted to process
+/// them.
+///
+/// Context rules of the patterns are not evaluated by the stream searcher, since the text
+/// surrounding the matches may not be available.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie;
+///
+/// let dictionary = trie::add_keyword_slot(vec![String::from("abc"), String::from("cd")]);
+/// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+///
+/// let mut searcher = prefix_tree.stream_searcher();
+/// let mut matches = searcher.feed("xxab").unwrap();
+/// matches.append(&mut searcher.feed("cdxx").unwrap());
+/// matches.append(&mut searcher.finish().unwrap());
+/// assert_eq!(matches.len(), 2);
+/// ```
+pub struct StreamSearcher<'a> {
+    trie: &'a TrieRoot,
+    node_id: NodeId,
+    buffer: RingBuffer<ReadChar>,
+    prev: Option<char>,
+    held: Option<HeldChar>,
+    pending: String,
+    pending_pos: usize,
+    pending_byte: usize,
+    truncated: bool,
+    fingerprint: OnceCell<u64>,
+}
+
+/// A normalized character waiting for the following one to be processed, with the range of
+/// characters and bytes of the received text it comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HeldChar {
+    ch: char,
+    pos: usize,
+    byte: usize,
+    end: usize,
+    end_byte: usize,
+}
+
+impl<'a> StreamSearcher<'a> {
+    /// Process the next piece of the text, returning the matches that could be confirmed.
+    pub fn feed(&mut self, text: &str) -> SearchResult<Vec<Match<'a>>> {
+        let mut matches = Vec::new();
+        self.pending.push_str(text);
+        let cut = self
+            .pending
+            .char_indices()
+            .rev()
+            .find(|&(i, c)| i > 0 && self.is_boundary(c))
+            .map_or(0, |(i, _)| i);
+        self.process_pending(cut, &mut matches)?;
+        Ok(matches)
+    }
+
+    /// Signal the end of the text and return the matches that end at the last character.
+    pub fn finish(mut self) -> SearchResult<Vec<Match<'a>>> {
+        let mut matches = Vec::new();
+        self.process_pending(self.pending.len(), &mut matches)?;
+        self.step(None, &mut matches)?;
+        Ok(matches)
+    }
+
+    /// Total number of characters of the text that have been fully processed.
+    pub fn position(&self) -> usize {
+        self.held.map_or(self.pending_pos, |held| held.pos)
+    }
+
+    /// Return whether some patterns were left unchecked at some position of the text due to
+    /// the `max_chain_depth` search option. This is not kept in checkpoints.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Capture the current state of the search so it can be resumed later.
+    pub fn checkpoint(&self) -> SearchCheckpoint {
+        SearchCheckpoint {
+            node_id: self.node_id,
+            fingerprint: *self.fingerprint.get_or_init(|| tree_fingerprint(self.trie)),
+            recent_chars: self.buffer.iter().copied().collect(),
+            prev: self.prev,
+            held: self.held,
+            pending: self.pending.clone(),
+            pending_pos: self.pending_pos,
+            pending_byte: self.pending_byte,
+        }
+    }
+
+    /// Normalize the first `cut` bytes of the pending text and process its characters. The
+    /// text must be cut at a boundary of the NFC normalization and of the `normalizers` of the
+    /// search options.
+    fn process_pending(&mut self, cut: usize, out: &mut Vec<Match<'a>>) -> SearchResult<()> {
+        if cut == 0 {
+            return Ok(());
+        }
+        let chunk: String = self.pending.drain(..cut).collect();
+        let bytes: Vec<usize> = chunk
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(chunk.len()))
+            .collect();
+        let (pos, byte) = (self.pending_pos, self.pending_byte);
+        let prepared = self.trie.prepare_text(&chunk);
+        for (i, ch) in prepared.text.chars().enumerate() {
+            let (start, end) = prepared.original_range(i, i + 1);
+            let next = HeldChar {
+                ch,
+                pos: pos + start,
+                byte: byte + bytes[start],
+                end: pos + end,
+                end_byte: byte + bytes[end],
+            };
+            self.step(Some(next), out)?;
+        }
+        self.pending_pos += bytes.len() - 1;
+        self.pending_byte += chunk.len();
+        Ok(())
+    }
+
+    /// Receive the next normalized character of the text (None at the end of the text) and
+    /// process the held character now that the one following it is known.
+    ///
+    /// The matches are located with the ranges of the held characters, so that they refer to
+    /// the characters and bytes received even if normalization changed them.
+    fn step(&mut self, next: Option<HeldChar>, out: &mut Vec<Match<'a>>) -> SearchResult<()> {
+        if let Some(held) = self.held {
+            let next_ch = next.map(|c| c.ch);
+            if !self.trie.skip_char(self.prev, held.ch, next_ch) {
+                self.buffer.push(ReadChar {
+                    ch: held.ch,
+                    pos: held.pos,
+                    byte: held.byte,
+                    prev: self.prev,
+                });
+                self.node_id = self.trie.next_node_id(self.node_id, held.ch)?;
+                let found = out.len();
+                self.truncated |=
+                    self.trie
+                        .push_node_matches(self.node_id, &self.buffer, next_ch, None, 0, out);
+                for m in &mut out[found..] {
+                    m.end = held.end;
+                    m.end_byte = held.end_byte;
+                }
+            }
+            self.prev = Some(held.ch);
+        }
+        self.held = next;
+        Ok(())
+    }
+
+    /// Check whether the received text can be cut before a character without changing its
+    /// normalization.
+    fn is_boundary(&self, c: char) -> bool {
+        is_nfc_boundary(self.fold_char(c))
+            && self
+                .trie
+                .options
+                .normalizers
+                .iter()
+                .all(|n| n.is_boundary(c))
+    }
+
+    /// Transform a character of the text as required by the search options.
+    #[inline]
+    fn fold_char(&self, c: char) -> char {
+        let options = &self.trie.options;
+        if options.case_sensitive && options.whitespace == WhitespaceMatch::Exact {
+            c
+        } else {
+            options.fold_char(c)
+        }
+    }
+}
+
+/// Snapshot of the state of a `StreamSearcher`.
+///
+/// A checkpoint can be serialized with `to_bytes` and later used to resume the search on the
+/// same prefix tree with `TrieRoot::resume_stream`, feeding the text from `resume_from`
+/// onwards. The checkpoint keeps a fingerprint of the tree, so that resuming on another tree
+/// fails. The characters that were received but held back when the checkpoint was taken are
+/// included in the snapshot, so `resume_from` may be greater than `position`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchCheckpoint {
+    node_id: NodeId,
+    fingerprint: u64,
+    recent_chars: Vec<ReadChar>,
+    prev: Option<char>,
+    held: Option<HeldChar>,
+    pending: String,
+    pending_pos: usize,
+    pending_byte: usize,
+}
+
+impl SearchCheckpoint {
+    /// Number of characters of the text that had been processed when the checkpoint was taken.
+    pub fn position(&self) -> usize {
+        self.held.map_or(self.pending_pos, |held| held.pos)
+    }
+
+    /// Index of the first character of the text that must be fed after resuming.
+    pub fn resume_from(&self) -> usize {
+        self.pending_pos + self.pending.chars().count()
+    }
+
+    /// Serialize the checkpoint into a compact binary representation.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(81 + self.pending.len() + 24 * self.recent_chars.len());
+        out.extend_from_slice(CHECKPOINT_MAGIC);
+        out.push(CHECKPOINT_VERSION);
+        for num in [
+            self.node_id as u64,
+            self.fingerprint,
+            self.pending_pos as u64,
+            self.pending_byte as u64,
+        ] {
+            out.extend_from_slice(&num.to_le_bytes());
+        }
+        out.extend_from_slice(&self.prev.map_or(NO_CHAR, u32::from).to_le_bytes());
+        match self.held {
+            None => out.extend_from_slice(&NO_CHAR.to_le_bytes()),
+            Some(held) => {
+                out.extend_from_slice(&u32::from(held.ch).to_le_bytes());
+                for num in [held.pos, held.byte, held.end, held.end_byte] {
+                    out.extend_from_slice(&(num as u64).to_le_bytes());
+                }
+            }
+        }
+        out.extend_from_slice(&(self.pending.len() as u64).to_le_bytes());
+        out.extend_from_slice(self.pending.as_bytes());
+        out.extend_from_slice(&(self.recent_chars.len() as u64).to_le_bytes());
+        for rc in &self.recent_chars {
+            out.extend_from_slice(&u32::from(rc.ch).to_le_bytes());
+            out.extend_from_slice(&(rc.pos as u64).to_le_bytes());
+            out.extend_from_slice(&(rc.byte as u64).to_le_bytes());
+            out.extend_from_slice(&rc.prev.map_or(NO_CHAR, u32::from).to_le_bytes());
+        }
+        out
+    }
+
+    /// Deserialize a checkpoint created with `to_bytes`. Returns an error if the data is invalid.
+    pub fn from_bytes(data: &[u8]) -> SearchResult<Self> {
+        let mut reader = ByteReader { data };
+        if reader.take(4)? != CHECKPOINT_MAGIC || reader.take(1)?[0] != CHECKPOINT_VERSION {
+            return Err(SearchError::InvalidCheckpoint);
+        }
+        let node_id = reader.read_usize()?;
+        let fingerprint = reader.read_u64()?;
+        let pending_pos = reader.read_usize()?;
+        let pending_byte = reader.read_usize()?;
+        let prev = reader.read_char()?;
+        let held = match reader.read_char()? {
+            None => None,
+            Some(ch) => Some(HeldChar {
+                ch,
+                pos: reader.read_usize()?,
+                byte: reader.read_usize()?,
+                end: reader.read_usize()?,
+                end_byte: reader.read_usize()?,
+            }),
+        };
+        let pending_len = reader.read_usize()?;
+        let pending = std::str::from_utf8(reader.take(pending_len)?)
+            .map_err(|_| SearchError::InvalidCheckpoint)?
+            .to_string();
+
+        let total_chars = reader.read_usize()?;
+        let mut recent_chars = Vec::with_capacity(total_chars.min(reader.data.len() / 24));
+        for _ in 0..total_chars {
+            recent_chars.push(ReadChar {
+                ch: reader.read_char()?.ok_or(SearchError::InvalidCheckpoint)?,
+                pos: reader.read_usize()?,
+                byte: reader.read_usize()?,
+                prev: reader.read_char()?,
+            });
+        }
+        if !reader.data.is_empty() {
+            return Err(SearchError::InvalidCheckpoint);
+        }
+
+        Ok(Self {
+            node_id,
+            fingerprint,
+            recent_chars,
+            prev,
+            held,
+            pending,
+            pending_pos,
+            pending_byte,
+        })
+    }
+}
+
+/// Fingerprint of a prefix tree, saved in checkpoints to check that they are resumed on the
+/// same tree: the FNV-1a hash of its serialized form.
+fn tree_fingerprint(trie: &TrieRoot) -> u64 {
+    trie.to_bytes().iter().fold(FNV_OFFSET, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Helper to read little-endian numbers from a byte slice
+struct ByteReader<'d> {
+    data: &'d [u8],
+}
+
+impl<'d> ByteReader<'d> {
+    fn take(&mut self, n: usize) -> SearchResult<&'d [u8]> {
+        if self.data.len() < n {
+            return Err(SearchError::InvalidCheckpoint);
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> SearchResult<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_char(&mut self) -> SearchResult<Option<char>> {
+        match self.read_u32()? {
+            NO_CHAR => Ok(None),
+            c => char::from_u32(c)
+                .map(Some)
+                .ok_or(SearchError::InvalidCheckpoint),
+        }
+    }
+
+    fn read_u64(&mut self) -> SearchResult<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_usize(&mut self) -> SearchResult<usize> {
+        usize::try_from(self.read_u64()?).map_err(|_| SearchError::InvalidCheckpoint)
+    }
+}
+
+impl TrieRoot {
+    /// Create a searcher to process a text incrementally.
+    pub fn stream_searcher(&self) -> StreamSearcher<'_> {
+        StreamSearcher {
+            trie: self,
+            node_id: self.root_node_id(),
+            buffer: RingBuffer::new(self.max_pattern_len),
+            prev: None,
+            held: None,
+            pending: String::new(),
+            pending_pos: 0,
+            pending_byte: 0,
+            truncated: false,
+            fingerprint: OnceCell::new(),
+        }
+    }
+
+    /// Find all matches for the search dictionary in the UTF-8 text produced by a reader (e.g.
+    /// a large file), which is read in fixed-size pieces, so the whole text is never held in
+    /// memory. The search works as with a `StreamSearcher`: the text is normalized as in
+    /// `find_text_matches`, match positions are character indices from the start of the input,
+    /// and context rules are not evaluated.
+    ///
+    /// Returns an error if reading fails or the input is not valid UTF-8. See
+    /// `find_matches_lossy` to search data that may contain invalid sequences.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie;
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("error")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let log = "ok\nerror: disk full\nok\nerror: timeout\n";
+    /// let matches = prefix_tree.find_stream_matches(log.as_bytes()).unwrap();
+    /// assert_eq!(matches.len(), 2);
+    /// assert_eq!(matches[1].char_range(), (23, 28));
+    /// ```
+    pub fn find_stream_matches<R: io::Read>(&self, reader: R) -> SearchResult<Vec<Match<'_>>> {
+        self.read_stream_matches(reader, READ_BUFFER_SIZE)
+    }
+
+    /// Search the text of a reader, reading at most `buffer_size` bytes at a time.
+    fn read_stream_matches<R: io::Read>(
+        &self,
+        mut reader: R,
+        buffer_size: usize,
+    ) -> SearchResult<Vec<Match<'_>>> {
+        let mut searcher = self.stream_searcher();
+        let mut matches = Vec::new();
+        let mut buffer = vec![0; buffer_size.max(4)];
+        // Bytes of a character split between two reads, kept at the start of the buffer
+        let mut pending = 0;
+        // Bytes read before the start of the buffer
+        let mut offset = 0;
+        loop {
+            let read = match reader.read(&mut buffer[pending..]) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if read == 0 {
+                if pending > 0 {
+                    return Err(SearchError::InvalidText(format!(
+                        "incomplete UTF-8 sequence at byte {}",
+                        offset
+                    )));
+                }
+                break;
+            }
+
+            let filled = pending + read;
+            let valid = match std::str::from_utf8(&buffer[..filled]) {
+                Ok(text) => text,
+                Err(e) if e.error_len().is_none() => {
+                    // The last character is incomplete; it will be finished by the next read
+                    std::str::from_utf8(&buffer[..e.valid_up_to()]).unwrap()
+                }
+                Err(e) => {
+                    return Err(SearchError::InvalidText(format!(
+                        "invalid UTF-8 at byte {}",
+                        offset + e.valid_up_to()
+                    )));
+                }
+            };
+            matches.append(&mut searcher.feed(valid)?);
+
+            let consumed = valid.len();
+            buffer.copy_within(consumed..filled, 0);
+            pending = filled - consumed;
+            offset += consumed;
+        }
+        matches.append(&mut searcher.finish()?);
+        Ok(matches)
+    }
+
+    /// Create a searcher that continues a search from the given checkpoint.
+    ///
+    /// Returns an error if the checkpoint was not taken on a prefix tree with the same patterns
+    /// and options, which is checked with a fingerprint of the tree saved in the checkpoint.
+    pub fn resume_stream(&self, checkpoint: &SearchCheckpoint) -> SearchResult<StreamSearcher<'_>> {
+        let fingerprint = tree_fingerprint(self);
+        if checkpoint.fingerprint != fingerprint {
+            return Err(SearchError::InvalidCheckpoint);
+        }
+        self.get_node(checkpoint.node_id)?;
+
+        let mut searcher = self.stream_searcher();
+        searcher.fingerprint = OnceCell::from(fingerprint);
+        searcher.node_id = checkpoint.node_id;
+        searcher.prev = checkpoint.prev;
+        searcher.held = checkpoint.held;
+        searcher.pending.clone_from(&checkpoint.pending);
+        searcher.pending_pos = checkpoint.pending_pos;
+        searcher.pending_byte = checkpoint.pending_byte;
+        for &c in &checkpoint.recent_chars {
+            searcher.buffer.push(c);
+        }
+        Ok(searcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    /// Make a sample tree for the dictionary {ab, abc, cd, bcd}
+    fn sample_tree(check_bounds: bool) -> TrieRoot {
+        create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ab"),
+                String::from("abc"),
+                String::from("cd"),
+                String::from("bcd"),
+            ]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stream_same_as_full_search() {
+        let text = "abcd xab, cd-abc bcd. AbCd";
+        for check_bounds in [false, true] {
+            let pt = sample_tree(check_bounds);
+            let expected = pt.find_text_matches(text).unwrap();
+
+            for piece_len in 1..6 {
+                let chars: Vec<char> = text.chars().collect();
+                let mut searcher = pt.stream_searcher();
+                let mut matches = Vec::new();
+                for piece in chars.chunks(piece_len) {
+                    let piece: String = piece.iter().collect();
+                    matches.append(&mut searcher.feed(&piece).unwrap());
+                }
+                matches.append(&mut searcher.finish().unwrap());
+                assert_eq!(matches, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let text = "abcd xab, cd-abc bcd. abcd";
+        let pt = sample_tree(true);
+        let expected = pt.find_text_matches(text).unwrap();
+
+        let mut searcher = pt.stream_searcher();
+        let mut matches = searcher.feed(&text[..11]).unwrap();
+        let saved = searcher.checkpoint().to_bytes();
+        drop(searcher);
+
+        let checkpoint = dbg!(SearchCheckpoint::from_bytes(&saved).unwrap());
+        assert_eq!(checkpoint.resume_from(), 11);
+        let mut searcher = pt.resume_stream(&checkpoint).unwrap();
+        matches.append(&mut searcher.feed(&text[checkpoint.resume_from()..]).unwrap());
+        matches.append(&mut searcher.finish().unwrap());
+        assert_eq!(matches, expected);
+    }
+
+    #[test]
+    fn test_stream_ignore_separators() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("e-mail")]),
+            Some(SearchOptions {
+                check_bounds: true,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "e mail, e-mail, em-ail";
+        let expected = pt.find_text_matches(text).unwrap();
+        assert_eq!(expected.len(), 3);
+
+        let mut searcher = pt.stream_searcher();
+        let mut matches = searcher.feed(&text[..9]).unwrap();
+        let checkpoint = searcher.checkpoint();
+        let mut searcher = pt.resume_stream(&checkpoint).unwrap();
+        matches.append(&mut searcher.feed(&text[checkpoint.resume_from()..]).unwrap());
+        matches.append(&mut searcher.finish().unwrap());
+        assert_eq!(matches, expected);
+    }
+
+    #[test]
+    fn test_stream_reader() {
+        let text = "abcd xab, cd-abc bcd. AbCd né ab";
+        let pt = sample_tree(true);
+        let expected = pt.find_text_matches(text).unwrap();
+
+        // Small buffers split the characters between reads
+        for buffer_size in [1, 3, 5, 1024] {
+            let found = pt
+                .read_stream_matches(text.as_bytes(), buffer_size)
+                .unwrap();
+            assert_eq!(found, expected, "Buffer size {buffer_size}");
+        }
+        assert_eq!(pt.find_stream_matches(text.as_bytes()).unwrap(), expected);
+
+        assert_eq!(
+            pt.read_stream_matches(b"ab \xffab".as_slice(), 4),
+            Err(SearchError::InvalidText(String::from(
+                "invalid UTF-8 at byte 3"
+            )))
+        );
+        assert_eq!(
+            pt.find_stream_matches(b"ab \xc3".as_slice()),
+            Err(SearchError::InvalidText(String::from(
+                "incomplete UTF-8 sequence at byte 3"
+            )))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn test_stream_decomposed() {
+        fn search<'a>(pt: &'a TrieRoot, pieces: &[&str]) -> Vec<Match<'a>> {
+            let mut searcher = pt.stream_searcher();
+            let mut matches = Vec::new();
+            for piece in pieces {
+                matches.append(&mut searcher.feed(piece).unwrap());
+            }
+            matches.append(&mut searcher.finish().unwrap());
+            matches
+        }
+        let ranges = |matches: &[Match]| -> Vec<_> {
+            matches
+                .iter()
+                .map(|m| (m.char_range(), m.byte_range()))
+                .collect()
+        };
+
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("é")]), None).unwrap();
+        let text = "e\u{301} é";
+        let expected = pt.find_text_matches(text).unwrap();
+        assert_eq!(ranges(&expected), vec![((0, 2), 0..3), ((3, 4), 4..6)]);
+        // The accent may come in the piece after its letter
+        for pieces in [
+            &[text][..],
+            &["e", "\u{301} é"],
+            &["e", "\u{301}", " ", "é"],
+        ] {
+            assert_eq!(search(&pt, pieces), expected);
+        }
+
+        // A letter followed by a combining accent is a different character
+        let pt = create_prefix_tree(add_keyword_slot(vec![String::from("e")]), None).unwrap();
+        let text = "e\u{301} e";
+        assert_eq!(
+            ranges(&search(&pt, &["e", "\u{301} e"])),
+            vec![((3, 4), 4..5)]
+        );
+        assert_eq!(search(&pt, &[text]), pt.find_text_matches(text).unwrap());
+
+        // The held back characters are kept in checkpoints
+        let pt = sample_tree(true);
+        let text = "abcd\u{301} ab";
+        let mut searcher = pt.stream_searcher();
+        let mut matches = searcher.feed("abcd").unwrap();
+        let checkpoint = SearchCheckpoint::from_bytes(&searcher.checkpoint().to_bytes()).unwrap();
+        assert_eq!((checkpoint.position(), checkpoint.resume_from()), (2, 4));
+        let mut searcher = pt.resume_stream(&checkpoint).unwrap();
+        matches.append(&mut searcher.feed("\u{301} ab").unwrap());
+        matches.append(&mut searcher.finish().unwrap());
+        assert_eq!(matches, pt.find_text_matches(text).unwrap());
+        assert_eq!(ranges(&matches), vec![((6, 8), 7..9)]);
+    }
+
+    #[test]
+    fn test_stream_normalizers() {
+        use super::super::{CollapseWhitespace, Lowercase};
+        use std::sync::Arc;
+
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("new york")]),
+            Some(SearchOptions {
+                normalizers: vec![Arc::new(CollapseWhitespace), Arc::new(Lowercase)],
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "NEW  \t YORK, new\nyork";
+        let expected = pt.find_text_matches(text).unwrap();
+        assert_eq!(expected.len(), 2);
+
+        // Pieces that split the runs of whitespace
+        let chars: Vec<char> = text.chars().collect();
+        for piece_len in 1..6 {
+            let mut searcher = pt.stream_searcher();
+            let mut matches = Vec::new();
+            for piece in chars.chunks(piece_len) {
+                let piece: String = piece.iter().collect();
+                matches.append(&mut searcher.feed(&piece).unwrap());
+            }
+            matches.append(&mut searcher.finish().unwrap());
+            assert_eq!(matches, expected, "Piece length {piece_len}");
+        }
+        assert_eq!(
+            expected.iter().map(|m| m.char_range()).collect::<Vec<_>>(),
+            vec![(0, 11), (13, 21)]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_invalid() {
+        let pt = sample_tree(false);
+        let mut searcher = pt.stream_searcher();
+        searcher.feed("xxabc").unwrap();
+        let saved = searcher.checkpoint().to_bytes();
+
+        assert_eq!(
+            SearchCheckpoint::from_bytes(&saved[..saved.len() - 1]),
+            Err(SearchError::InvalidCheckpoint)
+        );
+        assert_eq!(
+            SearchCheckpoint::from_bytes(b"not a checkpoint"),
+            Err(SearchError::InvalidCheckpoint)
+        );
+
+        // Checkpoint from a different tree
+        let other = create_prefix_tree(add_keyword_slot(vec![String::from("x")]), None).unwrap();
+        let checkpoint = SearchCheckpoint::from_bytes(&saved).unwrap();
+        assert!(other.resume_stream(&checkpoint).is_err());
+        assert!(pt.resume_stream(&checkpoint).is_ok());
+
+        // Checkpoint from a tree of the same size with other patterns or options
+        let patterns = |words: [&str; 4]| add_keyword_slot(words.map(String::from).to_vec());
+        let other = create_prefix_tree(patterns(["xy", "xyc", "cd", "bcd"]), None).unwrap();
+        assert_eq!(other.total_nodes(), pt.total_nodes());
+        assert_eq!(
+            other.resume_stream(&checkpoint).err(),
+            Some(SearchError::InvalidCheckpoint)
+        );
+        let other = sample_tree(true);
+        assert_eq!(other.total_nodes(), pt.total_nodes());
+        assert!(other.resume_stream(&checkpoint).is_err());
+    }
+}
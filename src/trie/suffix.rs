@@ -0,0 +1,160 @@
+use super::anchored::PREFIX_WINDOW_MARGIN;
+use super::{Match, SearchOptions, SearchResult, TrieRoot, create_prefix_tree};
+
+impl TrieRoot {
+    /// Instantiate a prefix tree over the reversed patterns of a dictionary of (pattern,
+    /// keyword) entries, to find the patterns that end at a position of a text with
+    /// `find_suffix_matches`. This is the query of "what dictionary term ends here" made by
+    /// some word segmentation algorithms, which read the text from right to left.
+    ///
+    /// The values of the matches found with the tree are the reversed patterns, so the
+    /// keyword of the patterns without one is set to the pattern as given. Regular searches
+    /// with the tree find the reversed patterns in the texts.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, TrieRoot};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ball"), String::from("all")]);
+    /// let reversed = TrieRoot::build_reversed(dictionary, None).unwrap();
+    /// assert_eq!(reversed.pattern(0), Some(("llab", "ball")));
+    /// ```
+    pub fn build_reversed(
+        dictionary: Vec<(String, Option<String>)>,
+        opts: Option<SearchOptions>,
+    ) -> SearchResult<Self> {
+        let reversed = dictionary
+            .into_iter()
+            .map(|(pattern, keyword)| {
+                let reversed_pattern = pattern.chars().rev().collect();
+                (reversed_pattern, Some(keyword.unwrap_or(pattern)))
+            })
+            .collect();
+        create_prefix_tree(reversed, opts)
+    }
+
+    /// Find the patterns of a tree built with `build_reversed` that end exactly at the given
+    /// character position of the text, reading the text from right to left. The matches are
+    /// returned from the shortest to the longest, with their ranges in the given text. Returns
+    /// no matches if the position is past the end of the text.
+    ///
+    /// Only the characters before the position that can be part of a match are read, so
+    /// repeated queries on a long text are cheap. Word bounds are checked as in a regular
+    /// search. Decomposed accents in the text are not composed, since they are read after the
+    /// character they modify.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, TrieRoot};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![
+    ///     String::from("ball"),
+    ///     String::from("all"),
+    ///     String::from("foot"),
+    /// ]);
+    /// let reversed = TrieRoot::build_reversed(dictionary, None).unwrap();
+    ///
+    /// let found = reversed.find_suffix_matches("football", 8);
+    /// let ends: Vec<(&str, (usize, usize))> =
+    ///     found.iter().map(|m| (m.keyword(), m.char_range())).collect();
+    /// assert_eq!(ends, [("all", (5, 8)), ("ball", (4, 8))]);
+    /// assert_eq!(reversed.find_suffix_matches("football", 4)[0].keyword(), "foot");
+    /// assert!(reversed.find_suffix_matches("football", 6).is_empty());
+    /// ```
+    pub fn find_suffix_matches(&self, text: &str, end: usize) -> Vec<Match<'_>> {
+        let Some(end_byte) = text
+            .char_indices()
+            .map(|(b, _)| b)
+            .chain([text.len()])
+            .nth(end)
+        else {
+            return Vec::new();
+        };
+        let before = &text[..end_byte];
+
+        // Read a window of the text before the position, widened when a match might extend
+        // beyond it
+        let mut window = self.max_pattern_len + PREFIX_WINDOW_MARGIN;
+        loop {
+            let reversed: String = before.chars().rev().take(window).collect();
+            let whole = reversed.len() == before.len();
+            let prepared = self.prepare_text(&reversed);
+            let (mut found, read) = self.anchored_matches(&prepared, 0, false);
+            if whole || read <= prepared.text.chars().count() {
+                for m in &mut found {
+                    (m.start, m.end) = (end - m.end, end - m.start);
+                    (m.start_byte, m.end_byte) = (end_byte - m.end_byte, end_byte - m.start_byte);
+                    m.line_col = None;
+                }
+                return found;
+            }
+            window *= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::add_keyword_slot;
+    use super::*;
+
+    #[test]
+    fn test_find_suffix_matches() {
+        let pt = TrieRoot::build_reversed(
+            vec![
+                (String::from("b"), None),
+                (String::from("ab"), None),
+                (String::from("cab"), Some(String::from("taxi"))),
+                (String::from("xyz"), None),
+            ],
+            None,
+        )
+        .unwrap();
+        let text = "xcéab";
+        let found: Vec<_> = pt
+            .find_suffix_matches(text, 5)
+            .iter()
+            .map(|m| {
+                (
+                    m.value(),
+                    m.keyword(),
+                    m.char_range(),
+                    (m.start_byte, m.end_byte),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [("b", "b", (4, 5), (5, 6)), ("ba", "ab", (3, 5), (4, 6))]
+        );
+        assert_eq!(pt.find_suffix_matches("cab", 3).len(), 3);
+        assert!(pt.find_suffix_matches(text, 4).is_empty());
+        assert!(pt.find_suffix_matches(text, 0).is_empty());
+        assert!(pt.find_suffix_matches(text, 6).is_empty());
+    }
+
+    #[test]
+    fn test_find_suffix_matches_options() {
+        let pt = TrieRoot::build_reversed(
+            add_keyword_slot(vec![String::from("new york"), String::from("abc")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds: true,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let text = "in NEW-YORK city";
+        let found = pt.find_suffix_matches(text, 11);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].matched_text(text), "NEW-YORK");
+        assert!(pt.find_suffix_matches("xabc", 4).is_empty());
+
+        // Separators beyond the first window of text read
+        let text = format!("a{}bc", " -".repeat(30));
+        let found = pt.find_suffix_matches(&text, text.chars().count());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].char_range(), (0, text.len()));
+    }
+}
@@ -0,0 +1,272 @@
+use super::{NodeId, ReadChar, RingBuffer, SearchError, SearchResult, TrieRoot};
+
+/// Outcome of checking a candidate match found by the automaton.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// The match was reported
+    Accepted,
+
+    /// The match was rejected because it does not begin and end at word boundaries
+    NotWordBounded,
+
+    /// The match was rejected by one of the context rules of the pattern
+    ContextRule,
+
+    /// The pattern was not checked, since the maximum number of patterns to check at this
+    /// position (`max_chain_depth`) was reached
+    Truncated,
+}
+
+/// A pattern found to end at a position of the text, and whether it was reported as a match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceCandidate {
+    /// The pattern, as stored in the tree
+    pub pattern: String,
+
+    /// The keyword of the pattern
+    pub keyword: String,
+
+    /// Index of the first character of the candidate in the searched text
+    pub start: usize,
+
+    /// 1 + index of the last character of the candidate in the searched text
+    pub end: usize,
+
+    /// Whether the candidate was accepted, or why it was rejected
+    pub verdict: Verdict,
+}
+
+/// What the automaton did upon reading a character of the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceStep {
+    /// Index of the character in the searched text
+    pub position: usize,
+
+    /// The character read
+    pub ch: char,
+
+    /// Whether the character was skipped according to the search options (e.g. an ignored
+    /// separator). Skipped characters do not change the state of the automaton.
+    pub skipped: bool,
+
+    /// Node of the automaton before reading the character
+    pub from_node: NodeId,
+
+    /// Nodes visited by following failure links, in order, before finding a link for the
+    /// character (or reaching the root)
+    pub failures: Vec<NodeId>,
+
+    /// Node of the automaton after reading the character
+    pub to_node: NodeId,
+
+    /// Patterns ending at this character, found by following the dictionary links
+    pub candidates: Vec<TraceCandidate>,
+}
+
+/// Detailed record of a search, as returned by `TrieRoot::explain`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchTrace {
+    /// The text as seen by the automaton, i.e. after the transformations required by the
+    /// search options (lowercasing, normalization). Positions in the trace refer to it.
+    pub text: String,
+
+    /// One step for each character of the text
+    pub steps: Vec<TraceStep>,
+}
+
+impl SearchTrace {
+    /// Iterate over all the candidate matches of the search, in the order they were found.
+    pub fn candidates(&self) -> impl Iterator<Item = &TraceCandidate> {
+        self.steps.iter().flat_map(|s| s.candidates.iter())
+    }
+}
+
+impl TrieRoot {
+    /// Search the text recording every step taken by the automaton: the states visited, the
+    /// failure transitions followed and the candidate matches found, along with the reason
+    /// any of them was rejected. This is meant for debugging, e.g. to find out why a pattern
+    /// did not match, and is much slower than a regular search.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, Verdict};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("cat")]);
+    /// let opts = trie::SearchOptions{check_bounds: true, ..Default::default()};
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, Some(opts)).unwrap();
+    ///
+    /// let trace = prefix_tree.explain(String::from("concatenate")).unwrap();
+    /// let candidate = trace.candidates().next().unwrap();
+    /// assert_eq!((candidate.start, candidate.end), (3, 6));
+    /// assert_eq!(candidate.verdict, Verdict::NotWordBounded);
+    /// ```
+    pub fn explain<T: AsRef<str>>(&self, text: T) -> SearchResult<SearchTrace> {
+        let text = self.prepare_text(text.as_ref()).text.into_owned();
+        let root_id = self.root_node_id();
+        let mut buffer = RingBuffer::new(self.max_pattern_len);
+        let mut node_id = root_id;
+        let mut steps = Vec::with_capacity(text.len());
+
+        let mut chars_iter = text.char_indices().enumerate().peekable();
+        let mut prev: Option<char> = None;
+        while let Some((pos, (byte, ch))) = chars_iter.next() {
+            let nxt_ch: Option<char> = chars_iter.peek().map(|&(_, (_, c))| c);
+            let skipped = self.skip_char(prev, ch, nxt_ch);
+            let mut step = TraceStep {
+                position: pos,
+                ch,
+                skipped,
+                from_node: node_id,
+                failures: Vec::new(),
+                to_node: node_id,
+                candidates: Vec::new(),
+            };
+            if !skipped {
+                buffer.push(ReadChar {
+                    ch,
+                    pos,
+                    byte,
+                    prev,
+                });
+
+                // Same transitions as `next_node_id`, recording the failure links followed
+                let mut current = self.get_node_unchecked(node_id);
+                while node_id != root_id && current.follow_link(ch).is_none() {
+                    node_id = current
+                        .fail_node()
+                        .ok_or(SearchError::MissingLink(node_id))?;
+                    step.failures.push(node_id);
+                    current = self.get_node_unchecked(node_id);
+                }
+                node_id = current.follow_link(ch).unwrap_or(node_id);
+                step.to_node = node_id;
+                step.candidates = self.trace_candidates(node_id, &buffer, nxt_ch, &text);
+            }
+            steps.push(step);
+            prev = Some(ch);
+        }
+        Ok(SearchTrace { text, steps })
+    }
+
+    /// Check the patterns ending at the given node as in `push_node_matches`, recording the
+    /// verdict for each of them.
+    fn trace_candidates(
+        &self,
+        node_id: NodeId,
+        buffer: &RingBuffer<ReadChar>,
+        next_char: Option<char>,
+        text: &str,
+    ) -> Vec<TraceCandidate> {
+        let root_id = self.root_node_id();
+        let last = *buffer.back().expect("Buffer is empty");
+        let max_depth = self.options.max_chain_depth.unwrap_or(usize::MAX);
+        let mut candidates = Vec::new();
+        let mut check_id = node_id;
+        while check_id != root_id {
+            let check = self.get_node_unchecked(check_id);
+            if let Some((value, kw)) = check.value_keyword() {
+                let first = *buffer.get_item(buffer.len() - check.pattern_len());
+                let pattern_node = check.pattern_node_id().unwrap_or(check_id);
+                let verdict = if candidates.len() >= max_depth {
+                    Verdict::Truncated
+                } else if !self.is_match_bounded(
+                    pattern_node,
+                    &first,
+                    last.ch,
+                    last.byte + last.ch.len_utf8(),
+                    next_char,
+                    Some(text),
+                ) {
+                    Verdict::NotWordBounded
+                } else if !self.context_allows(
+                    pattern_node,
+                    text,
+                    first.byte,
+                    last.byte + last.ch.len_utf8(),
+                ) {
+                    Verdict::ContextRule
+                } else {
+                    Verdict::Accepted
+                };
+                candidates.push(TraceCandidate {
+                    pattern: value.to_string(),
+                    keyword: kw.to_string(),
+                    start: first.pos,
+                    end: last.pos + 1,
+                    verdict,
+                });
+            }
+            check_id = check.fail_dct().unwrap_or(root_id);
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ContextRule, SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_explain_steps() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("abc"), String::from("bd")]),
+            Some(SearchOptions {
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let ab = pt.node_by_path("ab").unwrap();
+        let b = pt.node_by_path("b").unwrap();
+        let bd = pt.node_by_path("bd").unwrap();
+
+        let trace = pt.explain(String::from("a-bd")).unwrap();
+        assert_eq!(trace.text, "a-bd");
+        assert_eq!(trace.steps.len(), 4);
+        assert!(trace.steps[1].skipped);
+        assert_eq!(trace.steps[2].to_node, ab);
+
+        // 'd' does not follow "ab": fail to "b" and move to "bd"
+        let last = &trace.steps[3];
+        assert_eq!((last.from_node, last.failures.as_slice()), (ab, &[b][..]));
+        assert_eq!(last.to_node, bd);
+        assert_eq!(
+            last.candidates,
+            [TraceCandidate {
+                pattern: String::from("bd"),
+                keyword: String::from("bd"),
+                start: 2,
+                end: 4,
+                verdict: Verdict::Accepted,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_explain_rejections() {
+        let mut pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("cd")]),
+            Some(SearchOptions {
+                check_bounds: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        pt.add_context_rule("cd", ContextRule::NotPrecededBy(String::from("#")))
+            .unwrap();
+
+        let trace = pt.explain(String::from("xab #cd ab")).unwrap();
+        let verdicts: Vec<Verdict> = trace.candidates().map(|c| c.verdict).collect();
+        assert_eq!(
+            verdicts,
+            [
+                Verdict::NotWordBounded,
+                Verdict::ContextRule,
+                Verdict::Accepted
+            ]
+        );
+        let matches = pt.find_text_matches(String::from("xab #cd ab")).unwrap();
+        assert_eq!(matches.len(), 1);
+    }
+}
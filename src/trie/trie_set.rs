@@ -0,0 +1,175 @@
+use super::{Match, ReadChar, ScanState, SearchResult, TrieRoot};
+
+/// A group of independently built prefix trees that are searched together in a single pass
+/// over the text.
+///
+/// This is useful to layer several dictionaries (e.g. a global one plus per-customer ones)
+/// without scanning the text once per dictionary. The tries are borrowed, so the same trie
+/// can be shared by several sets. Each match is tagged with the index of the trie it comes
+/// from in the set.
+///
+/// Example
+/// ```rust
+/// use ac_search_rs::trie::{self, TrieSet};
+///
+/// let global = trie::create_prefix_tree(
+///     trie::add_keyword_slot(vec![String::from("invoice")]),
+///     None,
+/// ).unwrap();
+/// let customer = trie::create_prefix_tree(
+///     trie::add_keyword_slot(vec![String::from("acme")]),
+///     Some(trie::SearchOptions{case_sensitive: false, ..Default::default()}),
+/// ).unwrap();
+///
+/// let tries = TrieSet::new(vec![&global, &customer]);
+/// let matches = tries.find_text_matches(String::from("ACME invoice")).unwrap();
+/// let found: Vec<(usize, &str)> = matches.iter().map(|(i, m)| (*i, m.value())).collect();
+/// assert_eq!(found, [(1, "acme"), (0, "invoice")]);
+/// ```
+pub struct TrieSet<'t> {
+    tries: Vec<&'t TrieRoot>,
+}
+
+impl<'t> TrieSet<'t> {
+    /// Create a set from the given prefix trees.
+    pub fn new(tries: Vec<&'t TrieRoot>) -> Self {
+        Self { tries }
+    }
+
+    /// Number of prefix trees in the set
+    pub fn len(&self) -> usize {
+        self.tries.len()
+    }
+
+    /// Return whether the set has no prefix trees
+    pub fn is_empty(&self) -> bool {
+        self.tries.is_empty()
+    }
+
+    /// Find the matches of all the prefix trees in the given text. Each match is returned
+    /// along with the index of the trie it comes from. Matches are sorted by their end
+    /// position, then by trie index.
+    ///
+    /// The text is traversed once for all the tries that share the same case sensitivity,
+    /// since these search the same prepared text.
+    pub fn find_text_matches<T: AsRef<str>>(
+        &self,
+        text: T,
+    ) -> SearchResult<Vec<(usize, Match<'t>)>> {
+        let mut per_trie: Vec<Vec<Match<'t>>> = self.tries.iter().map(|_| Vec::new()).collect();
+        for case_sensitive in [true, false] {
+            let group: Vec<usize> = (0..self.tries.len())
+                .filter(|&i| self.tries[i].options.case_sensitive == case_sensitive)
+                .collect();
+            let Some(&first) = group.first() else {
+                continue;
+            };
+
+            let prepared = self.tries[first].prepare_text(text.as_ref());
+            let mut states: Vec<ScanState> = group
+                .iter()
+                .map(|&i| ScanState::new(self.tries[i]))
+                .collect();
+            let mut chars_iter = prepared.text.char_indices().enumerate().peekable();
+            let mut prev: Option<char> = None;
+            while let Some((pos, (byte, ch))) = chars_iter.next() {
+                let nxt_ch: Option<char> = chars_iter.peek().map(|&(_, (_, c))| c);
+                let read = ReadChar {
+                    ch,
+                    pos,
+                    byte,
+                    prev,
+                };
+                for (state, &i) in states.iter_mut().zip(&group) {
+                    self.tries[i].scan_char(
+                        state,
+                        read,
+                        nxt_ch,
+                        &prepared.text,
+                        0,
+                        &mut per_trie[i],
+                    )?;
+                }
+                prev = Some(ch);
+            }
+
+            for (state, &i) in states.iter().zip(&group) {
+                if state.truncated {
+                    log::warn!(
+                        "Some matches of trie {} were skipped due to the maximum chain depth",
+                        i
+                    );
+                }
+                let mut found = std::mem::take(&mut per_trie[i]);
+                self.tries[i].retain_exact_case(&mut found, &prepared);
+                per_trie[i] = self.tries[i].select_matches(found);
+                self.tries[i].finish_matches(&mut per_trie[i], &prepared);
+            }
+        }
+
+        let mut matches: Vec<(usize, Match<'t>)> = per_trie
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, found)| found.into_iter().map(move |m| (i, m)))
+            .collect();
+        matches.sort_by_key(|(_, m)| m.end);
+        Ok(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_trie_set_matches() {
+        let words = |w: &[&str]| add_keyword_slot(w.iter().map(|s| s.to_string()).collect());
+        let global = create_prefix_tree(words(&["ab", "bcd"]), None).unwrap();
+        let local = create_prefix_tree(
+            words(&["B-C", "d"]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                ignore_separators: true,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        let other = create_prefix_tree(words(&["abc"]), None).unwrap();
+
+        let tries = TrieSet::new(vec![&global, &local, &other]);
+        assert_eq!(tries.len(), 3);
+
+        let text = String::from("İ abcd B c");
+        let found: Vec<(usize, &str, (usize, usize))> = tries
+            .find_text_matches(text.clone())
+            .unwrap()
+            .iter()
+            .map(|(i, m)| (*i, m.value(), m.char_range()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (0, "ab", (2, 4)),
+                (1, "bc", (3, 5)),
+                (2, "abc", (2, 5)),
+                (0, "bcd", (3, 6)),
+                (1, "d", (5, 6)),
+                (1, "bc", (7, 10)),
+            ]
+        );
+
+        // Same matches as searching each trie separately
+        for (i, trie) in [&global, &local, &other].into_iter().enumerate() {
+            let single: Vec<(usize, usize)> = trie
+                .find_text_matches(text.clone())
+                .unwrap()
+                .iter()
+                .map(|m| m.char_range())
+                .collect();
+            let from_set: Vec<(usize, usize)> =
+                found.iter().filter(|f| f.0 == i).map(|f| f.2).collect();
+            assert_eq!(single, from_set);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::{Node, NodeId, TrieRoot};
+use std::collections::VecDeque;
+
+/// Order in which `TrieRoot::walk` visits the nodes of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkOrder {
+    /// Visit each node before its children, exploring each branch fully before the next one
+    DepthFirst,
+
+    /// Visit the nodes level by level, in order of increasing depth
+    BreadthFirst,
+}
+
+/// A node reached while walking the tree, passed to the visitor of `TrieRoot::walk`.
+#[derive(Debug, Clone, Copy)]
+pub struct WalkStep<'a> {
+    /// ID of the node
+    pub node_id: NodeId,
+
+    /// ID of the parent node (None for the root)
+    pub parent: Option<NodeId>,
+
+    /// Character of the edge from the parent to the node (None for the root)
+    pub edge: Option<char>,
+
+    /// Depth of the node, i.e. length in characters of the path from the root
+    pub depth: usize,
+
+    /// The node itself, to access its value, keyword, category and links
+    pub node: Node<'a>,
+}
+
+impl TrieRoot {
+    /// Visit all the nodes of the tree, starting from the root and following the tree edges
+    /// (not the failure links) in the given order. Children are visited in order of their
+    /// edge character.
+    ///
+    /// Example
+    /// ```rust
+    /// use ac_search_rs::trie::{self, WalkOrder};
+    ///
+    /// let dictionary = trie::add_keyword_slot(vec![String::from("ab"), String::from("c")]);
+    /// let prefix_tree = trie::create_prefix_tree(dictionary, None).unwrap();
+    ///
+    /// let mut paths = Vec::new();
+    /// prefix_tree.walk(WalkOrder::BreadthFirst, |step| {
+    ///     paths.push((step.depth, step.edge, step.node.value_keyword().is_some()));
+    /// });
+    /// assert_eq!(
+    ///     paths,
+    ///     [(0, None, false), (1, Some('a'), false), (1, Some('c'), true), (2, Some('b'), true)]
+    /// );
+    /// ```
+    pub fn walk<'a, F>(&'a self, order: WalkOrder, mut visitor: F)
+    where
+        F: FnMut(WalkStep<'a>),
+    {
+        let root_id = self.root_node_id();
+        let mut pending = VecDeque::from([WalkStep {
+            node_id: root_id,
+            parent: None,
+            edge: None,
+            depth: 0,
+            node: self.root_node(),
+        }]);
+
+        loop {
+            let step = match order {
+                WalkOrder::DepthFirst => pending.pop_back(),
+                WalkOrder::BreadthFirst => pending.pop_front(),
+            };
+            let Some(step) = step else {
+                break;
+            };
+
+            let mut children: Vec<WalkStep> = step
+                .node
+                .next_nodes()
+                .iter()
+                .map(|link| WalkStep {
+                    node_id: link.get_node_id(),
+                    parent: Some(step.node_id),
+                    edge: Some(link.get_char()),
+                    depth: step.depth + 1,
+                    node: self.get_node_unchecked(link.get_node_id()),
+                })
+                .collect();
+            children.sort_unstable_by_key(|c| c.edge);
+            match order {
+                // Stack: push in reverse so the first child is visited first
+                WalkOrder::DepthFirst => pending.extend(children.into_iter().rev()),
+                WalkOrder::BreadthFirst => pending.extend(children),
+            }
+            visitor(step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    #[test]
+    fn test_walk_depth_first() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("ba"),
+                String::from("ab"),
+                String::from("abc"),
+                String::from("ad"),
+            ]),
+            None,
+        )
+        .unwrap();
+
+        let mut paths: Vec<String> = Vec::new();
+        let mut current = String::new();
+        pt.walk(WalkOrder::DepthFirst, |step| {
+            current.truncate(step.depth.saturating_sub(1));
+            current.extend(step.edge);
+            if let Some((value, _)) = step.node.value_keyword() {
+                assert_eq!(value, current);
+                assert_eq!(pt.node_by_path(value), Some(step.node_id));
+            }
+            paths.push(current.clone());
+        });
+        assert_eq!(paths, ["", "a", "ab", "abc", "ad", "b", "ba"]);
+    }
+
+    #[test]
+    fn test_walk_covers_all_nodes() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![
+                String::from("xyz"),
+                String::from("xz"),
+                String::from("y"),
+            ]),
+            None,
+        )
+        .unwrap();
+
+        for order in [WalkOrder::DepthFirst, WalkOrder::BreadthFirst] {
+            let mut visited = Vec::new();
+            let mut max_depth = 0;
+            pt.walk(order, |step| {
+                if step.depth > 0 {
+                    let parent = pt.get_node(step.parent.unwrap()).unwrap();
+                    assert_eq!(parent.follow_link(step.edge.unwrap()), Some(step.node_id));
+                }
+                if order == WalkOrder::BreadthFirst {
+                    assert!(step.depth >= max_depth);
+                }
+                max_depth = max_depth.max(step.depth);
+                visited.push(step.node_id);
+            });
+            visited.sort_unstable();
+            assert_eq!(visited, (0..pt.total_nodes()).collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+/// How the whitespace in the patterns is matched. Set with the `whitespace` search option.
+///
+/// With the flexible modes, whitespace in the patterns matches any run of whitespace in the
+/// texts, so that "new york" matches "new  york" or "new\tyork". Runs of whitespace in the
+/// patterns are equivalent to a single space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WhitespaceMatch {
+    /// Whitespace characters are matched exactly, like any other character.
+    #[default]
+    Exact,
+
+    /// Whitespace matches any run of spaces, tabs and other whitespace, except line breaks.
+    Flexible,
+
+    /// Whitespace matches any run of whitespace, including line breaks, so that patterns can
+    /// match across lines.
+    FlexibleMultiline,
+}
+
+/// Check whether a character is a line break
+#[inline]
+fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{B}' | '\u{C}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+impl WhitespaceMatch {
+    /// Get a whitespace mode from its name in snake case (e.g. "flexible_multiline"), as used
+    /// by the Python bindings and the search service. Returns None if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "exact" => Some(Self::Exact),
+            "flexible" => Some(Self::Flexible),
+            "flexible_multiline" => Some(Self::FlexibleMultiline),
+            _ => None,
+        }
+    }
+
+    /// Check whether a character is whitespace matched flexibly in this mode
+    #[inline]
+    fn is_flexible(self, c: char) -> bool {
+        match self {
+            Self::Exact => false,
+            Self::Flexible => c.is_whitespace() && !is_line_break(c),
+            Self::FlexibleMultiline => c.is_whitespace(),
+        }
+    }
+
+    /// Map the whitespace matched flexibly to a space, leaving other characters unchanged.
+    #[inline]
+    pub(crate) fn normalize(self, c: char) -> char {
+        if c != ' ' && self.is_flexible(c) {
+            ' '
+        } else {
+            c
+        }
+    }
+
+    /// Check whether `normalize` would change any character of the text
+    pub(crate) fn needs_normalizing(self, text: &str) -> bool {
+        self != Self::Exact && text.chars().any(|c| c != ' ' && self.is_flexible(c))
+    }
+
+    /// Normalize the whitespace of a pattern: the whitespace matched flexibly is mapped to a
+    /// space, and runs of spaces are collapsed into one.
+    pub(crate) fn normalize_pattern(self, pattern: &str) -> String {
+        if self == Self::Exact {
+            return pattern.to_string();
+        }
+        let mut out = String::with_capacity(pattern.len());
+        for c in pattern.chars().map(|c| self.normalize(c)) {
+            if !(c == ' ' && out.ends_with(' ')) {
+                out.push(c);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{SearchOptions, TrieRoot, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    fn flexible_tree(whitespace: WhitespaceMatch) -> TrieRoot {
+        create_prefix_tree(
+            add_keyword_slot(vec![String::from("New  York"), String::from("york")]),
+            Some(SearchOptions {
+                case_sensitive: false,
+                check_bounds: true,
+                whitespace,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_search_flexible_whitespace() {
+        let text = "NEW \t YORK, new\n  york, New York, newyork";
+        let found = |pt: &TrieRoot| -> Vec<&str> {
+            pt.find_text_matches(text)
+                .unwrap()
+                .iter()
+                .filter(|m| m.keyword() != "york")
+                .map(|m| m.matched_text(text))
+                .collect()
+        };
+
+        assert!(found(&flexible_tree(WhitespaceMatch::Exact)).is_empty());
+        let pt = flexible_tree(WhitespaceMatch::Flexible);
+        assert_eq!(found(&pt), ["NEW \t YORK", "New York"]);
+        assert_eq!(pt.find_text_matches(text).unwrap().len(), 5);
+
+        let pt = flexible_tree(WhitespaceMatch::FlexibleMultiline);
+        let expected = ["NEW \t YORK", "new\n  york", "New York"];
+        assert_eq!(found(&pt), expected);
+        assert_eq!(
+            pt.find_text_matches_parallel(text, Some(2)).unwrap().len(),
+            6
+        );
+
+        let mut stream = pt.stream_searcher();
+        let mut streamed = Vec::new();
+        for piece in ["NEW \t", " YORK, new\n", "  york"] {
+            streamed.extend(stream.feed(piece).unwrap());
+        }
+        streamed.extend(stream.finish().unwrap());
+        assert_eq!(streamed.iter().filter(|m| m.keyword() != "york").count(), 2);
+
+        let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+        assert_eq!(
+            restored.options().whitespace,
+            WhitespaceMatch::FlexibleMultiline
+        );
+        assert_eq!(found(&restored), expected);
+    }
+
+    #[test]
+    fn test_normalize_whitespace() {
+        let pattern = "new \t york\n city";
+        assert_eq!(WhitespaceMatch::Exact.normalize_pattern(pattern), pattern);
+        assert_eq!(
+            WhitespaceMatch::Flexible.normalize_pattern(pattern),
+            "new york\n city"
+        );
+        assert_eq!(
+            WhitespaceMatch::FlexibleMultiline.normalize_pattern(pattern),
+            "new york city"
+        );
+
+        assert!(!WhitespaceMatch::Exact.needs_normalizing("a\tb"));
+        assert!(!WhitespaceMatch::Flexible.needs_normalizing("a  b\n"));
+        assert!(WhitespaceMatch::FlexibleMultiline.needs_normalizing("a  b\n"));
+        assert_eq!(WhitespaceMatch::Flexible.normalize('\u{A0}'), ' ');
+        assert_eq!(
+            WhitespaceMatch::from_name("flexible_multiline"),
+            Some(WhitespaceMatch::FlexibleMultiline)
+        );
+    }
+}
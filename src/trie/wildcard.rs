@@ -0,0 +1,242 @@
+use super::{Link, NodeId, PatternId, SearchError, SearchResult, TrieRoot, opt_id};
+use std::collections::VecDeque;
+
+/// Maximum number of nodes that the expansion of the wildcards can add to a tree
+pub(crate) const MAX_WILDCARD_NODES: usize = 1 << 20;
+
+impl TrieRoot {
+    /// Make the automaton deterministic for the patterns with wildcards. Must be called after
+    /// adding patterns and before computing the failure links.
+    ///
+    /// The wildcard characters of the patterns are inserted as links like any other
+    /// character. A character of the text that has its own link must also match the
+    /// wildcard, so the subtree of each wildcard link is copied under the link of every
+    /// character of the dictionary from the same node. The wildcard links are then only
+    /// followed by the characters that appear in no pattern, which all lead to the same
+    /// failure nodes. Nodes of the copies where a pattern ends reference it, and when several
+    /// patterns end at the same node, the one with the fewest wildcards is kept.
+    ///
+    /// Each wildcard multiplies the size of the subtree following it by the number of
+    /// distinct characters of the dictionary, so the expansion fails with a `BuildBudget` error
+    /// once it would add more than `MAX_WILDCARD_NODES` nodes.
+    pub(crate) fn expand_wildcards(&mut self) -> SearchResult<()> {
+        let Some(wildcard) = self.options.wildcard else {
+            return Ok(());
+        };
+        let mut node_links = self.unpack_links();
+        let mut alphabet: Vec<char> = node_links
+            .iter()
+            .flatten()
+            .map(Link::get_char)
+            .filter(|&c| c != wildcard)
+            .collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        let max_nodes = self.nodes.len().saturating_add(MAX_WILDCARD_NODES);
+
+        // Nodes are expanded before their children, so that the copies made in a subtree are
+        // expanded in turn
+        let mut queue = VecDeque::from([self.root_node_id()]);
+        while let Some(node_id) = queue.pop_front() {
+            if let Ok(i) = node_links[node_id].binary_search_by_key(&wildcard, Link::get_char) {
+                let wild_id = node_links[node_id][i].get_node_id();
+                for &c in &alphabet {
+                    let child_id = self.child_or_insert(&mut node_links, node_id, c, max_nodes)?;
+                    self.merge_subtree(&mut node_links, wild_id, child_id, max_nodes)?;
+                }
+            }
+            queue.extend(node_links[node_id].iter().map(Link::get_node_id));
+        }
+        self.pack_links(node_links);
+        Ok(())
+    }
+
+    /// Get the child of a node through the link of a character, adding it if missing. Fails if
+    /// the tree already has `max_nodes` nodes.
+    fn child_or_insert(
+        &mut self,
+        node_links: &mut Vec<Vec<Link>>,
+        node_id: NodeId,
+        c: char,
+        max_nodes: usize,
+    ) -> SearchResult<NodeId> {
+        match node_links[node_id].binary_search_by_key(&c, Link::get_char) {
+            Ok(i) => Ok(node_links[node_id][i].get_node_id()),
+            Err(_) if self.nodes.len() >= max_nodes => Err(SearchError::BuildBudget(format!(
+                "expanding the wildcards would add more than {} nodes",
+                MAX_WILDCARD_NODES
+            ))),
+            Err(i) => {
+                let child_id = self.add_node()?;
+                node_links[node_id].insert(i, Link(c, child_id as u32));
+                node_links.push(Vec::new());
+                Ok(child_id)
+            }
+        }
+    }
+
+    /// Copy the links and patterns of the subtree of a node into the subtree of another one.
+    fn merge_subtree(
+        &mut self,
+        node_links: &mut Vec<Vec<Link>>,
+        from: NodeId,
+        to: NodeId,
+        max_nodes: usize,
+    ) -> SearchResult<()> {
+        let mut stack = vec![(from, to)];
+        while let Some((from, to)) = stack.pop() {
+            if let Some(pattern_id) = opt_id(self.nodes[from].pattern) {
+                let keep = opt_id(self.nodes[to].pattern)
+                    .is_some_and(|id| self.wildcard_rank(id) <= self.wildcard_rank(pattern_id));
+                if !keep {
+                    self.nodes[to].pattern = pattern_id as u32;
+                    self.nodes[to].pattern_len = self.nodes[from].pattern_len;
+                }
+            }
+            for i in 0..node_links[from].len() {
+                let link = node_links[from][i];
+                let child_id = self.child_or_insert(node_links, to, link.get_char(), max_nodes)?;
+                stack.push((link.get_node_id(), child_id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Order of preference of the patterns that end at the same node: fewest wildcards first,
+    /// then first in the dictionary.
+    fn wildcard_rank(&self, pattern_id: PatternId) -> (usize, PatternId) {
+        let wildcard = self.options.wildcard.unwrap_or_default();
+        let value = &self.patterns[pattern_id].value;
+        (value.chars().filter(|&c| c == wildcard).count(), pattern_id)
+    }
+
+    /// Set the nodes of the patterns read from serialized data, where each pattern may end at
+    /// several nodes: its own node is the one at the end of its path.
+    pub(crate) fn find_wildcard_pattern_nodes(&mut self) -> Option<()> {
+        for pattern_id in 0..self.patterns.len() {
+            let node_id = self
+                .node_by_path(&self.patterns[pattern_id].value)
+                .filter(|&nid| self.nodes[nid].pattern == pattern_id as u32)?;
+            self.patterns[pattern_id].node_id = node_id as u32;
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{MatchKind, SearchOptions, add_keyword_slot, create_prefix_tree};
+    use super::*;
+
+    fn wildcard_tree(patterns: &[&str], match_kind: MatchKind) -> TrieRoot {
+        create_prefix_tree(
+            add_keyword_slot(patterns.iter().map(|p| p.to_string()).collect()),
+            Some(SearchOptions {
+                wildcard: Some('?'),
+                match_kind,
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_search_wildcards() {
+        let pt = wildcard_tree(
+            &["gr?y", "colour", "colo?r", "?b", "ry"],
+            MatchKind::Overlapping,
+        );
+        let text = "grey gray gr?y grxy colour coloUr ab bb ?b";
+        let found: Vec<(&str, &str)> = pt
+            .find_text_matches(text)
+            .unwrap()
+            .iter()
+            .map(|m| (m.value(), m.matched_text(text)))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("gr?y", "grey"),
+                ("gr?y", "gray"),
+                ("gr?y", "gr?y"),
+                ("gr?y", "grxy"),
+                ("colour", "colour"),
+                ("colo?r", "coloUr"),
+                ("?b", "ab"),
+                ("?b", " b"),
+                ("?b", "bb"),
+                ("?b", "?b"),
+            ]
+        );
+        assert_eq!(pt.pattern(2), Some(("colo?r", "colo?r")));
+
+        // Same matches from the other search paths
+        let matches = pt.find_text_matches(text).unwrap();
+        assert_eq!(
+            pt.find_text_matches_parallel(text, Some(3)).unwrap(),
+            matches
+        );
+        let restored = TrieRoot::from_bytes(&pt.to_bytes()).unwrap();
+        assert_eq!(restored.options().wildcard, Some('?'));
+        assert_eq!(restored.find_text_matches(text).unwrap(), matches);
+        let mut compiled = wildcard_tree(
+            &["gr?y", "colour", "colo?r", "?b", "ry"],
+            MatchKind::Overlapping,
+        );
+        compiled.compile_dfa().unwrap();
+        assert_eq!(compiled.find_text_matches(text).unwrap(), matches);
+    }
+
+    #[test]
+    fn test_wildcard_failure_links() {
+        // The state after a wildcard depends on the character read
+        let pt = wildcard_tree(&["a?c", "bcd", "a??e"], MatchKind::Overlapping);
+        let values = |text: &str| -> Vec<&str> {
+            pt.find_text_matches(text)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.value())
+                .collect()
+        };
+        assert_eq!(values("abcd"), ["a?c", "bcd"]);
+        assert_eq!(values("axcd"), ["a?c"]);
+        assert_eq!(values("abce"), ["a?c", "a??e"]);
+        assert_eq!(values("aaxc"), ["a?c"]);
+
+        let pt = wildcard_tree(&["a?", "ab?"], MatchKind::LeftmostLongest);
+        let found = pt.find_text_matches("abc axy").unwrap();
+        let ranges: Vec<(usize, usize)> = found.iter().map(|m| m.char_range()).collect();
+        assert_eq!(ranges, [(0, 3), (4, 6)]);
+
+        // Patterns added later extend the alphabet copied under the wildcards
+        let mut pt = wildcard_tree(&["a?c"], MatchKind::Overlapping);
+        pt.add_patterns(add_keyword_slot(vec![
+            String::from("xc"),
+            String::from("a?"),
+        ]))
+        .unwrap();
+        let values: Vec<&str> = pt
+            .find_text_matches("axc")
+            .unwrap()
+            .into_iter()
+            .map(|m| m.value())
+            .collect();
+        assert_eq!(values, ["a?", "a?c", "xc"]);
+    }
+
+    #[test]
+    fn test_wildcard_budget() {
+        let pt = wildcard_tree(&["abcdefghij", "???"], MatchKind::Overlapping);
+        assert!(pt.total_nodes() < 2000);
+
+        // Each wildcard multiplies the size of the tree by the size of the alphabet
+        let result = create_prefix_tree(
+            add_keyword_slot(vec![String::from("abcdefghij"), String::from("????????")]),
+            Some(SearchOptions {
+                wildcard: Some('?'),
+                ..Default::default()
+            }),
+        );
+        assert!(matches!(result, Err(SearchError::BuildBudget(_))));
+    }
+}
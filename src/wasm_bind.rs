@@ -0,0 +1,165 @@
+//! WebAssembly bindings.
+//!
+//! Exposes the matcher to JavaScript with `wasm-bindgen`, so that dictionaries can be matched
+//! client-side in the browser. Build the package with
+//! `wasm-pack build --target web -- --no-default-features --features wasm_bind`, leaving out the
+//! `unicode` feature if texts are already NFC normalized to make the module smaller.
+use super::trie::{Match, SearchOptions, TrieRoot, create_prefix_tree};
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// A match with its range in UTF-16 code units, as JavaScript strings are indexed.
+#[derive(Debug, PartialEq, Eq)]
+struct WasmMatch<'a> {
+    value: &'a str,
+    keyword: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl WasmMatch<'_> {
+    /// Convert the match into a plain JavaScript object.
+    fn to_js(&self) -> JsValue {
+        let obj = Object::new();
+        for (key, value) in [
+            ("value", JsValue::from_str(self.value)),
+            ("keyword", JsValue::from_str(self.keyword)),
+            ("start", JsValue::from(self.start)),
+            ("end", JsValue::from(self.end)),
+        ] {
+            // Setting a property of a new plain object can't fail
+            let _ = Reflect::set(&obj, &JsValue::from_str(key), &value);
+        }
+        obj.into()
+    }
+}
+
+/// Convert the matches found in a text, mapping their byte ranges to UTF-16 offsets.
+fn to_wasm_matches<'a>(text: &str, matches: &[Match<'a>]) -> Vec<WasmMatch<'a>> {
+    // Byte offsets to convert, sorted, with their UTF-16 offsets filled in a single pass
+    let mut bounds: Vec<(usize, usize)> = matches
+        .iter()
+        .flat_map(|m| [m.byte_range().start, m.byte_range().end])
+        .map(|b| (b, b))
+        .collect();
+    if !text.is_ascii() {
+        bounds.sort_unstable();
+        bounds.dedup();
+        let mut utf16_pos = 0;
+        let mut chars = text.char_indices().peekable();
+        for (byte, utf16) in bounds.iter_mut() {
+            while let Some(&(b, c)) = chars.peek()
+                && b < *byte
+            {
+                utf16_pos += c.len_utf16();
+                chars.next();
+            }
+            *utf16 = utf16_pos;
+        }
+    }
+    let to_utf16 = |byte: usize| match bounds.binary_search_by_key(&byte, |&(b, _)| b) {
+        Ok(i) => bounds[i].1,
+        Err(_) => byte,
+    };
+
+    matches
+        .iter()
+        .map(|m| WasmMatch {
+            value: m.value(),
+            keyword: m.keyword(),
+            start: to_utf16(m.byte_range().start),
+            end: to_utf16(m.byte_range().end),
+        })
+        .collect()
+}
+
+/// Prefix tree exposed to JavaScript.
+#[wasm_bindgen]
+pub struct WasmTrie {
+    trie: TrieRoot,
+}
+
+#[wasm_bindgen]
+impl WasmTrie {
+    /// Build a prefix tree from an object mapping pattern -> keyword. A keyword that is null
+    /// or undefined reports the pattern itself.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        dictionary: &Object,
+        case_sensitive: Option<bool>,
+        check_bounds: Option<bool>,
+    ) -> Result<Self, JsError> {
+        let mut entries = Vec::new();
+        for entry in Object::entries(dictionary).iter() {
+            let entry: Array = entry.unchecked_into();
+            let pattern = entry.get(0).as_string().unwrap_or_default();
+            let keyword = entry.get(1);
+            let keyword = match keyword.is_null_or_undefined() {
+                true => None,
+                false => Some(
+                    keyword
+                        .as_string()
+                        .ok_or_else(|| JsError::new("Keywords must be strings"))?,
+                ),
+            };
+            entries.push((pattern, keyword));
+        }
+
+        let opts = SearchOptions {
+            case_sensitive: case_sensitive.unwrap_or(true),
+            check_bounds: check_bounds.unwrap_or(false),
+            ..Default::default()
+        };
+        let trie =
+            create_prefix_tree(entries, Some(opts)).map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self { trie })
+    }
+
+    /// Search the text, returning an array of matches as objects with their `value`,
+    /// `keyword`, `start` and `end`. The range is in UTF-16 code units, so that
+    /// `text.slice(start, end)` is the matched text.
+    pub fn search(&self, text: &str) -> Result<JsValue, JsError> {
+        let matches = self
+            .trie
+            .find_text_matches(text)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        let out = Array::new();
+        for m in to_wasm_matches(text, &matches) {
+            out.push(&m.to_js());
+        }
+        Ok(out.into())
+    }
+
+    /// Total number of patterns in the tree.
+    #[wasm_bindgen(getter, js_name = totalPatterns)]
+    pub fn total_patterns(&self) -> usize {
+        self.trie.total_patterns()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::trie::add_keyword_slot;
+    use super::*;
+
+    #[test]
+    fn test_utf16_ranges() {
+        let pt = create_prefix_tree(
+            add_keyword_slot(vec![String::from("ab"), String::from("é😀x")]),
+            None,
+        )
+        .unwrap();
+        for (text, expected) in [
+            ("xab ab", vec![(1, 3), (4, 6)]),
+            ("😀ab é😀x ab", vec![(2, 4), (5, 9), (10, 12)]),
+            ("", vec![]),
+        ] {
+            let matches = pt.find_text_matches(text).unwrap();
+            let ranges: Vec<_> = to_wasm_matches(text, &matches)
+                .iter()
+                .map(|m| (m.start, m.end))
+                .collect();
+            assert_eq!(ranges, expected, "{text}");
+        }
+    }
+}
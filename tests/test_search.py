@@ -1,6 +1,12 @@
+import ast
+import inspect
+import pathlib
+
+import ac_search
 import pytest
 from ac_search import (
     PyMatch,
+    PyMatchIter,
     PyTrie,
     normalize_string,
     search_in_text,
@@ -181,3 +187,549 @@ def test_search_word_bounds():
     assert matches[1].kw == "ab" and matches[1].value == "ab"
     assert matches[2].kw == "epq" and matches[2].value == "épqr"
     assert matches[3].kw == "epq" and matches[3].value == "épq"
+
+
+def test_search_min_length():
+    """
+    Test that short patterns can be ignored at search time.
+    """
+    trie = PyTrie(to_dictionary(["a", "ab", "abcd", "bcd"]))
+
+    hs = "abcd ab bcd"
+    assert len(trie.search(hs)) == 7
+
+    matches = trie.search(hs, min_length=3)
+    assert [m.value for m in matches] == ["abcd", "bcd", "bcd"]
+
+    many = trie.search_many([hs, "ab"], min_length=2)
+    assert [len(m) for m in many] == [5, 1]
+
+
+def test_search_scores():
+    """
+    Test that match scores are only computed when requested.
+    """
+    dct = to_dictionary(["New York"])
+    hs = "new york, New York."
+
+    assert all(m.score is None for m in PyTrie(dct).search(hs))
+
+    trie = PyTrie(dct, case_sensitive=False, compute_scores=True)
+    first, second = trie.search(hs)
+    assert 0.0 < first.score < second.score <= 1.0
+
+
+def test_search_categories():
+    """
+    Test that the categories of the patterns are reported with the matches.
+    """
+    dct = {"aspirin": "aspirin", "Acme": "Acme Corp.", "Ada": "Ada Lovelace"}
+    trie = PyTrie(dct, categories={"aspirin": "DRUG", "Acme": "ORG"})
+
+    matches = trie.search("Ada bought aspirin from Acme")
+    assert [(m.kw, m.category) for m in matches] == [
+        ("Ada Lovelace", None),
+        ("aspirin", "DRUG"),
+        ("Acme Corp.", "ORG"),
+    ]
+
+
+def test_search_bytes():
+    """
+    Test searching raw bytes with invalid UTF-8 sequences.
+    """
+    trie = PyTrie(to_dictionary(["ab", "né"]))
+    data = b"a\xffb ab n\xc3\xa9"
+
+    matches = trie.search_bytes(data)
+    assert [(m.from_char, m.to_char) for m in matches] == [(4, 6), (7, 10)]
+
+    matches = trie.search_bytes(data, skip_invalid=True)
+    assert [m.value for m in matches] == ["ab", "ab", "né"]
+
+
+def test_search_coverage():
+    """
+    Test tracking the patterns that never matched.
+    """
+    dct = to_dictionary(["ab", "cd", "ef"])
+    assert PyTrie(dct).never_matched() is None
+
+    trie = PyTrie(dct, track_coverage=True)
+    trie.search("xab")
+    trie.search_many(["ef"])
+    assert trie.never_matched() == [("cd", "cd")]
+
+
+def test_search_many_timeout():
+    """
+    Test searching many texts with a time budget per text.
+    """
+    trie = PyTrie(to_dictionary(["ab", "cd"]))
+    results = trie.search_many(["ab cd", "xy"], timeout=60.0)
+    assert [len(r) for r in results] == [2, 0]
+
+    results = trie.search_many(["ab cd"], timeout=0.0)
+    assert results == [None]
+
+    with pytest.raises(ValueError):
+        _ = trie.search_many(["ab"], timeout=-1.0)
+
+
+def test_search_many_progress():
+    """
+    Test reporting the progress of a search of many texts.
+    """
+    trie = PyTrie(to_dictionary(["ab", "cd"]))
+    reported = []
+    texts = ["ab cd", "xy", "ab"] * 10
+    results = trie.search_many(
+        texts, num_threads=3, on_progress=lambda d, t: reported.append((d, t))
+    )
+    assert len(results) == len(texts)
+    assert reported == [(d, len(texts)) for d in range(1, len(texts) + 1)]
+
+    def fail(done: int, total: int) -> None:
+        raise RuntimeError("stop")
+
+    with pytest.raises(RuntimeError):
+        _ = trie.search_many(texts, timeout=10.0, on_progress=fail)
+
+
+def test_search_limits():
+    """
+    Test abandoning the searches that exceed the limits of the tree.
+    """
+    trie = PyTrie(to_dictionary(["a", "aa"]), max_matches=4, max_text_chars=10)
+    assert len(trie.search("aa")) == 3
+    with pytest.raises(ValueError, match="limit"):
+        _ = trie.search("aaa")
+    with pytest.raises(ValueError, match="limit"):
+        _ = trie.search_many(["b", "b" * 11])
+
+    trie = PyTrie(to_dictionary(["ab"]), time_limit=0.0)
+    with pytest.raises(ValueError, match="limit"):
+        _ = trie.search("ab")
+    with pytest.raises(ValueError):
+        _ = PyTrie(to_dictionary(["ab"]), time_limit=-1.0)
+
+
+def test_search_scan():
+    """
+    Test consuming the matches of a search with a callback.
+    """
+    trie = PyTrie(to_dictionary(["ab", "cd"]))
+    found = []
+    assert trie.scan("ab cd ab", found.append)
+    assert [m.value for m in found] == ["ab", "cd", "ab"]
+
+    found = []
+
+    def first_only(m):
+        found.append(m)
+        return False
+
+    assert not trie.scan("ab cd ab", first_only)
+    assert len(found) == 1
+
+    def failing(_):
+        raise RuntimeError("callback failed")
+
+    with pytest.raises(RuntimeError):
+        trie.scan("ab", failing)
+
+
+def test_longest_match():
+    """
+    Test finding the longest entry matching at a given position.
+    """
+    trie = PyTrie(to_dictionary(["new", "new york", "york"]))
+    text = "new york city"
+
+    found = trie.longest_match(text)
+    assert (found.value, found.from_char, found.to_char) == ("new york", 0, 8)
+    assert trie.longest_match(text, start=4).value == "york"
+    assert trie.longest_match(text, start=1) is None
+    assert trie.longest_match(text, start=100) is None
+
+
+def test_match_kind():
+    """
+    Test reporting non-overlapping matches with the leftmost match kinds.
+    """
+    patterns = ["ab", "abcd", "bcd"]
+    text = "abcd"
+
+    trie = PyTrie(to_dictionary(patterns))
+    assert len(trie.search(text)) == 3
+
+    trie = PyTrie(to_dictionary(patterns), match_kind="leftmost_longest")
+    assert [m.value for m in trie.search(text)] == ["abcd"]
+
+    # The priority of leftmost_first is the order of the dictionary, which to_dictionary
+    # does not keep
+    trie = PyTrie({p: p for p in patterns}, match_kind="leftmost_first")
+    assert [m.value for m in trie.search(text)] == ["ab"]
+
+    with pytest.raises(ValueError):
+        _ = PyTrie(to_dictionary(patterns), match_kind="longest")
+
+
+def test_replace_all():
+    """
+    Test replacing the matches of a text with their keywords.
+    """
+    trie = PyTrie({"NYC": "New York", "New York City": "New York", "ny": "New York"})
+    assert trie.replace_all("NYC or New York City") == "New York or New York"
+    assert trie.replace_all("nothing here") == "nothing here"
+
+
+def test_match_byte_range():
+    """
+    Test the byte ranges of the matches.
+    """
+    text = "Un CAFÉ noir, un café"
+    trie = PyTrie({"café": "café"}, case_sensitive=False)
+    matches = trie.search(text)
+    assert len(matches) == 2
+    data = text.encode()
+    for m in matches:
+        assert data[m.byte_range()].decode() == text[m.from_char : m.to_char]
+    assert (matches[0].from_byte, matches[0].to_byte) == (3, 8)
+
+
+def test_save_load(tmp_path):
+    """
+    Test saving a trie to a file and loading it back.
+    """
+    trie = PyTrie({"NYC": "New York", "new york": "New York"}, case_sensitive=False)
+    path = str(tmp_path / "trie.bin")
+    trie.save(path)
+    loaded = PyTrie.load(path)
+    assert sorted(loaded.keywords) == ["New York"]
+    text = "nyc is New York"
+    assert [(m.from_char, m.kw) for m in loaded.search(text)] == [
+        (m.from_char, m.kw) for m in trie.search(text)
+    ]
+
+
+def test_add_patterns():
+    """
+    Test adding patterns to a trie after creating it.
+    """
+    trie = PyTrie({"abc": "ABC"})
+    trie.add_patterns({"ab": "AB", "cd": "CD"})
+    assert sorted(trie.keywords) == ["AB", "ABC", "CD"]
+    assert [m.kw for m in trie.search("abcd")] == ["AB", "ABC", "CD"]
+    with pytest.raises(ValueError):
+        trie.add_patterns({"ab": "AB"})
+
+
+def test_trie_from_pairs():
+    """
+    Test creating a trie from an iterable of (pattern, keyword) tuples.
+    """
+    pairs = [("ab", "AB"), ("cd", "CD")]
+    trie = PyTrie(iter(pairs), check_bounds=True)
+    assert sorted(trie.keywords) == ["AB", "CD"]
+    assert [m.kw for m in trie.search("ab cd abcd")] == ["AB", "CD"]
+    trie.add_patterns((p, p.upper()) for p in ["abcd"])
+    assert trie.search("abcd")[0].kw == "ABCD"
+    with pytest.raises(ValueError):
+        _ = PyTrie([("ab",)])
+    with pytest.raises(TypeError):
+        _ = PyTrie(42)
+
+
+def test_trie_from_file(tmp_path):
+    """
+    Test creating a trie from TSV and JSON dictionary files.
+    """
+    tsv_path = tmp_path / "dictionary.tsv"
+    tsv_path.write_text("NYC\tNew York\tCITY\nparis\tParis\tCITY\n")
+    trie = PyTrie.from_file(str(tsv_path), case_sensitive=False)
+    found = trie.search("From nyc to Paris")
+    assert [(m.kw, m.category) for m in found] == [
+        ("New York", "CITY"),
+        ("Paris", "CITY"),
+    ]
+
+    json_path = tmp_path / "dictionary.txt"
+    json_path.write_text('["spam", {"pattern": "eggs", "keyword": "EGGS"}]')
+    trie = PyTrie.from_file(str(json_path), format="json")
+    assert [m.kw for m in trie.search("spam and eggs")] == ["spam", "EGGS"]
+
+    with pytest.raises(ValueError):
+        _ = PyTrie.from_file(str(json_path), format="xml")
+    with pytest.raises(OSError):
+        _ = PyTrie.from_file(str(tmp_path / "missing.tsv"))
+
+
+def test_pattern_id():
+    """
+    Test getting the patterns of the matches from their IDs.
+    """
+    trie = PyTrie({"NYC": "New York", "New York City": "New York"})
+    for m in trie.search("NYC, New York City"):
+        assert trie.pattern(m.pattern_id) == (m.value, m.kw)
+    assert trie.pattern(5) is None
+
+
+def test_count_and_contains():
+    """
+    Test counting matches and checking whether there are any.
+    """
+    trie = PyTrie({"spam": "spam", "eggs": "eggs"}, case_sensitive=False)
+    assert trie.count_matches("Spam, eggs and spam") == 3
+    assert trie.contains_any("more SPAM")
+    assert not trie.contains_any("nothing here")
+    assert trie.count_matches("") == 0
+
+
+def test_finditer():
+    """
+    Test iterating lazily over the matches of a text.
+    """
+    trie = PyTrie({"ab": "AB", "bc": "BC"}, match_kind="leftmost_longest")
+    text = "abc bc " * 100
+    matches = trie.finditer(text)
+    assert isinstance(matches, PyMatchIter)
+    assert next(matches) == trie.search(text)[0]
+    with pytest.raises(RuntimeError):
+        trie.add_patterns({"cd": "CD"})
+    assert [next(matches)] + list(matches) == trie.search(text)[1:]
+    assert next(matches, None) is None
+
+    del matches
+    trie.add_patterns({"cd": "CD"})
+    assert [m.kw for m in trie.finditer("abcd")] == ["AB", "CD"]
+
+
+def test_search_arrays():
+    """
+    Test getting the matches of a search as columns.
+    """
+    trie = PyTrie(
+        {"spam": "SPAM", "eggs": "EGGS"}, categories={"spam": "FOOD"}
+    )
+    text = "spam, eggs and spam"
+    columns = trie.search_arrays(text)
+    matches = trie.search(text)
+    assert list(columns) == list(matches[0].to_dict())
+    assert [dict(zip(columns, row)) for row in zip(*columns.values())] == [
+        m.to_dict() for m in matches
+    ]
+    assert columns["category"] == ["FOOD", None, "FOOD"]
+    assert columns["kw"][0] is columns["kw"][2]
+    assert trie.search_arrays(text, min_length=5)["value"] == []
+
+
+def test_trie_stats():
+    """
+    Test getting the statistics of the prefix tree.
+    """
+    trie = PyTrie({"ab": "ab", "abc": "abc", "b": "b"})
+    stats = trie.stats()
+    assert stats["total_nodes"] == trie.total_nodes() == 5
+    assert stats["total_edges"] == 4
+    assert stats["total_patterns"] == 3
+    assert stats["max_depth"] == 3
+    assert stats["max_fan_out"] == 2
+    assert abs(stats["avg_depth"] - 1.4) < 1e-9
+    assert stats["heap_bytes"] > 0
+
+
+def test_first_matches():
+    """
+    Test finding only the first matches of a text.
+    """
+    trie = PyTrie({"spam": "spam", "eggs": "eggs"})
+    first = trie.first_match("eggs and spam")
+    assert first is not None and first.kw == "eggs"
+    assert trie.first_match("nothing here") is None
+    found = trie.first_matches("spam spam eggs spam", 3)
+    assert [m.from_char for m in found] == [0, 5, 10]
+    assert trie.first_matches("spam", 0) == []
+
+
+def test_search_from_threads():
+    """
+    Test searching the same trie from several Python threads, which run while the GIL is
+    released.
+    """
+    from concurrent.futures import ThreadPoolExecutor
+
+    trie = PyTrie({"spam": "spam", "eggs": "eggs"})
+    texts = ["spam and eggs " * 1000] * 8
+    with ThreadPoolExecutor(max_workers=4) as pool:
+        results = list(pool.map(trie.search, texts))
+    assert all(len(found) == 2000 for found in results)
+    spans = [[(m.from_char, m.to_char) for m in found] for found in results]
+    assert [
+        [(m.from_char, m.to_char) for m in found] for found in trie.search_many(texts[:2])
+    ] == spans[:2]
+
+
+def test_pickle():
+    """
+    Test pickling a trie and restoring it.
+    """
+    import pickle
+
+    trie = PyTrie({"ab": "AB", "Cd": "CD"}, case_sensitive=False, match_kind="leftmost_longest")
+    restored = pickle.loads(pickle.dumps(trie))
+    assert isinstance(restored, PyTrie)
+    assert sorted(restored.keywords) == ["AB", "CD"]
+    assert [m.kw for m in restored.search("xAB cd")] == ["AB", "CD"]
+    assert PyTrie.from_bytes(trie.to_bytes()).total_nodes() == trie.total_nodes()
+    with pytest.raises(ValueError):
+        PyTrie.from_bytes(b"not a trie")
+
+
+def test_match_conversions():
+    """
+    Test converting matches to dictionaries and tuples, and comparing them.
+    """
+    trie = PyTrie({"spam": "SPAM", "eggs": "EGGS"})
+    first, second = trie.search("spam eggs")
+    assert first.to_dict() == {
+        "value": "spam",
+        "kw": "SPAM",
+        "from_char": 0,
+        "to_char": 4,
+        "from_byte": 0,
+        "to_byte": 4,
+        "score": None,
+        "category": None,
+        "pattern_id": 0,
+    }
+    from_char, to_char, value, kw = second
+    assert (from_char, to_char, value, kw) == (5, 9, "eggs", "EGGS")
+    assert len(second) == 4
+
+    assert trie.search("spam eggs") == [first, second]
+    assert first != second
+    assert first != "spam"
+    assert len({first, second, *trie.search("spam eggs")}) == 2
+
+
+def test_word_segmentation():
+    """
+    Test checking word bounds with the Unicode word segmentation rules.
+    """
+    dct = {"don": "DON", "3": "THREE", "cat": "CAT"}
+    text = "I don't, 3.14 cats. A cat!"
+    simple = PyTrie(dct, check_bounds=True)
+    assert [m.kw for m in simple.search(text)] == ["DON", "THREE", "CAT"]
+
+    segmented = PyTrie(dct, check_bounds=True, word_segmentation=True)
+    assert [m.kw for m in segmented.search(text)] == ["CAT"]
+
+
+def test_search_with_context():
+    """
+    Test getting the text surrounding the matches.
+    """
+    trie = PyTrie({"fox": "FOX", "dog": "DOG"})
+    text = "the quick brown fox jumps over the lazy dog"
+    found = trie.search_with_context(text, 6)
+    assert [(m.kw, before, after) for m, before, after in found] == [
+        ("FOX", "brown ", " jumps"),
+        ("DOG", " lazy ", ""),
+    ]
+    assert [m for m, _, _ in found] == trie.search(text)
+    assert trie.search_with_context("ñ fox ñ", 2)[0][1:] == ("ñ ", " ñ")
+
+
+def test_flexible_whitespace():
+    """
+    Test matching the whitespace of the patterns with any run of whitespace.
+    """
+    dct = {"new york": "NYC"}
+    text = "New  York\nnew\n\tyork"
+    assert PyTrie(dct, case_sensitive=False).search(text) == []
+
+    flexible = PyTrie(dct, case_sensitive=False, whitespace="flexible")
+    found = flexible.search(text)
+    assert [(m.from_char, m.to_char) for m in found] == [(0, 9)]
+
+    multiline = PyTrie(
+        dct, case_sensitive=False, whitespace="flexible_multiline"
+    )
+    found = multiline.search(text)
+    assert [(m.from_char, m.to_char) for m in found] == [(0, 9), (10, 19)]
+
+    with pytest.raises(ValueError):
+        PyTrie(dct, whitespace="any")
+
+
+def test_wildcard():
+    """
+    Test patterns with a wildcard matching any single character.
+    """
+    trie = PyTrie({"gr?y": "GREY", "grey": "grey", "?b": "B"}, wildcard="?")
+    found = trie.search("gray grey xb")
+    assert [(m.value, m.kw) for m in found] == [
+        ("gr?y", "GREY"),
+        ("grey", "grey"),
+        ("?b", "B"),
+    ]
+    assert PyTrie({"gr?y": "GREY"}).search("gray") == []
+
+
+def _stub_params(node: ast.FunctionDef) -> list[tuple[str, object]]:
+    """
+    Get the names and default values of the parameters of a function stub.
+    """
+    args = node.args.posonlyargs + node.args.args
+    defaults = [inspect.Parameter.empty] * (
+        len(args) - len(node.args.defaults)
+    ) + [ast.literal_eval(d) for d in node.args.defaults]
+    return [
+        (arg.arg, default)
+        for arg, default in zip(args, defaults)
+        if arg.arg not in ("self", "cls")
+    ]
+
+
+def _runtime_params(obj) -> list[tuple[str, object]]:
+    """
+    Get the names and default values of the parameters of a compiled function.
+    """
+    return [
+        (p.name, p.default)
+        for p in inspect.signature(obj).parameters.values()
+        if p.name != "self"
+    ]
+
+
+def test_type_stubs():
+    """
+    Test that the type stubs declare the functions and methods of the compiled
+    module with the same parameters.
+    """
+    stub_path = pathlib.Path(ac_search.__file__).parent / "ac_search_rs.pyi"
+    stub = ast.parse(stub_path.read_text())
+    declared = {
+        node.name: node
+        for node in stub.body
+        if isinstance(node, (ast.FunctionDef, ast.ClassDef))
+    }
+
+    for func in (normalize_string, search_in_text, search_in_texts):
+        assert _stub_params(declared[func.__name__]) == _runtime_params(func)
+
+    for cls in (PyMatch, PyTrie):
+        members = {
+            node.name: node
+            for node in declared[cls.__name__].body
+            if isinstance(node, ast.FunctionDef)
+        }
+        assert _stub_params(members["__new__"]) == _runtime_params(cls)
+        for name in dir(cls):
+            if name.startswith("_"):
+                continue
+            assert name in members, f"{cls.__name__}.{name} missing in stubs"
+            attr = getattr(cls, name)
+            if callable(attr):
+                assert _stub_params(members[name]) == _runtime_params(attr)